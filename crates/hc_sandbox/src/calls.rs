@@ -14,7 +14,7 @@ use anyhow::ensure;
 use holochain_conductor_api::AdminInterfaceConfig;
 use holochain_conductor_api::AdminRequest;
 use holochain_conductor_api::AdminResponse;
-use holochain_conductor_api::InterfaceDriver;
+use holochain_conductor_api::AppInterfaceInfo;
 use holochain_p2p::kitsune_p2p;
 use holochain_p2p::kitsune_p2p::agent_store::AgentInfoSigned;
 use holochain_types::prelude::InstallAppDnaPayload;
@@ -41,6 +41,10 @@ pub struct Call {
     /// If this is empty existing sandboxes will be used.
     /// Cannot be combined with existing sandboxes.
     pub running: Vec<u16>,
+    #[structopt(long, conflicts_with_all = &["running", "existing_paths", "indices"], value_delimiter = ",")]
+    /// Unix sockets of running conductor admin interfaces.
+    /// Cannot be combined with ports or existing sandboxes.
+    pub running_sockets: Vec<PathBuf>,
     #[structopt(flatten)]
     pub existing: Existing,
     #[structopt(subcommand)]
@@ -55,6 +59,10 @@ pub struct Call {
 pub enum AdminRequestCli {
     AddAdminWs(AddAdminWs),
     AddAppWs(AddAppWs),
+    AddAppSocket(AddAppSocket),
+    /// Calls AdminRequest::ListAppInterfaces.
+    ListAppInterfaces,
+    DetachAppInterface(DetachAppInterface),
     InstallApp(InstallApp),
     InstallAppBundle(InstallAppBundle),
     /// Calls AdminRequest::ListDnas.
@@ -89,6 +97,38 @@ pub struct AddAppWs {
     /// Optional port number.
     /// Defaults to assigned by OS.
     pub port: Option<u16>,
+    /// Optional address to bind to.
+    /// Defaults to the loopback address 127.0.0.1.
+    #[structopt(long)]
+    pub bind_address: Option<std::net::IpAddr>,
+    /// Origins browsers may connect from.
+    /// Defaults to allowing any origin.
+    #[structopt(long, value_delimiter = ",")]
+    pub allowed_origins: Option<Vec<String>>,
+    /// Optional app to attach the interface to.
+    /// Crypto requests are only answered on an interface attached to an app.
+    #[structopt(long)]
+    pub app_id: Option<String>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::AttachAppUnixSocket
+/// and adds an app interface on a Unix socket.
+pub struct AddAppSocket {
+    /// Where to create the socket.
+    pub path: PathBuf,
+    /// Optional app to attach the interface to.
+    /// Crypto requests are only answered on an interface attached to an app.
+    #[structopt(long)]
+    pub app_id: Option<String>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::DetachAppInterface
+/// and stops and removes an app interface.
+pub struct DetachAppInterface {
+    /// The id of the interface, e.g. `interface-8000`.
+    pub interface_id: String,
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -182,9 +222,16 @@ pub async fn call(holochain_path: &Path, req: Call) -> anyhow::Result<()> {
     let Call {
         existing,
         running,
+        running_sockets,
         call,
     } = req;
-    let cmds = if running.is_empty() {
+    let cmds = if !running_sockets.is_empty() {
+        let mut cmds = Vec::with_capacity(running_sockets.len());
+        for path in running_sockets {
+            cmds.push((CmdRunner::try_new_unix(&path).await?, None));
+        }
+        cmds
+    } else if running.is_empty() {
         let paths = if existing.is_empty() {
             crate::save::load(std::env::current_dir()?)?
         } else {
@@ -231,6 +278,21 @@ async fn call_inner(cmd: &mut CmdRunner, call: AdminRequestCli) -> anyhow::Resul
             let port = attach_app_interface(cmd, args).await?;
             msg!("Added App port {}", port);
         }
+        AdminRequestCli::AddAppSocket(args) => {
+            let path = attach_app_unix_socket(cmd, args).await?;
+            msg!("Added App socket {}", path.display());
+        }
+        AdminRequestCli::ListAppInterfaces => {
+            let interfaces = list_app_interfaces(cmd).await?;
+            for interface in interfaces {
+                msg!("{}: {:?}", interface.interface_id, interface.driver);
+            }
+        }
+        AdminRequestCli::DetachAppInterface(args) => {
+            let interface_id = args.interface_id.clone();
+            detach_app_interface(cmd, args).await?;
+            msg!("Detached app interface: {}", interface_id);
+        }
         AdminRequestCli::InstallApp(args) => {
             let app_id = args.app_id.clone();
             let cells = install_app(cmd, args).await?;
@@ -338,9 +400,7 @@ pub async fn add_admin_interface(cmd: &mut CmdRunner, args: AddAdminWs) -> anyho
     };
     let resp = cmd
         .command(AdminRequest::AddAdminInterfaces(vec![
-            AdminInterfaceConfig {
-                driver: InterfaceDriver::Websocket { port },
-            },
+            AdminInterfaceConfig::websocket(port),
         ]))
         .await?;
     ensure!(
@@ -504,7 +564,12 @@ pub async fn attach_app_interface(cmd: &mut CmdRunner, args: AddAppWs) -> anyhow
         ensure!(is_free(port), "port {} is not free", port);
     }
     let resp = cmd
-        .command(AdminRequest::AttachAppInterface { port: args.port })
+        .command(AdminRequest::AttachAppInterface {
+            port: args.port,
+            bind_address: args.bind_address,
+            allowed_origins: args.allowed_origins,
+            installed_app_id: args.app_id,
+        })
         .await?;
     match resp {
         AdminResponse::AppInterfaceAttached { port } => Ok(port),
@@ -516,6 +581,51 @@ pub async fn attach_app_interface(cmd: &mut CmdRunner, args: AddAppWs) -> anyhow
     }
 }
 
+/// Calls [`AdminRequest::AttachAppUnixSocket`] and adds an app interface on a Unix socket.
+pub async fn attach_app_unix_socket(
+    cmd: &mut CmdRunner,
+    args: AddAppSocket,
+) -> anyhow::Result<PathBuf> {
+    let resp = cmd
+        .command(AdminRequest::AttachAppUnixSocket {
+            path: args.path.clone(),
+            installed_app_id: args.app_id,
+        })
+        .await?;
+    match resp {
+        AdminResponse::AppUnixSocketAttached { path } => Ok(path),
+        _ => Err(anyhow!(
+            "Failed to attach app socket {}, got: {:?}",
+            args.path.display(),
+            resp
+        )),
+    }
+}
+
+/// Calls [`AdminRequest::ListAppInterfaces`] and lists the attached app interfaces.
+pub async fn list_app_interfaces(cmd: &mut CmdRunner) -> anyhow::Result<Vec<AppInterfaceInfo>> {
+    let resp = cmd.command(AdminRequest::ListAppInterfaces).await?;
+    Ok(expect_match!(resp => AdminResponse::AppInterfacesListed, "Failed to list app interfaces"))
+}
+
+/// Calls [`AdminRequest::DetachAppInterface`] and removes an app interface.
+pub async fn detach_app_interface(
+    cmd: &mut CmdRunner,
+    args: DetachAppInterface,
+) -> anyhow::Result<()> {
+    let resp = cmd
+        .command(AdminRequest::DetachAppInterface {
+            interface_id: args.interface_id,
+        })
+        .await?;
+    ensure!(
+        matches!(resp, AdminResponse::AppInterfaceDetached),
+        "Failed to detach app interface, got: {:?}",
+        resp
+    );
+    Ok(())
+}
+
 /// Calls [`AdminRequest::DumpState`] and dumps the current cell's state.
 // TODO: Add pretty print.
 // TODO: Default to dumping all cell state.
@@ -1,4 +1,5 @@
 //! Helpers for working with websockets and ports.
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -31,7 +32,8 @@ pub async fn get_admin_ports(paths: Vec<PathBuf>) -> anyhow::Result<Vec<u16>> {
         if let Some(config) = read_config(p)? {
             if let Some(ai) = config.admin_interfaces {
                 if let Some(AdminInterfaceConfig {
-                    driver: InterfaceDriver::Websocket { port },
+                    driver: InterfaceDriver::Websocket { port, .. },
+                    ..
                 }) = ai.get(0)
                 {
                     ports.push(*port)
@@ -47,6 +49,22 @@ pub(crate) async fn get_admin_api(port: u16) -> std::io::Result<WebsocketSender>
     websocket_client_by_port(port).await.map(|p| p.0)
 }
 
+#[cfg(unix)]
+pub(crate) async fn get_admin_api_unix(path: &Path) -> std::io::Result<WebsocketSender> {
+    tracing::debug!(path = %path.display());
+    holochain_websocket::websocket_connect_unix(path, Arc::new(WebsocketConfig::default()))
+        .await
+        .map(|p| p.0)
+}
+
+#[cfg(not(unix))]
+pub(crate) async fn get_admin_api_unix(_path: &Path) -> std::io::Result<WebsocketSender> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        "Unix sockets are not supported on this platform",
+    ))
+}
+
 async fn websocket_client_by_port(
     port: u16,
 ) -> std::io::Result<(WebsocketSender, WebsocketReceiver)> {
@@ -60,26 +78,25 @@ async fn websocket_client_by_port(
 pub(crate) fn random_admin_port(config: &mut ConductorConfig) {
     match config.admin_interfaces.as_mut().and_then(|i| i.first_mut()) {
         Some(AdminInterfaceConfig {
-            driver: InterfaceDriver::Websocket { port },
+            driver: InterfaceDriver::Websocket { port, .. },
+            ..
         }) => {
             if *port != 0 {
                 *port = 0;
             }
         }
+        // Unix socket interfaces have no port to pick
+        Some(_) => {}
         None => {
             let port = 0;
-            config.admin_interfaces = Some(vec![AdminInterfaceConfig {
-                driver: InterfaceDriver::Websocket { port },
-            }]);
+            config.admin_interfaces = Some(vec![AdminInterfaceConfig::websocket(port)]);
         }
     }
 }
 
 pub(crate) fn set_admin_port(config: &mut ConductorConfig, port: u16) {
     let p = port;
-    let port = AdminInterfaceConfig {
-        driver: InterfaceDriver::Websocket { port },
-    };
+    let port = AdminInterfaceConfig::websocket(port);
     match config
         .admin_interfaces
         .as_mut()
@@ -39,6 +39,9 @@ pub async fn run(
             &mut cmd,
             AddAppWs {
                 port: Some(app_port),
+                bind_address: None,
+                allowed_origins: None,
+                app_id: None,
             },
         )
         .await?;
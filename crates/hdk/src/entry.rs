@@ -205,6 +205,12 @@ where
 ///       contacts on their current network partition, there could always be an older live entry
 ///       on another partition, and of course the oldest live entry could be deleted and no longer
 ///       be live.
+///
+/// Note: If the entry def of an entry declares a [ `CrdtType` ], e.g. with
+///       `#[hdk_entry(id = "tags", crdt_type = "grow_only_set")]`, then a [ `get` ] on its entry
+///       hash returns the value merged from the "oldest live" element and the tree of updates
+///       made to it, in the element of the latest update.
+///       The merged entry of a set or counter is not the entry the header's entry hash refers to.
 pub fn get<H>(hash: H, options: GetOptions) -> ExternResult<Option<Element>>
 where
     AnyDhtHash: From<H>,
@@ -144,7 +144,7 @@ pub struct Path(Vec<Component>);
 
 entry_def!(Path EntryDef {
     id: core::str::from_utf8(&NAME).unwrap().into(),
-    crdt_type: CrdtType,
+    crdt_type: CrdtType::None,
     required_validations: RequiredValidations::default(),
     visibility: EntryVisibility::Public,
     required_validation_type: RequiredValidationType::default(),
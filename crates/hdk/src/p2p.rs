@@ -92,6 +92,21 @@ where
     host_call::<AppSignal, ()>(__emit_signal, AppSignal::new(ExternIO::encode(input)?))
 }
 
+/// Emit an app-defined Signal with a topic.
+///
+/// Clients can subscribe to only the topics they are interested in.
+/// See [`emit_signal`].
+pub fn emit_signal_with_topic<I>(topic: impl Into<String>, input: I) -> ExternResult<()>
+where
+    I: serde::Serialize + std::fmt::Debug,
+{
+    #[allow(clippy::unit_arg)]
+    host_call::<AppSignal, ()>(
+        __emit_signal,
+        AppSignal::with_topic(topic.into(), ExternIO::encode(input)?),
+    )
+}
+
 /// ## Remote Signal
 /// Send a signal to a list of other agents.
 /// This will send the data as an [ `AppSignal` ] to
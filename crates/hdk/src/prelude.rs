@@ -41,6 +41,7 @@ pub use crate::map_extern::ExternResult;
 pub use crate::p2p::call;
 pub use crate::p2p::call_remote;
 pub use crate::p2p::emit_signal;
+pub use crate::p2p::emit_signal_with_topic;
 pub use crate::p2p::remote_signal;
 pub use crate::random::*;
 pub use crate::register_entry;
@@ -20,7 +20,7 @@ impl Parse for EntryDef {
         let mut required_validations =
             holochain_zome_types::entry_def::RequiredValidations::default();
         let mut visibility = holochain_zome_types::entry_def::EntryVisibility::default();
-        let crdt_type = holochain_zome_types::crdt::CrdtType::default();
+        let mut crdt_type = holochain_zome_types::crdt::CrdtType::default();
         let mut required_validation_type =
             holochain_zome_types::validate::RequiredValidationType::default();
 
@@ -86,7 +86,28 @@ impl Parse for EntryDef {
                         };
                     }
                     "crdt_type" => {
-                        unimplemented!();
+                        match var.lit {
+                            syn::Lit::Str(s) => {
+                                crdt_type = match s.value().as_str() {
+                                    "none" => holochain_zome_types::crdt::CrdtType::None,
+                                    "last_writer_wins" => {
+                                        holochain_zome_types::crdt::CrdtType::LastWriterWins
+                                    }
+                                    "grow_only_set" => {
+                                        holochain_zome_types::crdt::CrdtType::GrowOnlySet
+                                    }
+                                    "observed_remove_set" => {
+                                        holochain_zome_types::crdt::CrdtType::ObservedRemoveSet
+                                    }
+                                    "counter" => holochain_zome_types::crdt::CrdtType::Counter,
+                                    _ => unreachable!(
+                                        "Invalid crdt_type
+                                        Options are: none, last_writer_wins, grow_only_set, observed_remove_set and counter"
+                                    ),
+                                }
+                            }
+                            _ => unreachable!(),
+                        };
                     }
                     _ => {}
                 }
@@ -104,8 +125,18 @@ impl Parse for EntryDef {
 
 impl quote::ToTokens for CrdtType {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let variant = syn::Ident::new(
+            match self.0 {
+                holochain_zome_types::crdt::CrdtType::None => "None",
+                holochain_zome_types::crdt::CrdtType::LastWriterWins => "LastWriterWins",
+                holochain_zome_types::crdt::CrdtType::GrowOnlySet => "GrowOnlySet",
+                holochain_zome_types::crdt::CrdtType::ObservedRemoveSet => "ObservedRemoveSet",
+                holochain_zome_types::crdt::CrdtType::Counter => "Counter",
+            },
+            proc_macro2::Span::call_site(),
+        );
         tokens.append_all(quote::quote! {
-            hdk::prelude::CrdtType
+            hdk::prelude::CrdtType::#variant
         });
     }
 }
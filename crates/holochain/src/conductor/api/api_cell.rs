@@ -13,7 +13,6 @@ use holo_hash::DnaHash;
 use holochain_conductor_api::ZomeCall;
 use holochain_keystore::KeystoreSender;
 use holochain_types::prelude::*;
-use tracing::*;
 
 /// The concrete implementation of [CellConductorApiT], which is used to give
 /// Cells an API for calling back to their [Conductor].
@@ -62,9 +61,16 @@ impl CellConductorApiT for CellConductorApi {
         }
     }
 
-    async fn dpki_request(&self, _method: String, _args: String) -> ConductorApiResult<String> {
-        warn!("Using placeholder dpki");
-        Ok("TODO".to_string())
+    async fn dpki_register_key(&self, key: AgentPubKey) -> ConductorApiResult<()> {
+        self.conductor_handle.dpki_register_key(key).await
+    }
+
+    async fn dpki_key_state(
+        &self,
+        key: AgentPubKey,
+        at: Timestamp,
+    ) -> ConductorApiResult<Option<KeyState>> {
+        self.conductor_handle.dpki_key_state(key, at).await
     }
 
     async fn autonomic_cue(&self, cue: AutonomicCue) -> ConductorApiResult<()> {
@@ -125,9 +131,16 @@ pub trait CellConductorApiT: Clone + Send + Sync + Sized {
         call: ZomeCall,
     ) -> ConductorApiResult<ZomeCallResult>;
 
-    /// Make a request to the DPKI service running for this Conductor.
-    /// TODO: decide on actual signature
-    async fn dpki_request(&self, method: String, args: String) -> ConductorApiResult<String>;
+    /// Register an agent key with the DPKI app running for this Conductor.
+    async fn dpki_register_key(&self, key: AgentPubKey) -> ConductorApiResult<()>;
+
+    /// Ask the DPKI app running for this Conductor for the state of a key at a time.
+    /// Returns `None` if this Conductor is not using DPKI.
+    async fn dpki_key_state(
+        &self,
+        key: AgentPubKey,
+        at: Timestamp,
+    ) -> ConductorApiResult<Option<KeyState>>;
 
     /// Cue the autonomic system to run an [AutonomicProcess] earlier than its scheduled time.
     /// This is basically a heuristic designed to help things run more smoothly.
@@ -172,6 +185,9 @@ pub trait CellConductorReadHandleT: Send + Sync {
 
     /// Get a zome from this cell's Dna
     async fn get_zome(&self, dna_hash: &DnaHash, zome_name: &ZomeName) -> ConductorApiResult<Zome>;
+
+    /// Get a [EntryDef] from the [EntryDefBuf]
+    async fn get_entry_def(&self, key: &EntryDefBufferKey) -> Option<EntryDef>;
 }
 
 #[async_trait]
@@ -197,4 +213,8 @@ impl CellConductorReadHandleT for CellConductorApi {
     async fn get_zome(&self, dna_hash: &DnaHash, zome_name: &ZomeName) -> ConductorApiResult<Zome> {
         CellConductorApiT::get_zome(self, dna_hash, zome_name).await
     }
+
+    async fn get_entry_def(&self, key: &EntryDefBufferKey) -> Option<EntryDef> {
+        CellConductorApiT::get_entry_def(self, key).await
+    }
 }
@@ -8,13 +8,16 @@ use crate::conductor::error::CreateAppError;
 use crate::conductor::interface::error::InterfaceError;
 use crate::conductor::interface::error::InterfaceResult;
 use crate::conductor::ConductorHandle;
+use holochain_keystore::AgentPubKeyExt;
 use holochain_keystore::KeystoreSenderExt;
 use holochain_serialized_bytes::prelude::*;
 use holochain_types::prelude::*;
 
 use holochain_zome_types::cell::CellId;
 
+use rand::Rng;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::*;
 
 pub use holochain_conductor_api::*;
@@ -29,6 +32,13 @@ pub trait AdminInterfaceApi: 'static + Send + Sync + Clone {
         request: AdminRequest,
     ) -> ConductorApiResult<AdminResponse>;
 
+    /// A copy of this api for a new connection,
+    /// which starts out unauthenticated if the interface requires it.
+    fn for_connection(&self) -> Self;
+
+    /// Should conductor events be sent over this connection?
+    fn events_subscribed(&self) -> bool;
+
     // -- provided -- //
 
     /// Deal with error cases produced by `handle_admin_request_inner`
@@ -49,11 +59,113 @@ pub trait AdminInterfaceApi: 'static + Send + Sync + Clone {
 pub struct RealAdminInterfaceApi {
     /// Mutable access to the Conductor
     conductor_handle: ConductorHandle,
+    /// How connections must authenticate, if they must
+    auth: Option<Arc<AdminAuthConfig>>,
+    /// The requests allowed over this interface
+    permission: AdminPermission,
+    /// The authentication state of this connection
+    session: Arc<parking_lot::Mutex<AdminSession>>,
+}
+
+/// The length of the challenges issued for [`AdminAuthConfig::SignedChallenge`]
+const AUTH_CHALLENGE_LEN: usize = 32;
+
+struct AdminSession {
+    authenticated: bool,
+    /// The last challenge issued to this connection
+    challenge: Option<Vec<u8>>,
+    /// Whether this connection has subscribed to conductor events
+    events_subscribed: bool,
+}
+
+impl AdminSession {
+    /// A new session, which only needs to authenticate if there is auth
+    fn start(auth: &Option<Arc<AdminAuthConfig>>) -> Arc<parking_lot::Mutex<Self>> {
+        Arc::new(parking_lot::Mutex::new(AdminSession {
+            authenticated: auth.is_none(),
+            challenge: None,
+            events_subscribed: false,
+        }))
+    }
 }
 
 impl RealAdminInterfaceApi {
+    /// An interface with no authentication and full permission
     pub(crate) fn new(conductor_handle: ConductorHandle) -> Self {
-        RealAdminInterfaceApi { conductor_handle }
+        Self::with_access(conductor_handle, None, AdminPermission::Full)
+    }
+
+    /// An interface with this authentication and permission
+    pub(crate) fn with_access(
+        conductor_handle: ConductorHandle,
+        auth: Option<AdminAuthConfig>,
+        permission: AdminPermission,
+    ) -> Self {
+        let auth = auth.map(Arc::new);
+        RealAdminInterfaceApi {
+            conductor_handle,
+            session: AdminSession::start(&auth),
+            auth,
+            permission,
+        }
+    }
+
+    /// Issue a new challenge to this connection.
+    fn issue_challenge(&self) -> ConductorApiResult<Vec<u8>> {
+        match self.auth.as_deref() {
+            Some(AdminAuthConfig::SignedChallenge { .. }) => {
+                let mut challenge = vec![0; AUTH_CHALLENGE_LEN];
+                rand::thread_rng().fill(&mut challenge[..]);
+                self.session.lock().challenge = Some(challenge.clone());
+                Ok(challenge)
+            }
+            _ => Err(ConductorApiError::AdminAuthFailed(
+                "This interface doesn't authenticate with signed challenges".to_string(),
+            )),
+        }
+    }
+
+    /// Check the credential against this interface's auth config
+    /// and mark the connection authenticated if it matches.
+    async fn authenticate(&self, credential: AdminCredential) -> ConductorApiResult<()> {
+        let authenticated = match (self.auth.as_deref(), credential) {
+            // Nothing to authenticate with
+            (None, _) => true,
+            (Some(AdminAuthConfig::Token { token }), AdminCredential::Token(given)) => {
+                ring::constant_time::verify_slices_are_equal(token.as_bytes(), given.as_bytes())
+                    .is_ok()
+            }
+            (
+                Some(AdminAuthConfig::SignedChallenge { authorized_keys }),
+                AdminCredential::SignedChallenge { key, signature },
+            ) => {
+                // A challenge can only be used once
+                let challenge = self.session.lock().challenge.take().ok_or_else(|| {
+                    ConductorApiError::AdminAuthFailed(
+                        "No challenge has been issued to this connection".to_string(),
+                    )
+                })?;
+                authorized_keys.contains(&key)
+                    && key
+                        .verify_signature_raw(
+                            &signature,
+                            &AdminCredential::challenge_message(&challenge),
+                        )
+                        .await?
+            }
+            _ => {
+                return Err(ConductorApiError::AdminAuthFailed(
+                    "This interface doesn't accept that kind of credential".to_string(),
+                ))
+            }
+        };
+        if !authenticated {
+            return Err(ConductorApiError::AdminAuthFailed(
+                "The credential was not valid".to_string(),
+            ));
+        }
+        self.session.lock().authenticated = true;
+        Ok(())
     }
 }
 
@@ -64,6 +176,17 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
         request: AdminRequest,
     ) -> ConductorApiResult<AdminResponse> {
         use AdminRequest::*;
+        if request.required_permission() > self.permission {
+            return Err(ConductorApiError::AdminPermissionDenied(self.permission));
+        }
+        match request {
+            RequestAuthChallenge | Authenticate(_) => (),
+            _ => {
+                if !self.session.lock().authenticated {
+                    return Err(ConductorApiError::AdminNotAuthenticated);
+                }
+            }
+        }
         match request {
             AddAdminInterfaces(configs) => {
                 self.conductor_handle
@@ -238,19 +361,77 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     .await?;
                 Ok(AdminResponse::AppDeactivated)
             }
-            AttachAppInterface { port } => {
-                let port = port.unwrap_or(0);
+            UninstallApp {
+                installed_app_id,
+                delete_data,
+            } => {
+                self.conductor_handle
+                    .uninstall_app(installed_app_id, delete_data)
+                    .await?;
+                Ok(AdminResponse::AppUninstalled)
+            }
+            ListApps { status_filter } => {
+                let apps = self.conductor_handle.list_apps(status_filter).await?;
+                Ok(AdminResponse::AppsListed(apps))
+            }
+            AttachAppInterface {
+                port,
+                bind_address,
+                allowed_origins,
+                installed_app_id,
+            } => {
+                let driver = InterfaceDriver::Websocket {
+                    port: port.unwrap_or(0),
+                    bind_address,
+                    allowed_origins,
+                };
                 let port = self
                     .conductor_handle
                     .clone()
-                    .add_app_interface(port)
-                    .await?;
+                    .add_app_interface(driver, installed_app_id)
+                    .await?
+                    .ok_or(InterfaceError::PortError)?;
                 Ok(AdminResponse::AppInterfaceAttached { port })
             }
+            AttachAppUnixSocket {
+                path,
+                installed_app_id,
+            } => {
+                self.conductor_handle
+                    .clone()
+                    .add_app_interface(
+                        InterfaceDriver::UnixSocket { path: path.clone() },
+                        installed_app_id,
+                    )
+                    .await?;
+                Ok(AdminResponse::AppUnixSocketAttached { path })
+            }
+            ListAppInterfaces => {
+                let interfaces = self.conductor_handle.list_app_interfaces().await?;
+                Ok(AdminResponse::AppInterfacesListed(interfaces))
+            }
+            DetachAppInterface { interface_id } => {
+                self.conductor_handle
+                    .remove_app_interface(interface_id.as_str().into())
+                    .await?;
+                Ok(AdminResponse::AppInterfaceDetached)
+            }
             DumpState { cell_id } => {
                 let state = self.conductor_handle.dump_cell_state(&cell_id).await?;
                 Ok(AdminResponse::StateDumped(state))
             }
+            ExportSourceChain { cell_id } => {
+                let export = self.conductor_handle.export_source_chain(&cell_id).await?;
+                Ok(AdminResponse::SourceChainExported(export.to_bytes()?))
+            }
+            ImportSourceChain(payload) => {
+                let app = self
+                    .conductor_handle
+                    .clone()
+                    .import_source_chain(*payload)
+                    .await?;
+                Ok(AdminResponse::AppInstalled(app))
+            }
             AddAgentInfo { agent_infos } => {
                 self.conductor_handle.add_agent_infos(agent_infos).await?;
                 Ok(AdminResponse::AgentInfoAdded)
@@ -259,8 +440,41 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                 let r = self.conductor_handle.get_agent_infos(cell_id).await?;
                 Ok(AdminResponse::AgentInfoRequested(r))
             }
+            SubscribeConductorEvents => {
+                self.session.lock().events_subscribed = true;
+                Ok(AdminResponse::ConductorEventsSubscribed)
+            }
+            ListManagedTasks => Ok(AdminResponse::ManagedTasksListed(
+                self.conductor_handle.list_managed_tasks().await,
+            )),
+            DumpMetrics => Ok(AdminResponse::MetricsDumped(
+                self.conductor_handle.dump_metrics().await?,
+            )),
+            RevokeAgentKey { agent_key } => {
+                self.conductor_handle.dpki_revoke_key(agent_key).await?;
+                Ok(AdminResponse::AgentKeyRevoked)
+            }
+            RequestAuthChallenge => {
+                let challenge = self.issue_challenge()?;
+                Ok(AdminResponse::AuthChallengeIssued(challenge))
+            }
+            Authenticate(credential) => {
+                self.authenticate(credential).await?;
+                Ok(AdminResponse::Authenticated)
+            }
         }
     }
+
+    fn for_connection(&self) -> Self {
+        RealAdminInterfaceApi {
+            session: AdminSession::start(&self.auth),
+            ..self.clone()
+        }
+    }
+
+    fn events_subscribed(&self) -> bool {
+        self.session.lock().events_subscribed
+    }
 }
 
 /// Reads the [Dna] from disk and parses to [SerializedBytes]
@@ -308,13 +522,17 @@ mod test {
     use super::*;
     use crate::conductor::Conductor;
     use anyhow::Result;
+    use futures::StreamExt;
     use holochain_lmdb::test_utils::test_environments;
     use holochain_types::app::InstallAppDnaPayload;
     use holochain_types::test_utils::fake_agent_pubkey_1;
+    use holochain_types::test_utils::fake_cell_id;
     use holochain_types::test_utils::fake_dna_file;
     use holochain_types::test_utils::fake_dna_zomes;
     use holochain_types::test_utils::write_fake_dna_file;
     use holochain_wasm_test_utils::TestWasm;
+    use holochain_websocket::WebsocketMessage;
+    use holochain_websocket::WebsocketReceiver;
     use matches::assert_matches;
     use observability;
     use uuid::Uuid;
@@ -527,6 +745,426 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn uninstall_and_list_apps() -> Result<()> {
+        observability::test_run().ok();
+        let envs = test_environments();
+        let handle = Conductor::builder().test(&envs).await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(handle.clone());
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let dna_hash = dna.dna_hash().clone();
+        let (dna_path, _tempdir) = write_fake_dna_file(dna.clone()).await.unwrap();
+
+        for (installed_app_id, agent_key) in vec![
+            ("active", fake_agent_pubkey_1()),
+            ("inactive", fake_agent_pubkey_2()),
+        ] {
+            let payload = InstallAppPayload {
+                dnas: vec![InstallAppDnaPayload::path_only(
+                    dna_path.clone(),
+                    "".to_string(),
+                )],
+                installed_app_id: installed_app_id.to_string(),
+                agent_key,
+            };
+            let res = admin_api
+                .handle_admin_request(AdminRequest::InstallApp(Box::new(payload)))
+                .await;
+            assert_matches!(res, AdminResponse::AppInstalled(_));
+        }
+        let res = admin_api
+            .handle_admin_request(AdminRequest::ActivateApp {
+                installed_app_id: "active".to_string(),
+            })
+            .await;
+        assert_matches!(res, AdminResponse::AppActivated);
+
+        let list = |status_filter| {
+            let admin_api = admin_api.clone();
+            async move {
+                match admin_api
+                    .handle_admin_request(AdminRequest::ListApps { status_filter })
+                    .await
+                {
+                    AdminResponse::AppsListed(apps) => {
+                        let mut ids: Vec<_> = apps
+                            .into_iter()
+                            .map(|app| (app.installed_app_id, app.active))
+                            .collect();
+                        ids.sort();
+                        ids
+                    }
+                    r => panic!("unexpected response {:?}", r),
+                }
+            }
+        };
+        assert_eq!(
+            list(None).await,
+            vec![
+                ("active".to_string(), true),
+                ("inactive".to_string(), false)
+            ]
+        );
+        assert_eq!(
+            list(Some(AppStatusFilter::Active)).await,
+            vec![("active".to_string(), true)]
+        );
+        assert_eq!(
+            list(Some(AppStatusFilter::Inactive)).await,
+            vec![("inactive".to_string(), false)]
+        );
+
+        let res = admin_api
+            .handle_admin_request(AdminRequest::UninstallApp {
+                installed_app_id: "active".to_string(),
+                delete_data: true,
+            })
+            .await;
+        assert_matches!(res, AdminResponse::AppUninstalled);
+        assert_eq!(list(None).await, vec![("inactive".to_string(), false)]);
+        let res = admin_api
+            .handle_admin_request(AdminRequest::ListCellIds)
+            .await;
+        let removed_cell = CellId::new(dna_hash, fake_agent_pubkey_1());
+        assert_matches!(res, AdminResponse::CellIdsListed(v) if !v.contains(&removed_cell));
+
+        // Inactive apps can be uninstalled too
+        let res = admin_api
+            .handle_admin_request(AdminRequest::UninstallApp {
+                installed_app_id: "inactive".to_string(),
+                delete_data: false,
+            })
+            .await;
+        assert_matches!(res, AdminResponse::AppUninstalled);
+        assert_eq!(list(None).await, Vec::<(String, bool)>::new());
+
+        let res = admin_api
+            .handle_admin_request(AdminRequest::UninstallApp {
+                installed_app_id: "inactive".to_string(),
+                delete_data: false,
+            })
+            .await;
+        assert_matches!(res, AdminResponse::Error(_));
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(5), shutdown)
+            .await
+            .expect("The conductor should shut down")
+            .expect("The task manager should not panic");
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn authenticated_admin_interfaces() -> Result<()> {
+        observability::test_run().ok();
+        let envs = test_environments();
+        let handle = Conductor::builder().test(&envs).await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let keystore = handle.keystore().clone();
+
+        // Token auth
+        let token_api = RealAdminInterfaceApi::with_access(
+            handle.clone(),
+            Some(AdminAuthConfig::Token {
+                token: "secret".to_string(),
+            }),
+            AdminPermission::Full,
+        );
+        let res = token_api.handle_admin_request(AdminRequest::ListDnas).await;
+        assert_matches!(
+            res,
+            AdminResponse::Error(ExternalApiWireError::AdminUnauthorized(_))
+        );
+        let res = token_api
+            .handle_admin_request(AdminRequest::Authenticate(AdminCredential::Token(
+                "guess".to_string(),
+            )))
+            .await;
+        assert_matches!(
+            res,
+            AdminResponse::Error(ExternalApiWireError::AdminUnauthorized(_))
+        );
+        let res = token_api
+            .handle_admin_request(AdminRequest::Authenticate(AdminCredential::Token(
+                "secret".to_string(),
+            )))
+            .await;
+        assert_matches!(res, AdminResponse::Authenticated);
+        let res = token_api.handle_admin_request(AdminRequest::ListDnas).await;
+        assert_matches!(res, AdminResponse::DnasListed(_));
+
+        // Each connection authenticates on its own
+        let res = token_api
+            .for_connection()
+            .handle_admin_request(AdminRequest::ListDnas)
+            .await;
+        assert_matches!(
+            res,
+            AdminResponse::Error(ExternalApiWireError::AdminUnauthorized(_))
+        );
+
+        // Signed challenge auth
+        let authorized_key = keystore.generate_sign_keypair_from_pure_entropy().await?;
+        let other_key = keystore.generate_sign_keypair_from_pure_entropy().await?;
+        let challenge_api = RealAdminInterfaceApi::with_access(
+            handle.clone(),
+            Some(AdminAuthConfig::SignedChallenge {
+                authorized_keys: vec![authorized_key.clone()],
+            }),
+            AdminPermission::Full,
+        );
+        let sign_challenge = |key: AgentPubKey, with_context: bool| {
+            let challenge_api = challenge_api.clone();
+            let keystore = keystore.clone();
+            async move {
+                let challenge = match challenge_api
+                    .handle_admin_request(AdminRequest::RequestAuthChallenge)
+                    .await
+                {
+                    AdminResponse::AuthChallengeIssued(challenge) => challenge,
+                    r => panic!("Unexpected response: {:?}", r),
+                };
+                let message = if with_context {
+                    AdminCredential::challenge_message(&challenge)
+                } else {
+                    challenge
+                };
+                let signature = keystore
+                    .sign(Sign::new_raw(key.clone(), message))
+                    .await
+                    .unwrap();
+                AdminCredential::SignedChallenge { key, signature }
+            }
+        };
+        let credential = sign_challenge(other_key, true).await;
+        let res = challenge_api
+            .handle_admin_request(AdminRequest::Authenticate(credential))
+            .await;
+        assert_matches!(
+            res,
+            AdminResponse::Error(ExternalApiWireError::AdminUnauthorized(_))
+        );
+        // The bare challenge isn't enough
+        let credential = sign_challenge(authorized_key.clone(), false).await;
+        let res = challenge_api
+            .handle_admin_request(AdminRequest::Authenticate(credential))
+            .await;
+        assert_matches!(
+            res,
+            AdminResponse::Error(ExternalApiWireError::AdminUnauthorized(_))
+        );
+        let credential = sign_challenge(authorized_key, true).await;
+        let res = challenge_api
+            .handle_admin_request(AdminRequest::Authenticate(credential.clone()))
+            .await;
+        assert_matches!(res, AdminResponse::Authenticated);
+        let res = challenge_api
+            .handle_admin_request(AdminRequest::ListDnas)
+            .await;
+        assert_matches!(res, AdminResponse::DnasListed(_));
+
+        // A challenge can't be used again
+        let res = challenge_api
+            .handle_admin_request(AdminRequest::Authenticate(credential))
+            .await;
+        assert_matches!(
+            res,
+            AdminResponse::Error(ExternalApiWireError::AdminUnauthorized(_))
+        );
+
+        // Read only permission
+        let read_only_api =
+            RealAdminInterfaceApi::with_access(handle.clone(), None, AdminPermission::ReadOnly);
+        let res = read_only_api
+            .handle_admin_request(AdminRequest::ListDnas)
+            .await;
+        assert_matches!(res, AdminResponse::DnasListed(_));
+        let res = read_only_api
+            .handle_admin_request(AdminRequest::UninstallApp {
+                installed_app_id: "app".to_string(),
+                delete_data: true,
+            })
+            .await;
+        assert_matches!(
+            res,
+            AdminResponse::Error(ExternalApiWireError::AdminUnauthorized(_))
+        );
+        // Dumps include private entries
+        let res = read_only_api
+            .handle_admin_request(AdminRequest::DumpState {
+                cell_id: Box::new(fake_cell_id(1)),
+            })
+            .await;
+        assert_matches!(
+            res,
+            AdminResponse::Error(ExternalApiWireError::AdminUnauthorized(_))
+        );
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(5), shutdown)
+            .await
+            .expect("The conductor should shut down")
+            .expect("The task manager should not panic");
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn attach_list_detach_app_interfaces() -> Result<()> {
+        observability::test_run().ok();
+        let envs = test_environments();
+        let handle = Conductor::builder().test(&envs).await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(handle.clone());
+
+        let port = match admin_api
+            .handle_admin_request(AdminRequest::AttachAppInterface {
+                port: None,
+                bind_address: None,
+                allowed_origins: None,
+                installed_app_id: None,
+            })
+            .await
+        {
+            AdminResponse::AppInterfaceAttached { port } => port,
+            r => panic!("Unexpected response: {:?}", r),
+        };
+        let interface_id = format!("interface-127.0.0.1:{}", port);
+        match admin_api
+            .handle_admin_request(AdminRequest::ListAppInterfaces)
+            .await
+        {
+            AdminResponse::AppInterfacesListed(interfaces) => {
+                assert_eq!(interfaces.len(), 1);
+                assert_eq!(interfaces[0].interface_id, interface_id);
+                assert_matches!(
+                    interfaces[0].driver,
+                    InterfaceDriver::Websocket { port: p, .. } if p == port
+                );
+                assert!(interfaces[0].signal_subscriptions.is_empty());
+            }
+            r => panic!("Unexpected response: {:?}", r),
+        }
+
+        let url = url2::url2!("ws://127.0.0.1:{}", port);
+        let config = Arc::new(holochain_websocket::WebsocketConfig::default());
+        let (_client, mut client_rx) =
+            holochain_websocket::websocket_connect(url.clone(), config.clone()).await?;
+
+        let res = admin_api
+            .handle_admin_request(AdminRequest::DetachAppInterface {
+                interface_id: interface_id.clone(),
+            })
+            .await;
+        assert_matches!(res, AdminResponse::AppInterfaceDetached);
+
+        // The connection is closed and the port is no longer listened on
+        tokio::time::timeout(std::time::Duration::from_secs(5), client_rx.next())
+            .await
+            .expect("connection wasn't closed");
+        assert!(holochain_websocket::websocket_connect(url, config)
+            .await
+            .is_err());
+
+        let res = admin_api
+            .handle_admin_request(AdminRequest::ListAppInterfaces)
+            .await;
+        assert_matches!(
+            res,
+            AdminResponse::AppInterfacesListed(interfaces) if interfaces.is_empty()
+        );
+        let res = admin_api
+            .handle_admin_request(AdminRequest::DetachAppInterface { interface_id })
+            .await;
+        assert_matches!(res, AdminResponse::Error(_));
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
+            .await
+            .ok();
+        Ok(())
+    }
+
+    async fn next_event(client_rx: &mut WebsocketReceiver) -> ConductorEvent {
+        let msg = tokio::time::timeout(std::time::Duration::from_secs(5), client_rx.next())
+            .await
+            .expect("no conductor event was sent")
+            .unwrap();
+        match msg {
+            WebsocketMessage::Signal(bytes) => ConductorEvent::try_from(bytes).unwrap(),
+            _ => panic!("Expected a conductor event signal"),
+        }
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn subscribe_to_conductor_events() -> Result<()> {
+        observability::test_run().ok();
+        let envs = test_environments();
+        let handle = Conductor::builder().test(&envs).await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        handle
+            .clone()
+            .add_admin_interfaces(vec![AdminInterfaceConfig::websocket(0)])
+            .await?;
+        let port = handle.get_arbitrary_admin_websocket_port().await.unwrap();
+
+        let url = url2::url2!("ws://127.0.0.1:{}", port);
+        let config = Arc::new(holochain_websocket::WebsocketConfig::default());
+        let (mut client, mut client_rx) =
+            holochain_websocket::websocket_connect(url, config).await?;
+        let response: AdminResponse = client
+            .request(AdminRequest::SubscribeConductorEvents)
+            .await?;
+        assert_matches!(response, AdminResponse::ConductorEventsSubscribed);
+
+        let dna = fake_dna_file(&Uuid::new_v4().to_string());
+        let cell_id = CellId::new(dna.dna_hash().clone(), fake_agent_pubkey_1());
+        handle.register_dna(dna).await?;
+        handle
+            .clone()
+            .install_app(
+                "test app".to_string(),
+                vec![(InstalledCell::new(cell_id.clone(), "nick".into()), None)],
+            )
+            .await?;
+        assert_eq!(
+            next_event(&mut client_rx).await,
+            ConductorEvent::CellGenesis {
+                cell_id,
+                error: None,
+            }
+        );
+
+        handle.activate_app("test app".to_string()).await?;
+        assert_eq!(
+            next_event(&mut client_rx).await,
+            ConductorEvent::AppStatusChanged {
+                installed_app_id: "test app".to_string(),
+                active: true,
+                reason: None,
+            }
+        );
+        handle.deactivate_app("test app".to_string()).await?;
+        assert_eq!(
+            next_event(&mut client_rx).await,
+            ConductorEvent::AppStatusChanged {
+                installed_app_id: "test app".to_string(),
+                active: false,
+                reason: None,
+            }
+        );
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
+            .await
+            .ok();
+        Ok(())
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn dna_read_parses() -> Result<()> {
         let uuid = Uuid::new_v4();
@@ -1,15 +1,21 @@
 use super::InterfaceApi;
+use crate::conductor::api::error::ConductorApiError;
 use crate::conductor::api::error::ConductorApiResult;
 use crate::conductor::api::error::ExternalApiWireError;
 use crate::conductor::api::error::SerializationError;
+use crate::conductor::error::ConductorError;
 use crate::conductor::interface::error::InterfaceError;
 use crate::conductor::interface::error::InterfaceResult;
 use crate::conductor::state::AppInterfaceId;
 use crate::conductor::ConductorHandle;
 
+use holochain_conductor_api::signal_subscription::SignalFilterSet;
+use holochain_keystore::KeystoreSenderExt;
 use holochain_serialized_bytes::prelude::*;
-
 use holochain_types::prelude::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
 
 pub use holochain_conductor_api::*;
 
@@ -22,6 +28,13 @@ pub trait AppInterfaceApi: 'static + Send + Sync + Clone {
         request: AppRequest,
     ) -> ConductorApiResult<AppResponse>;
 
+    /// A copy of this api for a new connection,
+    /// starting with no signal subscriptions.
+    fn for_connection(&self) -> Self;
+
+    /// Should this signal be sent over this connection?
+    async fn signal_allowed(&self, signal: &Signal) -> bool;
+
     // -- provided -- //
 
     /// Deal with error cases produced by `handle_app_request_inner`
@@ -41,6 +54,22 @@ pub trait AppInterfaceApi: 'static + Send + Sync + Clone {
 pub struct RealAppInterfaceApi {
     conductor_handle: ConductorHandle,
     interface_id: AppInterfaceId,
+    /// The app this interface is attached to, which crypto requests act for
+    installed_app_id: Option<InstalledAppId>,
+    /// The signal subscriptions made over this connection.
+    signal_subscriptions: Arc<parking_lot::RwLock<HashMap<InstalledAppId, AppSubscription>>>,
+}
+
+/// A signal subscription for an app, with the app's Cells
+/// as of the last time they were looked up.
+struct AppSubscription {
+    filters: SignalFilterSet,
+    /// The Cells the filters apply to
+    cells: HashSet<CellId>,
+    /// The [`ConductorHandleT::apps_version`] when `cells` was looked up
+    ///
+    /// [`ConductorHandleT::apps_version`]: crate::conductor::handle::ConductorHandleT::apps_version
+    apps_version: u64,
 }
 
 impl RealAppInterfaceApi {
@@ -49,7 +78,142 @@ impl RealAppInterfaceApi {
         Self {
             conductor_handle,
             interface_id,
+            installed_app_id: None,
+            signal_subscriptions: Default::default(),
+        }
+    }
+
+    /// Attach this interface to an app, so crypto requests can act for it
+    pub fn attached_to_app(self, installed_app_id: Option<InstalledAppId>) -> Self {
+        Self {
+            installed_app_id,
+            ..self
+        }
+    }
+
+    /// Info for an app which must be installed and active.
+    async fn active_app_info(
+        &self,
+        installed_app_id: &InstalledAppId,
+    ) -> ConductorApiResult<InstalledAppInfo> {
+        let app_info = self
+            .conductor_handle
+            .get_app_info(installed_app_id)
+            .await?
+            .ok_or_else(|| ConductorError::AppNotInstalled(installed_app_id.clone()))?;
+        if !app_info.active {
+            return Err(ConductorError::AppNotActive(installed_app_id.clone()).into());
         }
+        Ok(app_info)
+    }
+
+    /// Do crypto with the keystore on behalf of the app this interface is attached to.
+    /// The app is never taken from the request,
+    /// so a client can only use the keys of its own app.
+    async fn handle_crypto_request(
+        &self,
+        request: CryptoRequest,
+    ) -> ConductorApiResult<CryptoResponse> {
+        let installed_app_id = self
+            .installed_app_id
+            .clone()
+            .ok_or(ConductorApiError::InterfaceNotAttachedToApp)?;
+        let app_info = self.active_app_info(&installed_app_id).await?;
+        let keystore = self.conductor_handle.keystore();
+        match request {
+            CryptoRequest::Sign { key, data } => {
+                if !app_info
+                    .cell_data
+                    .iter()
+                    .any(|cell| cell.as_id().agent_pubkey() == &key)
+                {
+                    return Err(ConductorApiError::AgentNotInApp(key, installed_app_id));
+                }
+                let signature = keystore.sign(Sign::new_raw(key, data)).await?;
+                Ok(CryptoResponse::Signature(signature))
+            }
+            CryptoRequest::CreateX25519Keypair => {
+                let key = self
+                    .conductor_handle
+                    .create_app_x25519_keypair(&installed_app_id)
+                    .await?;
+                Ok(CryptoResponse::X25519PubKey(key))
+            }
+            CryptoRequest::Encrypt { input } => {
+                self.check_x25519_key(&installed_app_id, input.as_sender_ref())
+                    .await?;
+                let encrypted = keystore.x_25519_x_salsa20_poly1305_encrypt(input).await?;
+                Ok(CryptoResponse::Encrypted(encrypted))
+            }
+            CryptoRequest::Decrypt { input } => {
+                self.check_x25519_key(&installed_app_id, input.as_recipient_ref())
+                    .await?;
+                let decrypted = keystore.x_25519_x_salsa20_poly1305_decrypt(input).await?;
+                Ok(CryptoResponse::Decrypted(decrypted))
+            }
+        }
+    }
+
+    /// Fail unless this x25519 key was created for the app
+    async fn check_x25519_key(
+        &self,
+        installed_app_id: &InstalledAppId,
+        key: &X25519PubKey,
+    ) -> ConductorApiResult<()> {
+        if self
+            .conductor_handle
+            .app_has_x25519_key(installed_app_id, key)
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(ConductorApiError::X25519KeyNotInApp(
+                *key,
+                installed_app_id.clone(),
+            ))
+        }
+    }
+
+    /// The Cells of an app, or none if it isn't installed.
+    async fn app_cells(
+        &self,
+        installed_app_id: &InstalledAppId,
+    ) -> ConductorApiResult<HashSet<CellId>> {
+        Ok(self
+            .conductor_handle
+            .get_app_info(installed_app_id)
+            .await?
+            .map(|app_info| {
+                app_info
+                    .cell_data
+                    .into_iter()
+                    .map(|cell| cell.into_id())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Look up the Cells again for subscriptions made before apps last changed.
+    /// Fails if any can't be looked up, so no signal is let through
+    /// on an out of date view of the apps.
+    async fn refresh_subscribed_cells(&self) -> ConductorApiResult<()> {
+        let apps_version = self.conductor_handle.apps_version();
+        let stale: Vec<_> = self
+            .signal_subscriptions
+            .read()
+            .iter()
+            .filter(|(_, subscription)| subscription.apps_version != apps_version)
+            .map(|(installed_app_id, _)| installed_app_id.clone())
+            .collect();
+        for installed_app_id in stale {
+            let cells = self.app_cells(&installed_app_id).await?;
+            if let Some(subscription) = self.signal_subscriptions.write().get_mut(&installed_app_id)
+            {
+                subscription.cells = cells;
+                subscription.apps_version = apps_version;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -97,9 +261,62 @@ impl AppInterfaceApi for RealAppInterfaceApi {
                     Err(e) => Ok(AppResponse::Error(e.into())),
                 }
             }
-            AppRequest::SignalSubscription(_) => Ok(AppResponse::Unimplemented(request)),
-            AppRequest::Crypto(_) => Ok(AppResponse::Unimplemented(request)),
+            AppRequest::SignalSubscription(subscription) => {
+                // Read before the lookup, so an app changing during it is looked up again
+                let apps_version = self.conductor_handle.apps_version();
+                let app_info = self
+                    .conductor_handle
+                    .get_app_info(&subscription.installed_app_id)
+                    .await?
+                    .ok_or_else(|| {
+                        ConductorError::AppNotInstalled(subscription.installed_app_id.clone())
+                    })?;
+                self.signal_subscriptions.write().insert(
+                    subscription.installed_app_id.clone(),
+                    AppSubscription {
+                        filters: subscription.filters.clone(),
+                        cells: app_info
+                            .cell_data
+                            .into_iter()
+                            .map(|cell| cell.into_id())
+                            .collect(),
+                        apps_version,
+                    },
+                );
+                self.conductor_handle
+                    .put_signal_subscription(self.interface_id.clone(), subscription)
+                    .await?;
+                Ok(AppResponse::SignalSubscriptionUpdated)
+            }
+            AppRequest::Crypto(request) => Ok(AppResponse::Crypto(Box::new(
+                self.handle_crypto_request(*request).await?,
+            ))),
+        }
+    }
+
+    fn for_connection(&self) -> Self {
+        Self::new(self.conductor_handle.clone(), self.interface_id.clone())
+    }
+
+    async fn signal_allowed(&self, signal: &Signal) -> bool {
+        if let Err(e) = self.refresh_subscribed_cells().await {
+            tracing::warn!(
+                msg = "Failed to look up the cells of subscribed apps, dropping signal",
+                ?e
+            );
+            return false;
         }
+        self.signal_subscriptions
+            .read()
+            .values()
+            .all(|subscription| {
+                let applies = match signal {
+                    // Signals from other apps' cells are up to their own filters.
+                    Signal::App(cell_id, _, _) => subscription.cells.contains(cell_id),
+                    Signal::System(_) => true,
+                };
+                !applies || subscription.filters.allows(signal)
+            })
     }
 }
 
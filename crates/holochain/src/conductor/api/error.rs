@@ -7,6 +7,7 @@ use crate::conductor::CellError;
 use crate::core::ribosome::error::RibosomeError;
 use crate::core::workflow::error::WorkflowError;
 use holo_hash::DnaHash;
+use holochain_conductor_api::AdminPermission;
 use holochain_lmdb::error::DatabaseError;
 use holochain_state::source_chain::SourceChainError;
 use holochain_state::workspace::WorkspaceError;
@@ -90,6 +91,38 @@ pub enum ConductorApiError {
 
     #[error(transparent)]
     JsonDumpError(#[from] serde_json::Error),
+
+    /// The DPKI app could not be reached.
+    #[error("DPKI is not configured or the DPKI app is not active")]
+    DpkiUnavailable,
+
+    /// The DPKI app failed to handle a request.
+    #[error("The DPKI app failed to handle a request: {0}")]
+    DpkiError(String),
+
+    /// A crypto request used a key that doesn't belong to the app.
+    #[error("Agent {0} is not an agent of app {1}")]
+    AgentNotInApp(AgentPubKey, InstalledAppId),
+
+    /// A crypto request used an x25519 key that wasn't created for the app.
+    #[error("x25519 key {0:?} was not created for app {1}")]
+    X25519KeyNotInApp(X25519PubKey, InstalledAppId),
+
+    /// Crypto requests need to know which app they act for.
+    #[error("Crypto requests need an app interface attached to an app")]
+    InterfaceNotAttachedToApp,
+
+    /// The admin connection must authenticate before making this request.
+    #[error("This admin interface requires the connection to authenticate")]
+    AdminNotAuthenticated,
+
+    /// The admin interface doesn't allow this request.
+    #[error("This admin interface only allows {0:?} requests")]
+    AdminPermissionDenied(AdminPermission),
+
+    /// The credential given doesn't authenticate with this admin interface.
+    #[error("Admin authentication failed: {0}")]
+    AdminAuthFailed(String),
 }
 
 /// All the serialization errors that can occur
@@ -113,6 +146,16 @@ impl From<ConductorApiError> for ExternalApiWireError {
     fn from(err: ConductorApiError) -> Self {
         match err {
             ConductorApiError::DnaReadError(e) => ExternalApiWireError::DnaReadError(e),
+            e @ ConductorApiError::AgentNotInApp(_, _)
+            | e @ ConductorApiError::X25519KeyNotInApp(_, _)
+            | e @ ConductorApiError::InterfaceNotAttachedToApp => {
+                ExternalApiWireError::CryptoUnauthorized(e.to_string())
+            }
+            e @ ConductorApiError::AdminNotAuthenticated
+            | e @ ConductorApiError::AdminPermissionDenied(_)
+            | e @ ConductorApiError::AdminAuthFailed(_) => {
+                ExternalApiWireError::AdminUnauthorized(e.to_string())
+            }
             e => ExternalApiWireError::internal(e),
         }
     }
@@ -28,7 +28,13 @@ mock! {
 
         fn sync_autonomic_cue(&self, cue: AutonomicCue) -> ConductorApiResult<()>;
 
-        fn sync_dpki_request(&self, method: String, args: String) -> ConductorApiResult<String>;
+        fn sync_dpki_register_key(&self, key: AgentPubKey) -> ConductorApiResult<()>;
+
+        fn sync_dpki_key_state(
+            &self,
+            key: AgentPubKey,
+            at: Timestamp,
+        ) -> ConductorApiResult<Option<KeyState>>;
 
         fn mock_keystore(&self) -> &KeystoreSender;
         fn mock_signal_broadcaster(&self) -> SignalBroadcaster;
@@ -58,8 +64,16 @@ impl CellConductorApiT for MockCellConductorApi {
         self.sync_call_zome(cell_id, call)
     }
 
-    async fn dpki_request(&self, method: String, args: String) -> ConductorApiResult<String> {
-        self.sync_dpki_request(method, args)
+    async fn dpki_register_key(&self, key: AgentPubKey) -> ConductorApiResult<()> {
+        self.sync_dpki_register_key(key)
+    }
+
+    async fn dpki_key_state(
+        &self,
+        key: AgentPubKey,
+        at: Timestamp,
+    ) -> ConductorApiResult<Option<KeyState>> {
+        self.sync_dpki_key_state(key, at)
     }
 
     async fn autonomic_cue(&self, cue: AutonomicCue) -> ConductorApiResult<()> {
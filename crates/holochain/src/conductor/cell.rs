@@ -7,12 +7,14 @@
 use super::api::ZomeCall;
 use super::interface::SignalBroadcaster;
 use super::manager::ManagedTaskAdd;
+use super::manager::TaskGroup;
 use crate::conductor::api::error::ConductorApiError;
 use crate::conductor::api::CellConductorApi;
 use crate::conductor::api::CellConductorApiT;
 use crate::conductor::cell::error::CellResult;
 use crate::conductor::entry_def_store::get_entry_def_from_ids;
 use crate::conductor::handle::ConductorHandle;
+use crate::core::metrics::Metrics;
 use crate::core::queue_consumer::spawn_queue_consumer_tasks;
 use crate::core::queue_consumer::InitialQueueTriggers;
 use crate::core::queue_consumer::QueueTriggers;
@@ -22,6 +24,7 @@ use crate::core::ribosome::ZomeCallInvocation;
 use crate::core::workflow::call_zome_workflow;
 use crate::core::workflow::error::WorkflowError;
 use crate::core::workflow::genesis_workflow::genesis_workflow;
+use crate::core::workflow::import_source_chain_workflow::import_source_chain_workflow;
 use crate::core::workflow::incoming_dht_ops_workflow::incoming_dht_ops_workflow;
 use crate::core::workflow::initialize_zomes_workflow;
 use crate::core::workflow::produce_dht_ops_workflow::dht_op_light::light_to_op;
@@ -29,6 +32,8 @@ use crate::core::workflow::CallZomeWorkflowArgs;
 use crate::core::workflow::CallZomeWorkspace;
 use crate::core::workflow::GenesisWorkflowArgs;
 use crate::core::workflow::GenesisWorkspace;
+use crate::core::workflow::ImportSourceChainWorkflowArgs;
+use crate::core::workflow::ImportSourceChainWorkspace;
 use crate::core::workflow::InitializeZomesWorkflowArgs;
 use crate::core::workflow::ZomeCallResult;
 use call_zome_workflow::call_zome_workspace_lock::CallZomeWorkspaceLock;
@@ -102,6 +107,8 @@ where
     env: EnvironmentWrite,
     holochain_p2p_cell: P2pCell,
     queue_triggers: QueueTriggers,
+    /// The Cell's queue consumers
+    tasks: TaskGroup,
 }
 
 impl Cell {
@@ -120,6 +127,8 @@ impl Cell {
         holochain_p2p_cell: holochain_p2p::HolochainP2pCell,
         managed_task_add_sender: sync::mpsc::Sender<ManagedTaskAdd>,
         managed_task_stop_broadcaster: sync::broadcast::Sender<()>,
+        validation_workers: usize,
+        metrics: Metrics,
     ) -> CellResult<(Self, InitialQueueTriggers)> {
         let conductor_api = CellConductorApi::new(conductor_handle.clone(), id.clone());
 
@@ -134,12 +143,16 @@ impl Cell {
                 let mut network = holochain_p2p_cell.clone();
                 async move { network.join().await }
             });
+            let tasks = TaskGroup::new();
             let (queue_triggers, initial_queue_triggers) = spawn_queue_consumer_tasks(
                 &env,
                 holochain_p2p_cell.clone(),
                 conductor_api.clone(),
                 managed_task_add_sender,
                 managed_task_stop_broadcaster,
+                &tasks,
+                validation_workers,
+                metrics.add_cell(id.clone(), env.clone().into()),
             )
             .await;
 
@@ -150,6 +163,7 @@ impl Cell {
                     env,
                     holochain_p2p_cell,
                     queue_triggers,
+                    tasks,
                 },
                 initial_queue_triggers,
             ))
@@ -190,6 +204,35 @@ impl Cell {
         Ok(())
     }
 
+    /// Performs the Import Source Chain workflow for the Cell, committing the
+    /// exported elements instead of fresh genesis elements. The exported chain
+    /// must have been authored by this Cell's agent on this Cell's Dna.
+    pub async fn import_source_chain(
+        id: CellId,
+        conductor_handle: ConductorHandle,
+        cell_env: EnvironmentWrite,
+        export: SourceChainExport,
+    ) -> CellResult<()> {
+        let conductor_api = CellConductorApi::new(conductor_handle, id.clone());
+
+        let workspace = ImportSourceChainWorkspace::new(cell_env.clone().into())
+            .await
+            .map_err(ConductorApiError::from)
+            .map_err(Box::new)?;
+        let args = ImportSourceChainWorkflowArgs::new(
+            id.dna_hash().clone(),
+            id.agent_pubkey().clone(),
+            export,
+        );
+
+        import_source_chain_workflow(workspace, cell_env.clone().into(), conductor_api, args)
+            .await
+            .map_err(Box::new)
+            .map_err(ConductorApiError::from)
+            .map_err(Box::new)?;
+        Ok(())
+    }
+
     fn dna_hash(&self) -> &DnaHash {
         &self.id.dna_hash()
     }
@@ -209,6 +252,11 @@ impl Cell {
         &self.holochain_p2p_cell
     }
 
+    /// Stop the Cell's queue consumers and wait until they have finished
+    pub(super) async fn stop_tasks(&self) {
+        self.tasks.stop().await
+    }
+
     async fn signal_broadcaster(&self) -> SignalBroadcaster {
         self.conductor_api.signal_broadcaster().await
     }
@@ -465,7 +513,7 @@ impl Cell {
         if header.author() == self.id.agent_pubkey() {
             validation_package::get_as_author(
                 header,
-                env,
+                self.env.clone(),
                 &ribosome,
                 &self.conductor_api,
                 &self.holochain_p2p_cell,
@@ -35,8 +35,15 @@ async fn test_cell_handle_publish() {
         .await
         .unwrap();
 
-    let (add_task_sender, shutdown) = spawn_task_manager();
+    let (event_tx, _) = sync::broadcast::channel(1);
     let (stop_tx, _) = sync::broadcast::channel(1);
+    let (deactivate_cell_tx, _deactivate_cell_rx) = sync::mpsc::unbounded_channel();
+    let (add_task_sender, shutdown) = spawn_task_manager(
+        event_tx,
+        stop_tx.clone(),
+        Default::default(),
+        deactivate_cell_tx,
+    );
 
     let (cell, _) = super::Cell::create(
         cell_id,
@@ -45,6 +52,8 @@ async fn test_cell_handle_publish() {
         holochain_p2p_cell,
         add_task_sender,
         stop_tx.clone(),
+        1,
+        Default::default(),
     )
     .await
     .unwrap();
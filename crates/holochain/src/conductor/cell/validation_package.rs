@@ -1,5 +1,6 @@
 use call_zome_workflow::CallZomeWorkspaceLock;
 use holochain_lmdb::env::EnvironmentRead;
+use holochain_lmdb::env::EnvironmentWrite;
 use holochain_lmdb::error::DatabaseResult;
 use holochain_lmdb::prelude::*;
 use holochain_p2p::HolochainP2pCell;
@@ -50,7 +51,7 @@ impl ValidationPackageDb {
 #[instrument(skip(header_hashed, env, ribosome, conductor_api, network))]
 pub(super) async fn get_as_author(
     header_hashed: HeaderHashed,
-    env: EnvironmentRead,
+    env: EnvironmentWrite,
     ribosome: &impl RibosomeT,
     conductor_api: &impl CellConductorApiT,
     network: &HolochainP2pCell,
@@ -58,7 +59,7 @@ pub(super) async fn get_as_author(
     let header = header_hashed.as_content();
 
     // Get the source chain with public data only
-    let source_chain = SourceChain::public_only(env.clone())?;
+    let source_chain = SourceChain::public_only(env.clone().into())?;
 
     // Get the header data
     let (app_entry_type, header_seq) = match header
@@ -80,13 +81,13 @@ pub(super) async fn get_as_author(
     .await?;
 
     // Get the required validation package
-    let required_validation_type = match entry_def {
-        Some(ed) => ed.required_validation_type,
+    let entry_def = match entry_def {
+        Some(ed) => ed,
         None => return Ok(None.into()),
     };
 
     // Gather the package
-    match required_validation_type {
+    match entry_def.required_validation_type {
         RequiredValidationType::Element => {
             // TODO: I'm not sure if we should handle this case, it seems like they should already have the element
             Ok(None.into())
@@ -101,10 +102,11 @@ pub(super) async fn get_as_author(
             Ok(Some(get_as_author_full(header_seq, &source_chain)?).into())
         }
         RequiredValidationType::Custom => {
-            let element_authored = ElementBuf::authored(env.clone(), false)?;
-            let meta_authored = MetadataBuf::authored(env.clone())?;
-            let mut element_cache = ElementBuf::cache(env.clone())?;
-            let mut meta_cache = MetadataBuf::cache(env.clone())?;
+            let env_read: EnvironmentRead = env.clone().into();
+            let element_authored = ElementBuf::authored(env_read.clone(), false)?;
+            let meta_authored = MetadataBuf::authored(env_read.clone())?;
+            let mut element_cache = ElementBuf::cache(env_read.clone())?;
+            let mut meta_cache = MetadataBuf::cache(env_read.clone())?;
             let cascade = Cascade::empty()
                 .with_cache(DbPairMut::new(&mut element_cache, &mut meta_cache))
                 .with_authored(DbPair::new(&element_authored, &meta_authored));
@@ -112,15 +114,20 @@ pub(super) async fn get_as_author(
             if let Some(elements) =
                 cascade.get_validation_package_local(&header_hashed.as_hash())?
             {
-                return Ok(Some(ValidationPackage::new(elements)).into());
+                return Ok(Some(public_only(ValidationPackage::new(elements))).into());
             }
 
-            let workspace_lock = CallZomeWorkspaceLock::new(CallZomeWorkspace::new(env)?);
-            let result =
-                match get_as_author_custom(&header_hashed, ribosome, network, workspace_lock)? {
-                    Some(result) => result,
-                    None => return Ok(None.into()),
-                };
+            let workspace_lock = CallZomeWorkspaceLock::new(CallZomeWorkspace::new(env_read)?);
+            let result = match get_as_author_custom(
+                &header_hashed,
+                entry_def.id,
+                ribosome,
+                network,
+                workspace_lock,
+            )? {
+                Some(result) => result,
+                None => return Ok(None.into()),
+            };
             match result {
                 ValidationPackageResult::Success(validation_package) => {
                     // Cache the package for future calls
@@ -131,8 +138,10 @@ pub(super) async fn get_as_author(
                             .iter()
                             .map(|el| el.header_address().clone()),
                     );
+                    env.with_commit_retry(|writer| meta_cache.flush_to_txn_ref(writer))
+                        .await?;
 
-                    Ok(Some(validation_package).into())
+                    Ok(Some(public_only(validation_package)).into())
                 }
                 ValidationPackageResult::Fail(reason) => {
                     warn!(
@@ -162,6 +171,29 @@ pub(super) async fn get_as_author(
     }
 }
 
+/// The callback reads the whole source chain, so private entries
+/// are taken out before the package is sent to anyone else
+fn public_only(validation_package: ValidationPackage) -> ValidationPackage {
+    ValidationPackage::new(
+        validation_package
+            .0
+            .into_iter()
+            .map(|el| {
+                let private = matches!(
+                    el.header().entry_type().map(|et| et.visibility()),
+                    Some(EntryVisibility::Private)
+                );
+                if private {
+                    let (shh, _) = el.into_inner();
+                    Element::new(shh, None)
+                } else {
+                    el
+                }
+            })
+            .collect(),
+    )
+}
+
 pub(super) async fn get_as_authority(
     header: HeaderHashed,
     env: EnvironmentRead,
@@ -257,3 +289,36 @@ pub(super) async fn get_as_authority(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::public_only;
+    use crate::fixt::*;
+    use ::fixt::prelude::*;
+    use holochain_types::prelude::*;
+
+    fn element(visibility: EntryVisibility) -> Element {
+        let mut create = fixt!(Create);
+        create.entry_type = EntryType::App(AppEntryType::new(0.into(), 0.into(), visibility));
+        let header = HeaderHashed::from_content_sync(create.into());
+        Element::new(
+            SignedHeaderHashed::with_presigned(header, fixt!(Signature)),
+            Some(fixt!(Entry)),
+        )
+    }
+
+    #[test]
+    fn private_entries_are_left_out_of_the_package() {
+        let public = element(EntryVisibility::Public);
+        let private = element(EntryVisibility::Private);
+        let package = public_only(ValidationPackage::new(vec![
+            public.clone(),
+            private.clone(),
+        ]));
+
+        assert_eq!(package.0.len(), 2);
+        assert_eq!(package.0[0], public);
+        assert_eq!(package.0[1].header_address(), private.header_address());
+        assert_eq!(*package.0[1].entry(), ElementEntry::Hidden);
+    }
+}
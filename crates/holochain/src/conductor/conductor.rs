@@ -12,6 +12,8 @@ use super::api::RealAdminInterfaceApi;
 use super::api::RealAppInterfaceApi;
 use super::config::AdminInterfaceConfig;
 use super::config::InterfaceDriver;
+use super::config::LmdbMapSizeConfig;
+use super::config::PassphraseServiceConfig;
 use super::dna_store::DnaDefBuf;
 use super::dna_store::RealDnaStore;
 use super::entry_def_store::get_entry_defs;
@@ -20,6 +22,8 @@ use super::error::ConductorError;
 use super::error::CreateAppError;
 use super::handle::ConductorHandleImpl;
 use super::interface::error::InterfaceResult;
+use super::interface::metrics::bind_metrics_endpoint;
+use super::interface::metrics::spawn_metrics_endpoint_task;
 use super::interface::websocket::spawn_admin_interface_task;
 use super::interface::websocket::spawn_app_interface_task;
 use super::interface::websocket::spawn_websocket_listener;
@@ -28,7 +32,12 @@ use super::interface::SignalBroadcaster;
 use super::manager::keep_alive_task;
 use super::manager::spawn_task_manager;
 use super::manager::ManagedTaskAdd;
+use super::manager::ManagedTaskError;
 use super::manager::ManagedTaskHandle;
+use super::manager::ManagedTaskResult;
+use super::manager::ManagedTaskStatuses;
+use super::manager::RestartPolicy;
+use super::manager::SpawnTask;
 use super::manager::TaskManagerRunHandle;
 use super::p2p_store;
 use super::p2p_store::all_agent_infos;
@@ -45,16 +54,26 @@ use crate::conductor::cell::Cell;
 use crate::conductor::config::ConductorConfig;
 use crate::conductor::error::ConductorResult;
 use crate::conductor::handle::ConductorHandle;
+use crate::core::metrics::Metrics;
 use crate::core::queue_consumer::InitialQueueTriggers;
 use crate::core::workflow::integrate_dht_ops_workflow;
 pub use builder::*;
 use fallible_iterator::FallibleIterator;
 use futures::future;
-use futures::future::TryFutureExt;
+use futures::future::FutureExt;
 use futures::stream::StreamExt;
 use holo_hash::DnaHash;
+use holochain_conductor_api::signal_subscription::SignalSubscription;
+use holochain_conductor_api::AppInterfaceInfo;
+use holochain_conductor_api::AppStatusFilter;
+use holochain_conductor_api::ConductorEvent;
+use holochain_conductor_api::InstalledAppInfo;
 use holochain_conductor_api::JsonDump;
+use holochain_conductor_api::ManagedTaskInfo;
 use holochain_keystore::lair_keystore::spawn_lair_keystore;
+use holochain_keystore::lair_keystore::PassphraseService;
+use holochain_keystore::remote_keystore::spawn_remote_keystore;
+use holochain_keystore::remote_keystore::RemoteKeystoreConfig;
 use holochain_keystore::test_keystore::spawn_test_keystore;
 use holochain_keystore::KeystoreSender;
 use holochain_keystore::KeystoreSenderExt;
@@ -64,15 +83,20 @@ use holochain_lmdb::buffer::KvStoreT;
 use holochain_lmdb::db;
 use holochain_lmdb::env::EnvironmentKind;
 use holochain_lmdb::env::EnvironmentWrite;
+use holochain_lmdb::env::MapSize;
 use holochain_lmdb::env::ReadManager;
 use holochain_lmdb::exports::SingleStore;
 use holochain_lmdb::fresh_reader;
 use holochain_lmdb::prelude::*;
 use holochain_state::source_chain::SourceChainBuf;
+use holochain_state::source_chain::SourceChainExport;
 use holochain_state::wasm::WasmBuf;
 use holochain_types::prelude::*;
 use kitsune_p2p::agent_store::AgentInfoSigned;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::sync::RwLock;
@@ -99,8 +123,49 @@ where
     _state: CellState,
 }
 
+/// The peer counts of each Dna's network, shared with the tasks counting them
+#[derive(Clone, Default)]
+struct PeerCounts {
+    /// The last count sent for each Dna,
+    /// so that events are only sent when it changes
+    sent: Arc<parking_lot::Mutex<HashMap<DnaHash, usize>>>,
+    /// The Dnas with a count waiting to run
+    pending: Arc<parking_lot::Mutex<HashSet<DnaHash>>>,
+}
+
+impl PeerCounts {
+    /// Record a count, sending an event if it has changed
+    fn record(&self, events: &ConductorEventBroadcaster, dna_hash: DnaHash, peer_count: usize) {
+        let last_count = self.sent.lock().insert(dna_hash.clone(), peer_count);
+        if last_count != Some(peer_count) {
+            // It's fine if nobody is subscribed
+            events
+                .send(ConductorEvent::PeerCountChanged {
+                    dna_hash,
+                    peer_count,
+                })
+                .ok();
+        }
+    }
+}
+
 pub type StopBroadcaster = tokio::sync::broadcast::Sender<()>;
 pub type StopReceiver = tokio::sync::broadcast::Receiver<()>;
+pub type ConductorEventBroadcaster = tokio::sync::broadcast::Sender<ConductorEvent>;
+
+/// Number of conductor events in buffer before
+/// slow admin connections start missing them.
+const CONDUCTOR_EVENT_BUFFER_SIZE: usize = 100;
+
+/// How long to wait before restarting an interface which failed the first time
+const INTERFACE_RESTART_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// The longest to wait before restarting an interface which keeps failing
+const INTERFACE_MAX_RESTART_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How long after agent infos arrive for a Dna its peers are counted,
+/// so a burst of gossip only scans the peer store once.
+const PEER_COUNT_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
 
 /// A Conductor is a group of [Cell]s
 pub struct Conductor<DS = RealDnaStore, CA = CellConductorApi>
@@ -141,10 +206,24 @@ where
     /// By sending on this channel,
     managed_task_stop_broadcaster: StopBroadcaster,
 
+    /// Channel on which to send events to subscribed admin connections
+    conductor_event_broadcaster: ConductorEventBroadcaster,
+
+    /// The last peer count sent for each Dna,
+    /// and the Dnas waiting to be counted again
+    peer_counts: PeerCounts,
+
     /// The main task join handle to await on.
     /// The conductor is intended to live as long as this task does.
     task_manager_run_handle: Option<TaskManagerRunHandle>,
 
+    /// The status of the tasks the TaskManager looks after
+    managed_task_statuses: ManagedTaskStatuses,
+
+    /// Requests from the TaskManager to deactivate the apps running a Cell
+    /// whose tasks keep failing, taken when the conductor is built
+    deactivate_cell_rx: Option<mpsc::UnboundedReceiver<(CellId, String)>>,
+
     /// Placeholder for what will be the real DNA/Wasm cache
     dna_store: DS,
 
@@ -154,6 +233,15 @@ where
     /// The root environment directory where all environments are created
     root_env_dir: EnvironmentRootPath,
 
+    /// The initial and maximum map size for new cell environments
+    cell_map_size: MapSize,
+
+    /// The number of ops each cell validates concurrently
+    validation_workers: usize,
+
+    /// Workflow and limbo metrics of every running cell
+    metrics: Metrics,
+
     /// Handle to the network actor.
     holochain_p2p: holochain_p2p::HolochainP2pRef,
 }
@@ -234,6 +322,15 @@ where
         self.task_manager_run_handle.take()
     }
 
+    /// The status of every task the TaskManager looks after
+    pub(super) fn list_managed_tasks(&self) -> Vec<ManagedTaskInfo> {
+        self.managed_task_statuses
+            .lock()
+            .values()
+            .cloned()
+            .collect()
+    }
+
     /// Spawn all admin interface tasks, register them with the TaskManager,
     /// and modify the conductor accordingly, based on the config passed in
     pub(super) async fn add_admin_interfaces_via_handle(
@@ -244,26 +341,50 @@ where
     where
         DS: DnaStore + 'static,
     {
-        let admin_api = RealAdminInterfaceApi::new(handle);
         let stop_tx = self.managed_task_stop_broadcaster.clone();
+        let event_tx = self.conductor_event_broadcaster.clone();
 
         // Closure to process each admin config item
-        let spawn_from_config = |AdminInterfaceConfig { driver, .. }| {
-            let admin_api = admin_api.clone();
+        let spawn_from_config = |config: AdminInterfaceConfig| {
+            let AdminInterfaceConfig {
+                driver,
+                auth,
+                permission,
+            } = config;
+            let admin_api = RealAdminInterfaceApi::with_access(handle.clone(), auth, permission);
             let stop_tx = stop_tx.clone();
+            let event_tx = event_tx.clone();
             async move {
-                match driver {
-                    InterfaceDriver::Websocket { port } => {
-                        let listener = spawn_websocket_listener(port).await?;
-                        let port = listener.local_addr().port().unwrap_or(port);
-                        let handle: ManagedTaskHandle = spawn_admin_interface_task(
-                            listener,
-                            admin_api.clone(),
-                            stop_tx.subscribe(),
-                        )?;
-                        InterfaceResult::Ok((port, handle))
-                    }
-                }
+                let listener = spawn_websocket_listener(&driver).await?;
+                // Unix socket interfaces have no port
+                let port = listener.local_addr().port();
+                let handle: ManagedTaskHandle = spawn_admin_interface_task(
+                    listener,
+                    admin_api.clone(),
+                    event_tx.clone(),
+                    stop_tx.subscribe(),
+                )?;
+                // A restart binds the same port, even if any port was asked for
+                let driver = bound_driver(driver, port);
+                let name = format!("admin_interface-{}", interface_address(&driver));
+                let respawn: SpawnTask = Arc::new(move |stop_rx| {
+                    let driver = driver.clone();
+                    let admin_api = admin_api.clone();
+                    let event_tx = event_tx.clone();
+                    tokio::spawn(async move {
+                        let listener = spawn_websocket_listener(&driver).await?;
+                        spawn_admin_interface_task(listener, admin_api, event_tx, stop_rx)?.await?
+                    })
+                });
+                InterfaceResult::Ok((
+                    port,
+                    ManagedTaskAdd::with_restart_policy(
+                        name,
+                        handle,
+                        interface_restart_policy(),
+                        respawn,
+                    ),
+                ))
             }
         };
 
@@ -282,24 +403,17 @@ where
 
             // First, register the keepalive task, to ensure the conductor doesn't shut down
             // in the absence of other "real" tasks
-            self.manage_task(ManagedTaskAdd::dont_handle(tokio::spawn(keep_alive_task(
-                stop_tx.subscribe(),
-            ))))
+            self.manage_task(ManagedTaskAdd::dont_handle(
+                "keep_alive",
+                tokio::spawn(keep_alive_task(stop_tx.subscribe())),
+            ))
             .await?;
 
-            // Now that tasks are spawned, register them with the TaskManager
-            for (port, handle) in handles {
-                ports.push(port);
-                self.manage_task(ManagedTaskAdd::new(
-                    handle,
-                    Box::new(|result| {
-                        result.unwrap_or_else(|e| {
-                            error!(error = &e as &dyn std::error::Error, "Interface died")
-                        });
-                        None
-                    }),
-                ))
-                .await?
+            // Now that tasks are spawned, register them with the TaskManager,
+            // which restarts them if they fail
+            for (port, task) in handles {
+                ports.extend(port);
+                self.manage_task(task).await?
             }
             for p in ports {
                 self.add_admin_port(p);
@@ -310,28 +424,68 @@ where
 
     pub(super) async fn add_app_interface_via_handle(
         &mut self,
-        port: u16,
+        driver: InterfaceDriver,
+        installed_app_id: Option<InstalledAppId>,
         handle: ConductorHandle,
-    ) -> ConductorResult<u16> {
-        let interface_id: AppInterfaceId = format!("interface-{}", port).into();
-        let app_api = RealAppInterfaceApi::new(handle, interface_id.clone());
+    ) -> ConductorResult<Option<u16>> {
+        let listener = spawn_websocket_listener(&driver).await.map_err(Box::new)?;
+        // Unix socket interfaces have no port
+        let port = listener.local_addr().port();
+        // A restart binds the same port, even if any port was asked for
+        let driver = bound_driver(driver, port);
+        let interface_id = app_interface_id(&driver);
+        if self.app_interfaces.contains_key(&interface_id) {
+            return Err(ConductorError::AppInterfaceIdCollision(interface_id));
+        }
+        let app_api = RealAppInterfaceApi::new(handle, interface_id.clone())
+            .attached_to_app(installed_app_id.clone());
         // This receiver is thrown away because we can produce infinite new
         // receivers from the Sender
         let (signal_tx, _r) = tokio::sync::broadcast::channel(SIGNAL_BUFFER_SIZE);
         let stop_rx = self.managed_task_stop_broadcaster.subscribe();
-        let (port, task) = spawn_app_interface_task(port, app_api, signal_tx.clone(), stop_rx)
-            .await
-            .map_err(Box::new)?;
-        // TODO: RELIABILITY: Handle this task by restarting it if it fails and log the error
-        self.manage_task(ManagedTaskAdd::dont_handle(task)).await?;
-        let interface = AppInterfaceRuntime::Websocket { signal_tx };
-
-        if self.app_interfaces.contains_key(&interface_id) {
-            return Err(ConductorError::AppInterfaceIdCollision(interface_id));
-        }
+        let (detach_tx, detach_rx) = tokio::sync::broadcast::channel(1);
+        let task = spawn_app_interface_task(
+            listener,
+            app_api.clone(),
+            signal_tx.clone(),
+            stop_rx,
+            detach_rx,
+        )
+        .map_err(Box::new)?;
+        let respawn: SpawnTask = {
+            let driver = driver.clone();
+            let signal_tx = signal_tx.clone();
+            let detach_tx = detach_tx.clone();
+            Arc::new(move |stop_rx| {
+                let driver = driver.clone();
+                let app_api = app_api.clone();
+                let signal_tx = signal_tx.clone();
+                let detach_rx = detach_tx.subscribe();
+                tokio::spawn(async move {
+                    let listener = spawn_websocket_listener(&driver).await?;
+                    spawn_app_interface_task(listener, app_api, signal_tx, stop_rx, detach_rx)?
+                        .await?
+                })
+            })
+        };
+        self.manage_task(ManagedTaskAdd::with_restart_policy(
+            format!("app_{}", interface_id),
+            task,
+            interface_restart_policy(),
+            respawn,
+        ))
+        .await?;
+        let interface = AppInterfaceRuntime::Websocket {
+            signal_tx,
+            detach_tx,
+        };
 
         self.app_interfaces.insert(interface_id.clone(), interface);
-        let config = AppInterfaceConfig::websocket(port);
+        // The driver with the bound port is saved, so a restart binds the same one
+        let config = AppInterfaceConfig {
+            installed_app_id,
+            ..AppInterfaceConfig::new(driver)
+        };
         self.update_state(|mut state| {
             state.app_interfaces.insert(interface_id, config);
             Ok(state)
@@ -340,6 +494,64 @@ where
         Ok(port)
     }
 
+    /// List the app interfaces in state, which are started on startup
+    pub(super) async fn list_app_interfaces(&self) -> ConductorResult<Vec<AppInterfaceInfo>> {
+        Ok(self
+            .get_state()
+            .await?
+            .app_interfaces
+            .into_iter()
+            .map(|(id, config)| AppInterfaceInfo {
+                interface_id: id.to_string(),
+                driver: config.driver,
+                signal_subscriptions: config.signal_subscriptions,
+                installed_app_id: config.installed_app_id,
+            })
+            .collect())
+    }
+
+    /// Save the latest signal subscription made over an app interface.
+    /// Interfaces which aren't in state, like those made for tests,
+    /// have nowhere to save it.
+    pub(super) async fn put_signal_subscription(
+        &self,
+        interface_id: AppInterfaceId,
+        subscription: SignalSubscription,
+    ) -> ConductorResult<()> {
+        self.update_state(move |mut state| {
+            if let Some(config) = state.app_interfaces.get_mut(&interface_id) {
+                config
+                    .signal_subscriptions
+                    .insert(subscription.installed_app_id.clone(), subscription);
+            }
+            Ok(state)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Stop an app interface and remove it from state
+    pub(super) async fn remove_app_interface(
+        &mut self,
+        interface_id: AppInterfaceId,
+    ) -> ConductorResult<()> {
+        self.update_state({
+            let interface_id = interface_id.clone();
+            move |mut state| {
+                state
+                    .app_interfaces
+                    .remove(&interface_id)
+                    .ok_or(ConductorError::AppInterfaceNotFound(interface_id))?;
+                Ok(state)
+            }
+        })
+        .await?;
+        if let Some(interface) = self.app_interfaces.remove(&interface_id) {
+            interface.detach();
+        }
+        Ok(())
+    }
+
     pub(super) async fn register_dna_wasm(
         &self,
         dna: DnaFile,
@@ -373,25 +585,71 @@ where
 
     /// Start all app interfaces currently in state.
     /// This should only be run at conductor initialization.
-    #[allow(irrefutable_let_patterns)]
     pub(super) async fn startup_app_interfaces_via_handle(
         &mut self,
         handle: ConductorHandle,
     ) -> ConductorResult<()> {
-        for i in self.get_state().await?.app_interfaces.values() {
+        for (id, i) in self.get_state().await?.app_interfaces {
             tracing::debug!("Starting up app interface: {:?}", i);
-            let port = if let InterfaceDriver::Websocket { port } = i.driver {
-                port
-            } else {
-                unreachable!()
-            };
             let _ = self
-                .add_app_interface_via_handle(port, handle.clone())
+                .add_app_interface_via_handle(
+                    i.driver.clone(),
+                    i.installed_app_id.clone(),
+                    handle.clone(),
+                )
                 .await?;
+            // Interfaces saved before ids included the bind address
+            // were just saved again under their new id
+            if id != app_interface_id(&i.driver) {
+                self.update_state(move |mut state| {
+                    state.app_interfaces.remove(&id);
+                    Ok(state)
+                })
+                .await?;
+            }
         }
         Ok(())
     }
 
+    /// Send an event to the admin connections subscribed to conductor events
+    pub(super) fn emit_event(&self, event: ConductorEvent) {
+        // It's fine if nobody is subscribed
+        self.conductor_event_broadcaster.send(event).ok();
+    }
+
+    /// Record the number of peers in the network of a Dna,
+    /// sending an event if it has changed
+    pub(super) fn update_peer_count(&self, dna_hash: DnaHash, peer_count: usize) {
+        self.peer_counts
+            .record(&self.conductor_event_broadcaster, dna_hash, peer_count);
+    }
+
+    /// Count the peers in the network of a Dna after [`PEER_COUNT_DELAY`],
+    /// unless a count is already waiting, then record it
+    pub(super) fn schedule_peer_count(&self, dna_hash: DnaHash) {
+        if !self.peer_counts.pending.lock().insert(dna_hash.clone()) {
+            return;
+        }
+        let peer_counts = self.peer_counts.clone();
+        let events = self.conductor_event_broadcaster.clone();
+        let env = self.p2p_env();
+        let mut stop_rx = self.managed_task_stop_broadcaster.subscribe();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::time::delay_for(PEER_COUNT_DELAY) => {}
+                _ = stop_rx.recv() => return,
+            }
+            // Agent infos arriving from here on schedule another count
+            peer_counts.pending.lock().remove(&dna_hash);
+            let space = Arc::new(holochain_p2p::space_holo_to_kit(dna_hash.clone()));
+            // Expired peers are removed by the query
+            match p2p_store::query_agent_info_signed(env, space) {
+                Ok(agent_infos) => peer_counts.record(&events, dna_hash, agent_infos.len()),
+                Err(e) => warn!(error = ?e, "Failed to count peers"),
+            }
+        });
+    }
+
     pub(super) fn signal_broadcaster(&self) -> SignalBroadcaster {
         SignalBroadcaster::new(
             self.app_interfaces
@@ -402,69 +660,27 @@ where
         )
     }
 
-    /// Perform Genesis on the source chains for each of the specified CellIds.
-    ///
-    /// If genesis fails for any cell, this entire function fails, and all other
-    /// partial or complete successes are rolled back.
-    pub(super) async fn genesis_cells(
-        &self,
-        cell_ids_with_proofs: Vec<(CellId, Option<MembraneProof>)>,
-        conductor_handle: ConductorHandle,
-    ) -> ConductorResult<()> {
-        let root_env_dir = std::path::PathBuf::from(self.root_env_dir.clone());
-        let keystore = self.keystore.clone();
-
-        let cells_tasks = cell_ids_with_proofs.into_iter().map(|(cell_id, proof)| {
-            let root_env_dir = root_env_dir.clone();
-            let keystore = self.keystore.clone();
-            let conductor_handle = conductor_handle.clone();
-            let cell_id_inner = cell_id.clone();
-            tokio::spawn(async move {
-                let env = EnvironmentWrite::new(
-                    &root_env_dir,
-                    EnvironmentKind::Cell(cell_id_inner.clone()),
-                    keystore.clone(),
-                )?;
-                Cell::genesis(cell_id_inner, conductor_handle, env, proof).await
-            })
-            .map_err(CellError::from)
-            .and_then(|result| async move { result.map(|_| cell_id) })
-        });
-        let (success, errors): (Vec<_>, Vec<_>) = futures::future::join_all(cells_tasks)
-            .await
-            .into_iter()
-            .partition(Result::is_ok);
-
-        // unwrap safe because of the partition
-        let success = success.into_iter().map(Result::unwrap);
-
-        // If there were errors, cleanup and return the errors
-        if !errors.is_empty() {
-            for cell_id in success {
-                let env = EnvironmentWrite::new(
-                    &root_env_dir,
-                    EnvironmentKind::Cell(cell_id),
-                    keystore.clone(),
-                )?;
-                env.remove().await?;
-            }
-
-            // match needed to avoid Debug requirement on unwrap_err
-            let errors = errors
-                .into_iter()
-                .map(|e| match e {
-                    Err(e) => e,
-                    Ok(_) => unreachable!("Safe because of the partition"),
-                })
-                .collect();
-
-            Err(ConductorError::GenesisFailed { errors })
-        } else {
-            // No errors so return the cells
-            Ok(())
+    /// What's needed to set up new cell environments,
+    /// so it can be done without holding the conductor lock
+    pub(super) fn cell_env_setup(&self) -> CellEnvSetup {
+        CellEnvSetup {
+            root_env_dir: std::path::PathBuf::from(self.root_env_dir.clone()),
+            keystore: self.keystore.clone(),
+            cell_map_size: self.cell_map_size,
+            conductor_event_broadcaster: self.conductor_event_broadcaster.clone(),
         }
     }
 
+    /// Export the authored source chain of an active Cell
+    pub(super) async fn export_source_chain(
+        &self,
+        cell_id: &CellId,
+    ) -> ConductorApiResult<SourceChainExport> {
+        let cell = self.cell_by_id(cell_id)?;
+        let source_chain = SourceChainBuf::new(cell.env().clone().into())?;
+        Ok(source_chain.export()?)
+    }
+
     /// Create Cells for each CellId marked active in the ConductorState db
     pub(super) async fn create_active_app_cells(
         &self,
@@ -510,10 +726,11 @@ where
                                 cell_id.agent_pubkey().clone(),
                             );
 
-                            let env = EnvironmentWrite::new_cell(
+                            let env = EnvironmentWrite::new_with_map_size(
                                 &dir,
-                                cell_id.clone(),
+                                EnvironmentKind::Cell(cell_id.clone()),
                                 keystore.clone(),
+                                self.cell_map_size,
                             )?;
                             Cell::create(
                                 cell_id.clone(),
@@ -522,6 +739,8 @@ where
                                 holochain_p2p_cell,
                                 self.managed_task_add_sender.clone(),
                                 self.managed_task_stop_broadcaster.clone(),
+                                self.validation_workers,
+                                self.metrics.clone(),
                             )
                             .await
                         },
@@ -639,6 +858,97 @@ where
             .collect())
     }
 
+    /// Remove an app from the database, whether it is active or inactive.
+    /// Returns the app's cells which no remaining app uses.
+    pub(super) async fn remove_app_in_db(
+        &mut self,
+        installed_app_id: InstalledAppId,
+    ) -> ConductorResult<Vec<CellId>> {
+        let (_, unused_cells) = self
+            .update_state_prime(move |mut state| {
+                let app = state
+                    .active_apps
+                    .remove(&installed_app_id)
+                    .or_else(|| state.inactive_apps.remove(&installed_app_id))
+                    .ok_or_else(|| ConductorError::AppNotInstalled(installed_app_id.clone()))?;
+                for interface in state.app_interfaces.values_mut() {
+                    interface.signal_subscriptions.remove(&installed_app_id);
+                }
+                state.app_x25519_keys.remove(&installed_app_id);
+                let used_cells: HashSet<&CellId> = state
+                    .active_apps
+                    .values()
+                    .chain(state.inactive_apps.values())
+                    .flat_map(|app| app.all_cells())
+                    .collect();
+                let unused_cells = app
+                    .all_cells()
+                    .filter(|cell_id| !used_cells.contains(cell_id))
+                    .cloned()
+                    .collect();
+                Ok((state, unused_cells))
+            })
+            .await?;
+        Ok(unused_cells)
+    }
+
+    /// Record that an x25519 key was created for an installed app
+    pub(super) async fn add_app_x25519_key(
+        &self,
+        installed_app_id: InstalledAppId,
+        key: X25519PubKey,
+    ) -> ConductorResult<()> {
+        self.update_state(move |mut state| {
+            if state.get_app_info(&installed_app_id).is_none() {
+                return Err(ConductorError::AppNotInstalled(installed_app_id));
+            }
+            state
+                .app_x25519_keys
+                .entry(installed_app_id)
+                .or_default()
+                .push(key);
+            Ok(state)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Delete the LMDB environments of these cells.
+    /// The cells should already have been removed and their tasks stopped.
+    pub(super) async fn delete_cell_envs(&self, cell_ids: Vec<CellId>) -> ConductorResult<()> {
+        let root_env_dir = std::path::PathBuf::from(self.root_env_dir.clone());
+        for cell_id in cell_ids {
+            let env = EnvironmentWrite::new(
+                &root_env_dir,
+                EnvironmentKind::Cell(cell_id),
+                self.keystore.clone(),
+            )?;
+            env.remove().await?;
+        }
+        Ok(())
+    }
+
+    /// List the installed apps, optionally only those with a status
+    pub(super) async fn list_apps(
+        &self,
+        status_filter: Option<AppStatusFilter>,
+    ) -> ConductorResult<Vec<InstalledAppInfo>> {
+        let state = self.get_state().await?;
+        let active = state
+            .active_apps
+            .values()
+            .map(|app| InstalledAppInfo::from_installed_app(app, true));
+        let inactive = state
+            .inactive_apps
+            .values()
+            .map(|app| InstalledAppInfo::from_installed_app(app, false));
+        Ok(match status_filter {
+            Some(AppStatusFilter::Active) => active.collect(),
+            Some(AppStatusFilter::Inactive) => inactive.collect(),
+            None => active.chain(inactive).collect(),
+        })
+    }
+
     /// Add fully constructed cells to the cell map in the Conductor
     pub(super) fn add_cells(&mut self, cells: Vec<(Cell, InitialQueueTriggers)>) {
         for (cell, trigger) in cells {
@@ -743,18 +1053,29 @@ where
         Ok((dnas, defs))
     }
 
-    /// Remove cells from the cell map in the Conductor
-    pub(super) fn remove_cells(&mut self, cell_ids: Vec<CellId>) {
-        for cell_id in cell_ids {
-            self.cells.remove(&cell_id);
-        }
+    /// Remove cells from the cell map in the Conductor,
+    /// returning the removed cells so their tasks can be stopped
+    /// once the conductor lock is released
+    pub(super) fn remove_cells(&mut self, cell_ids: Vec<CellId>) -> Vec<Arc<Cell<CA>>> {
+        cell_ids
+            .into_iter()
+            .filter_map(|cell_id| {
+                self.metrics.remove_cell(&cell_id);
+                self.cells.remove(&cell_id).map(|item| item.cell)
+            })
+            .collect()
+    }
+
+    /// Render the metrics of every running cell in the Prometheus text format
+    pub(super) fn render_metrics(&self) -> ConductorResult<String> {
+        Ok(self.metrics.render()?)
     }
 
-    pub(super) fn add_agent_infos(
+    pub(super) async fn add_agent_infos(
         &self,
         agent_infos: Vec<AgentInfoSigned>,
     ) -> ConductorApiResult<()> {
-        Ok(inject_agent_infos(self.p2p_env.clone(), agent_infos)?)
+        Ok(inject_agent_infos(self.p2p_env.clone(), agent_infos).await?)
     }
 
     pub(super) fn get_agent_infos(
@@ -800,17 +1121,20 @@ where
         if dna_def_buf.get(dna.dna_hash()).await?.is_none() {
             dna_def_buf.put(dna.dna_def().clone()).await?;
         }
-        {
-            let env = environ.guard();
-            // write the wasm db
-            env.with_commit(|writer| wasm_buf.flush_to_txn(writer))?;
+        // write the wasm db
+        environ
+            .with_commit_retry(|writer| wasm_buf.flush_to_txn_ref(writer))
+            .await?;
 
-            // write the dna_def db
-            env.with_commit(|writer| dna_def_buf.flush_to_txn(writer))?;
+        // write the dna_def db
+        environ
+            .with_commit_retry(|writer| dna_def_buf.flush_to_txn_ref(writer))
+            .await?;
 
-            // write the entry_def db
-            env.with_commit(|writer| entry_def_buf.flush_to_txn(writer))?;
-        }
+        // write the entry_def db
+        environ
+            .with_commit_retry(|writer| entry_def_buf.flush_to_txn_ref(writer))
+            .await?;
         Ok(zome_defs)
     }
 
@@ -830,7 +1154,9 @@ where
 
         let peer_dump = p2p_store::dump_state(self.p2p_env.clone().into(), Some(cell_id.clone()))?;
         let source_chain_dump = source_chain.dump_state().await?;
-        let integration_dump = integrate_dht_ops_workflow::dump_state(arc.clone().into())?;
+        let integration_dump = integrate_dht_ops_workflow::dump_state(arc.clone().into())
+            .await
+            .map_err(Box::new)?;
 
         let out = JsonDump {
             peer_dump,
@@ -893,12 +1219,22 @@ where
         dna_store: DS,
         keystore: KeystoreSender,
         root_env_dir: EnvironmentRootPath,
+        cell_map_size: MapSize,
+        validation_workers: usize,
         holochain_p2p: holochain_p2p::HolochainP2pRef,
     ) -> ConductorResult<Self> {
         let db: SingleStore = env.get_db(&db::CONDUCTOR_STATE)?;
-        let (task_tx, task_manager_run_handle) = spawn_task_manager();
-        let task_manager_run_handle = Some(task_manager_run_handle);
+        let (event_tx, _) = tokio::sync::broadcast::channel(CONDUCTOR_EVENT_BUFFER_SIZE);
         let (stop_tx, _) = tokio::sync::broadcast::channel::<()>(1);
+        let managed_task_statuses = ManagedTaskStatuses::default();
+        let (deactivate_cell_tx, deactivate_cell_rx) = mpsc::unbounded_channel();
+        let (task_tx, task_manager_run_handle) = spawn_task_manager(
+            event_tx.clone(),
+            stop_tx.clone(),
+            managed_task_statuses.clone(),
+            deactivate_cell_tx,
+        );
+        let task_manager_run_handle = Some(task_manager_run_handle);
         Ok(Self {
             env,
             wasm_env,
@@ -909,11 +1245,18 @@ where
             app_interfaces: HashMap::new(),
             managed_task_add_sender: task_tx,
             managed_task_stop_broadcaster: stop_tx,
+            conductor_event_broadcaster: event_tx,
+            peer_counts: PeerCounts::default(),
             task_manager_run_handle,
+            managed_task_statuses,
+            deactivate_cell_rx: Some(deactivate_cell_rx),
             admin_websocket_ports: Vec::new(),
             dna_store,
             keystore,
             root_env_dir,
+            cell_map_size,
+            validation_workers,
+            metrics: Metrics::default(),
             holochain_p2p,
         })
     }
@@ -936,18 +1279,40 @@ where
     /// Update the internal state with a pure function mapping old state to new,
     /// which may also produce an output value which will be the output of
     /// this function
-    async fn update_state_prime<F: Send, O>(&self, f: F) -> ConductorResult<(ConductorState, O)>
+    async fn update_state_prime<F: Send, O: Send>(
+        &self,
+        f: F,
+    ) -> ConductorResult<(ConductorState, O)>
     where
         F: FnOnce(ConductorState) -> ConductorResult<(ConductorState, O)>,
     {
         self.check_running()?;
-        let guard = self.env.guard();
-        let output = guard.with_commit(|txn| {
-            let state: ConductorState = self.state_db.get(txn, &UnitDbKey)?.unwrap_or_default();
-            let (new_state, output) = f(state)?;
-            self.state_db.put(txn, &UnitDbKey, &new_state)?;
-            Result::<_, ConductorError>::Ok((new_state, output))
-        })?;
+        let mut f = Some(f);
+        // The state f was given and what it returned
+        let mut updated: Option<(ConductorState, (ConductorState, O))> = None;
+        self.env
+            .with_commit_retry(|txn| {
+                let state: ConductorState = self.state_db.get(txn, &UnitDbKey)?.unwrap_or_default();
+                match f.take() {
+                    Some(f) => updated = Some((state.clone(), f(state)?)),
+                    // The map filled up, so the same update is written in a
+                    // new transaction, as long as the state hasn't changed
+                    None => {
+                        let (seen, _) = updated.as_ref().expect("The update ran on the first try");
+                        if holochain_serialized_bytes::encode(seen)?
+                            != holochain_serialized_bytes::encode(&state)?
+                        {
+                            return Err(ConductorError::StateChangedDuringRetry);
+                        }
+                    }
+                }
+                let (_, (new_state, _)) =
+                    updated.as_ref().expect("The update ran on the first try");
+                self.state_db.put(txn, &UnitDbKey, new_state)?;
+                ConductorResult::Ok(())
+            })
+            .await?;
+        let (_, output) = updated.expect("The transaction committed so the update ran");
         Ok(output)
     }
 
@@ -964,6 +1329,142 @@ where
     }
 }
 
+/// Creates and sets up the environments of new cells.
+///
+/// Genesis calls back into the conductor through its handle,
+/// e.g. to register agent keys with DPKI, so it must run
+/// without the conductor lock held.
+pub(super) struct CellEnvSetup {
+    root_env_dir: std::path::PathBuf,
+    keystore: KeystoreSender,
+    cell_map_size: MapSize,
+    conductor_event_broadcaster: ConductorEventBroadcaster,
+}
+
+impl CellEnvSetup {
+    /// Create the environment for a new Cell and import its source chain,
+    /// removing the environment again if the chain is invalid.
+    pub(super) async fn import_source_chain(
+        &self,
+        cell_id: CellId,
+        export: SourceChainExport,
+        conductor_handle: ConductorHandle,
+    ) -> ConductorResult<()> {
+        let env = EnvironmentWrite::new_with_map_size(
+            &self.root_env_dir,
+            EnvironmentKind::Cell(cell_id.clone()),
+            self.keystore.clone(),
+            self.cell_map_size,
+        )?;
+        // Never touch an existing chain, even if its app was uninstalled
+        if !SourceChainBuf::new(env.clone().into())?.is_empty() {
+            return Err(ConductorError::SourceChainAlreadyExists(cell_id));
+        }
+        if let Err(e) =
+            Cell::import_source_chain(cell_id, conductor_handle, env.clone(), export).await
+        {
+            env.remove().await?;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Delete the environment of a Cell which was never added to an app
+    pub(super) async fn remove_env(&self, cell_id: CellId) -> ConductorResult<()> {
+        let env = EnvironmentWrite::new(
+            &self.root_env_dir,
+            EnvironmentKind::Cell(cell_id),
+            self.keystore.clone(),
+        )?;
+        env.remove().await?;
+        Ok(())
+    }
+
+    /// Perform Genesis on the source chains for each of the specified CellIds.
+    ///
+    /// If genesis fails for any cell, this entire function fails, and all other
+    /// partial or complete successes are rolled back.
+    pub(super) async fn genesis_cells(
+        &self,
+        cell_ids_with_proofs: Vec<(CellId, Option<MembraneProof>)>,
+        conductor_handle: ConductorHandle,
+    ) -> ConductorResult<()> {
+        let root_env_dir = self.root_env_dir.clone();
+        let keystore = self.keystore.clone();
+        let cell_map_size = self.cell_map_size;
+
+        let cells_tasks = cell_ids_with_proofs.into_iter().map(|(cell_id, proof)| {
+            let root_env_dir = root_env_dir.clone();
+            let keystore = self.keystore.clone();
+            let conductor_handle = conductor_handle.clone();
+            let cell_id_inner = cell_id.clone();
+            tokio::spawn(async move {
+                let env = EnvironmentWrite::new_with_map_size(
+                    &root_env_dir,
+                    EnvironmentKind::Cell(cell_id_inner.clone()),
+                    keystore.clone(),
+                    cell_map_size,
+                )?;
+                Cell::genesis(cell_id_inner, conductor_handle, env, proof).await
+            })
+            .map(|result| (cell_id, result.map_err(CellError::from).and_then(|r| r)))
+        });
+        let (success, errors): (Vec<_>, Vec<_>) = futures::future::join_all(cells_tasks)
+            .await
+            .into_iter()
+            .partition(|(_, result)| result.is_ok());
+
+        let success = success.into_iter().map(|(cell_id, _)| cell_id);
+
+        // If there were errors, cleanup and return the errors
+        if !errors.is_empty() {
+            for cell_id in success {
+                let env = EnvironmentWrite::new(
+                    &root_env_dir,
+                    EnvironmentKind::Cell(cell_id.clone()),
+                    keystore.clone(),
+                )?;
+                env.remove().await?;
+                self.emit_event(ConductorEvent::CellGenesis {
+                    cell_id,
+                    error: Some("Rolled back because genesis failed for another cell".to_string()),
+                });
+            }
+
+            // match needed to avoid Debug requirement on unwrap_err
+            let errors = errors
+                .into_iter()
+                .map(|(cell_id, e)| match e {
+                    Err(e) => {
+                        self.emit_event(ConductorEvent::CellGenesis {
+                            cell_id,
+                            error: Some(e.to_string()),
+                        });
+                        e
+                    }
+                    Ok(_) => unreachable!("Safe because of the partition"),
+                })
+                .collect();
+
+            Err(ConductorError::GenesisFailed { errors })
+        } else {
+            for cell_id in success {
+                self.emit_event(ConductorEvent::CellGenesis {
+                    cell_id,
+                    error: None,
+                });
+            }
+            // No errors so return the cells
+            Ok(())
+        }
+    }
+
+    fn emit_event(&self, event: ConductorEvent) {
+        // It's fine if nobody is subscribed
+        self.conductor_event_broadcaster.send(event).ok();
+    }
+}
+
 /// The database used to store ConductorState. It has only one key-value pair.
 pub type ConductorStateDb = KvStore<UnitDbKey, ConductorState>;
 
@@ -974,6 +1475,7 @@ mod builder {
     use holochain_lmdb::env::EnvironmentKind;
     #[cfg(any(test, feature = "test_utils"))]
     use holochain_lmdb::test_utils::TestEnvironments;
+    use std::sync::atomic::AtomicU64;
 
     /// A configurable Builder for Conductor and sometimes ConductorHandle
     #[derive(Default)]
@@ -1048,21 +1550,44 @@ mod builder {
                     .unwrap();
                 keystore
             } else {
-                spawn_lair_keystore(self.config.keystore_path.as_deref()).await?
+                let keystore = spawn_lair_keystore(
+                    self.config.keystore_path.as_deref(),
+                    self.config
+                        .passphrase_service
+                        .clone()
+                        .map(passphrase_service),
+                )
+                .await?;
+                let remote_config = remote_keystore_config(&self.config)?;
+                if remote_config.is_empty() {
+                    keystore
+                } else {
+                    spawn_remote_keystore(remote_config, keystore).await?
+                }
             };
             let env_path = self.config.environment_path.clone();
+            let map_sizes = self.config.lmdb_map_sizes.clone().unwrap_or_default();
 
-            let environment = EnvironmentWrite::new(
+            let environment = EnvironmentWrite::new_with_map_size(
                 env_path.as_ref(),
                 EnvironmentKind::Conductor,
                 keystore.clone(),
+                lmdb_map_size(map_sizes.conductor),
             )?;
 
-            let wasm_environment =
-                EnvironmentWrite::new(env_path.as_ref(), EnvironmentKind::Wasm, keystore.clone())?;
+            let wasm_environment = EnvironmentWrite::new_with_map_size(
+                env_path.as_ref(),
+                EnvironmentKind::Wasm,
+                keystore.clone(),
+                lmdb_map_size(map_sizes.wasm),
+            )?;
 
-            let p2p_environment =
-                EnvironmentWrite::new(env_path.as_ref(), EnvironmentKind::P2p, keystore.clone())?;
+            let p2p_environment = EnvironmentWrite::new_with_map_size(
+                env_path.as_ref(),
+                EnvironmentKind::P2p,
+                keystore.clone(),
+                lmdb_map_size(map_sizes.p2p),
+            )?;
 
             #[cfg(any(test, feature = "test_utils"))]
             let state = self.state;
@@ -1093,6 +1618,8 @@ mod builder {
                 dna_store,
                 keystore,
                 env_path,
+                lmdb_map_size(map_sizes.cell),
+                config.validation_workers.unwrap_or_else(num_cpus::get),
                 holochain_p2p,
             )
             .await?;
@@ -1108,24 +1635,75 @@ mod builder {
             conductor_config: ConductorConfig,
             p2p_evt: holochain_p2p::event::HolochainP2pEventReceiver,
         ) -> ConductorResult<ConductorHandle> {
+            let mut conductor = conductor;
+            let deactivate_cell_rx = conductor.deactivate_cell_rx.take();
+            let stop_tx = conductor.managed_task_stop_broadcaster.clone();
+            let mut task_tx = conductor.managed_task_add_sender.clone();
+
             // Get data before handle
             let keystore = conductor.keystore.clone();
             let holochain_p2p = conductor.holochain_p2p.clone();
+            let metrics = conductor.metrics.clone();
 
             // Create handle
             let handle: ConductorHandle = Arc::new(ConductorHandleImpl {
                 conductor: RwLock::new(conductor),
                 keystore,
                 holochain_p2p,
+                dpki: conductor_config.dpki.clone(),
+                apps_version: AtomicU64::new(0),
             });
 
             handle.load_dnas().await?;
 
-            tokio::task::spawn(p2p_event_task(p2p_evt, handle.clone()));
+            // The network's event receiver can't be made again,
+            // so this task is never restarted, only reported if it fails
+            let p2p_task =
+                tokio::task::spawn(p2p_event_task(p2p_evt, handle.clone(), stop_tx.subscribe()));
+            task_tx
+                .send(ManagedTaskAdd::dont_handle("p2p_event_task", p2p_task))
+                .await
+                .map_err(|e| ConductorError::SubmitTaskError(format!("{}", e)))?;
+
+            if let Some(addr) = conductor_config.metrics_endpoint {
+                let listener = bind_metrics_endpoint(addr).await.map_err(Box::new)?;
+                // A restart binds the same port, even if any port was asked for
+                let addr = listener.local_addr()?;
+                let task =
+                    spawn_metrics_endpoint_task(listener, metrics.clone(), stop_tx.subscribe());
+                let respawn: SpawnTask = Arc::new(move |stop_rx| {
+                    let metrics = metrics.clone();
+                    tokio::spawn(async move {
+                        let listener = bind_metrics_endpoint(addr).await?;
+                        spawn_metrics_endpoint_task(listener, metrics, stop_rx).await?
+                    })
+                });
+                task_tx
+                    .send(ManagedTaskAdd::with_restart_policy(
+                        format!("metrics_endpoint-{}", addr),
+                        task,
+                        interface_restart_policy(),
+                        respawn,
+                    ))
+                    .await
+                    .map_err(|e| ConductorError::SubmitTaskError(format!("{}", e)))?;
+            }
+
+            if let Some(mut deactivate_cell_rx) = deactivate_cell_rx {
+                let handle = handle.clone();
+                tokio::task::spawn(async move {
+                    while let Some((cell_id, reason)) = deactivate_cell_rx.recv().await {
+                        if let Err(e) = handle.deactivate_apps_with_cell(&cell_id, reason).await {
+                            error!(?cell_id, error = ?e, "Failed to deactivate apps");
+                        }
+                    }
+                });
+            }
 
             let cell_startup_errors = handle.clone().setup_cells().await?;
 
-            // TODO: This should probably be emitted over the admin interface
+            // These are also sent as conductor events by `setup_cells`,
+            // but no admin connection can have subscribed yet
             if !cell_startup_errors.is_empty() {
                 error!(
                     msg = "Failed to create the following active apps",
@@ -1193,6 +1771,8 @@ mod builder {
                 self.dna_store,
                 keystore,
                 envs.tempdir().path().to_path_buf().into(),
+                MapSize::default(),
+                self.config.validation_workers.unwrap_or_else(num_cpus::get),
                 holochain_p2p,
             )
             .await?;
@@ -1205,32 +1785,109 @@ mod builder {
     }
 }
 
-#[instrument(skip(p2p_evt, handle))]
+/// The configured map size for an environment, or the default if not configured.
+fn lmdb_map_size(config: Option<LmdbMapSizeConfig>) -> MapSize {
+    config
+        .map(|size| MapSize::from_mb(size.initial_mb, size.max_mb))
+        .unwrap_or_default()
+}
+
+/// Where the keystore should get its passphrase from.
+fn passphrase_service(config: PassphraseServiceConfig) -> PassphraseService {
+    match config {
+        PassphraseServiceConfig::Cmd => PassphraseService::Cmd,
+        PassphraseServiceConfig::UnixSocket { path } => PassphraseService::UnixSocket(path),
+        PassphraseServiceConfig::FromConfig { passphrase } => PassphraseService::Fixed(passphrase),
+    }
+}
+
+/// Which operations the keystore should send to remote services.
+fn remote_keystore_config(config: &ConductorConfig) -> ConductorResult<RemoteKeystoreConfig> {
+    let parse = |uri: &Option<String>| uri.as_deref().map(str::parse).transpose();
+    Ok(RemoteKeystoreConfig {
+        signing: parse(&config.signing_service_uri)?,
+        encryption: parse(&config.encryption_service_uri)?,
+        decryption: parse(&config.decryption_service_uri)?,
+    })
+}
+
+/// Interfaces are restarted when they fail, backing off if they keep failing
+fn interface_restart_policy() -> RestartPolicy {
+    RestartPolicy::OnError {
+        initial_delay: INTERFACE_RESTART_DELAY,
+        max_delay: INTERFACE_MAX_RESTART_DELAY,
+    }
+}
+
+/// The driver for an interface which was bound to `port`,
+/// so that restarting it binds the same port
+fn bound_driver(driver: InterfaceDriver, port: Option<u16>) -> InterfaceDriver {
+    match (driver, port) {
+        (
+            InterfaceDriver::Websocket {
+                bind_address,
+                allowed_origins,
+                ..
+            },
+            Some(port),
+        ) => InterfaceDriver::Websocket {
+            port,
+            bind_address,
+            allowed_origins,
+        },
+        (driver, _) => driver,
+    }
+}
+
+/// The address and port or socket path an interface is bound to
+fn interface_address(driver: &InterfaceDriver) -> String {
+    match driver {
+        InterfaceDriver::Websocket {
+            port, bind_address, ..
+        } => {
+            let address = bind_address.unwrap_or_else(|| Ipv4Addr::LOCALHOST.into());
+            SocketAddr::new(address, *port).to_string()
+        }
+        InterfaceDriver::UnixSocket { path } => path.display().to_string(),
+    }
+}
+
+/// The id of an app interface, taken from where it is bound
+/// so it is the same after a restart
+fn app_interface_id(driver: &InterfaceDriver) -> AppInterfaceId {
+    format!("interface-{}", interface_address(driver)).into()
+}
+
+#[instrument(skip(p2p_evt, handle, stop_rx))]
 async fn p2p_event_task(
     p2p_evt: holochain_p2p::event::HolochainP2pEventReceiver,
     handle: ConductorHandle,
-) {
+    mut stop_rx: StopReceiver,
+) -> ManagedTaskResult {
     /// The number of events we allow to run in parallel before
     /// starting to await on the join handles.
     const NUM_PARALLEL_EVTS: usize = 100;
-    p2p_evt
-        .for_each_concurrent(NUM_PARALLEL_EVTS, |evt| {
-            let handle = handle.clone();
-            async move {
-                let cell_id =
-                    CellId::new(evt.dna_hash().clone(), evt.target_agent_as_ref().clone());
-                if let Err(e) = handle.dispatch_holochain_p2p_event(&cell_id, evt).await {
-                    tracing::error!(
-                        message = "error dispatching network event",
-                        error = ?e,
-                    );
-                }
+    let events = p2p_evt.for_each_concurrent(NUM_PARALLEL_EVTS, |evt| {
+        let handle = handle.clone();
+        async move {
+            let cell_id = CellId::new(evt.dna_hash().clone(), evt.target_agent_as_ref().clone());
+            if let Err(e) = handle.dispatch_holochain_p2p_event(&cell_id, evt).await {
+                tracing::error!(
+                    message = "error dispatching network event",
+                    error = ?e,
+                );
             }
-            .in_current_span()
-        })
-        .await;
+        }
+        .in_current_span()
+    });
 
-    tracing::warn!("p2p_event_task has ended");
+    tokio::select! {
+        _ = events => {
+            tracing::warn!("p2p_event_task has ended");
+            Err(ManagedTaskError::NetworkEventsEnded)
+        }
+        _ = stop_rx.recv() => Ok(()),
+    }
 }
 
 #[cfg(test)]
@@ -4,6 +4,8 @@ use super::*;
 use crate::test_utils::fake_valid_dna_file;
 use ::fixt::prelude::*;
 use holochain_lmdb::test_utils::test_environments;
+use holochain_state::source_chain::SourceChainExportElement;
+use holochain_state::source_chain::SOURCE_CHAIN_EXPORT_VERSION;
 use holochain_types::test_utils::fake_cell_id;
 use matches::assert_matches;
 
@@ -183,6 +185,55 @@ async fn can_set_fake_state() {
     assert_eq!(state, conductor.get_state_from_handle().await.unwrap());
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn import_source_chain_needs_a_registered_dna() {
+    let envs = test_environments();
+    let conductor = ConductorBuilder::new().test(&envs).await.unwrap();
+    let export = SourceChainExport {
+        version: SOURCE_CHAIN_EXPORT_VERSION,
+        elements: vec![SourceChainExportElement {
+            header: SignedHeader(Header::Dna(fixt!(Dna)), fixt!(Signature)),
+            entry: None,
+        }],
+    };
+    let result = conductor
+        .clone()
+        .import_source_chain(ImportSourceChainPayload {
+            installed_app_id: "imported".to_string(),
+            nick: "nick".to_string(),
+            source_chain: export.to_bytes().unwrap(),
+        })
+        .await;
+    assert_matches!(
+        result,
+        Err(ConductorError::DnaError(DnaError::DnaMissing(_)))
+    );
+    assert!(conductor
+        .get_state_from_handle()
+        .await
+        .unwrap()
+        .inactive_apps
+        .is_empty());
+    conductor.shutdown().await;
+}
+
+#[test]
+fn app_interface_ids_include_the_bind_address() {
+    let id_on = |bind_address: Option<std::net::IpAddr>| {
+        app_interface_id(&InterfaceDriver::Websocket {
+            port: 8888,
+            bind_address,
+            allowed_origins: None,
+        })
+    };
+    assert_eq!(id_on(None), "interface-127.0.0.1:8888".into());
+    assert_eq!(
+        id_on(Some(std::net::Ipv6Addr::LOCALHOST.into())),
+        "interface-[::1]:8888".into()
+    );
+    assert_ne!(id_on(Some([0, 0, 0, 0].into())), id_on(None));
+}
+
 #[tokio::test(threaded_scheduler)]
 async fn proxy_tls_with_test_keystore() {
     use ghost_actor::GhostControlSender;
@@ -4,6 +4,7 @@ use crate::conductor::cell::error::CellError;
 use crate::core::workflow::error::WorkflowError;
 use holochain_conductor_api::conductor::ConductorConfigError;
 use holochain_lmdb::error::DatabaseError;
+use holochain_state::source_chain::SourceChainError;
 use holochain_types::prelude::*;
 use holochain_zome_types::cell::CellId;
 use thiserror::Error;
@@ -66,6 +67,9 @@ pub enum ConductorError {
     #[error("Attempted to add two app interfaces with the same id: {0}")]
     AppInterfaceIdCollision(AppInterfaceId),
 
+    #[error("No app interface with the id: {0}")]
+    AppInterfaceNotFound(AppInterfaceId),
+
     // Box is to avoid cycle in error definition
     #[error(transparent)]
     InterfaceError(#[from] Box<InterfaceError>),
@@ -105,6 +109,15 @@ pub enum ConductorError {
 
     #[error(transparent)]
     MrBundleError(#[from] mr_bundle::error::MrBundleError),
+
+    #[error(transparent)]
+    SourceChainError(#[from] SourceChainError),
+
+    #[error("Tried to import a source chain for a Cell which already has one: {0:?}")]
+    SourceChainAlreadyExists(CellId),
+
+    #[error("The conductor state changed while an update was retried after the map filled up")]
+    StateChangedDuringRetry,
 }
 
 #[derive(Error, Debug)]
@@ -41,9 +41,13 @@
 //! types for testing. If we did not have a way of hiding this type genericity,
 //! code which interacted with the Conductor would also have to be highly generic.
 
+use super::api::error::ConductorApiError;
 use super::api::error::ConductorApiResult;
+use super::api::error::SerializationError;
 use super::api::ZomeCall;
 use super::config::AdminInterfaceConfig;
+use super::config::DpkiConfig;
+use super::config::InterfaceDriver;
 use super::error::ConductorResult;
 use super::error::CreateAppError;
 use super::interface::SignalBroadcaster;
@@ -51,16 +55,29 @@ use super::manager::TaskManagerRunHandle;
 use super::p2p_store::get_agent_info_signed;
 use super::p2p_store::put_agent_info_signed;
 use super::p2p_store::query_agent_info_signed;
+use super::state::AppInterfaceId;
 use super::Cell;
 use super::Conductor;
 use crate::core::workflow::CallZomeWorkspaceLock;
 use crate::core::workflow::ZomeCallResult;
 use derive_more::From;
 use futures::future::FutureExt;
+use holochain_conductor_api::signal_subscription::SignalSubscription;
+use holochain_conductor_api::AppInterfaceInfo;
+use holochain_conductor_api::AppStatusFilter;
+use holochain_conductor_api::ConductorEvent;
 use holochain_conductor_api::InstalledAppInfo;
+use holochain_conductor_api::ManagedTaskInfo;
+use holochain_keystore::KeystoreSenderExt;
 use holochain_p2p::event::HolochainP2pEvent::*;
+use holochain_state::source_chain::ChainInvalidReason;
+use holochain_state::source_chain::SourceChainError;
+use holochain_state::source_chain::SourceChainExport;
 use holochain_types::prelude::*;
 use kitsune_p2p::agent_store::AgentInfoSigned;
+use std::collections::HashSet;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::*;
@@ -98,8 +115,28 @@ pub trait ConductorHandleT: Send + Sync {
     /// Should only be run once at Conductor initialization.
     async fn startup_app_interfaces(self: Arc<Self>) -> ConductorResult<()>;
 
-    /// Add an app interface
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16>;
+    /// Add an app interface, optionally attached to an app,
+    /// returning the port it was bound to if it is a websocket interface
+    async fn add_app_interface(
+        self: Arc<Self>,
+        driver: InterfaceDriver,
+        installed_app_id: Option<InstalledAppId>,
+    ) -> ConductorResult<Option<u16>>;
+
+    /// List the attached app interfaces
+    async fn list_app_interfaces(&self) -> ConductorResult<Vec<AppInterfaceInfo>>;
+
+    /// Save the latest signal subscription made over an app interface,
+    /// so it is listed with the interface
+    async fn put_signal_subscription(
+        &self,
+        interface_id: AppInterfaceId,
+        subscription: SignalSubscription,
+    ) -> ConductorResult<()>;
+
+    /// Stop an app interface, closing its connections,
+    /// and remove it so it isn't started up again
+    async fn remove_app_interface(&self, interface_id: AppInterfaceId) -> ConductorResult<()>;
 
     /// Install a [Dna] in this Conductor
     async fn register_dna(&self, dna: DnaFile) -> ConductorResult<()>;
@@ -191,15 +228,58 @@ pub trait ConductorHandleT: Send + Sync {
     /// Deactivate an app
     async fn deactivate_app(&self, installed_app_id: InstalledAppId) -> ConductorResult<()>;
 
+    /// Deactivate every active app running a Cell, because of `reason`
+    async fn deactivate_apps_with_cell(
+        &self,
+        cell_id: &CellId,
+        reason: String,
+    ) -> ConductorResult<()>;
+
+    /// The status of every long running task the conductor looks after
+    async fn list_managed_tasks(&self) -> Vec<ManagedTaskInfo>;
+
     /// List Cell Ids
     async fn list_cell_ids(&self) -> ConductorResult<Vec<CellId>>;
 
+    /// Uninstall an app, removing any of its cells that no other app uses.
+    /// If `delete_data` is set the removed cells' environments are deleted.
+    async fn uninstall_app(
+        &self,
+        installed_app_id: InstalledAppId,
+        delete_data: bool,
+    ) -> ConductorResult<()>;
+
+    /// A number which changes whenever an app is installed or uninstalled,
+    /// or a Cell is added to an app, so a cached set of an app's Cells
+    /// can be looked up again only when it may be out of date.
+    fn apps_version(&self) -> u64;
+
     /// List Active AppIds
     async fn list_active_apps(&self) -> ConductorResult<Vec<InstalledAppId>>;
 
+    /// List info about installed apps, optionally only those with a status
+    async fn list_apps(
+        &self,
+        status_filter: Option<AppStatusFilter>,
+    ) -> ConductorResult<Vec<InstalledAppInfo>>;
+
     /// Dump the cells state
     async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String>;
 
+    /// Render the workflow and limbo metrics of every running cell
+    /// in the Prometheus text format
+    async fn dump_metrics(&self) -> ConductorApiResult<String>;
+
+    /// Export the authored source chain of a Cell
+    async fn export_source_chain(&self, cell_id: &CellId) -> ConductorApiResult<SourceChainExport>;
+
+    /// Install an app with a single Cell whose source chain is imported
+    /// from another conductor's export instead of running genesis
+    async fn import_source_chain(
+        self: Arc<Self>,
+        payload: ImportSourceChainPayload,
+    ) -> ConductorResult<InstalledApp>;
+
     /// Access the broadcast Sender which will send a Signal across every
     /// attached app interface
     async fn signal_broadcaster(&self) -> SignalBroadcaster;
@@ -210,6 +290,19 @@ pub trait ConductorHandleT: Send + Sync {
         installed_app_id: &InstalledAppId,
     ) -> ConductorResult<Option<InstalledAppInfo>>;
 
+    /// Create an x25519 keypair in the keystore for an installed app
+    async fn create_app_x25519_keypair(
+        &self,
+        installed_app_id: &InstalledAppId,
+    ) -> ConductorResult<X25519PubKey>;
+
+    /// Was this x25519 key created for this app?
+    async fn app_has_x25519_key(
+        &self,
+        installed_app_id: &InstalledAppId,
+        key: &X25519PubKey,
+    ) -> ConductorResult<bool>;
+
     /// Add signed agent info to the conductor
     async fn add_agent_infos(&self, agent_infos: Vec<AgentInfoSigned>) -> ConductorApiResult<()>;
 
@@ -222,6 +315,22 @@ pub trait ConductorHandleT: Send + Sync {
     /// Print the current setup in a machine readable way.
     async fn print_setup(&self);
 
+    /// Register an agent key with the DPKI app.
+    /// Does nothing if this conductor is not using DPKI.
+    async fn dpki_register_key(&self, key: AgentPubKey) -> ConductorApiResult<()>;
+
+    /// Revoke an agent key with the DPKI app.
+    /// Anything the key signs from now on will fail validation.
+    async fn dpki_revoke_key(&self, key: AgentPubKey) -> ConductorApiResult<()>;
+
+    /// Ask the DPKI app for the state of an agent key at a point in time.
+    /// Returns `None` if this conductor is not using DPKI.
+    async fn dpki_key_state(
+        &self,
+        key: AgentPubKey,
+        at: Timestamp,
+    ) -> ConductorApiResult<Option<KeyState>>;
+
     /// Retrieve the LMDB environment for this cell. FOR TESTING ONLY.
     #[cfg(any(test, feature = "test_utils"))]
     async fn get_cell_env(&self, cell_id: &CellId) -> ConductorApiResult<EnvironmentWrite>;
@@ -256,6 +365,9 @@ pub struct ConductorHandleImpl<DS: DnaStore + 'static> {
     pub(crate) conductor: RwLock<Conductor<DS>>,
     pub(crate) keystore: KeystoreSender,
     pub(crate) holochain_p2p: holochain_p2p::HolochainP2pRef,
+    pub(crate) dpki: Option<DpkiConfig>,
+    /// Bumped after the apps in the db change, see [`ConductorHandleT::apps_version`]
+    pub(crate) apps_version: AtomicU64,
 }
 
 #[async_trait::async_trait]
@@ -282,9 +394,38 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             .await
     }
 
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16> {
+    async fn add_app_interface(
+        self: Arc<Self>,
+        driver: InterfaceDriver,
+        installed_app_id: Option<InstalledAppId>,
+    ) -> ConductorResult<Option<u16>> {
         let mut lock = self.conductor.write().await;
-        lock.add_app_interface_via_handle(port, self.clone()).await
+        lock.add_app_interface_via_handle(driver, installed_app_id, self.clone())
+            .await
+    }
+
+    async fn list_app_interfaces(&self) -> ConductorResult<Vec<AppInterfaceInfo>> {
+        self.conductor.read().await.list_app_interfaces().await
+    }
+
+    async fn put_signal_subscription(
+        &self,
+        interface_id: AppInterfaceId,
+        subscription: SignalSubscription,
+    ) -> ConductorResult<()> {
+        self.conductor
+            .read()
+            .await
+            .put_signal_subscription(interface_id, subscription)
+            .await
+    }
+
+    async fn remove_app_interface(&self, interface_id: AppInterfaceId) -> ConductorResult<()> {
+        self.conductor
+            .write()
+            .await
+            .remove_app_interface(interface_id)
+            .await
     }
 
     async fn register_dna(&self, dna: DnaFile) -> ConductorResult<()> {
@@ -343,8 +484,10 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             } => {
                 let env = { self.conductor.read().await.p2p_env() };
                 let res = put_agent_info_signed(env, agent_info_signed)
+                    .await
                     .map_err(holochain_p2p::HolochainP2pError::other);
                 respond.respond(Ok(async move { res }.boxed().into()));
+                self.schedule_peer_count(cell_id.dna_hash().clone()).await;
             }
             GetAgentInfoSigned {
                 kitsune_space,
@@ -365,6 +508,12 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
                 let env = { self.conductor.read().await.p2p_env() };
                 let res = query_agent_info_signed(env, kitsune_space)
                     .map_err(holochain_p2p::HolochainP2pError::other);
+                if let Ok(agent_infos) = &res {
+                    self.conductor
+                        .read()
+                        .await
+                        .update_peer_count(cell_id.dna_hash().clone(), agent_infos.len());
+                }
                 respond.respond(Ok(async move { res }.boxed().into()));
             }
             SignNetworkData { respond, data, .. } => {
@@ -439,10 +588,10 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             membrane_proof,
         } = payload;
         {
-            let conductor = self.conductor.read().await;
+            let cell_env_setup = self.conductor.read().await.cell_env_setup();
             let cell_id = CellId::new(dna_hash, agent_key);
             let cells = vec![(cell_id.clone(), membrane_proof)];
-            conductor.genesis_cells(cells, self.clone()).await?;
+            cell_env_setup.genesis_cells(cells, self.clone()).await?;
         }
         {
             let mut conductor = self.conductor.write().await;
@@ -450,6 +599,7 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             let cell_id = conductor
                 .add_clone_cell_to_app(&installed_app_id, &slot_id, properties)
                 .await?;
+            self.apps_changed();
             Ok(cell_id)
         }
     }
@@ -463,9 +613,9 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         installed_app_id: InstalledAppId,
         cell_data: Vec<(InstalledCell, Option<MembraneProof>)>,
     ) -> ConductorResult<()> {
-        self.conductor
-            .read()
-            .await
+        // The lock is released before genesis, which calls back into the conductor
+        let cell_env_setup = self.conductor.read().await.cell_env_setup();
+        cell_env_setup
             .genesis_cells(
                 cell_data
                     .iter()
@@ -483,7 +633,9 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             .write()
             .await
             .add_inactive_app_to_db(app)
-            .await
+            .await?;
+        self.apps_changed();
+        Ok(())
     }
 
     async fn install_app_bundle(
@@ -511,9 +663,8 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             self.clone().register_dna(dna).await?;
         }
 
-        self.conductor
-            .read()
-            .await
+        let cell_env_setup = self.conductor.read().await.cell_env_setup();
+        cell_env_setup
             .genesis_cells(cells_to_create, self.clone())
             .await?;
 
@@ -526,6 +677,7 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             .await
             .add_inactive_app_to_db(app.clone())
             .await?;
+        self.apps_changed();
 
         Ok(app)
     }
@@ -546,53 +698,181 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
                 Err(e) => Some(e),
             }
         });
-        let r = futures::future::join_all(add_cells_tasks)
+        let r: Vec<CreateAppError> = futures::future::join_all(add_cells_tasks)
             .await
             .into_iter()
             // Remove successful and collect the errors
             .filter_map(|r| r)
             .collect();
+        {
+            let lock = self.conductor.read().await;
+            for error in &r {
+                let CreateAppError::Failed {
+                    installed_app_id, ..
+                } = error;
+                lock.emit_event(ConductorEvent::AppStatusChanged {
+                    installed_app_id: installed_app_id.clone(),
+                    active: false,
+                    reason: Some(error.to_string()),
+                });
+            }
+        }
         Ok(r)
     }
 
     async fn activate_app(&self, installed_app_id: InstalledAppId) -> ConductorResult<()> {
-        self.conductor
-            .write()
-            .await
-            .activate_app_in_db(installed_app_id)
-            .await
+        let mut lock = self.conductor.write().await;
+        lock.activate_app_in_db(installed_app_id.clone()).await?;
+        lock.emit_event(ConductorEvent::AppStatusChanged {
+            installed_app_id,
+            active: true,
+            reason: None,
+        });
+        Ok(())
         // MD: Should we be doing `Conductor::add_cells()` here? (see below comment)
     }
 
     async fn deactivate_app(&self, installed_app_id: InstalledAppId) -> ConductorResult<()> {
-        let cell_ids_to_remove = self
-            .conductor
-            .write()
-            .await
-            .deactivate_app_in_db(installed_app_id)
-            .await?;
-        // MD: I'm not sure about this. We never add the cells back in after re-activating an app,
-        //     so it seems either we shouldn't remove them here, or we should be sure to add them
-        //     back in when re-activating.
-        self.conductor
-            .write()
+        self.deactivate_app_with_reason(installed_app_id, None)
             .await
-            .remove_cells(cell_ids_to_remove);
+    }
+
+    async fn deactivate_apps_with_cell(
+        &self,
+        cell_id: &CellId,
+        reason: String,
+    ) -> ConductorResult<()> {
+        let state = self.conductor.read().await.get_state().await?;
+        let installed_app_ids: Vec<_> = state
+            .active_apps
+            .iter()
+            .filter(|(_, app)| app.provisioned_cells().any(|(_, id)| id == cell_id))
+            .map(|(installed_app_id, _)| installed_app_id.clone())
+            .collect();
+        for installed_app_id in installed_app_ids {
+            warn!(?installed_app_id, %reason, "Deactivating app");
+            self.deactivate_app_with_reason(installed_app_id, Some(reason.clone()))
+                .await?;
+        }
         Ok(())
     }
 
+    async fn list_managed_tasks(&self) -> Vec<ManagedTaskInfo> {
+        self.conductor.read().await.list_managed_tasks()
+    }
+
     async fn list_cell_ids(&self) -> ConductorResult<Vec<CellId>> {
         self.conductor.read().await.list_cell_ids().await
     }
 
+    async fn uninstall_app(
+        &self,
+        installed_app_id: InstalledAppId,
+        delete_data: bool,
+    ) -> ConductorResult<()> {
+        let (cell_ids_to_remove, removed_cells) = {
+            let mut conductor = self.conductor.write().await;
+            let cell_ids_to_remove = conductor.remove_app_in_db(installed_app_id).await?;
+            let removed_cells = conductor.remove_cells(cell_ids_to_remove.clone());
+            (cell_ids_to_remove, removed_cells)
+        };
+        self.apps_changed();
+        // The lock is released first because running workflows may need it to finish
+        futures::future::join_all(removed_cells.iter().map(|cell| cell.stop_tasks())).await;
+        if delete_data {
+            self.conductor
+                .read()
+                .await
+                .delete_cell_envs(cell_ids_to_remove)
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn apps_version(&self) -> u64 {
+        self.apps_version.load(Ordering::Acquire)
+    }
+
     async fn list_active_apps(&self) -> ConductorResult<Vec<InstalledAppId>> {
         self.conductor.read().await.list_active_apps().await
     }
 
+    async fn list_apps(
+        &self,
+        status_filter: Option<AppStatusFilter>,
+    ) -> ConductorResult<Vec<InstalledAppInfo>> {
+        self.conductor.read().await.list_apps(status_filter).await
+    }
+
     async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String> {
         self.conductor.read().await.dump_cell_state(cell_id).await
     }
 
+    async fn dump_metrics(&self) -> ConductorApiResult<String> {
+        Ok(self.conductor.read().await.render_metrics()?)
+    }
+
+    async fn export_source_chain(&self, cell_id: &CellId) -> ConductorApiResult<SourceChainExport> {
+        self.conductor
+            .read()
+            .await
+            .export_source_chain(cell_id)
+            .await
+    }
+
+    async fn import_source_chain(
+        self: Arc<Self>,
+        payload: ImportSourceChainPayload,
+    ) -> ConductorResult<InstalledApp> {
+        let ImportSourceChainPayload {
+            installed_app_id,
+            nick,
+            source_chain,
+        } = payload;
+        let export = SourceChainExport::from_bytes(&source_chain)?;
+        // The Dna header at the start of the chain says which Cell it belongs to
+        let cell_id = match export.elements.first().map(|element| &element.header.0) {
+            Some(Header::Dna(dna)) => CellId::new(dna.hash.clone(), dna.author.clone()),
+            _ => {
+                return Err(SourceChainError::InvalidStructure(
+                    ChainInvalidReason::GenesisDataMissing,
+                )
+                .into())
+            }
+        };
+
+        // The chain can only be checked against a registered Dna
+        if self.get_dna(cell_id.dna_hash()).await.is_none() {
+            return Err(DnaError::DnaMissing(cell_id.dna_hash().clone()).into());
+        }
+        let app = InstalledApp::new_legacy(
+            installed_app_id,
+            vec![InstalledCell::new(cell_id.clone(), nick)],
+        )?;
+
+        // The lock is released before the import, which calls back into the conductor
+        let cell_env_setup = self.conductor.read().await.cell_env_setup();
+        cell_env_setup
+            .import_source_chain(cell_id.clone(), export, self.clone())
+            .await?;
+
+        // Update the db, removing the imported chain again if the app can't be added.
+        // Its ops are produced when the app is activated, as starting a Cell
+        // runs each of its workflows.
+        let added = self
+            .conductor
+            .write()
+            .await
+            .add_inactive_app_to_db(app.clone())
+            .await;
+        if let Err(e) = added {
+            cell_env_setup.remove_env(cell_id).await?;
+            return Err(e);
+        }
+
+        Ok(app)
+    }
+
     async fn signal_broadcaster(&self) -> SignalBroadcaster {
         self.conductor.read().await.signal_broadcaster()
     }
@@ -610,8 +890,51 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             .get_app_info(installed_app_id))
     }
 
+    async fn create_app_x25519_keypair(
+        &self,
+        installed_app_id: &InstalledAppId,
+    ) -> ConductorResult<X25519PubKey> {
+        let key = self.keystore.create_x25519_keypair().await?;
+        self.conductor
+            .read()
+            .await
+            .add_app_x25519_key(installed_app_id.clone(), key)
+            .await?;
+        Ok(key)
+    }
+
+    async fn app_has_x25519_key(
+        &self,
+        installed_app_id: &InstalledAppId,
+        key: &X25519PubKey,
+    ) -> ConductorResult<bool> {
+        Ok(self
+            .conductor
+            .read()
+            .await
+            .get_state()
+            .await?
+            .app_x25519_keys
+            .get(installed_app_id)
+            .map_or(false, |keys| keys.contains(key)))
+    }
+
     async fn add_agent_infos(&self, agent_infos: Vec<AgentInfoSigned>) -> ConductorApiResult<()> {
-        self.conductor.read().await.add_agent_infos(agent_infos)
+        self.conductor
+            .read()
+            .await
+            .add_agent_infos(agent_infos)
+            .await?;
+        let dna_hashes: HashSet<_> = self
+            .list_cell_ids()
+            .await?
+            .into_iter()
+            .map(|cell_id| cell_id.into_dna_and_agent().0)
+            .collect();
+        for dna_hash in dna_hashes {
+            self.schedule_peer_count(dna_hash).await;
+        }
+        Ok(())
     }
 
     async fn get_agent_infos(
@@ -625,6 +948,45 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         self.conductor.read().await.print_setup()
     }
 
+    async fn dpki_register_key(&self, key: AgentPubKey) -> ConductorApiResult<()> {
+        if let Some(dpki_cell_id) = self.dpki_cell_id().await? {
+            let input = RegisterKey {
+                key,
+                registered_at: timestamp::now(),
+            };
+            self.call_dpki(dpki_cell_id, DPKI_REGISTER_KEY_FN, input)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn dpki_revoke_key(&self, key: AgentPubKey) -> ConductorApiResult<()> {
+        let dpki_cell_id = self
+            .dpki_cell_id()
+            .await?
+            .ok_or(ConductorApiError::DpkiUnavailable)?;
+        let input = RevokeKey {
+            key,
+            revoked_at: timestamp::now(),
+        };
+        self.call_dpki(dpki_cell_id, DPKI_REVOKE_KEY_FN, input)
+            .await
+    }
+
+    async fn dpki_key_state(
+        &self,
+        key: AgentPubKey,
+        at: Timestamp,
+    ) -> ConductorApiResult<Option<KeyState>> {
+        match self.dpki_cell_id().await? {
+            Some(dpki_cell_id) => Ok(Some(
+                self.call_dpki(dpki_cell_id, DPKI_KEY_STATE_FN, KeyStateQuery { key, at })
+                    .await?,
+            )),
+            None => Ok(None),
+        }
+    }
+
     #[cfg(any(test, feature = "test_utils"))]
     async fn get_cell_env(&self, cell_id: &CellId) -> ConductorApiResult<EnvironmentWrite> {
         let cell = self.cell_by_id(cell_id).await?;
@@ -660,8 +1022,104 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
 }
 
 impl<DS: DnaStore + 'static> ConductorHandleImpl<DS> {
+    /// Let anything caching the Cells of apps know they may have changed
+    fn apps_changed(&self) {
+        self.apps_version.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Deactivate an app, saying why if it wasn't requested by an admin
+    async fn deactivate_app_with_reason(
+        &self,
+        installed_app_id: InstalledAppId,
+        reason: Option<String>,
+    ) -> ConductorResult<()> {
+        let cell_ids_to_remove = self
+            .conductor
+            .write()
+            .await
+            .deactivate_app_in_db(installed_app_id.clone())
+            .await?;
+        // MD: I'm not sure about this. We never add the cells back in after re-activating an app,
+        //     so it seems either we shouldn't remove them here, or we should be sure to add them
+        //     back in when re-activating.
+        let removed_cells = {
+            let mut lock = self.conductor.write().await;
+            let removed_cells = lock.remove_cells(cell_ids_to_remove);
+            lock.emit_event(ConductorEvent::AppStatusChanged {
+                installed_app_id,
+                active: false,
+                reason,
+            });
+            removed_cells
+        };
+        // Stop the queue consumers which are still running,
+        // e.g. the ones beside a consumer which kept failing
+        futures::future::join_all(removed_cells.iter().map(|cell| cell.stop_tasks())).await;
+        Ok(())
+    }
+
     async fn cell_by_id(&self, cell_id: &CellId) -> ConductorApiResult<Arc<Cell>> {
         let lock = self.conductor.read().await;
         Ok(lock.cell_by_id(cell_id)?)
     }
+
+    /// Count the peers in the network of a Dna shortly,
+    /// sending a conductor event if the number has changed.
+    async fn schedule_peer_count(&self, dna_hash: DnaHash) {
+        self.conductor.read().await.schedule_peer_count(dna_hash);
+    }
+
+    /// The cell running the configured DPKI app.
+    ///
+    /// The DPKI app's own cells go through genesis before it can be
+    /// activated, so until it is active keys are treated as if
+    /// DPKI wasn't configured.
+    async fn dpki_cell_id(&self) -> ConductorApiResult<Option<CellId>> {
+        let installed_app_id = match &self.dpki {
+            Some(dpki) => &dpki.instance_id,
+            None => return Ok(None),
+        };
+        let state = self.conductor.read().await.get_state().await?;
+        match state.active_apps.get(installed_app_id) {
+            Some(app) => Ok(app
+                .provisioned_cells()
+                .min_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(_, cell_id)| cell_id.clone())),
+            None => {
+                warn!(
+                    msg = "DPKI is configured but the DPKI app is not active",
+                    ?installed_app_id
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Call a DPKI function as the agent of the DPKI cell.
+    async fn call_dpki<I, O>(
+        &self,
+        dpki_cell_id: CellId,
+        fn_name: &str,
+        input: I,
+    ) -> ConductorApiResult<O>
+    where
+        I: serde::Serialize + std::fmt::Debug,
+        O: serde::de::DeserializeOwned + std::fmt::Debug,
+    {
+        let call = ZomeCall {
+            provenance: dpki_cell_id.agent_pubkey().clone(),
+            cell_id: dpki_cell_id,
+            zome_name: DPKI_ZOME_NAME.into(),
+            fn_name: fn_name.into(),
+            payload: ExternIO::encode(input).map_err(SerializationError::from)?,
+            cap: None,
+        };
+        match self.call_zome(call).await? {
+            Ok(ZomeCallResponse::Ok(output)) => {
+                Ok(output.decode().map_err(SerializationError::from)?)
+            }
+            Ok(response) => Err(ConductorApiError::DpkiError(format!("{:?}", response))),
+            Err(e) => Err(ConductorApiError::DpkiError(e.to_string())),
+        }
+    }
 }
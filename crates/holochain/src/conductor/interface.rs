@@ -4,8 +4,10 @@
 //!
 //! Currently the only InterfaceDriver is a Websocket-based one, whose
 //! implementation can be found in the `websocket` module here.
+//! The `metrics` module serves the conductor's metrics over plain HTTP.
 
 use crate::conductor::api::*;
+use crate::conductor::conductor::StopBroadcaster;
 use error::InterfaceError;
 use error::InterfaceResult;
 use holochain_types::signal::Signal;
@@ -14,6 +16,7 @@ use tokio::sync::broadcast;
 
 #[allow(missing_docs)]
 pub mod error;
+pub mod metrics;
 pub mod websocket;
 
 /// Represents runtime data about an existing App interface.
@@ -24,6 +27,8 @@ pub enum AppInterfaceRuntime {
     Websocket {
         /// The channel for this interface to send Signals across
         signal_tx: broadcast::Sender<Signal>,
+        /// Stops just this interface when it is detached
+        detach_tx: StopBroadcaster,
     },
 
     #[cfg(any(test, feature = "test_utils"))]
@@ -43,6 +48,18 @@ impl AppInterfaceRuntime {
             Self::Test { signal_tx, .. } => signal_tx,
         }
     }
+
+    /// Stop the interface's task, closing its connections
+    pub fn detach(&self) {
+        match self {
+            Self::Websocket { detach_tx, .. } => {
+                // The task may have already stopped
+                let _ = detach_tx.send(());
+            }
+            #[cfg(any(test, feature = "test_utils"))]
+            Self::Test { .. } => {}
+        }
+    }
 }
 
 /// A collection of Senders to be used for emitting Signals from a Cell.
@@ -29,6 +29,8 @@ pub enum InterfaceError {
     IoTodo(#[from] std::io::Error),
     #[error("Failed to find free port")]
     PortError,
+    #[error("The metrics endpoint must be on a loopback address, not {0}")]
+    MetricsEndpointNotLoopback(std::net::SocketAddr),
 }
 
 impl From<String> for InterfaceError {
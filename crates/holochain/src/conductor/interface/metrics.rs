@@ -0,0 +1,185 @@
+//! A plain HTTP endpoint which serves the conductor's [`Metrics`]
+//! in the Prometheus text format, for scraping.
+//!
+//! Every request gets the current metrics, whatever its method or path.
+//! The endpoint can only be bound to a loopback address, as anyone who can
+//! reach it can read the metrics.
+
+use super::error::InterfaceError;
+use super::error::InterfaceResult;
+use crate::conductor::conductor::StopReceiver;
+use crate::conductor::manager::ManagedTaskHandle;
+use crate::conductor::manager::ManagedTaskResult;
+use crate::core::metrics::Metrics;
+use futures::stream::FuturesUnordered;
+use futures::stream::StreamExt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tracing::*;
+
+/// The largest request head read before responding
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// How long a client has to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many requests are answered at the same time.
+/// Further connections wait to be accepted.
+const MAX_CONCURRENT_SCRAPES: usize = 4;
+
+/// Bind the listener for the metrics endpoint,
+/// which must be on a loopback address
+pub async fn bind_metrics_endpoint(addr: SocketAddr) -> InterfaceResult<TcpListener> {
+    if !addr.ip().is_loopback() {
+        return Err(InterfaceError::MetricsEndpointNotLoopback(addr));
+    }
+    Ok(TcpListener::bind(addr).await?)
+}
+
+/// Serve `metrics` on `listener` until the conductor stops
+pub fn spawn_metrics_endpoint_task(
+    listener: TcpListener,
+    metrics: Metrics,
+    stop_rx: StopReceiver,
+) -> ManagedTaskHandle {
+    tokio::task::spawn(serve(listener, metrics, stop_rx))
+}
+
+async fn serve(
+    mut listener: TcpListener,
+    metrics: Metrics,
+    mut stop_rx: StopReceiver,
+) -> ManagedTaskResult {
+    let mut scrapes = FuturesUnordered::new();
+    loop {
+        tokio::select! {
+            _ = stop_rx.recv() => { break; },
+            Some(()) = scrapes.next(), if !scrapes.is_empty() => {},
+            connection = listener.accept(), if scrapes.len() < MAX_CONCURRENT_SCRAPES => {
+                let (stream, remote_addr) = connection?;
+                let metrics = metrics.clone();
+                scrapes.push(async move {
+                    if let Err(err) = respond(stream, &metrics).await {
+                        warn!(%remote_addr, "Metrics request failed: {}", err);
+                    }
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn respond(mut stream: TcpStream, metrics: &Metrics) -> std::io::Result<()> {
+    // The request itself doesn't matter, but it is read
+    // so the client doesn't see the connection reset under it
+    tokio::time::timeout(REQUEST_TIMEOUT, read_request_head(&mut stream))
+        .await
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+    let (status, body) = match metrics.render() {
+        Ok(body) => ("200 OK", body),
+        Err(err) => {
+            error!(?err, "Failed to render metrics");
+            ("500 Internal Server Error", String::new())
+        }
+    };
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown(std::net::Shutdown::Write)
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_SIZE {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn serves_metrics_over_http() {
+        let listener = bind_metrics_endpoint(([127, 0, 0, 1], 0).into())
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop_tx, stop_rx) = tokio::sync::broadcast::channel(1);
+        let task = spawn_metrics_endpoint_task(listener, Metrics::default(), stop_rx);
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4\r\n"));
+
+        stop_tx.send(()).unwrap();
+        task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn only_binds_loopback_addresses() {
+        let addr = ([0, 0, 0, 0], 0).into();
+        matches::assert_matches!(
+            bind_metrics_endpoint(addr).await,
+            Err(InterfaceError::MetricsEndpointNotLoopback(a)) if a == addr
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn answers_a_few_requests_at_a_time() {
+        let listener = bind_metrics_endpoint(([127, 0, 0, 1], 0).into())
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop_tx, stop_rx) = tokio::sync::broadcast::channel(1);
+        let task = spawn_metrics_endpoint_task(listener, Metrics::default(), stop_rx);
+
+        // Connections which haven't sent their requests yet
+        let mut idle = Vec::new();
+        for _ in 0..MAX_CONCURRENT_SCRAPES {
+            idle.push(TcpStream::connect(addr).await.unwrap());
+        }
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        // isn't answered until one of them is done
+        assert!(tokio::time::timeout(
+            Duration::from_millis(500),
+            stream.read_to_string(&mut response)
+        )
+        .await
+        .is_err());
+        drop(idle.pop());
+        tokio::time::timeout(Duration::from_secs(1), stream.read_to_string(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+
+        stop_tx.send(()).unwrap();
+        task.await.unwrap().unwrap();
+    }
+}
@@ -3,23 +3,34 @@
 
 use super::error::InterfaceError;
 use super::error::InterfaceResult;
+use crate::conductor::conductor::ConductorEventBroadcaster;
 use crate::conductor::conductor::StopReceiver;
 use crate::conductor::interface::*;
 use crate::conductor::manager::ManagedTaskHandle;
-use crate::conductor::manager::ManagedTaskResult;
+use futures::future::AbortHandle;
+use futures::future::Abortable;
+use futures::future::Aborted;
+use holochain_conductor_api::ConductorEvent;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_types::signal::Signal;
 use holochain_websocket::websocket_bind;
+#[cfg(unix)]
+use holochain_websocket::websocket_bind_unix;
 use holochain_websocket::WebsocketConfig;
 use holochain_websocket::WebsocketListener;
 use holochain_websocket::WebsocketMessage;
 use holochain_websocket::WebsocketReceiver;
 use holochain_websocket::WebsocketSender;
 use std::convert::TryFrom;
+use std::future::Future;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
 
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::stream::StreamExt;
 use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
@@ -31,29 +42,64 @@ use url2::url2;
 /// back pressure.
 pub(crate) const SIGNAL_BUFFER_SIZE: usize = 50;
 const MAX_CONNECTIONS: usize = 400;
-
-/// Create a WebsocketListener to be used in interfaces
-pub async fn spawn_websocket_listener(port: u16) -> InterfaceResult<WebsocketListener> {
-    trace!("Initializing Admin interface");
-    let listener = websocket_bind(
-        url2!("ws://127.0.0.1:{}", port),
-        Arc::new(WebsocketConfig::default()),
-    )
-    .await?;
+/// How long connections are given to respond to the requests they are
+/// handling when their interface stops, before they are cut off.
+const CONNECTION_DRAIN_PERIOD: Duration = Duration::from_secs(5);
+
+/// Create a WebsocketListener to be used in interfaces.
+/// Websocket interfaces refuse browsers from origins other than their
+/// `allowed_origins`, and Unix socket interfaces can only be reached by
+/// the user running the conductor.
+pub async fn spawn_websocket_listener(
+    driver: &InterfaceDriver,
+) -> InterfaceResult<WebsocketListener> {
+    trace!("Initializing interface");
+    let listener = match driver {
+        InterfaceDriver::Websocket {
+            port,
+            bind_address,
+            allowed_origins,
+        } => {
+            let config = WebsocketConfig {
+                allowed_origins: allowed_origins.clone(),
+                ..Default::default()
+            };
+            let bind_address = bind_address.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+            websocket_bind(
+                url2!("ws://{}", SocketAddr::new(bind_address, *port)),
+                Arc::new(config),
+            )
+            .await?
+        }
+        #[cfg(unix)]
+        InterfaceDriver::UnixSocket { path } => {
+            websocket_bind_unix(path, Arc::new(WebsocketConfig::default())).await?
+        }
+        #[cfg(not(unix))]
+        InterfaceDriver::UnixSocket { .. } => {
+            return Err(InterfaceError::Other(
+                "Unix sockets are not supported on this platform".to_string(),
+            ))
+        }
+    };
     trace!("LISTENING AT: {}", listener.local_addr());
     Ok(listener)
 }
 
-/// Create an Admin Interface, which only receives AdminRequest messages
-/// from the external client
-pub fn spawn_admin_interface_task<A: InterfaceApi>(
+/// Create an Admin Interface, which receives AdminRequest messages
+/// from the external client, and sends conductor events to the
+/// connections which have subscribed to them.
+/// Each connection authenticates separately, if the interface requires it.
+/// When the conductor stops, connections are given a drain period
+/// to respond to the requests they are handling before they are cut off.
+pub fn spawn_admin_interface_task<A: InterfaceApi + AdminInterfaceApi>(
     mut listener: WebsocketListener,
     api: A,
+    event_broadcaster: ConductorEventBroadcaster,
     mut stop_rx: StopReceiver,
 ) -> InterfaceResult<ManagedTaskHandle> {
     Ok(tokio::task::spawn(async move {
-        let mut listener_handles = Vec::new();
-        let mut send_sockets = Vec::new();
+        let mut connections = Connections::new();
         let num_connections = Arc::new(AtomicUsize::new(0));
         loop {
             tokio::select! {
@@ -65,18 +111,20 @@ pub fn spawn_admin_interface_task<A: InterfaceApi>(
                     match connection {
                         Ok((mut tx_to_iface, rx_from_iface)) => {
                             if num_connections.fetch_add(1, Ordering::Relaxed) > MAX_CONNECTIONS {
-                                if let Err(e) = WebsocketSender::close(&mut tx_to_iface, 1000, "Connections Full".into()).await {
-                                    warn!("Admin socket full failed to close: {}", e);
-                                }
+                                num_connections.fetch_sub(1, Ordering::Relaxed);
+                                close_connection(&mut tx_to_iface, CloseReason::ConnectionsFull).await;
                                 continue;
                             };
-                            send_sockets.push(tx_to_iface.clone());
-                            listener_handles.push(tokio::task::spawn(recv_incoming_admin_msgs(
-                                api.clone(),
+                            let remote_addr = rx_from_iface.remote_addr().to_string();
+                            let task = recv_incoming_admin_msgs(
+                                api.for_connection(),
                                 rx_from_iface,
-                                tx_to_iface,
+                                event_broadcaster.subscribe(),
+                                tx_to_iface.clone(),
+                                connections.drain_receiver(),
                                 num_connections.clone(),
-                            )));
+                            );
+                            connections.spawn(remote_addr, tx_to_iface, task);
                         }
                         Err(err) => {
                             warn!("Admin socket connection failed: {}", err);
@@ -90,177 +138,352 @@ pub fn spawn_admin_interface_task<A: InterfaceApi>(
                 }
             }
         }
-        // TODO: TK-01261: drop listener, make sure all these tasks finish!
+        // Stop accepting connections, freeing the port or socket path
         drop(listener);
 
-        // TODO: TK-01261: Make tx_to_iface close tell the recv socket to close locally in the websocket code
-        for mut tx_to_iface in send_sockets {
-            // TODO: TK-01261: change from u16 code to enum
-            WebsocketSender::close(&mut tx_to_iface, 1000, "Shutting down".into()).await?;
-        }
-
-        // These SHOULD end soon after we get here, or by the time we get here.
-        for h in listener_handles {
-            // Show if these are actually finishing
-            match tokio::time::timeout(std::time::Duration::from_secs(1), h).await {
-                Ok(r) => r?,
-                Err(_) => warn!("Websocket listener failed to join child tasks"),
-            }
-        }
-        ManagedTaskResult::Ok(())
+        connections.drain(CloseReason::Shutdown).await;
+        Ok(())
     }))
 }
 
 /// Create an App Interface, which includes the ability to receive signals
-/// from Cells via a broadcast channel
-pub async fn spawn_app_interface_task<A: InterfaceApi>(
-    port: u16,
+/// from Cells via a broadcast channel.
+/// Each connection only receives the signals it has subscribed to.
+/// The interface stops when the conductor stops or when it is detached
+/// on its own through `detach_rx`, and drains its connections
+/// like the Admin interface does.
+pub fn spawn_app_interface_task<A: InterfaceApi + AppInterfaceApi>(
+    mut listener: WebsocketListener,
     api: A,
     signal_broadcaster: broadcast::Sender<Signal>,
     mut stop_rx: StopReceiver,
-) -> InterfaceResult<(u16, ManagedTaskHandle)> {
+    mut detach_rx: StopReceiver,
+) -> InterfaceResult<ManagedTaskHandle> {
     trace!("Initializing App interface");
-    let mut listener = websocket_bind(
-        url2!("ws://127.0.0.1:{}", port),
-        Arc::new(WebsocketConfig::default()),
-    )
-    .await?;
-    trace!("LISTENING AT: {}", listener.local_addr());
-    let port = listener
-        .local_addr()
-        .port()
-        .ok_or(InterfaceError::PortError)?;
-    let task = tokio::task::spawn(async move {
-        let mut listener_handles = Vec::new();
-
-        let mut handle_connection =
-            |tx_to_iface: WebsocketSender, rx_from_iface: WebsocketReceiver| {
-                let rx_from_cell = signal_broadcaster.subscribe();
-                listener_handles.push(tokio::task::spawn(recv_incoming_msgs_and_outgoing_signals(
-                    api.clone(),
-                    rx_from_iface,
-                    rx_from_cell,
-                    tx_to_iface,
-                )));
-            };
-
-        loop {
+    Ok(tokio::task::spawn(async move {
+        let mut connections = Connections::new();
+        let close_reason = loop {
             tokio::select! {
                 // break if we receive on the stop channel
-                _ = stop_rx.recv() => { break; },
+                _ = stop_rx.recv() => { break CloseReason::Shutdown; },
+
+                // break if this interface is detached
+                _ = detach_rx.recv() => { break CloseReason::Detached; },
 
                 // establish a new connection to a client
                 maybe_con = listener.next() => if let Some(connection) = maybe_con {
                     match connection {
                         Ok((tx_to_iface, rx_from_iface)) => {
-                            handle_connection(tx_to_iface, rx_from_iface);
+                            let remote_addr = rx_from_iface.remote_addr().to_string();
+                            let task = recv_incoming_msgs_and_outgoing_signals(
+                                api.for_connection(),
+                                rx_from_iface,
+                                signal_broadcaster.subscribe(),
+                                tx_to_iface.clone(),
+                                connections.drain_receiver(),
+                            );
+                            connections.spawn(remote_addr, tx_to_iface, task);
                         }
                         Err(err) => {
-                            warn!("Admin socket connection failed: {}", err);
+                            warn!("App socket connection failed: {}", err);
                         }
                     }
                 } else {
-                    break;
+                    break CloseReason::Shutdown;
                 }
             }
+        };
+        // Stop accepting connections, freeing the port or socket path
+        drop(listener);
+
+        connections.drain(close_reason).await;
+        Ok(())
+    }))
+}
+
+/// Why an interface closed a connection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseReason {
+    /// The conductor is shutting down
+    Shutdown,
+    /// The interface was detached from the conductor
+    Detached,
+    /// The interface already has as many connections as it allows
+    ConnectionsFull,
+    /// The client closed the connection
+    ClientClosed,
+}
+
+impl CloseReason {
+    /// The websocket close code sent for this reason, as defined in RFC 6455
+    pub fn code(&self) -> u16 {
+        match self {
+            // Going Away
+            CloseReason::Shutdown | CloseReason::Detached => 1001,
+            // Try Again Later
+            CloseReason::ConnectionsFull => 1013,
+            // Normal Closure
+            CloseReason::ClientClosed => 1000,
         }
+    }
+
+    /// The text sent along with the close code
+    pub fn message(&self) -> &'static str {
+        match self {
+            CloseReason::Shutdown => "Shutting down",
+            CloseReason::Detached => "Interface detached",
+            CloseReason::ConnectionsFull => "Connections full",
+            CloseReason::ClientClosed => "Closed",
+        }
+    }
+}
+
+/// Close a connection, which the client may have already closed
+async fn close_connection(tx_to_iface: &mut WebsocketSender, reason: CloseReason) {
+    if let Err(e) =
+        WebsocketSender::close(tx_to_iface, reason.code(), reason.message().into()).await
+    {
+        debug!(?reason, "Socket failed to close: {}", e);
+    }
+}
+
+type DrainReceiver = broadcast::Receiver<CloseReason>;
 
-        handle_shutdown(listener_handles).await;
-        ManagedTaskResult::Ok(())
-    });
-    Ok((port, task))
+/// The connections of an interface, each handled by its own task
+struct Connections {
+    /// Tells the connections to close, and why
+    drain_tx: broadcast::Sender<CloseReason>,
+    /// How long connections are given to close when draining
+    drain_period: Duration,
+    connections: Vec<Connection>,
 }
 
-async fn handle_shutdown(listener_handles: Vec<JoinHandle<InterfaceResult<()>>>) {
-    for h in listener_handles {
-        // Show if these are actually finishing
-        match tokio::time::timeout(std::time::Duration::from_secs(1), h).await {
-            Ok(Ok(Ok(_))) => {}
-            r => warn!(message = "Websocket listener failed to join child tasks", result = ?r),
+/// The task handling a connection, and what is needed to cut it off
+struct Connection {
+    remote_addr: String,
+    tx_to_iface: WebsocketSender,
+    abort_handle: AbortHandle,
+    handle: JoinHandle<Result<InterfaceResult<()>, Aborted>>,
+}
+
+impl Connections {
+    fn new() -> Self {
+        Self::with_drain_period(CONNECTION_DRAIN_PERIOD)
+    }
+
+    fn with_drain_period(drain_period: Duration) -> Self {
+        let (drain_tx, _) = broadcast::channel(1);
+        Self {
+            drain_tx,
+            drain_period,
+            connections: Vec::new(),
+        }
+    }
+
+    /// A receiver for a new connection's task to learn when to close
+    fn drain_receiver(&self) -> DrainReceiver {
+        self.drain_tx.subscribe()
+    }
+
+    /// Spawn the task handling a connection
+    fn spawn<F>(&mut self, remote_addr: String, tx_to_iface: WebsocketSender, task: F)
+    where
+        F: Future<Output = InterfaceResult<()>> + Send + 'static,
+    {
+        trace!("CONNECTION: {}", remote_addr);
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let handle = tokio::task::spawn(Abortable::new(task, abort_registration));
+        self.connections.push(Connection {
+            remote_addr,
+            tx_to_iface,
+            abort_handle,
+            handle,
+        });
+    }
+
+    /// Tell every connection to close for `reason`. Each connection stops
+    /// taking new requests and closes once it has responded to the request
+    /// it is handling, if any.
+    /// Connections which take longer than the drain period are cut off,
+    /// cancelling their requests, and their remote addresses are returned.
+    /// The interface was asked to stop, so cutting them off is only logged
+    /// rather than failing its task, which would restart it.
+    async fn drain(self, reason: CloseReason) -> Vec<String> {
+        // Every connection may have already closed
+        self.drain_tx.send(reason).ok();
+        let drain_period = self.drain_period;
+        let drained = self
+            .connections
+            .into_iter()
+            .map(|mut connection| async move {
+                match tokio::time::timeout(drain_period, &mut connection.handle).await {
+                    Ok(Ok(Ok(Ok(_)))) => None,
+                    Ok(r) => {
+                        warn!(message = "Connection task failed", result = ?r);
+                        None
+                    }
+                    Err(_) => {
+                        connection.abort_handle.abort();
+                        // The task's future is dropped the next time it's polled
+                        if let Err(e) = connection.handle.await {
+                            warn!(
+                                error = &e as &dyn std::error::Error,
+                                "Failed to cut off connection"
+                            );
+                        }
+                        close_connection(&mut connection.tx_to_iface, reason).await;
+                        Some(connection.remote_addr)
+                    }
+                }
+            });
+        let cut: Vec<_> = futures::future::join_all(drained)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        if !cut.is_empty() {
+            warn!(?reason, ?cut, "Cut off connections of a stopped interface");
         }
+        cut
     }
 }
 
-/// Polls for messages coming in from the external client.
+/// Polls for messages coming in from the external client while
+/// sending conductor events out, if the connection has subscribed to them.
 /// Used by Admin interface.
-async fn recv_incoming_admin_msgs<A: InterfaceApi>(
+async fn recv_incoming_admin_msgs<A: InterfaceApi + AdminInterfaceApi>(
     api: A,
     mut rx_from_iface: WebsocketReceiver,
+    mut rx_from_conductor: broadcast::Receiver<ConductorEvent>,
     mut tx_to_iface: WebsocketSender,
+    mut drain_rx: DrainReceiver,
     num_connections: Arc<AtomicUsize>,
-) {
-    while let Some(msg) = rx_from_iface.next().await {
-        match handle_incoming_message(msg, api.clone()).await {
-            Err(InterfaceError::Closed) => {
-                if let Err(e) =
-                    WebsocketSender::close(&mut tx_to_iface, 1000, "Shutting down".into()).await
-                {
-                    warn!("Admin socket failed to close: {}", e);
-                }
-                // Do an atomic checked sub.
-                // This can still fail to decrement but won't overflow.
-                // This is ok because we really only need a rough idea if of the number of connections
-                // and failing to decrement should be rare.
-                let old_value = num_connections.load(Ordering::SeqCst);
-                if old_value > 0 {
-                    let prev_value = num_connections.compare_and_swap(
-                        old_value,
-                        old_value - 1,
-                        Ordering::SeqCst,
-                    );
-                    if prev_value != old_value {
-                        warn!(msg = "Websocket didn't successfully decrement connections on close");
+) -> InterfaceResult<()> {
+    let close_reason = loop {
+        let msg = tokio::select! {
+            // The interface is stopping
+            reason = drain_rx.recv() => break reason.unwrap_or(CloseReason::Shutdown),
+
+            event = rx_from_conductor.next() => {
+                match event {
+                    Some(Ok(event)) => {
+                        if api.events_subscribed() {
+                            trace!(msg = "Sending conductor event", ?event);
+                            if let Err(e) = send_conductor_event(&mut tx_to_iface, event).await {
+                                warn!(
+                                    error = &e as &dyn std::error::Error,
+                                    "Failed to send conductor event"
+                                );
+                            }
+                        }
+                    }
+                    Some(Err(broadcast::RecvError::Lagged(missed))) => {
+                        warn!(missed, "Admin connection fell behind on conductor events");
                     }
+                    // The conductor has gone away
+                    Some(Err(broadcast::RecvError::Closed)) | None => break CloseReason::Shutdown,
                 }
-                break;
-            }
+                continue;
+            },
+            msg = rx_from_iface.next() => match msg {
+                Some(msg) => msg,
+                None => break CloseReason::ClientClosed,
+            },
+        };
+        // Don't take new requests once the interface is stopping
+        if let Ok(reason) = drain_rx.try_recv() {
+            break reason;
+        }
+        match handle_incoming_message(msg, api.clone()).await {
+            Err(InterfaceError::Closed) => break CloseReason::ClientClosed,
             Err(e) => error!(error = &e as &dyn std::error::Error),
             Ok(()) => {}
         }
+    };
+    close_connection(&mut tx_to_iface, close_reason).await;
+
+    // Do an atomic checked sub.
+    // This can still fail to decrement but won't overflow.
+    // This is ok because we really only need a rough idea if of the number of connections
+    // and failing to decrement should be rare.
+    let old_value = num_connections.load(Ordering::SeqCst);
+    if old_value > 0 {
+        let prev_value =
+            num_connections.compare_and_swap(old_value, old_value - 1, Ordering::SeqCst);
+        if prev_value != old_value {
+            warn!(msg = "Websocket didn't successfully decrement connections on close");
+        }
     }
+    Ok(())
+}
+
+/// Send a conductor event to the external client as a signal
+async fn send_conductor_event(
+    tx_to_iface: &mut WebsocketSender,
+    event: ConductorEvent,
+) -> InterfaceResult<()> {
+    let bytes = SerializedBytes::try_from(event)?;
+    tx_to_iface.signal(bytes).await?;
+    Ok(())
 }
 
 /// Polls for messages coming in from the external client while simultaneously
 /// polling for signals being broadcast from the Cells associated with this
 /// App interface.
-async fn recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi>(
+async fn recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi + AppInterfaceApi>(
     api: A,
     mut rx_from_iface: WebsocketReceiver,
     mut rx_from_cell: broadcast::Receiver<Signal>,
     mut tx_to_iface: WebsocketSender,
+    mut drain_rx: DrainReceiver,
 ) -> InterfaceResult<()> {
-    trace!("CONNECTION: {}", rx_from_iface.remote_addr());
-
-    loop {
+    let close_reason = loop {
         tokio::select! {
+            // The interface is stopping
+            reason = drain_rx.recv() => break reason.unwrap_or(CloseReason::Shutdown),
+
             // If we receive a Signal broadcasted from a Cell, push it out
             // across the interface
             // NOTE: we could just use futures::StreamExt::forward to hook this
             // tx and rx together in a new spawned task
             signal = rx_from_cell.next() => {
                 if let Some(signal) = signal {
+                    let signal = signal.map_err(InterfaceError::SignalReceive)?;
+                    if !api.signal_allowed(&signal).await {
+                        trace!(msg = "Signal filtered out by subscription", ?signal);
+                        continue;
+                    }
                     trace!(msg = "Sending signal!", ?signal);
-                    let bytes = SerializedBytes::try_from(
-                        signal.map_err(InterfaceError::SignalReceive)?,
-                    )?;
+                    let bytes = SerializedBytes::try_from(signal)?;
                     tx_to_iface.signal(bytes).await?;
                 } else {
                     debug!("Closing interface: signal stream empty");
-                    break;
+                    break CloseReason::Shutdown;
                 }
             },
 
             // If we receive a message from outside, handle it
             msg = rx_from_iface.next() => {
                 if let Some(msg) = msg {
-                    handle_incoming_message(msg, api.clone()).await?
+                    // Don't take new requests once the interface is stopping
+                    if let Ok(reason) = drain_rx.try_recv() {
+                        break reason;
+                    }
+                    match handle_incoming_message(msg, api.clone()).await {
+                        Err(InterfaceError::Closed) => {
+                            debug!("Closing interface: connection closed");
+                            break CloseReason::ClientClosed;
+                        }
+                        r => r?,
+                    }
                 } else {
                     debug!("Closing interface: message stream empty");
-                    break;
+                    break CloseReason::ClientClosed;
                 }
             },
         }
-    }
+    };
+    close_connection(&mut tx_to_iface, close_reason).await;
 
     Ok(())
 }
@@ -335,6 +558,7 @@ pub mod test_utils {
 pub mod test {
     use super::test_utils::setup_app;
     use super::*;
+    use crate::conductor::api::error::ConductorApiResult;
     use crate::conductor::api::error::ExternalApiWireError;
     use crate::conductor::api::AdminRequest;
     use crate::conductor::api::AdminResponse;
@@ -350,6 +574,10 @@ pub mod test {
     use ::fixt::prelude::*;
     use fallible_iterator::FallibleIterator;
     use futures::future::FutureExt;
+    use holochain_conductor_api::signal_subscription::SignalFilter;
+    use holochain_conductor_api::signal_subscription::SignalFilterSet;
+    use holochain_conductor_api::signal_subscription::SignalSubscription;
+    use holochain_keystore::KeystoreSenderExt;
     use holochain_lmdb::buffer::KvStoreT;
     use holochain_lmdb::fresh_reader_test;
     use holochain_lmdb::test_utils::test_environments;
@@ -360,9 +588,12 @@ pub mod test {
     use holochain_types::test_utils::fake_dna_zomes;
     use holochain_types::{app::InstallAppDnaPayload, prelude::InstallAppPayload};
     use holochain_wasm_test_utils::TestWasm;
+    use holochain_websocket::websocket_connect;
+    use holochain_websocket::WebsocketClosed;
     use holochain_websocket::WebsocketMessage;
     use holochain_zome_types::cell::CellId;
     use holochain_zome_types::test_utils::fake_agent_pubkey_2;
+    use holochain_zome_types::test_utils::fake_cell_id;
     use holochain_zome_types::ExternIO;
     use kitsune_p2p::agent_store::AgentInfoSigned;
     use kitsune_p2p::fixt::AgentInfoSignedFixturator;
@@ -544,6 +775,305 @@ pub mod test {
         shutdown.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn app_signal_subscription() {
+        observability::test_run().ok();
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let dna_hash = dna.dna_hash().clone();
+        let cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_1()));
+        let installed_cell = InstalledCell::new(cell_id.clone(), "handle".into());
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store
+            .expect_get()
+            .with(predicate::eq(dna_hash))
+            .returning(move |_| Some(dna.clone()));
+        dna_store
+            .expect_add_dnas::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        dna_store
+            .expect_add_entry_defs::<Vec<_>>()
+            .times(1)
+            .return_const(());
+
+        let (_tmpdir, app_api, handle) = setup_app(vec![(installed_cell, None)], dna_store).await;
+        let app_api = app_api.for_connection();
+
+        let payload = ExternIO::encode(()).unwrap();
+        let untopical = Signal::App(
+            cell_id.clone(),
+            TestWasm::Foo.into(),
+            AppSignal::new(payload.clone()),
+        );
+        let topical = Signal::App(
+            cell_id.clone(),
+            TestWasm::Foo.into(),
+            AppSignal::with_topic("posts".to_string(), payload.clone()),
+        );
+        let other_app = Signal::App(
+            fake_cell_id(9),
+            TestWasm::Foo.into(),
+            AppSignal::new(payload),
+        );
+        assert!(app_api.signal_allowed(&untopical).await);
+
+        let mut filters = HashMap::new();
+        filters.insert(cell_id, SignalFilter::topics(vec!["posts".to_string()]));
+        let response = app_api
+            .handle_app_request(AppRequest::SignalSubscription(SignalSubscription {
+                installed_app_id: "test app".to_string(),
+                filters: SignalFilterSet::Include(filters),
+            }))
+            .await;
+        assert_matches!(response, AppResponse::SignalSubscriptionUpdated);
+        assert!(!app_api.signal_allowed(&untopical).await);
+        assert!(app_api.signal_allowed(&topical).await);
+        assert!(app_api.signal_allowed(&other_app).await);
+
+        // Other connections keep their own subscriptions
+        assert!(app_api.for_connection().signal_allowed(&untopical).await);
+
+        let response = app_api
+            .handle_app_request(AppRequest::SignalSubscription(SignalSubscription {
+                installed_app_id: "not installed".to_string(),
+                filters: SignalFilterSet::block_all(),
+            }))
+            .await;
+        assert_matches!(response, AppResponse::Error(_));
+
+        // The app's cells are looked up again once it's uninstalled,
+        // so its subscription stops applying to them
+        handle
+            .uninstall_app("test app".to_string(), false)
+            .await
+            .unwrap();
+        assert!(app_api.signal_allowed(&untopical).await);
+
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        handle.shutdown().await;
+        shutdown.await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn app_signal_subscriptions_are_listed() {
+        observability::test_run().ok();
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let dna_hash = dna.dna_hash().clone();
+        let cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_1()));
+        let installed_cell = InstalledCell::new(cell_id.clone(), "handle".into());
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store
+            .expect_get()
+            .with(predicate::eq(dna_hash))
+            .returning(move |_| Some(dna.clone()));
+        dna_store
+            .expect_add_dnas::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        dna_store
+            .expect_add_entry_defs::<Vec<_>>()
+            .times(1)
+            .return_const(());
+
+        let (_tmpdir, _, handle) = setup_app(vec![(installed_cell, None)], dna_store).await;
+        handle
+            .clone()
+            .add_app_interface(InterfaceDriver::websocket(0), None)
+            .await
+            .unwrap();
+        let interfaces = handle.list_app_interfaces().await.unwrap();
+        assert!(interfaces[0].signal_subscriptions.is_empty());
+        let app_api =
+            RealAppInterfaceApi::new(handle.clone(), interfaces[0].interface_id.as_str().into())
+                .for_connection();
+
+        let mut filters = HashMap::new();
+        filters.insert(cell_id, SignalFilter::topics(vec!["posts".to_string()]));
+        let subscription = SignalSubscription {
+            installed_app_id: "test app".to_string(),
+            filters: SignalFilterSet::Include(filters),
+        };
+        let response = app_api
+            .handle_app_request(AppRequest::SignalSubscription(subscription.clone()))
+            .await;
+        assert_matches!(response, AppResponse::SignalSubscriptionUpdated);
+
+        let interfaces = handle.list_app_interfaces().await.unwrap();
+        assert_eq!(
+            interfaces[0].signal_subscriptions.get("test app"),
+            Some(&subscription)
+        );
+
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        handle.shutdown().await;
+        shutdown.await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn app_crypto_requests() {
+        observability::test_run().ok();
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let dna_hash = dna.dna_hash().clone();
+        let cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_1()));
+        let installed_cell = InstalledCell::new(cell_id.clone(), "handle".into());
+        let other_cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_2()));
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store
+            .expect_get()
+            .with(predicate::eq(dna_hash))
+            .returning(move |_| Some(dna.clone()));
+        dna_store
+            .expect_add_dnas::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        dna_store
+            .expect_add_entry_defs::<Vec<_>>()
+            .times(1)
+            .return_const(());
+
+        let (_tmpdir, app_api, handle) = setup_app(vec![(installed_cell, None)], dna_store).await;
+        // Another app, whose keys the first app's clients must not be able to use
+        let other_cell = InstalledCell::new(other_cell_id, "other".into());
+        handle
+            .clone()
+            .install_app("other app".to_string(), vec![(other_cell, None)])
+            .await
+            .unwrap();
+        let unattached_api = app_api.clone();
+        let app_api = app_api.attached_to_app(Some("test app".to_string()));
+        let crypto = |request: CryptoRequest| {
+            let app_api = app_api.clone();
+            async move {
+                app_api
+                    .handle_app_request(AppRequest::Crypto(Box::new(request)))
+                    .await
+            }
+        };
+
+        // An interface that isn't attached to an app can't do crypto
+        let data = b"login challenge".to_vec();
+        let response = unattached_api
+            .handle_app_request(AppRequest::Crypto(Box::new(CryptoRequest::Sign {
+                key: fake_agent_pubkey_1(),
+                data: data.clone(),
+            })))
+            .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::CryptoUnauthorized(_))
+        );
+
+        // Sign with the app's agent
+        let response = crypto(CryptoRequest::Sign {
+            key: fake_agent_pubkey_1(),
+            data: data.clone(),
+        })
+        .await;
+        let signature = match response {
+            AppResponse::Crypto(response) => match *response {
+                CryptoResponse::Signature(signature) => signature,
+                r => panic!("unexpected crypto response {:?}", r),
+            },
+            r => panic!("unexpected response {:?}", r),
+        };
+        assert!(fake_agent_pubkey_1()
+            .verify_signature_raw(&signature, &data)
+            .await
+            .unwrap());
+
+        // The other app's agent
+        let response = crypto(CryptoRequest::Sign {
+            key: fake_agent_pubkey_2(),
+            data,
+        })
+        .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::CryptoUnauthorized(_))
+        );
+
+        // Box and open a message with keys created for the app
+        let mut keys = Vec::new();
+        for _ in 0..2 {
+            match crypto(CryptoRequest::CreateX25519Keypair).await {
+                AppResponse::Crypto(response) => match *response {
+                    CryptoResponse::X25519PubKey(key) => keys.push(key),
+                    r => panic!("unexpected crypto response {:?}", r),
+                },
+                r => panic!("unexpected response {:?}", r),
+            }
+        }
+        let (alice, bob) = (keys[0], keys[1]);
+        let message = XSalsa20Poly1305Data::from(b"hello bob".to_vec());
+        let response = crypto(CryptoRequest::Encrypt {
+            input: X25519XSalsa20Poly1305Encrypt::new(alice, bob, message.clone()),
+        })
+        .await;
+        let encrypted = match response {
+            AppResponse::Crypto(response) => match *response {
+                CryptoResponse::Encrypted(encrypted) => encrypted,
+                r => panic!("unexpected crypto response {:?}", r),
+            },
+            r => panic!("unexpected response {:?}", r),
+        };
+        let response = crypto(CryptoRequest::Decrypt {
+            input: X25519XSalsa20Poly1305Decrypt::new(alice, bob, encrypted.clone()),
+        })
+        .await;
+        let decrypted = match response {
+            AppResponse::Crypto(response) => match *response {
+                CryptoResponse::Decrypted(decrypted) => decrypted,
+                r => panic!("unexpected crypto response {:?}", r),
+            },
+            r => panic!("unexpected response {:?}", r),
+        };
+        assert_eq!(decrypted, Some(message.clone()));
+
+        // Keys created for the other app, or for no app at all, can't be used
+        let carol = handle
+            .create_app_x25519_keypair(&"other app".to_string())
+            .await
+            .unwrap();
+        let dave = handle.keystore().create_x25519_keypair().await.unwrap();
+        for key in vec![carol, dave] {
+            let response = crypto(CryptoRequest::Encrypt {
+                input: X25519XSalsa20Poly1305Encrypt::new(key, bob, message.clone()),
+            })
+            .await;
+            assert_matches!(
+                response,
+                AppResponse::Error(ExternalApiWireError::CryptoUnauthorized(_))
+            );
+            let response = crypto(CryptoRequest::Decrypt {
+                input: X25519XSalsa20Poly1305Decrypt::new(alice, key, encrypted.clone()),
+            })
+            .await;
+            assert_matches!(
+                response,
+                AppResponse::Error(ExternalApiWireError::CryptoUnauthorized(_))
+            );
+        }
+
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        handle.shutdown().await;
+        shutdown.await.unwrap();
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn activate_app() {
         observability::test_run().ok();
@@ -677,13 +1207,188 @@ pub mod test {
         shutdown.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn shutdown_closes_idle_connections() {
+        observability::test_run().ok();
+        let (_tmpdir, conductor_handle) = setup_admin().await;
+        let shutdown = conductor_handle.take_shutdown_handle().await.unwrap();
+        let port = conductor_handle
+            .clone()
+            .add_app_interface(InterfaceDriver::websocket(0), None)
+            .await
+            .unwrap()
+            .unwrap();
+        let (mut client, mut client_rx) = websocket_connect(
+            url2!("ws://127.0.0.1:{}", port),
+            Arc::new(WebsocketConfig::default()),
+        )
+        .await
+        .unwrap();
+        let request = AppRequest::AppInfo {
+            installed_app_id: "no such app".to_string(),
+        };
+        let response: AppResponse = client.request(request).await.unwrap();
+        assert_matches!(response, AppResponse::AppInfo(None));
+
+        conductor_handle.shutdown().await;
+
+        // The connection isn't handling a request so it closes straight away
+        let msg = tokio::time::timeout(Duration::from_secs(1), client_rx.next())
+            .await
+            .unwrap();
+        assert_matches!(
+            msg,
+            Some(WebsocketMessage::Close(WebsocketClosed { code: 1001, .. }))
+        );
+        shutdown.await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn drain_cuts_off_busy_connections() {
+        observability::test_run().ok();
+        let mut listener = websocket_bind(
+            url2!("ws://127.0.0.1:0"),
+            Arc::new(WebsocketConfig::default()),
+        )
+        .await
+        .unwrap();
+        let (_client, mut client_rx) = websocket_connect(
+            listener.local_addr().clone(),
+            Arc::new(WebsocketConfig::default()),
+        )
+        .await
+        .unwrap();
+        let (tx_to_iface, rx_from_iface) = listener.next().await.unwrap().unwrap();
+        let remote_addr = rx_from_iface.remote_addr().to_string();
+
+        // A connection which never finishes handling its request
+        let mut connections = Connections::with_drain_period(Duration::from_millis(100));
+        connections.spawn(
+            remote_addr.clone(),
+            tx_to_iface,
+            futures::future::pending::<InterfaceResult<()>>(),
+        );
+        let cut = connections.drain(CloseReason::Shutdown).await;
+        assert_eq!(cut, vec![remote_addr]);
+        let msg = tokio::time::timeout(Duration::from_secs(1), client_rx.next())
+            .await
+            .unwrap();
+        assert_matches!(
+            msg,
+            Some(WebsocketMessage::Close(WebsocketClosed { code: 1001, .. }))
+        );
+    }
+
+    /// An app api whose requests never finish
+    #[derive(Clone)]
+    struct StuckAppApi;
+
+    #[async_trait::async_trait]
+    impl InterfaceApi for StuckAppApi {
+        type ApiRequest = AppRequest;
+        type ApiResponse = AppResponse;
+        async fn handle_request(
+            &self,
+            _request: Result<AppRequest, SerializedBytesError>,
+        ) -> InterfaceResult<AppResponse> {
+            futures::future::pending().await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AppInterfaceApi for StuckAppApi {
+        async fn handle_app_request_inner(
+            &self,
+            _request: AppRequest,
+        ) -> ConductorApiResult<AppResponse> {
+            futures::future::pending().await
+        }
+
+        fn for_connection(&self) -> Self {
+            self.clone()
+        }
+
+        async fn signal_allowed(&self, _signal: &Signal) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn detaching_frees_the_port_and_cuts_off_busy_connections_without_failing() {
+        observability::test_run().ok();
+        let listener = websocket_bind(
+            url2!("ws://127.0.0.1:0"),
+            Arc::new(WebsocketConfig::default()),
+        )
+        .await
+        .unwrap();
+        let port = listener.local_addr().port().unwrap();
+        let (_stop_tx, stop_rx) = broadcast::channel(1);
+        let (detach_tx, detach_rx) = broadcast::channel(1);
+        let (signal_tx, _) = broadcast::channel(1);
+        let task =
+            spawn_app_interface_task(listener, StuckAppApi, signal_tx, stop_rx, detach_rx).unwrap();
+
+        // A connection which never finishes handling its request
+        let (mut client, mut client_rx) = websocket_connect(
+            url2!("ws://127.0.0.1:{}", port),
+            Arc::new(WebsocketConfig::default()),
+        )
+        .await
+        .unwrap();
+        let request = AppRequest::AppInfo {
+            installed_app_id: "stuck".to_string(),
+        };
+        tokio::task::spawn(async move {
+            let _: Result<AppResponse, _> = client.request(request).await;
+        });
+        tokio::time::delay_for(Duration::from_millis(100)).await;
+
+        detach_tx.send(()).unwrap();
+
+        // The port is freed straight away, while the connection is draining
+        let mut rebound = None;
+        for _ in 0..10 {
+            if let Ok(listener) = websocket_bind(
+                url2!("ws://127.0.0.1:{}", port),
+                Arc::new(WebsocketConfig::default()),
+            )
+            .await
+            {
+                rebound = Some(listener);
+                break;
+            }
+            tokio::time::delay_for(Duration::from_millis(100)).await;
+        }
+        assert!(rebound.is_some(), "The port was not freed");
+
+        // Detaching cuts it off without failing, so the interface task isn't restarted
+        let result = tokio::time::timeout(CONNECTION_DRAIN_PERIOD * 2, task)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_matches!(result, Ok(()));
+        let msg = tokio::time::timeout(Duration::from_secs(1), client_rx.next())
+            .await
+            .unwrap();
+        assert_matches!(
+            msg,
+            Some(WebsocketMessage::Close(WebsocketClosed { code: 1001, .. }))
+        );
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn attach_app_interface() {
         observability::test_run().ok();
         let (_tmpdir, conductor_handle) = setup_admin().await;
         let shutdown = conductor_handle.take_shutdown_handle().await.unwrap();
         let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone());
-        let msg = AdminRequest::AttachAppInterface { port: None };
+        let msg = AdminRequest::AttachAppInterface {
+            port: None,
+            bind_address: None,
+            allowed_origins: None,
+            installed_app_id: None,
+        };
         let msg = msg.try_into().unwrap();
         let respond = |bytes: SerializedBytes| {
             let response: AdminResponse = bytes.try_into().unwrap();
@@ -697,6 +1402,49 @@ pub mod test {
         shutdown.await.unwrap();
     }
 
+    #[cfg(unix)]
+    #[tokio::test(threaded_scheduler)]
+    async fn attach_app_unix_socket() {
+        observability::test_run().ok();
+        let (tmpdir, conductor_handle) = setup_admin().await;
+        let shutdown = conductor_handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone());
+        let path = tmpdir.path().join("app.sock");
+        let msg = AdminRequest::AttachAppUnixSocket {
+            path: path.clone(),
+            installed_app_id: None,
+        };
+        let msg = msg.try_into().unwrap();
+        let expected = path.clone();
+        let respond = move |bytes: SerializedBytes| {
+            let response: AdminResponse = bytes.try_into().unwrap();
+            assert_matches!(
+                response,
+                AdminResponse::AppUnixSocketAttached { path } if path == expected
+            );
+            async { Ok(()) }.boxed()
+        };
+        let respond = Box::new(respond);
+        let msg = WebsocketMessage::Request(msg, respond);
+        handle_incoming_message(msg, admin_api).await.unwrap();
+
+        // The app interface speaks the same framing as over a websocket
+        let (mut client, _) = holochain_websocket::websocket_connect_unix(
+            &path,
+            Arc::new(WebsocketConfig::default()),
+        )
+        .await
+        .unwrap();
+        let request = AppRequest::AppInfo {
+            installed_app_id: "no such app".to_string(),
+        };
+        let response: AppResponse = client.request(request).await.unwrap();
+        assert_matches!(response, AppResponse::AppInfo(None));
+
+        conductor_handle.shutdown().await;
+        shutdown.await.unwrap();
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn dump_state() {
         observability::test_run().ok();
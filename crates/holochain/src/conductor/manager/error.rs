@@ -1,6 +1,7 @@
 #![allow(missing_docs)]
 
 use crate::conductor::error::ConductorError;
+use crate::conductor::interface::error::InterfaceError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,6 +9,9 @@ pub enum ManagedTaskError {
     #[error(transparent)]
     Conductor(#[from] ConductorError),
 
+    #[error(transparent)]
+    Interface(#[from] InterfaceError),
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
@@ -16,6 +20,9 @@ pub enum ManagedTaskError {
 
     #[error(transparent)]
     Recv(#[from] tokio::sync::broadcast::RecvError),
+
+    #[error("The network stopped sending events to the conductor")]
+    NetworkEventsEnded,
 }
 
 pub type ManagedTaskResult = Result<(), ManagedTaskError>;
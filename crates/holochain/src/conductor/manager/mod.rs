@@ -4,15 +4,31 @@
 //! then a reaction can be set.
 //! An example would be a websocket closes with an error
 //! and you want to restart it.
+//!
+//! Tasks which can be started again are added with a [RestartPolicy]
+//! and a closure which spawns them, which the task manager calls whenever
+//! the policy says the task should be restarted.
+//! The status of every task is kept in [ManagedTaskStatuses].
 
 mod error;
 pub use error::*;
 
+use crate::conductor::conductor::ConductorEventBroadcaster;
 use futures::stream::FuturesUnordered;
+use holochain_conductor_api::ConductorEvent;
+use holochain_conductor_api::ManagedTaskInfo;
+use holochain_conductor_api::ManagedTaskStatus;
+use holochain_zome_types::cell::CellId;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
 use tokio::stream::StreamExt;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
@@ -28,65 +44,413 @@ pub(crate) type TaskManagerRunHandle = JoinHandle<()>;
 
 pub(crate) type OnDeath = Box<dyn Fn(ManagedTaskResult) -> Option<ManagedTaskAdd> + Send + Sync>;
 
+/// Spawns a new run of a task which has a [RestartPolicy],
+/// which stops when it receives a message on the "stop" channel passed in
+pub(crate) type SpawnTask = Arc<dyn Fn(broadcast::Receiver<()>) -> ManagedTaskHandle + Send + Sync>;
+
+/// The status of every task the task manager is looking after,
+/// and of the tasks which failed and weren't restarted, by task id
+pub(crate) type ManagedTaskStatuses = Arc<parking_lot::Mutex<BTreeMap<u64, ManagedTaskInfo>>>;
+
+/// Asks the conductor to deactivate the apps running a Cell, and why
+pub(crate) type DeactivateCellSender = mpsc::UnboundedSender<(CellId, String)>;
+
+/// What the task manager does when a task stops
+#[derive(Clone, Debug, PartialEq)]
+pub enum RestartPolicy {
+    /// Leave the task stopped
+    Never,
+    /// Start the task again whenever it stops,
+    /// unless the conductor is shutting down
+    Always,
+    /// Start the task again when it fails. The first restart is
+    /// `initial_delay` after the failure and the delay doubles
+    /// with every restart after that, up to `max_delay`.
+    OnError {
+        /// How long to wait before the first restart
+        initial_delay: Duration,
+        /// The longest to wait before a restart
+        max_delay: Duration,
+    },
+    /// Start the task again straight away when it fails,
+    /// at most `max_restarts` times.
+    /// When it fails after that the apps running the Cell are deactivated.
+    MaxRestartsThenDeactivate {
+        /// How many times the task can be restarted
+        max_restarts: u32,
+        /// The Cell the task belongs to
+        cell_id: CellId,
+    },
+}
+
+impl RestartPolicy {
+    /// How long to wait before restarting a task which has been restarted
+    /// `restarts` times already and stopped again, or `None` if it shouldn't be
+    fn restart_delay(&self, restarts: u32, failed: bool) -> Option<Duration> {
+        match self {
+            RestartPolicy::Never => None,
+            RestartPolicy::Always => Some(Duration::default()),
+            RestartPolicy::OnError {
+                initial_delay,
+                max_delay,
+            } if failed => Some(
+                initial_delay
+                    .checked_mul(2u32.saturating_pow(restarts))
+                    .unwrap_or(*max_delay)
+                    .min(*max_delay),
+            ),
+            RestartPolicy::MaxRestartsThenDeactivate { max_restarts, .. }
+                if failed && restarts < *max_restarts =>
+            {
+                Some(Duration::default())
+            }
+            RestartPolicy::OnError { .. } | RestartPolicy::MaxRestartsThenDeactivate { .. } => None,
+        }
+    }
+}
+
 /// A message sent to the TaskManager, registering an OnDeath closure to run upon
 /// completion of a task.
 ///
 /// The closure may itself return a new ManagedTaskAdd, which will cause another task to be
 /// added while this one is being removed.
 pub struct ManagedTaskAdd {
+    name: String,
     handle: ManagedTaskHandle,
     // TODO: B-01455: reevaluate whether this should be a callback
     on_death: OnDeath,
+    restart: Option<(RestartPolicy, SpawnTask)>,
 }
 
 impl ManagedTaskAdd {
-    pub(crate) fn new(handle: ManagedTaskHandle, on_death: OnDeath) -> Self {
-        ManagedTaskAdd { handle, on_death }
+    pub(crate) fn new(
+        name: impl Into<String>,
+        handle: ManagedTaskHandle,
+        on_death: OnDeath,
+    ) -> Self {
+        ManagedTaskAdd {
+            name: name.into(),
+            handle,
+            on_death,
+            restart: None,
+        }
     }
 
     /// You just want the task in the task manager but don't want
     /// to react to an error
-    pub(crate) fn dont_handle(handle: ManagedTaskHandle) -> Self {
+    pub(crate) fn dont_handle(name: impl Into<String>, handle: ManagedTaskHandle) -> Self {
         let on_death = Box::new(|_| None);
-        Self::new(handle, on_death)
+        Self::new(name, handle, on_death)
+    }
+
+    /// Manage a task which the task manager spawns again with `spawn`
+    /// whenever `policy` says it should be restarted.
+    /// `handle` is the first run of the task.
+    pub(crate) fn with_restart_policy(
+        name: impl Into<String>,
+        handle: ManagedTaskHandle,
+        policy: RestartPolicy,
+        spawn: SpawnTask,
+    ) -> Self {
+        ManagedTaskAdd {
+            name: name.into(),
+            handle,
+            on_death: Box::new(|_| None),
+            restart: Some((policy, spawn)),
+        }
     }
 }
 
-impl Future for ManagedTaskAdd {
-    type Output = Option<ManagedTaskAdd>;
+impl std::fmt::Debug for ManagedTaskAdd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManagedTaskAdd")
+            .field("name", &self.name)
+            .field("restart_policy", &self.restart.as_ref().map(|(p, _)| p))
+            .finish()
+    }
+}
+
+/// A group of managed tasks, like the queue consumers of a Cell,
+/// which can be stopped while the rest of the conductor keeps running.
+/// The tasks still stop when the conductor stops.
+///
+/// Tasks which are restarted when they stop without failing,
+/// as with [RestartPolicy::Always], don't belong in a group.
+pub(crate) struct TaskGroup {
+    stop: broadcast::Sender<()>,
+    stopped: Arc<AtomicBool>,
+    /// Every run of a task in the group holds a clone of this,
+    /// so the receiver closes once none of them are running
+    running: parking_lot::Mutex<Option<mpsc::Sender<()>>>,
+    all_stopped: tokio::sync::Mutex<mpsc::Receiver<()>>,
+}
+
+impl TaskGroup {
+    pub(crate) fn new() -> Self {
+        let (stop, _) = broadcast::channel(1);
+        let (running, all_stopped) = mpsc::channel(1);
+        Self {
+            stop,
+            stopped: Arc::new(AtomicBool::new(false)),
+            running: parking_lot::Mutex::new(Some(running)),
+            all_stopped: tokio::sync::Mutex::new(all_stopped),
+        }
+    }
+
+    /// Make every run of a task part of this group,
+    /// so it is stopped by either the conductor or the group
+    pub(crate) fn add(&self, spawn: SpawnTask) -> SpawnTask {
+        let stop = self.stop.clone();
+        let stopped = self.stopped.clone();
+        let running = self.running.lock().clone();
+        Arc::new(move |mut conductor_stop: broadcast::Receiver<()>| {
+            let (run_stop, run_stop_rx) = broadcast::channel(1);
+            // Subscribe before checking the flag so a stop can't be missed
+            let mut group_stop = stop.subscribe();
+            let stopped = stopped.clone();
+            let running = running.clone();
+            let mut handle = spawn(run_stop_rx);
+            tokio::spawn(async move {
+                let _running = running;
+                if !stopped.load(Ordering::SeqCst) {
+                    tokio::select! {
+                        result = &mut handle => return result?,
+                        _ = conductor_stop.recv() => {}
+                        _ = group_stop.recv() => {}
+                    }
+                }
+                run_stop.send(()).ok();
+                handle.await?
+            })
+        })
+    }
+
+    /// Stop the tasks in the group and wait until none of them are running.
+    /// Tasks added after this are stopped as soon as they start.
+    pub(crate) async fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // It's fine if no task is running
+        self.stop.send(()).ok();
+        self.running.lock().take();
+        let mut all_stopped = self.all_stopped.lock().await;
+        while all_stopped.recv().await.is_some() {}
+    }
+}
+
+/// A run of a task, which resolves to the task's id and how it ended
+struct RunningTask {
+    id: u64,
+    handle: ManagedTaskHandle,
+}
+
+impl Future for RunningTask {
+    type Output = (u64, ManagedTaskResult);
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let id = self.id;
         let p = std::pin::Pin::new(&mut self.handle);
         match JoinHandle::poll(p, cx) {
-            Poll::Ready(r) => Poll::Ready(handle_completed_task(
-                &self.on_death,
-                r.unwrap_or_else(|e| Err(e.into())),
-            )),
+            Poll::Ready(r) => Poll::Ready((id, r.unwrap_or_else(|e| Err(e.into())))),
             Poll::Pending => Poll::Pending,
         }
     }
 }
 
-impl std::fmt::Debug for ManagedTaskAdd {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ManagedTaskAdd").finish()
-    }
+/// What the task manager keeps about a task while it runs
+struct TaskEntry {
+    name: String,
+    on_death: OnDeath,
+    restart: Option<(RestartPolicy, SpawnTask)>,
+    restarts: u32,
 }
 
 struct TaskManager {
-    stream: FuturesUnordered<ManagedTaskAdd>,
+    stream: FuturesUnordered<RunningTask>,
+    tasks: HashMap<u64, TaskEntry>,
+    next_id: u64,
+    statuses: ManagedTaskStatuses,
+    events: ConductorEventBroadcaster,
+    stop: broadcast::Sender<()>,
+    stop_rx: broadcast::Receiver<()>,
+    stopping: bool,
+    deactivate_cell: DeactivateCellSender,
 }
 
 impl TaskManager {
-    fn new() -> Self {
+    fn new(
+        events: ConductorEventBroadcaster,
+        stop: broadcast::Sender<()>,
+        statuses: ManagedTaskStatuses,
+        deactivate_cell: DeactivateCellSender,
+    ) -> Self {
         let stream = FuturesUnordered::new();
-        TaskManager { stream }
+        let stop_rx = stop.subscribe();
+        TaskManager {
+            stream,
+            tasks: HashMap::new(),
+            next_id: 0,
+            statuses,
+            events,
+            stop,
+            stop_rx,
+            stopping: false,
+            deactivate_cell,
+        }
+    }
+
+    fn add(&mut self, task: ManagedTaskAdd) {
+        let ManagedTaskAdd {
+            name,
+            handle,
+            on_death,
+            restart,
+        } = task;
+        let id = self.next_id;
+        self.next_id += 1;
+        self.statuses.lock().insert(
+            id,
+            ManagedTaskInfo {
+                name: name.clone(),
+                status: ManagedTaskStatus::Running,
+                restarts: 0,
+            },
+        );
+        self.tasks.insert(
+            id,
+            TaskEntry {
+                name,
+                on_death,
+                restart,
+                restarts: 0,
+            },
+        );
+        self.stream.push(RunningTask { id, handle });
+    }
+
+    /// Has the conductor started shutting down
+    fn is_stopping(&mut self) -> bool {
+        if !self.stopping {
+            self.stopping = !matches!(self.stop_rx.try_recv(), Err(broadcast::TryRecvError::Empty));
+        }
+        self.stopping
+    }
+
+    /// Run the on death closure of a task which stopped
+    /// and restart it if its policy says so
+    fn task_stopped(&mut self, id: u64, result: ManagedTaskResult) {
+        let mut entry = match self.tasks.remove(&id) {
+            Some(entry) => entry,
+            None => return,
+        };
+        let error = result.as_ref().err().map(|e| e.to_string());
+        if let Some(error) = &error {
+            // It's fine if nobody is listening for events
+            self.events
+                .send(ConductorEvent::TaskFailed {
+                    name: entry.name.clone(),
+                    error: error.clone(),
+                })
+                .ok();
+        }
+        if let Some(new_task) = handle_completed_task(&entry.on_death, result) {
+            self.add(new_task);
+        }
+
+        // Subscribe before checking if we are stopping,
+        // so a restarted task is stopped by any stop signal we don't see
+        let task_stop = self.stop.subscribe();
+        let delay_stop = self.stop.subscribe();
+        let delay = match &entry.restart {
+            Some((policy, _)) if !self.is_stopping() => {
+                policy.restart_delay(entry.restarts, error.is_some())
+            }
+            _ => None,
+        };
+
+        let delay = match delay {
+            Some(delay) => delay,
+            None => {
+                let mut statuses = self.statuses.lock();
+                match error {
+                    Some(error) => {
+                        if let Some((RestartPolicy::MaxRestartsThenDeactivate { cell_id, .. }, _)) =
+                            &entry.restart
+                        {
+                            if !self.stopping {
+                                let reason = format!(
+                                    "{} failed after {} restarts: {}",
+                                    entry.name, entry.restarts, error
+                                );
+                                // It's fine if the conductor is gone
+                                self.deactivate_cell.send((cell_id.clone(), reason)).ok();
+                            }
+                        }
+                        if let Some(info) = statuses.get_mut(&id) {
+                            info.status = ManagedTaskStatus::Failed { error };
+                        }
+                    }
+                    None => {
+                        statuses.remove(&id);
+                    }
+                }
+                return;
+            }
+        };
+        let spawn = match &entry.restart {
+            Some((_, spawn)) => spawn.clone(),
+            None => unreachable!("Only tasks with a restart policy are restarted"),
+        };
+
+        entry.restarts += 1;
+        debug!(task = %entry.name, restarts = entry.restarts, ?delay, "Restarting task");
+        if let Some(info) = self.statuses.lock().get_mut(&id) {
+            info.restarts = entry.restarts;
+            info.status = match &error {
+                Some(error) if delay > Duration::default() => ManagedTaskStatus::Restarting {
+                    error: error.clone(),
+                },
+                _ => ManagedTaskStatus::Running,
+            };
+        }
+        self.events
+            .send(ConductorEvent::TaskRestarted {
+                name: entry.name.clone(),
+                restarts: entry.restarts,
+            })
+            .ok();
+
+        let handle = if delay == Duration::default() {
+            spawn(task_stop)
+        } else {
+            let statuses = self.statuses.clone();
+            let mut delay_stop = delay_stop;
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = tokio::time::delay_for(delay) => {}
+                    _ = delay_stop.recv() => return Ok(()),
+                }
+                if let Some(info) = statuses.lock().get_mut(&id) {
+                    info.status = ManagedTaskStatus::Running;
+                }
+                spawn(task_stop).await?
+            })
+        };
+        self.tasks.insert(id, entry);
+        self.stream.push(RunningTask { id, handle });
     }
 }
 
-pub(crate) fn spawn_task_manager() -> (mpsc::Sender<ManagedTaskAdd>, TaskManagerRunHandle) {
+/// Spawn the task manager, which reports tasks that fail on `events`
+/// and restarts them following their [RestartPolicy]
+pub(crate) fn spawn_task_manager(
+    events: ConductorEventBroadcaster,
+    stop: broadcast::Sender<()>,
+    statuses: ManagedTaskStatuses,
+    deactivate_cell: DeactivateCellSender,
+) -> (mpsc::Sender<ManagedTaskAdd>, TaskManagerRunHandle) {
     let (send, recv) = mpsc::channel(CHANNEL_SIZE);
-    (send, tokio::spawn(run(recv)))
+    let task_manager = TaskManager::new(events, stop, statuses, deactivate_cell);
+    (send, tokio::spawn(run(recv, task_manager)))
 }
 
 /// A super pessimistic task that is just waiting to die
@@ -97,11 +461,10 @@ pub(crate) async fn keep_alive_task(mut die: broadcast::Receiver<()>) -> Managed
     Ok(())
 }
 
-async fn run(mut new_task_channel: mpsc::Receiver<ManagedTaskAdd>) {
-    let mut task_manager = TaskManager::new();
+async fn run(mut new_task_channel: mpsc::Receiver<ManagedTaskAdd>, mut task_manager: TaskManager) {
     // Need to have at least one item in the stream or it will exit early
     if let Some(new_task) = new_task_channel.recv().await {
-        task_manager.stream.push(new_task);
+        task_manager.add(new_task);
     } else {
         error!("All senders to task manager were dropped before starting");
         return;
@@ -109,11 +472,10 @@ async fn run(mut new_task_channel: mpsc::Receiver<ManagedTaskAdd>) {
     loop {
         tokio::select! {
             Some(new_task) = new_task_channel.recv() => {
-                task_manager.stream.push(new_task);
+                task_manager.add(new_task);
             }
             result = task_manager.stream.next() => match result {
-                Some(Some(new_task)) => task_manager.stream.push(new_task),
-                Some(None) => (),
+                Some((id, result)) => task_manager.task_stopped(id, result),
                 None => break,
             }
         };
@@ -132,22 +494,68 @@ mod test {
     use super::*;
     use crate::conductor::error::ConductorError;
     use anyhow::Result;
+    use holochain_types::test_utils::fake_cell_id;
+    use matches::assert_matches;
     use observability;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering;
+
+    fn test_task_manager() -> (
+        mpsc::Sender<ManagedTaskAdd>,
+        TaskManagerRunHandle,
+        broadcast::Receiver<ConductorEvent>,
+        broadcast::Sender<()>,
+        ManagedTaskStatuses,
+        mpsc::UnboundedReceiver<(CellId, String)>,
+    ) {
+        let (events, events_rx) = broadcast::channel(10);
+        let (stop, _) = broadcast::channel(1);
+        let statuses = ManagedTaskStatuses::default();
+        let (deactivate_tx, deactivate_rx) = mpsc::unbounded_channel();
+        let (send_task_handle, main_task) =
+            spawn_task_manager(events, stop.clone(), statuses.clone(), deactivate_tx);
+        (
+            send_task_handle,
+            main_task,
+            events_rx,
+            stop,
+            statuses,
+            deactivate_rx,
+        )
+    }
+
+    /// A task which fails its first `failures` runs and then waits to be stopped
+    fn failing_task(failures: u32) -> SpawnTask {
+        let runs = Arc::new(AtomicU32::new(0));
+        Arc::new(move |mut stop: broadcast::Receiver<()>| {
+            let run = runs.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                if run < failures {
+                    Err(ConductorError::Todo("This task gotta die".to_string()).into())
+                } else {
+                    stop.recv().await.ok();
+                    Ok(())
+                }
+            })
+        })
+    }
 
     #[tokio::test]
     async fn spawn_and_handle_dying_task() -> Result<()> {
         observability::test_run().ok();
-        let (mut send_task_handle, main_task) = spawn_task_manager();
+        let (mut send_task_handle, main_task, mut events_rx, _stop, _, _) = test_task_manager();
         let handle = tokio::spawn(async {
             Err(ConductorError::Todo("This task gotta die".to_string()).into())
         });
         let handle = ManagedTaskAdd::new(
+            "dying task",
             handle,
             Box::new(|result| match result {
                 Ok(_) => panic!("Task should have died"),
                 Err(ManagedTaskError::Conductor(ConductorError::Todo(_))) => {
                     let handle = tokio::spawn(async { Ok(()) });
-                    let handle = ManagedTaskAdd::new(handle, Box::new(|_| None));
+                    let handle =
+                        ManagedTaskAdd::new("replacement task", handle, Box::new(|_| None));
                     Some(handle)
                 }
                 _ => None,
@@ -162,6 +570,131 @@ mod test {
             panic!("Failed to send the handle");
         }
         main_handle.await??;
+
+        // The dying task was reported
+        assert_matches!(
+            events_rx.recv().await,
+            Ok(ConductorEvent::TaskFailed { .. })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn restart_failing_task_with_backoff() -> Result<()> {
+        observability::test_run().ok();
+        let (mut send_task_handle, main_task, mut events_rx, stop, statuses, _) =
+            test_task_manager();
+        let policy = RestartPolicy::OnError {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(20),
+        };
+        let spawn = failing_task(2);
+        let task =
+            ManagedTaskAdd::with_restart_policy("flaky", spawn(stop.subscribe()), policy, spawn);
+        send_task_handle.send(task).await.unwrap();
+
+        for restarts in 1..=2 {
+            assert_matches!(
+                events_rx.recv().await,
+                Ok(ConductorEvent::TaskFailed { name, .. }) if name == "flaky"
+            );
+            assert_matches!(
+                events_rx.recv().await,
+                Ok(ConductorEvent::TaskRestarted { restarts: r, .. }) if r == restarts
+            );
+        }
+        tokio::time::delay_for(Duration::from_millis(100)).await;
+        {
+            let statuses = statuses.lock();
+            let info = statuses.values().next().unwrap();
+            assert_eq!(info.name, "flaky");
+            assert_eq!(info.restarts, 2);
+            assert_eq!(info.status, ManagedTaskStatus::Running);
+        }
+
+        // Stopping the conductor doesn't restart the task
+        stop.send(()).unwrap();
+        drop(send_task_handle);
+        main_task.await?;
+        assert!(statuses.lock().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn deactivate_cell_after_max_restarts() -> Result<()> {
+        observability::test_run().ok();
+        let (mut send_task_handle, main_task, _, stop, statuses, mut deactivate_rx) =
+            test_task_manager();
+        let cell_id = fake_cell_id(1);
+        let policy = RestartPolicy::MaxRestartsThenDeactivate {
+            max_restarts: 2,
+            cell_id: cell_id.clone(),
+        };
+        let spawn = failing_task(3);
+        let task =
+            ManagedTaskAdd::with_restart_policy("doomed", spawn(stop.subscribe()), policy, spawn);
+        send_task_handle.send(task).await.unwrap();
+        drop(send_task_handle);
+        main_task.await?;
+
+        let (deactivated, _) = deactivate_rx.recv().await.unwrap();
+        assert_eq!(deactivated, cell_id);
+        let statuses = statuses.lock();
+        let info = statuses.values().next().unwrap();
+        assert_eq!(info.restarts, 2);
+        assert_matches!(info.status, ManagedTaskStatus::Failed { .. });
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stop_a_group_of_tasks() -> Result<()> {
+        observability::test_run().ok();
+        let (mut send_task_handle, main_task, _, stop, statuses, _) = test_task_manager();
+        let group = TaskGroup::new();
+        let finished = Arc::new(AtomicU32::new(0));
+        let policy = RestartPolicy::MaxRestartsThenDeactivate {
+            max_restarts: 2,
+            cell_id: fake_cell_id(1),
+        };
+        for name in &["first", "second"] {
+            let finished = finished.clone();
+            let spawn: SpawnTask = Arc::new(move |mut stop: broadcast::Receiver<()>| {
+                let finished = finished.clone();
+                tokio::spawn(async move {
+                    stop.recv().await.ok();
+                    // Take a moment to wind down, like a running workflow
+                    tokio::time::delay_for(Duration::from_millis(50)).await;
+                    finished.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                })
+            });
+            let spawn = group.add(spawn);
+            let task = ManagedTaskAdd::with_restart_policy(
+                *name,
+                spawn(stop.subscribe()),
+                policy.clone(),
+                spawn,
+            );
+            send_task_handle.send(task).await.unwrap();
+        }
+        let spawn = failing_task(0);
+        let task =
+            ManagedTaskAdd::with_restart_policy("outside", spawn(stop.subscribe()), policy, spawn);
+        send_task_handle.send(task).await.unwrap();
+
+        // Stopping the group waits for its tasks and leaves the others running
+        group.stop().await;
+        assert_eq!(finished.load(Ordering::SeqCst), 2);
+        {
+            let statuses = statuses.lock();
+            assert_eq!(statuses.len(), 1);
+            assert_eq!(statuses.values().next().unwrap().name, "outside");
+        }
+
+        stop.send(()).unwrap();
+        drop(send_task_handle);
+        main_task.await?;
+        assert!(statuses.lock().is_empty());
         Ok(())
     }
 }
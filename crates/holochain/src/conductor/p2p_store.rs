@@ -168,22 +168,23 @@ impl AgentKv {
 }
 
 /// Inject multiple agent info entries into the peer store
-pub fn inject_agent_infos<I: IntoIterator<Item = AgentInfoSigned> + Send>(
+pub async fn inject_agent_infos<I: IntoIterator<Item = AgentInfoSigned> + Send>(
     env: EnvironmentWrite,
     iter: I,
 ) -> DatabaseResult<()> {
     let p2p_store = AgentKv::new(env.clone().into())?;
-    let env_ref = env.guard();
-    Ok(env_ref.with_commit(|writer| {
-        for agent_info_signed in iter {
+    let agent_infos: Vec<AgentInfoSigned> = iter.into_iter().collect();
+    env.with_commit_retry(|writer| {
+        for agent_info_signed in &agent_infos {
             p2p_store.as_store_ref().put(
                 writer,
-                &(&agent_info_signed).try_into()?,
-                &agent_info_signed,
+                &agent_info_signed.try_into()?,
+                agent_info_signed,
             )?
         }
         DatabaseResult::Ok(())
-    })?)
+    })
+    .await
 }
 
 /// Helper function to get all the peer data from this conductor
@@ -206,14 +207,18 @@ pub fn get_single_agent_info(
 
 /// Interconnect every provided pair of conductors via their peer store lmdb environments
 #[cfg(any(test, feature = "test_utils"))]
-pub fn exchange_peer_info(envs: Vec<EnvironmentWrite>) {
+pub async fn exchange_peer_info(envs: Vec<EnvironmentWrite>) {
     for (i, a) in envs.iter().enumerate() {
         for (j, b) in envs.iter().enumerate() {
             if i == j {
                 continue;
             }
-            inject_agent_infos(a.clone(), all_agent_infos(b.clone().into()).unwrap()).unwrap();
-            inject_agent_infos(b.clone(), all_agent_infos(a.clone().into()).unwrap()).unwrap();
+            inject_agent_infos(a.clone(), all_agent_infos(b.clone().into()).unwrap())
+                .await
+                .unwrap();
+            inject_agent_infos(b.clone(), all_agent_infos(a.clone().into()).unwrap())
+                .await
+                .unwrap();
         }
     }
 }
@@ -225,9 +230,8 @@ pub fn get_agent_info_signed(
     kitsune_agent: Arc<kitsune_p2p::KitsuneAgent>,
 ) -> ConductorResult<Option<AgentInfoSigned>> {
     let p2p_kv = AgentKv::new(environ.clone().into())?;
-    let env = environ.guard();
 
-    env.with_commit(|writer| {
+    environ.with_commit(|writer| {
         let res = p2p_kv
             .as_store_ref()
             .get(writer, &(&*kitsune_space, &*kitsune_agent).into())?;
@@ -260,10 +264,9 @@ pub fn query_agent_info_signed(
     kitsune_space: Arc<kitsune_p2p::KitsuneSpace>,
 ) -> ConductorResult<Vec<AgentInfoSigned>> {
     let p2p_kv = AgentKv::new(environ.clone().into())?;
-    let env = environ.guard();
 
     let mut out = Vec::new();
-    env.with_commit(|writer| {
+    environ.with_commit(|writer| {
         let mut expired = Vec::new();
 
         {
@@ -307,19 +310,20 @@ pub fn query_agent_info_signed(
 }
 
 /// Put single agent info into store
-pub fn put_agent_info_signed(
+pub async fn put_agent_info_signed(
     environ: EnvironmentWrite,
     agent_info_signed: kitsune_p2p::agent_store::AgentInfoSigned,
 ) -> ConductorResult<()> {
     let p2p_kv = AgentKv::new(environ.clone().into())?;
-    let env = environ.guard();
-    Ok(env.with_commit(|writer| {
-        p2p_kv.as_store_ref().put(
-            writer,
-            &(&agent_info_signed).try_into()?,
-            &agent_info_signed,
-        )
-    })?)
+    Ok(environ
+        .with_commit_retry(|writer| {
+            p2p_kv.as_store_ref().put(
+                writer,
+                &(&agent_info_signed).try_into()?,
+                &agent_info_signed,
+            )
+        })
+        .await?)
 }
 
 /// Dump the agents currently in the peer store
@@ -479,7 +483,7 @@ mod tests {
         expect.sort();
 
         // - Inject some data
-        inject_agent_infos(env.clone(), agent_infos).unwrap();
+        inject_agent_infos(env.clone(), agent_infos).await.unwrap();
 
         // - Check the same data is now in the store
         let mut agents = all_agent_infos(env.clone().into()).unwrap();
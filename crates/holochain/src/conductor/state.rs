@@ -25,6 +25,10 @@ pub struct ConductorState {
     /// List of interfaces any UI can use to access zome functions.
     #[serde(default)]
     pub app_interfaces: HashMap<AppInterfaceId, AppInterfaceConfig>,
+    /// The x25519 keys created for each app over its app interfaces,
+    /// which are the only ones the app's clients can box and open messages with.
+    #[serde(default)]
+    pub app_x25519_keys: HashMap<InstalledAppId, Vec<X25519PubKey>>,
 }
 
 /// A unique identifier used to refer to an App Interface internally.
@@ -80,21 +84,33 @@ impl ConductorState {
 #[derive(Clone, Deserialize, Serialize, Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct AppInterfaceConfig {
-    /// The signal subscription settings for each App
+    /// The latest signal subscription made over the interface for each App,
+    /// which is listed with the interface
     pub signal_subscriptions: HashMap<InstalledAppId, SignalSubscription>,
 
     /// The driver for the interface, e.g. Websocket
     pub driver: InterfaceDriver,
+
+    /// The app this interface is attached to, if any.
+    /// Crypto requests are only answered on an interface attached to an app.
+    #[serde(default)]
+    pub installed_app_id: Option<InstalledAppId>,
 }
 
 impl AppInterfaceConfig {
-    /// Create config for a websocket interface
-    pub fn websocket(port: u16) -> Self {
+    /// Create config for an interface with this driver
+    pub fn new(driver: InterfaceDriver) -> Self {
         Self {
             signal_subscriptions: HashMap::new(),
-            driver: InterfaceDriver::Websocket { port },
+            driver,
+            installed_app_id: None,
         }
     }
+
+    /// Create config for a websocket interface on the loopback address
+    pub fn websocket(port: u16) -> Self {
+        Self::new(InterfaceDriver::websocket(port))
+    }
 }
 
 // TODO: Tons of consistency check tests were ripped out in the great legacy code cleanup
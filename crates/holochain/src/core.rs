@@ -2,6 +2,7 @@
 
 #![deny(missing_docs)]
 
+pub mod metrics;
 pub mod queue_consumer;
 #[allow(missing_docs)]
 pub mod ribosome;
@@ -0,0 +1,397 @@
+//! Metrics about the work the queue consumers of each Cell do.
+//!
+//! Every run of a workflow is counted, along with the ops it processed,
+//! whether it failed and the mean time it took per op.
+//! Workflows process their ops together, so the time of a single op isn't known.
+//! The sizes of the validation and integration limbos are read from
+//! the Cell's environment when the metrics are rendered.
+//!
+//! [Metrics::render] produces the
+//! [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/),
+//! which the conductor serves from `AdminRequest::DumpMetrics`
+//! and the optional `metrics_endpoint`.
+
+use fallible_iterator::FallibleIterator;
+use holochain_lmdb::buffer::KvBufFresh;
+use holochain_lmdb::db::INTEGRATION_LIMBO;
+use holochain_lmdb::error::DatabaseResult;
+use holochain_lmdb::fresh_reader;
+use holochain_lmdb::prelude::*;
+use holochain_state::dht_op_integration::IntegrationLimboStore;
+use holochain_state::validation_db::ValidationLimboStatus;
+use holochain_state::validation_db::ValidationLimboStore;
+use holochain_zome_types::cell::CellId;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// The upper bounds, in seconds, of the buckets of the mean time per op histogram
+const OP_DURATION_BUCKETS: [f64; 10] = [
+    0.000_1, 0.000_5, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0,
+];
+
+/// The workflows run by a Cell's queue consumers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Workflow {
+    /// Produces DhtOps from the source chain
+    ProduceDhtOps,
+    /// Publishes authored DhtOps to the network
+    PublishDhtOps,
+    /// Runs system validation on ops in the validation limbo
+    SysValidation,
+    /// Runs app validation on ops in the validation limbo
+    AppValidation,
+    /// Integrates validated ops
+    IntegrateDhtOps,
+}
+
+impl Workflow {
+    /// Every workflow, in the order they are rendered
+    pub const ALL: [Workflow; 5] = [
+        Workflow::ProduceDhtOps,
+        Workflow::PublishDhtOps,
+        Workflow::SysValidation,
+        Workflow::AppValidation,
+        Workflow::IntegrateDhtOps,
+    ];
+
+    /// The name of the workflow, used as the `workflow` label
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workflow::ProduceDhtOps => "produce_dht_ops",
+            Workflow::PublishDhtOps => "publish_dht_ops",
+            Workflow::SysValidation => "sys_validation",
+            Workflow::AppValidation => "app_validation",
+            Workflow::IntegrateDhtOps => "integrate_dht_ops",
+        }
+    }
+}
+
+/// A single run of a workflow, which counts the ops it processes
+#[derive(Debug)]
+pub struct WorkflowRun {
+    started: Instant,
+    ops: u64,
+}
+
+impl WorkflowRun {
+    /// Start timing a run
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            ops: 0,
+        }
+    }
+
+    /// Count ops this run has processed
+    pub fn processed(&mut self, ops: usize) {
+        self.ops += ops as u64;
+    }
+}
+
+/// A histogram with the buckets in [OP_DURATION_BUCKETS]
+#[derive(Debug, Default)]
+struct Histogram {
+    /// How many observations fell in each bucket, not counting the ones below it
+    buckets: [AtomicU64; 10],
+    count: AtomicU64,
+    sum_nanos: AtomicU64,
+}
+
+impl Histogram {
+    /// Observe a value
+    fn observe(&self, value: Duration) {
+        let secs = value.as_secs_f64();
+        if let Some(i) = OP_DURATION_BUCKETS.iter().position(|le| secs <= *le) {
+            self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_nanos
+            .fetch_add(value.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+/// The counters for one workflow of one Cell
+#[derive(Debug, Default)]
+pub struct WorkflowMetrics {
+    runs: AtomicU64,
+    errors: AtomicU64,
+    ops: AtomicU64,
+    /// The mean time per op of each run which processed ops
+    mean_op_duration: Histogram,
+}
+
+impl WorkflowMetrics {
+    /// Record a finished run, and whether it failed
+    pub fn record(&self, run: WorkflowRun, failed: bool) {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        if run.ops > 0 {
+            self.ops.fetch_add(run.ops, Ordering::Relaxed);
+            let mean = run.started.elapsed().as_secs_f64() / run.ops as f64;
+            self.mean_op_duration.observe(Duration::from_secs_f64(mean));
+        }
+    }
+}
+
+/// The metrics of a single Cell
+pub struct CellMetrics {
+    env: EnvironmentRead,
+    workflows: [WorkflowMetrics; 5],
+}
+
+impl CellMetrics {
+    /// The counters for one of this Cell's workflows
+    pub fn workflow(&self, workflow: Workflow) -> &WorkflowMetrics {
+        &self.workflows[workflow as usize]
+    }
+
+    /// The number of ops in the validation limbo with each status
+    fn validation_limbo_sizes(&self) -> DatabaseResult<[u64; 6]> {
+        let validation_limbo = ValidationLimboStore::new(self.env.clone())?;
+        fresh_reader!(self.env, |r| validation_limbo.iter(&r)?.fold(
+            [0; 6],
+            |mut sizes, (_, vlv)| {
+                sizes[limbo_status_index(&vlv.status)] += 1;
+                Ok(sizes)
+            }
+        ))
+    }
+
+    /// The number of ops waiting to be integrated
+    fn integration_limbo_size(&self) -> DatabaseResult<u64> {
+        let db = self.env.get_db(&*INTEGRATION_LIMBO)?;
+        let integration_limbo: IntegrationLimboStore = KvBufFresh::new(self.env.clone(), db);
+        fresh_reader!(self.env, |r| integration_limbo
+            .iter(&r)?
+            .count()
+            .map(|c| c as u64))
+    }
+}
+
+/// The label for each [ValidationLimboStatus], by [limbo_status_index]
+const LIMBO_STATUSES: [&str; 6] = [
+    "pending",
+    "awaiting_sys_deps",
+    "sys_validated",
+    "awaiting_app_deps",
+    "awaiting_key_state",
+    "awaiting_validation_package",
+];
+
+fn limbo_status_index(status: &ValidationLimboStatus) -> usize {
+    match status {
+        ValidationLimboStatus::Pending => 0,
+        ValidationLimboStatus::AwaitingSysDeps(_) => 1,
+        ValidationLimboStatus::SysValidated => 2,
+        ValidationLimboStatus::AwaitingAppDeps(_) => 3,
+        ValidationLimboStatus::AwaitingKeyState => 4,
+        ValidationLimboStatus::AwaitingValidationPackage => 5,
+    }
+}
+
+/// The metrics of every running Cell in the conductor
+#[derive(Clone, Default)]
+pub struct Metrics(Arc<parking_lot::RwLock<HashMap<CellId, Arc<CellMetrics>>>>);
+
+impl Metrics {
+    /// Start collecting metrics for a Cell, whose limbos are in `env`
+    pub fn add_cell(&self, cell_id: CellId, env: EnvironmentRead) -> Arc<CellMetrics> {
+        let cell_metrics = Arc::new(CellMetrics {
+            env,
+            workflows: Default::default(),
+        });
+        self.0.write().insert(cell_id, cell_metrics.clone());
+        cell_metrics
+    }
+
+    /// Stop reporting the metrics of a Cell
+    pub fn remove_cell(&self, cell_id: &CellId) {
+        self.0.write().remove(cell_id);
+    }
+
+    /// Render the metrics of every Cell in the Prometheus text format
+    pub fn render(&self) -> DatabaseResult<String> {
+        let mut cells: Vec<_> = self
+            .0
+            .read()
+            .iter()
+            .map(|(cell_id, metrics)| (escape_label(&cell_id.to_string()), metrics.clone()))
+            .collect();
+        cells.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut out = String::new();
+        let counters: [(&str, &str, fn(&WorkflowMetrics) -> &AtomicU64); 3] = [
+            (
+                "holochain_workflow_runs_total",
+                "Runs of a Cell's workflow",
+                |m| &m.runs,
+            ),
+            (
+                "holochain_workflow_errors_total",
+                "Runs of a Cell's workflow which failed",
+                |m| &m.errors,
+            ),
+            (
+                "holochain_workflow_ops_total",
+                "Ops processed by a Cell's workflow",
+                |m| &m.ops,
+            ),
+        ];
+        for (name, help, counter) in counters.iter() {
+            header(&mut out, name, help, "counter");
+            for (cell_id, metrics) in &cells {
+                for workflow in Workflow::ALL.iter() {
+                    let value = counter(metrics.workflow(*workflow)).load(Ordering::Relaxed);
+                    writeln!(
+                        out,
+                        "{}{{cell_id=\"{}\",workflow=\"{}\"}} {}",
+                        name,
+                        cell_id,
+                        workflow.name(),
+                        value
+                    )
+                    .ok();
+                }
+            }
+        }
+
+        let name = "holochain_workflow_mean_op_duration_seconds";
+        header(
+            &mut out,
+            name,
+            "Mean time per op of each run of a Cell's workflow",
+            "histogram",
+        );
+        for (cell_id, metrics) in &cells {
+            for workflow in Workflow::ALL.iter() {
+                let histogram = &metrics.workflow(*workflow).mean_op_duration;
+                let labels = format!("cell_id=\"{}\",workflow=\"{}\"", cell_id, workflow.name());
+                let mut cumulative = 0;
+                for (le, bucket) in OP_DURATION_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                    cumulative += bucket.load(Ordering::Relaxed);
+                    writeln!(
+                        out,
+                        "{}_bucket{{{},le=\"{}\"}} {}",
+                        name, labels, le, cumulative
+                    )
+                    .ok();
+                }
+                let count = histogram.count.load(Ordering::Relaxed);
+                let sum = histogram.sum_nanos.load(Ordering::Relaxed) as f64 / 1e9;
+                writeln!(out, "{}_bucket{{{},le=\"+Inf\"}} {}", name, labels, count).ok();
+                writeln!(out, "{}_sum{{{}}} {}", name, labels, sum).ok();
+                writeln!(out, "{}_count{{{}}} {}", name, labels, count).ok();
+            }
+        }
+
+        let name = "holochain_validation_limbo_ops";
+        header(
+            &mut out,
+            name,
+            "Ops in a Cell's validation limbo by status",
+            "gauge",
+        );
+        for (cell_id, metrics) in &cells {
+            let sizes = metrics.validation_limbo_sizes()?;
+            for (status, size) in LIMBO_STATUSES.iter().zip(sizes.iter()) {
+                writeln!(
+                    out,
+                    "{}{{cell_id=\"{}\",status=\"{}\"}} {}",
+                    name, cell_id, status, size
+                )
+                .ok();
+            }
+        }
+
+        let name = "holochain_integration_limbo_ops";
+        header(
+            &mut out,
+            name,
+            "Ops waiting to be integrated by a Cell",
+            "gauge",
+        );
+        for (cell_id, metrics) in &cells {
+            let size = metrics.integration_limbo_size()?;
+            writeln!(out, "{}{{cell_id=\"{}\"}} {}", name, cell_id, size).ok();
+        }
+        Ok(out)
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    writeln!(out, "# HELP {} {}", name, help).ok();
+    writeln!(out, "# TYPE {} {}", name, kind).ok();
+}
+
+/// Escape a label value for the Prometheus text format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_lmdb::test_utils::test_cell_env;
+    use holochain_types::test_utils::fake_cell_id;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn render_workflow_metrics() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let metrics = Metrics::default();
+        let cell_id = fake_cell_id(1);
+        let cell_metrics = metrics.add_cell(cell_id.clone(), env.clone().into());
+
+        let mut run = WorkflowRun::start();
+        run.processed(3);
+        cell_metrics
+            .workflow(Workflow::SysValidation)
+            .record(run, false);
+        cell_metrics
+            .workflow(Workflow::SysValidation)
+            .record(WorkflowRun::start(), true);
+
+        let rendered = metrics.render().unwrap();
+        let labels = format!(
+            "cell_id=\"{}\",workflow=\"sys_validation\"",
+            escape_label(&cell_id.to_string())
+        );
+        let has = |line: String| {
+            assert!(
+                rendered.lines().any(|l| l == line),
+                "missing {} in\n{}",
+                line,
+                rendered
+            )
+        };
+        has(format!("holochain_workflow_runs_total{{{}}} 2", labels));
+        has(format!("holochain_workflow_errors_total{{{}}} 1", labels));
+        has(format!("holochain_workflow_ops_total{{{}}} 3", labels));
+        // One run processed ops
+        has(format!(
+            "holochain_workflow_mean_op_duration_seconds_count{{{}}} 1",
+            labels
+        ));
+        has(format!(
+            "holochain_workflow_mean_op_duration_seconds_bucket{{{},le=\"+Inf\"}} 1",
+            labels
+        ));
+        has(format!(
+            "holochain_validation_limbo_ops{{cell_id=\"{}\",status=\"pending\"}} 0",
+            escape_label(&cell_id.to_string())
+        ));
+
+        metrics.remove_cell(&cell_id);
+        assert!(!metrics.render().unwrap().contains("cell_id"));
+    }
+}
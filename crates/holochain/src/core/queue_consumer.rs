@@ -47,76 +47,104 @@ use produce_dht_ops_consumer::*;
 mod publish_dht_ops_consumer;
 use crate::conductor::api::CellConductorApiT;
 use crate::conductor::manager::ManagedTaskAdd;
+use crate::conductor::manager::RestartPolicy;
+use crate::conductor::manager::SpawnTask;
+use crate::conductor::manager::TaskGroup;
+use crate::core::metrics::CellMetrics;
 use holochain_p2p::HolochainP2pCell;
 use holochain_state::workspace::WorkspaceError;
 use publish_dht_ops_consumer::*;
+use std::sync::Arc;
+
+/// How many times a queue consumer which fails is restarted
+/// before the apps running its Cell are deactivated
+const QUEUE_CONSUMER_MAX_RESTARTS: u32 = 3;
 
 /// Spawns several long-running tasks which are responsible for processing work
 /// which shows up on various databases.
 ///
+/// Each task is restarted if it fails, and if it keeps failing
+/// the apps running the Cell are deactivated.
+/// Every task is part of `tasks`, so they can be stopped along with the Cell.
+///
 /// Waits for the initial loop to complete before returning, to prevent causing
 /// a race condition by trying to run a workflow too soon after cell creation.
 pub async fn spawn_queue_consumer_tasks(
     env: &EnvironmentWrite,
     cell_network: HolochainP2pCell,
     conductor_api: impl CellConductorApiT + 'static,
-    mut task_sender: sync::mpsc::Sender<ManagedTaskAdd>,
+    task_sender: sync::mpsc::Sender<ManagedTaskAdd>,
     stop: sync::broadcast::Sender<()>,
+    tasks: &TaskGroup,
+    validation_workers: usize,
+    metrics: Arc<CellMetrics>,
 ) -> (QueueTriggers, InitialQueueTriggers) {
+    let cell_id = conductor_api.cell_id().clone();
+    let manage = |name: &str, spawn: SpawnTask| {
+        let spawn = tasks.add(spawn);
+        let task = ManagedTaskAdd::with_restart_policy(
+            format!("{}-{}", name, cell_id),
+            spawn(stop.subscribe()),
+            RestartPolicy::MaxRestartsThenDeactivate {
+                max_restarts: QUEUE_CONSUMER_MAX_RESTARTS,
+                cell_id: cell_id.clone(),
+            },
+            spawn,
+        );
+        let mut task_sender = task_sender.clone();
+        async move {
+            task_sender
+                .send(task)
+                .await
+                .expect("Failed to manage workflow handle");
+        }
+    };
+
     // Publish
-    let (tx_publish, handle) =
-        spawn_publish_dht_ops_consumer(env.clone(), stop.subscribe(), cell_network.clone());
-    task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
-        .await
-        .expect("Failed to manage workflow handle");
+    let (tx_publish, spawn) = spawn_publish_dht_ops_consumer(
+        env.clone(),
+        cell_network.clone(),
+        conductor_api.clone(),
+        metrics.clone(),
+    );
+    manage("publish_dht_ops_consumer", spawn).await;
 
     let (create_tx_sys, get_tx_sys) = tokio::sync::oneshot::channel();
 
     // Integration
-    let (tx_integration, handle) =
-        spawn_integrate_dht_ops_consumer(env.clone(), stop.subscribe(), get_tx_sys);
-    task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
-        .await
-        .expect("Failed to manage workflow handle");
+    let (tx_integration, spawn) =
+        spawn_integrate_dht_ops_consumer(env.clone(), get_tx_sys, metrics.clone());
+    manage("integrate_dht_ops_consumer", spawn).await;
 
     // App validation
-    let (tx_app, handle) = spawn_app_validation_consumer(
+    let (tx_app, spawn) = spawn_app_validation_consumer(
         env.clone(),
-        stop.subscribe(),
         tx_integration.clone(),
         conductor_api.clone(),
         cell_network.clone(),
+        validation_workers,
+        metrics.clone(),
     );
-    task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
-        .await
-        .expect("Failed to manage workflow handle");
+    manage("app_validation_consumer", spawn).await;
 
     // Sys validation
-    let (tx_sys, handle) = spawn_sys_validation_consumer(
+    let (tx_sys, spawn) = spawn_sys_validation_consumer(
         env.clone(),
-        stop.subscribe(),
         tx_app.clone(),
         cell_network,
         conductor_api,
+        validation_workers,
+        metrics.clone(),
     );
-    task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
-        .await
-        .expect("Failed to manage workflow handle");
+    manage("sys_validation_consumer", spawn).await;
     if create_tx_sys.send(tx_sys.clone()).is_err() {
         panic!("Failed to send tx_sys");
     }
 
     // Produce
-    let (tx_produce, handle) =
-        spawn_produce_dht_ops_consumer(env.clone(), stop.subscribe(), tx_publish.clone());
-    task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
-        .await
-        .expect("Failed to manage workflow handle");
+    let (tx_produce, spawn) =
+        spawn_produce_dht_ops_consumer(env.clone(), tx_publish.clone(), metrics);
+    manage("produce_dht_ops_consumer", spawn).await;
 
     (
         QueueTriggers::new(tx_sys.clone(), tx_produce.clone()),
@@ -182,10 +210,25 @@ impl InitialQueueTriggers {
 }
 /// The means of nudging a queue consumer to tell it to look for more work
 #[derive(Clone)]
-pub struct TriggerSender(mpsc::Sender<()>);
+pub struct TriggerSender {
+    tx: mpsc::Sender<()>,
+    wake_up: Arc<WakeUp>,
+}
 
 /// The receiving end of a queue trigger channel
-pub struct TriggerReceiver(mpsc::Receiver<()>);
+pub struct TriggerReceiver {
+    rx: mpsc::Receiver<()>,
+    wake_up: Arc<WakeUp>,
+}
+
+/// A delayed nudge of a consumer. Only the earliest is kept,
+/// and it's waited for while the consumer listens for triggers,
+/// so it goes away with the consumer.
+struct WakeUp {
+    at: parking_lot::Mutex<Option<tokio::time::Instant>>,
+    /// Tells a listening consumer that the wake up time has moved
+    moved: sync::Notify,
+}
 
 impl TriggerSender {
     /// Create a new channel for waking a consumer
@@ -194,13 +237,23 @@ impl TriggerSender {
     /// inconsistency from the perspective of any particular CPU thread
     pub fn new() -> (TriggerSender, TriggerReceiver) {
         let (tx, rx) = mpsc::channel(num_cpus::get());
-        (TriggerSender(tx), TriggerReceiver(rx))
+        let wake_up = Arc::new(WakeUp {
+            at: parking_lot::Mutex::new(None),
+            moved: sync::Notify::new(),
+        });
+        (
+            TriggerSender {
+                tx,
+                wake_up: wake_up.clone(),
+            },
+            TriggerReceiver { rx, wake_up },
+        )
     }
 
     /// Lazily nudge the consumer task, ignoring the case where the consumer
     /// already has a pending trigger signal
     pub fn trigger(&mut self) {
-        match self.0.try_send(()) {
+        match self.tx.try_send(()) {
             Err(mpsc::error::TrySendError::Closed(_)) => {
                 tracing::warn!(
                     "Queue consumer trigger was sent while Cell is shutting down: ignoring."
@@ -210,26 +263,58 @@ impl TriggerSender {
             Ok(()) => {}
         };
     }
+
+    /// Nudge the consumer task once the delay has elapsed,
+    /// unless it's already due to be nudged sooner
+    pub fn trigger_after(&self, delay: std::time::Duration) {
+        let at = tokio::time::Instant::now() + delay;
+        let mut wake_up_at = self.wake_up.at.lock();
+        if wake_up_at.map_or(true, |wake_up_at| at < wake_up_at) {
+            *wake_up_at = Some(at);
+            self.wake_up.moved.notify();
+        }
+    }
 }
 
 impl TriggerReceiver {
     /// Listen for one or more items to come through, draining the channel
-    /// each time. Bubble up errors on empty channel.
+    /// each time, or for a delayed trigger to come due.
+    /// Bubble up errors on empty channel.
     pub async fn listen(&mut self) -> Result<(), QueueTriggerClosedError> {
         use tokio::sync::mpsc::error::TryRecvError;
 
-        // wait for next item
-        if self.0.recv().await.is_some() {
-            // drain the channel
-            loop {
-                match self.0.try_recv() {
-                    Err(TryRecvError::Closed) => return Err(QueueTriggerClosedError),
-                    Err(TryRecvError::Empty) => return Ok(()),
-                    Ok(()) => {}
+        loop {
+            let wake_up_at = *self.wake_up.at.lock();
+            let wake_up = async {
+                match wake_up_at {
+                    Some(at) => tokio::time::delay_until(at).await,
+                    None => futures::future::pending().await,
                 }
+            };
+            tokio::select! {
+                // wait for next item
+                item = self.rx.recv() => {
+                    if item.is_none() {
+                        return Err(QueueTriggerClosedError);
+                    }
+                    // drain the channel
+                    loop {
+                        match self.rx.try_recv() {
+                            Err(TryRecvError::Closed) => return Err(QueueTriggerClosedError),
+                            Err(TryRecvError::Empty) => return Ok(()),
+                            Ok(()) => {}
+                        }
+                    }
+                }
+                _ = wake_up => {
+                    let mut at = self.wake_up.at.lock();
+                    if *at == wake_up_at {
+                        *at = None;
+                    }
+                    return Ok(());
+                }
+                _ = self.wake_up.moved.notified() => {}
             }
-        } else {
-            Err(QueueTriggerClosedError)
         }
     }
 }
@@ -243,16 +328,14 @@ pub struct OneshotWriter(EnvironmentWrite);
 
 impl OneshotWriter {
     /// Create the writer and pass it into a closure.
-    pub fn with_writer<F>(self, f: F) -> Result<(), WorkspaceError>
+    ///
+    /// If the memory map fills up the map is grown and the closure is run
+    /// again in a fresh transaction, so it must only flush by reference.
+    pub async fn with_writer<F>(self, f: F) -> Result<(), WorkspaceError>
     where
-        F: FnOnce(&mut Writer) -> Result<(), WorkspaceError> + Send,
+        F: FnMut(&mut Writer) -> Result<(), WorkspaceError> + Send,
     {
-        let env_ref = self.0.guard();
-        env_ref.with_commit::<WorkspaceError, (), _>(|w| {
-            f(w)?;
-            Ok(())
-        })?;
-        Ok(())
+        self.0.with_commit_retry::<WorkspaceError, (), _>(f).await
     }
 }
 
@@ -297,3 +380,36 @@ async fn next_job_or_exit(
         Job::Run
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn only_the_earliest_delayed_trigger_is_kept() {
+        let (tx, mut rx) = TriggerSender::new();
+        tx.trigger_after(Duration::from_secs(60));
+        tx.trigger_after(Duration::from_millis(10));
+        tokio::time::timeout(Duration::from_secs(1), rx.listen())
+            .await
+            .unwrap()
+            .unwrap();
+        // The later trigger was replaced so nothing else is due
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), rx.listen())
+                .await
+                .is_err()
+        );
+
+        // A delayed trigger reaches a consumer which is already listening
+        let listening = tokio::spawn(async move { rx.listen().await });
+        tokio::time::delay_for(Duration::from_millis(10)).await;
+        tx.trigger_after(Duration::from_millis(10));
+        tokio::time::timeout(Duration::from_secs(1), listening)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+}
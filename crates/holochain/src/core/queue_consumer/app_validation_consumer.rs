@@ -1,52 +1,72 @@
 //! The workflow and queue consumer for sys validation
 
 use super::*;
-use crate::conductor::manager::ManagedTaskResult;
+use crate::conductor::manager::SpawnTask;
+use crate::core::metrics::CellMetrics;
+use crate::core::metrics::Workflow;
+use crate::core::metrics::WorkflowRun;
 use crate::core::workflow::app_validation_workflow::app_validation_workflow;
 use crate::core::workflow::app_validation_workflow::AppValidationWorkspace;
 use holochain_lmdb::env::EnvironmentWrite;
 
-use tokio::task::JoinHandle;
 use tracing::*;
 
-/// Spawn the QueueConsumer for AppValidation workflow
-#[instrument(skip(env, stop, trigger_integration, conductor_api, network))]
+/// Create the QueueConsumer for AppValidation workflow,
+/// which is spawned by calling the returned [SpawnTask]
+#[instrument(skip(env, trigger_integration, conductor_api, network, metrics))]
 pub fn spawn_app_validation_consumer(
     env: EnvironmentWrite,
-    mut stop: sync::broadcast::Receiver<()>,
-    mut trigger_integration: TriggerSender,
+    trigger_integration: TriggerSender,
     conductor_api: impl CellConductorApiT + 'static,
     network: HolochainP2pCell,
-) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
-    let (tx, mut rx) = TriggerSender::new();
-    let mut trigger_self = tx.clone();
-    let handle = tokio::spawn(async move {
-        loop {
-            // Wait for next job
-            if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
-                tracing::warn!(
-                    "Cell is shutting down: stopping app_validation_workflow queue consumer."
-                );
-                break;
-            }
+    validation_workers: usize,
+    metrics: Arc<CellMetrics>,
+) -> (TriggerSender, SpawnTask) {
+    let (tx, rx) = TriggerSender::new();
+    let rx = Arc::new(sync::Mutex::new(rx));
+    let trigger_self = tx.clone();
+    let spawn = move |mut stop: sync::broadcast::Receiver<()>| {
+        let rx = rx.clone();
+        let env = env.clone();
+        let metrics = metrics.clone();
+        let mut trigger_integration = trigger_integration.clone();
+        let conductor_api = conductor_api.clone();
+        let network = network.clone();
+        let mut trigger_self = trigger_self.clone();
+        tokio::spawn(async move {
+            let mut rx = rx.lock().await;
+            loop {
+                // Wait for next job
+                if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
+                    tracing::warn!(
+                        "Cell is shutting down: stopping app_validation_workflow queue consumer."
+                    );
+                    break;
+                }
 
-            // Run the workflow
-            let workspace = AppValidationWorkspace::new(env.clone().into())
-                .expect("Could not create Workspace");
-            if let WorkComplete::Incomplete = app_validation_workflow(
-                workspace,
-                env.clone().into(),
-                &mut trigger_integration,
-                conductor_api.clone(),
-                network.clone(),
-            )
-            .await
-            .expect("Error running Workflow")
-            {
-                trigger_self.trigger()
-            };
-        }
-        Ok(())
-    });
-    (tx, handle)
+                // Run the workflow
+                let workspace = AppValidationWorkspace::new(env.clone().into())
+                    .expect("Could not create Workspace");
+                let mut run = WorkflowRun::start();
+                let result = app_validation_workflow(
+                    workspace,
+                    env.clone().into(),
+                    &mut trigger_integration,
+                    conductor_api.clone(),
+                    network.clone(),
+                    validation_workers,
+                    &mut run,
+                )
+                .await;
+                metrics
+                    .workflow(Workflow::AppValidation)
+                    .record(run, result.is_err());
+                if let WorkComplete::Incomplete = result.expect("Error running Workflow") {
+                    trigger_self.trigger()
+                };
+            }
+            Ok(())
+        })
+    };
+    (tx, Arc::new(spawn))
 }
@@ -2,46 +2,70 @@
 
 use super::*;
 
-use crate::conductor::manager::ManagedTaskResult;
+use crate::conductor::manager::SpawnTask;
+use crate::core::metrics::CellMetrics;
+use crate::core::metrics::Workflow;
+use crate::core::metrics::WorkflowRun;
 use crate::core::workflow::integrate_dht_ops_workflow::integrate_dht_ops_workflow;
 use crate::core::workflow::integrate_dht_ops_workflow::IntegrateDhtOpsWorkspace;
+use futures::future::FutureExt;
 use holochain_lmdb::env::EnvironmentWrite;
 
-use tokio::task::JoinHandle;
 use tracing::*;
 
-/// Spawn the QueueConsumer for DhtOpIntegration workflow
-#[instrument(skip(env, stop, trigger_sys))]
+/// Create the QueueConsumer for DhtOpIntegration workflow,
+/// which is spawned by calling the returned [SpawnTask]
+#[instrument(skip(env, trigger_sys, metrics))]
 pub fn spawn_integrate_dht_ops_consumer(
     env: EnvironmentWrite,
-    mut stop: sync::broadcast::Receiver<()>,
     trigger_sys: sync::oneshot::Receiver<TriggerSender>,
-) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
-    let (tx, mut rx) = TriggerSender::new();
-    let mut trigger_self = tx.clone();
-    let handle = tokio::spawn(async move {
-        let mut trigger_sys = trigger_sys.await.expect("failed to get tx sys");
-        loop {
-            // Wait for next job
-            if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
-                tracing::warn!(
-                    "Cell is shutting down: stopping integrate_dht_ops_workflow queue consumer."
-                );
-                break;
-            }
+    metrics: Arc<CellMetrics>,
+) -> (TriggerSender, SpawnTask) {
+    let (tx, rx) = TriggerSender::new();
+    let rx = Arc::new(sync::Mutex::new(rx));
+    let trigger_self = tx.clone();
+    // Every run of the consumer waits for the same sys validation trigger
+    let trigger_sys = trigger_sys
+        .map(|r| r.expect("failed to get tx sys"))
+        .shared();
+    let spawn = move |mut stop: sync::broadcast::Receiver<()>| {
+        let rx = rx.clone();
+        let env = env.clone();
+        let metrics = metrics.clone();
+        let trigger_sys = trigger_sys.clone();
+        let mut trigger_self = trigger_self.clone();
+        tokio::spawn(async move {
+            let mut trigger_sys = trigger_sys.await;
+            let mut rx = rx.lock().await;
+            loop {
+                // Wait for next job
+                if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
+                    tracing::warn!(
+                        "Cell is shutting down: stopping integrate_dht_ops_workflow queue consumer."
+                    );
+                    break;
+                }
 
-            // Run the workflow
-            let workspace = IntegrateDhtOpsWorkspace::new(env.clone().into())
-                .expect("Could not create Workspace");
-            if let WorkComplete::Incomplete =
-                integrate_dht_ops_workflow(workspace, env.clone().into(), &mut trigger_sys)
-                    .await
-                    .expect("Error running Workflow")
-            {
-                trigger_self.trigger()
-            };
-        }
-        Ok(())
-    });
-    (tx, handle)
+                // Run the workflow
+                let workspace = IntegrateDhtOpsWorkspace::new(env.clone().into())
+                    .expect("Could not create Workspace");
+                let mut run = WorkflowRun::start();
+                let result = integrate_dht_ops_workflow(
+                    workspace,
+                    env.clone().into(),
+                    &mut trigger_sys,
+                    &mut run,
+                )
+                .await;
+                metrics
+                    .workflow(Workflow::IntegrateDhtOps)
+                    .record(run, result.is_err());
+                if let WorkComplete::Incomplete = result.expect("Error running Workflow") {
+                    trigger_self.trigger()
+                };
+            }
+            Ok(())
+        })
+    };
+    (tx, Arc::new(spawn))
 }
@@ -1,43 +1,62 @@
 //! The workflow and queue consumer for DhtOp production
 
 use super::*;
-use crate::conductor::manager::ManagedTaskResult;
+use crate::conductor::manager::SpawnTask;
+use crate::core::metrics::CellMetrics;
+use crate::core::metrics::Workflow;
+use crate::core::metrics::WorkflowRun;
 use crate::core::workflow::produce_dht_ops_workflow::produce_dht_ops_workflow;
 use crate::core::workflow::produce_dht_ops_workflow::ProduceDhtOpsWorkspace;
 use holochain_lmdb::env::EnvironmentWrite;
 
-use tokio::task::JoinHandle;
 use tracing::*;
 
-/// Spawn the QueueConsumer for Produce_dht_ops workflow
-#[instrument(skip(env, stop, trigger_publish))]
+/// Create the QueueConsumer for Produce_dht_ops workflow,
+/// which is spawned by calling the returned [SpawnTask]
+#[instrument(skip(env, trigger_publish, metrics))]
 pub fn spawn_produce_dht_ops_consumer(
     env: EnvironmentWrite,
-    mut stop: sync::broadcast::Receiver<()>,
-    mut trigger_publish: TriggerSender,
-) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
-    let (tx, mut rx) = TriggerSender::new();
-    let mut trigger_self = tx.clone();
-    let handle = tokio::spawn(async move {
-        loop {
-            if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
-                tracing::warn!(
-                    "Cell is shutting down: stopping produce_dht_ops_workflow queue consumer."
-                );
-                break;
-            }
+    trigger_publish: TriggerSender,
+    metrics: Arc<CellMetrics>,
+) -> (TriggerSender, SpawnTask) {
+    let (tx, rx) = TriggerSender::new();
+    let rx = Arc::new(sync::Mutex::new(rx));
+    let trigger_self = tx.clone();
+    let spawn = move |mut stop: sync::broadcast::Receiver<()>| {
+        let rx = rx.clone();
+        let env = env.clone();
+        let metrics = metrics.clone();
+        let mut trigger_publish = trigger_publish.clone();
+        let mut trigger_self = trigger_self.clone();
+        tokio::spawn(async move {
+            let mut rx = rx.lock().await;
+            loop {
+                if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
+                    tracing::warn!(
+                        "Cell is shutting down: stopping produce_dht_ops_workflow queue consumer."
+                    );
+                    break;
+                }
 
-            let workspace = ProduceDhtOpsWorkspace::new(env.clone().into())
-                .expect("Could not create Workspace");
-            if let WorkComplete::Incomplete =
-                produce_dht_ops_workflow(workspace, env.clone().into(), &mut trigger_publish)
-                    .await
-                    .expect("Error running Workflow")
-            {
-                trigger_self.trigger()
-            };
-        }
-        Ok(())
-    });
-    (tx, handle)
+                let workspace = ProduceDhtOpsWorkspace::new(env.clone().into())
+                    .expect("Could not create Workspace");
+                let mut run = WorkflowRun::start();
+                let result = produce_dht_ops_workflow(
+                    workspace,
+                    env.clone().into(),
+                    &mut trigger_publish,
+                    &mut run,
+                )
+                .await;
+                metrics
+                    .workflow(Workflow::ProduceDhtOps)
+                    .record(run, result.is_err());
+                if let WorkComplete::Incomplete = result.expect("Error running Workflow") {
+                    trigger_self.trigger()
+                };
+            }
+            Ok(())
+        })
+    };
+    (tx, Arc::new(spawn))
 }
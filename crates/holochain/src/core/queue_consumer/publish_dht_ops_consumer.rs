@@ -2,45 +2,67 @@
 
 use super::*;
 
-use crate::conductor::manager::ManagedTaskResult;
+use crate::conductor::manager::SpawnTask;
+use crate::core::metrics::CellMetrics;
+use crate::core::metrics::Workflow;
+use crate::core::metrics::WorkflowRun;
 use crate::core::workflow::publish_dht_ops_workflow::publish_dht_ops_workflow;
 use crate::core::workflow::publish_dht_ops_workflow::PublishDhtOpsWorkspace;
 use holochain_lmdb::env::EnvironmentWrite;
 
-use tokio::task::JoinHandle;
 use tracing::*;
 
-/// Spawn the QueueConsumer for Publish workflow
-#[instrument(skip(env, stop, cell_network))]
+/// Create the QueueConsumer for Publish workflow,
+/// which is spawned by calling the returned [SpawnTask]
+#[instrument(skip(env, cell_network, conductor_api, metrics))]
 pub fn spawn_publish_dht_ops_consumer(
     env: EnvironmentWrite,
-    mut stop: sync::broadcast::Receiver<()>,
-    mut cell_network: HolochainP2pCell,
-) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
-    let (tx, mut rx) = TriggerSender::new();
-    let mut trigger_self = tx.clone();
-    let handle = tokio::spawn(async move {
-        loop {
-            // Wait for next job
-            if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
-                tracing::warn!(
-                    "Cell is shutting down: stopping publish_dht_ops_workflow queue consumer."
-                );
-                break;
-            }
+    cell_network: HolochainP2pCell,
+    conductor_api: impl CellConductorApiT + 'static,
+    metrics: Arc<CellMetrics>,
+) -> (TriggerSender, SpawnTask) {
+    let (tx, rx) = TriggerSender::new();
+    let rx = Arc::new(sync::Mutex::new(rx));
+    let trigger_self = tx.clone();
+    let spawn = move |mut stop: sync::broadcast::Receiver<()>| {
+        let rx = rx.clone();
+        let env = env.clone();
+        let metrics = metrics.clone();
+        let mut cell_network = cell_network.clone();
+        let conductor_api = conductor_api.clone();
+        let mut trigger_self = trigger_self.clone();
+        tokio::spawn(async move {
+            let mut rx = rx.lock().await;
+            loop {
+                // Wait for next job
+                if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
+                    tracing::warn!(
+                        "Cell is shutting down: stopping publish_dht_ops_workflow queue consumer."
+                    );
+                    break;
+                }
 
-            // Run the workflow
-            let workspace = PublishDhtOpsWorkspace::new(env.clone().into())
-                .expect("Could not create Workspace");
-            if let WorkComplete::Incomplete =
-                publish_dht_ops_workflow(workspace, env.clone().into(), &mut cell_network)
-                    .await
-                    .expect("Error running Workflow")
-            {
-                trigger_self.trigger()
-            };
-        }
-        Ok(())
-    });
-    (tx, handle)
+                // Run the workflow
+                let workspace = PublishDhtOpsWorkspace::new(env.clone().into())
+                    .expect("Could not create Workspace");
+                let mut run = WorkflowRun::start();
+                let result = publish_dht_ops_workflow(
+                    workspace,
+                    env.clone().into(),
+                    &mut cell_network,
+                    &conductor_api,
+                    &mut run,
+                )
+                .await;
+                metrics
+                    .workflow(Workflow::PublishDhtOps)
+                    .record(run, result.is_err());
+                if let WorkComplete::Incomplete = result.expect("Error running Workflow") {
+                    trigger_self.trigger()
+                };
+            }
+            Ok(())
+        })
+    };
+    (tx, Arc::new(spawn))
 }
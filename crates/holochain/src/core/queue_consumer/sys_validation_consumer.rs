@@ -1,52 +1,72 @@
 //! The workflow and queue consumer for sys validation
 
 use super::*;
-use crate::conductor::manager::ManagedTaskResult;
+use crate::conductor::manager::SpawnTask;
+use crate::core::metrics::CellMetrics;
+use crate::core::metrics::Workflow;
+use crate::core::metrics::WorkflowRun;
 use crate::core::workflow::sys_validation_workflow::sys_validation_workflow;
 use crate::core::workflow::sys_validation_workflow::SysValidationWorkspace;
 use holochain_lmdb::env::EnvironmentWrite;
-use tokio::task::JoinHandle;
 use tracing::*;
 
-/// Spawn the QueueConsumer for SysValidation workflow
-#[instrument(skip(env, stop, trigger_app_validation, network, conductor_api))]
+/// Create the QueueConsumer for SysValidation workflow,
+/// which is spawned by calling the returned [SpawnTask]
+#[instrument(skip(env, trigger_app_validation, network, conductor_api, metrics))]
 pub fn spawn_sys_validation_consumer(
     env: EnvironmentWrite,
-    mut stop: sync::broadcast::Receiver<()>,
-    mut trigger_app_validation: TriggerSender,
+    trigger_app_validation: TriggerSender,
     network: HolochainP2pCell,
     conductor_api: impl CellConductorApiT + 'static,
-) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
-    let (tx, mut rx) = TriggerSender::new();
-    let mut trigger_self = tx.clone();
-    let handle = tokio::spawn(async move {
-        loop {
-            // Wait for next job
-            if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
-                tracing::warn!(
-                    "Cell is shutting down: stopping sys_validation_workflow queue consumer."
-                );
-                break;
-            }
+    validation_workers: usize,
+    metrics: Arc<CellMetrics>,
+) -> (TriggerSender, SpawnTask) {
+    let (tx, rx) = TriggerSender::new();
+    let rx = Arc::new(sync::Mutex::new(rx));
+    let trigger_self = tx.clone();
+    let spawn = move |mut stop: sync::broadcast::Receiver<()>| {
+        let rx = rx.clone();
+        let env = env.clone();
+        let metrics = metrics.clone();
+        let mut trigger_app_validation = trigger_app_validation.clone();
+        let network = network.clone();
+        let conductor_api = conductor_api.clone();
+        let mut trigger_self = trigger_self.clone();
+        tokio::spawn(async move {
+            let mut rx = rx.lock().await;
+            loop {
+                // Wait for next job
+                if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
+                    tracing::warn!(
+                        "Cell is shutting down: stopping sys_validation_workflow queue consumer."
+                    );
+                    break;
+                }
 
-            // Run the workflow
-            let workspace = SysValidationWorkspace::new(env.clone().into())
-                .expect("Could not create Workspace");
-            if let WorkComplete::Incomplete = sys_validation_workflow(
-                workspace,
-                env.clone().into(),
-                &mut trigger_app_validation,
-                trigger_self.clone(),
-                network.clone(),
-                conductor_api.clone(),
-            )
-            .await
-            .expect("Error running Workflow")
-            {
-                trigger_self.trigger()
-            };
-        }
-        Ok(())
-    });
-    (tx, handle)
+                // Run the workflow
+                let workspace = SysValidationWorkspace::new(env.clone().into())
+                    .expect("Could not create Workspace");
+                let mut run = WorkflowRun::start();
+                let result = sys_validation_workflow(
+                    workspace,
+                    env.clone().into(),
+                    &mut trigger_app_validation,
+                    trigger_self.clone(),
+                    network.clone(),
+                    conductor_api.clone(),
+                    validation_workers,
+                    &mut run,
+                )
+                .await;
+                metrics
+                    .workflow(Workflow::SysValidation)
+                    .record(run, result.is_err());
+                if let WorkComplete::Incomplete = result.expect("Error running Workflow") {
+                    trigger_self.trigger()
+                };
+            }
+            Ok(())
+        })
+    };
+    (tx, Arc::new(spawn))
 }
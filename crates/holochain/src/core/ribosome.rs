@@ -71,6 +71,22 @@ impl CallContext {
     }
 }
 
+/// The access each kind of call has to host functions.
+/// Callbacks that validate only get permissions which don't depend on
+/// the agent or on when they run, so all validators agree.
+/// [host_fn_permission](host_fn::host_fn_permission) lists the host
+/// functions each permission covers.
+///
+/// | Call                | agent_info | read_workspace | read_workspace_deterministic | write_workspace | write_network | dna_bindings | non_determinism | keystore | keystore_deterministic |
+/// |---------------------|:----------:|:--------------:|:----------------------------:|:---------------:|:-------------:|:------------:|:---------------:|:--------:|:----------------------:|
+/// | zome call           | x          | x              | x                            | x               | x             | x            | x               | x        | x                      |
+/// | init                | x          | x              | x                            | x               | x             | x            | x               | x        | x                      |
+/// | post commit         | x          | x              | x                            | x               | x             | x            | x               | x        | x                      |
+/// | migrate agent       | x          | x              | x                            |                 |               | x            |                 |          |                        |
+/// | validation package  | x          | x              | x                            |                 |               |              |                 |          |                        |
+/// | validate            |            |                | x                            |                 |               | x            |                 |          | x                      |
+/// | validate link       |            |                | x                            |                 |               | x            |                 |          | x                      |
+/// | entry defs          |            |                |                              |                 |               |              |                 |          |                        |
 #[derive(Clone)]
 pub enum HostAccess {
     ZomeCall(ZomeCallHostAccess),
@@ -103,6 +119,20 @@ impl From<&HostAccess> for HostFnAccess {
 }
 
 impl HostAccess {
+    /// The kind of call this is, for errors
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ZomeCall(_) => "a zome call",
+            Self::Validate(_) => "a validate callback",
+            Self::ValidateCreateLink(_) => "a validate link callback",
+            Self::Init(_) => "an init callback",
+            Self::EntryDefs(_) => "an entry defs callback",
+            Self::MigrateAgent(_) => "a migrate agent callback",
+            Self::ValidationPackage(_) => "a validation package callback",
+            Self::PostCommit(_) => "a post commit callback",
+        }
+    }
+
     /// Get the workspace, panics if none was provided
     pub fn workspace(&self) -> &CallZomeWorkspaceLock {
         match self {
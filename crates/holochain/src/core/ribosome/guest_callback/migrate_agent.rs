@@ -38,6 +38,7 @@ impl From<&MigrateAgentHostAccess> for HostFnAccess {
         let mut access = Self::none();
         // TODO: insert zome_name
         access.read_workspace = Permission::Allow;
+        access.read_workspace_deterministic = Permission::Allow;
         access.agent_info = Permission::Allow;
         access.dna_bindings = Permission::Allow;
         access
@@ -172,11 +173,13 @@ mod test {
             HostFnAccess {
                 agent_info: Allow,
                 read_workspace: Allow,
+                read_workspace_deterministic: Allow,
                 write_workspace: Deny,
                 non_determinism: Deny,
                 write_network: Deny,
                 dna_bindings: Allow,
                 keystore: Deny,
+                keystore_deterministic: Deny,
             }
         );
     }
@@ -41,8 +41,8 @@ impl From<ValidateHostAccess> for HostAccess {
 impl From<&ValidateHostAccess> for HostFnAccess {
     fn from(_: &ValidateHostAccess) -> Self {
         let mut access = Self::none();
-        access.read_workspace = Permission::Allow;
-        access.keystore = Permission::Allow;
+        access.read_workspace_deterministic = Permission::Allow;
+        access.keystore_deterministic = Permission::Allow;
         access.dna_bindings = Permission::Allow;
         access
     }
@@ -187,8 +187,8 @@ mod test {
             .next()
             .unwrap();
         let mut access = HostFnAccess::none();
-        access.read_workspace = Permission::Allow;
-        access.keystore = Permission::Allow;
+        access.read_workspace_deterministic = Permission::Allow;
+        access.keystore_deterministic = Permission::Allow;
         access.dna_bindings = Permission::Allow;
         assert_eq!(HostFnAccess::from(&validate_host_access), access);
     }
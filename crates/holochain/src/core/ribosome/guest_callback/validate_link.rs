@@ -88,7 +88,8 @@ impl From<ValidateLinkHostAccess> for HostAccess {
 impl From<&ValidateLinkHostAccess> for HostFnAccess {
     fn from(_: &ValidateLinkHostAccess) -> Self {
         let mut access = Self::none();
-        access.read_workspace = Permission::Allow;
+        access.read_workspace_deterministic = Permission::Allow;
+        access.keystore_deterministic = Permission::Allow;
         access.dna_bindings = Permission::Allow;
         access
     }
@@ -224,7 +225,8 @@ mod test {
                 .next()
                 .unwrap();
         let mut access = HostFnAccess::none();
-        access.read_workspace = Permission::Allow;
+        access.read_workspace_deterministic = Permission::Allow;
+        access.keystore_deterministic = Permission::Allow;
         access.dna_bindings = Permission::Allow;
         assert_eq!(HostFnAccess::from(&validate_link_add_host_access), access,);
     }
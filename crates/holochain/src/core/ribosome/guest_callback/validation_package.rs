@@ -13,13 +13,15 @@ use holochain_types::prelude::*;
 pub struct ValidationPackageInvocation {
     zome: Zome,
     app_entry_type: AppEntryType,
+    entry_def_id: EntryDefId,
 }
 
 impl ValidationPackageInvocation {
-    pub fn new(zome: Zome, app_entry_type: AppEntryType) -> Self {
+    pub fn new(zome: Zome, app_entry_type: AppEntryType, entry_def_id: EntryDefId) -> Self {
         Self {
             zome,
             app_entry_type,
+            entry_def_id,
         }
     }
 }
@@ -40,6 +42,7 @@ impl From<&ValidationPackageHostAccess> for HostFnAccess {
     fn from(_: &ValidationPackageHostAccess) -> Self {
         let mut access = Self::none();
         access.read_workspace = Permission::Allow;
+        access.read_workspace_deterministic = Permission::Allow;
         access.agent_info = Permission::Allow;
         access
     }
@@ -50,13 +53,13 @@ impl Invocation for ValidationPackageInvocation {
         ZomesToInvoke::One(self.zome.to_owned())
     }
     fn fn_components(&self) -> FnComponents {
-        // @todo zome_id is a u8, is this really an ergonomic way for us to interact with
-        // entry types at the happ code level?
-        vec![
-            "validation_package".into(),
-            format!("{}", self.app_entry_type.zome_id()),
-        ]
-        .into()
+        // Like validate, the callback can be specific to the entry def id
+        // e.g. `validation_package_comment`
+        let mut fns = vec!["validation_package".into()];
+        if let EntryDefId::App(entry_def_id) = &self.entry_def_id {
+            fns.push(entry_def_id.clone());
+        }
+        fns.into()
     }
     fn host_input(self) -> Result<ExternIO, SerializedBytesError> {
         ExternIO::encode(self.app_entry_type)
@@ -123,6 +126,7 @@ mod test {
     use crate::fixt::ValidationPackageHostAccessFixturator;
     use crate::fixt::ValidationPackageInvocationFixturator;
     use holochain_types::dna::zome::HostFnAccess;
+    use holochain_types::prelude::EntryDefId;
     use holochain_zome_types::validate::ValidationPackage;
     use holochain_zome_types::validate::ValidationPackageCallbackResult;
     use holochain_zome_types::ExternIO;
@@ -180,11 +184,13 @@ mod test {
             HostFnAccess {
                 agent_info: Allow,
                 read_workspace: Allow,
+                read_workspace_deterministic: Allow,
                 write_workspace: Deny,
                 write_network: Deny,
                 dna_bindings: Deny,
                 non_determinism: Deny,
                 keystore: Deny,
+                keystore_deterministic: Deny,
             }
         );
     }
@@ -204,17 +210,15 @@ mod test {
 
     #[tokio::test(threaded_scheduler)]
     async fn validation_package_invocation_fn_components() {
-        let validation_package_invocation =
+        let mut validation_package_invocation =
             ValidationPackageInvocationFixturator::new(::fixt::Unpredictable)
                 .next()
                 .unwrap();
+        validation_package_invocation.entry_def_id = EntryDefId::App("post".into());
 
         let mut expected = vec![
             "validation_package".to_string(),
-            format!(
-                "validation_package_{}",
-                validation_package_invocation.app_entry_type.zome_id()
-            ),
+            "validation_package_post".to_string(),
         ];
         for fn_component in validation_package_invocation.fn_components() {
             assert_eq!(fn_component, expected.pop().unwrap(),);
@@ -1,6 +1,8 @@
 use super::CallContext;
+use super::HostAccess;
 use super::RibosomeT;
 use holochain_types::prelude::*;
+use holochain_wasmer_host::prelude::WasmError;
 use std::sync::Arc;
 
 pub struct HostFnApi<Ribosome: RibosomeT> {
@@ -17,6 +19,80 @@ impl<Ribosome: RibosomeT> HostFnApi<Ribosome> {
     }
 }
 
+/// The permission in [HostFnAccess] a call needs to use a host function.
+/// Calls without it get [forbidden_host_fn] instead.
+///
+/// | Permission                     | Host functions                                                     |
+/// |--------------------------------|--------------------------------------------------------------------|
+/// | always allowed                 | `trace`, `hash_entry`, `version`, `unreachable`                    |
+/// | `read_workspace_deterministic` | `get` with [GetStrategy::Content]                                  |
+/// | `read_workspace`               | `get` with [GetStrategy::Latest], `get_details`, `get_links`,      |
+/// |                                | `get_link_details`, `get_agent_activity`, `query`                  |
+/// | `keystore_deterministic`       | `verify_signature`                                                 |
+/// | `keystore`                     | `sign`, `create_x25519_keypair`, `x_salsa20_poly1305_*`,           |
+/// |                                | `x_25519_x_salsa20_poly1305_*`                                     |
+/// | `dna_bindings`                 | `zome_info`, `app_info`, `dna_info`, `call_info`                   |
+/// | `agent_info`                   | `agent_info`, `capability_claims`, `capability_grants`,            |
+/// |                                | `capability_info`                                                  |
+/// | `non_determinism`              | `random_bytes`, `sys_time`, `sleep`                                |
+/// | `write_network`                | `call_remote`, `remote_signal`                                     |
+/// | `write_workspace`              | `call`, `create`, `update`, `delete`, `create_link`, `delete_link`,|
+/// |                                | `emit_signal`, `schedule`                                          |
+///
+/// Which permissions each kind of call has is listed on [HostAccess].
+pub(crate) fn host_fn_permission(host_fn: &str, access: &HostFnAccess) -> Permission {
+    match host_fn {
+        "trace" | "hash_entry" | "version" | "unreachable" => Permission::Allow,
+        "get" => access.read_workspace_deterministic,
+        "get_details" | "get_links" | "get_link_details" | "get_agent_activity" | "query" => {
+            access.read_workspace
+        }
+        "verify_signature" => access.keystore_deterministic,
+        "sign"
+        | "create_x25519_keypair"
+        | "x_salsa20_poly1305_encrypt"
+        | "x_salsa20_poly1305_decrypt"
+        | "x_25519_x_salsa20_poly1305_encrypt"
+        | "x_25519_x_salsa20_poly1305_decrypt" => access.keystore,
+        "zome_info" | "app_info" | "dna_info" | "call_info" => access.dna_bindings,
+        "agent_info" | "capability_claims" | "capability_grants" | "capability_info" => {
+            access.agent_info
+        }
+        "random_bytes" | "sys_time" | "sleep" => access.non_determinism,
+        "call_remote" | "remote_signal" => access.write_network,
+        "call" | "create" | "update" | "delete" | "create_link" | "delete_link" | "emit_signal"
+        | "schedule" => access.write_workspace,
+        _ => Permission::Deny,
+    }
+}
+
+/// The error a call gets for a host function its access doesn't allow
+pub(crate) fn forbidden_host_fn(host_fn: &str, host_access: &HostAccess) -> WasmError {
+    WasmError::Host(format!(
+        "Host function `{}` is not allowed in {}",
+        host_fn,
+        host_access.name()
+    ))
+}
+
+/// Gets for the latest data depend on what has reached this agent so far,
+/// so they need `read_workspace` rather than `read_workspace_deterministic`
+pub(crate) fn check_get_strategy(
+    host_fn: &str,
+    call_context: &CallContext,
+    get_options: &GetOptions,
+) -> Result<(), WasmError> {
+    let access = HostFnAccess::from(&call_context.host_access);
+    match (&get_options.strategy, access.read_workspace) {
+        (GetStrategy::Latest, Permission::Deny) => Err(WasmError::Host(format!(
+            "Host function `{}` with `GetStrategy::Latest` is not allowed in {}, use `GetStrategy::Content`",
+            host_fn,
+            call_context.host_access.name()
+        ))),
+        _ => Ok(()),
+    }
+}
+
 macro_rules! host_fn_api_impls {
     ( $( fn $f:ident ( $input:ty ) -> $output:ty; )* ) => {
         $(
@@ -26,6 +102,13 @@ macro_rules! host_fn_api_impls {
         impl<Ribosome: RibosomeT> HostFnApiT for HostFnApi<Ribosome> {
             $(
                 fn $f(&self, input: $input) -> Result<$output, HostFnApiError> {
+                    let access = HostFnAccess::from(&self.call_context.host_access);
+                    if let Permission::Deny = host_fn_permission(stringify!($f), &access) {
+                        return Err(HostFnApiError::RibosomeError(Box::new(forbidden_host_fn(
+                            stringify!($f),
+                            &self.call_context.host_access,
+                        ))));
+                    }
                     $f::$f(
                         self.ribosome.clone(),
                         self.call_context.clone(),
@@ -170,3 +253,51 @@ host_fn_api_impls! {
     fn zome_info (()) -> zt::info::ZomeInfo;
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::host_fn_permission;
+    use holochain_types::prelude::*;
+
+    #[test]
+    fn deterministic_access_only_allows_deterministic_host_fns() {
+        let mut access = HostFnAccess::none();
+        access.read_workspace_deterministic = Permission::Allow;
+        access.keystore_deterministic = Permission::Allow;
+        access.dna_bindings = Permission::Allow;
+
+        for host_fn in &[
+            "trace",
+            "hash_entry",
+            "get",
+            "verify_signature",
+            "zome_info",
+        ] {
+            assert_eq!(
+                host_fn_permission(host_fn, &access),
+                Permission::Allow,
+                "{}",
+                host_fn
+            );
+        }
+        for host_fn in &[
+            "get_details",
+            "get_links",
+            "query",
+            "sign",
+            "agent_info",
+            "random_bytes",
+            "sys_time",
+            "call_remote",
+            "create",
+            "not_a_host_fn",
+        ] {
+            assert_eq!(
+                host_fn_permission(host_fn, &access),
+                Permission::Deny,
+                "{}",
+                host_fn
+            );
+        }
+    }
+}
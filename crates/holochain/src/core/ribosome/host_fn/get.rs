@@ -1,35 +1,146 @@
+use crate::core::ribosome::guest_callback::entry_defs::EntryDefsInvocation;
+use crate::core::ribosome::guest_callback::entry_defs::EntryDefsResult;
+use crate::core::ribosome::host_fn::check_get_strategy;
 use crate::core::ribosome::CallContext;
+use crate::core::ribosome::HostAccess;
 use crate::core::ribosome::RibosomeT;
+use crate::core::ribosome::ZomeCallHostAccess;
+use holo_hash::hash_type::AnyDht;
 use holochain_types::prelude::*;
 use std::sync::Arc;
 use holochain_wasmer_host::prelude::WasmError;
 
 #[allow(clippy::extra_unused_lifetimes)]
 pub fn get<'a>(
-    _ribosome: Arc<impl RibosomeT>,
+    ribosome: Arc<impl RibosomeT>,
     call_context: Arc<CallContext>,
     input: GetInput,
 ) -> Result<Option<Element>, WasmError> {
     let GetInput{ any_dht_hash, get_options } = input;
+    check_get_strategy("get", &call_context, &get_options)?;
+    let is_entry_hash = matches!(any_dht_hash.hash_type(), AnyDht::Entry);
 
     // Get the network from the context
     let network = call_context.host_access.network().clone();
 
     // timeouts must be handled by the network
+    let maybe_element = tokio_safe_block_on::tokio_safe_block_forever_on({
+        let call_context = call_context.clone();
+        let network = network.clone();
+        let get_options = get_options.clone();
+        async move {
+            call_context
+                .host_access
+                .workspace()
+                .write()
+                .await
+                .cascade(network)
+                .dht_get(any_dht_hash, get_options)
+                .await
+                .map_err(|cascade_error| WasmError::Host(cascade_error.to_string()))
+        }
+    })?;
+
+    // An entry of a CRDT entry type is merged with the updates made to it
+    let element = match maybe_element {
+        Some(element) if is_entry_hash && merges_updates(&call_context.host_access) => element,
+        maybe_element => return Ok(maybe_element),
+    };
+    let crdt_type = crdt_type(ribosome, call_context.clone(), element.header())?;
+    if let CrdtType::None = crdt_type {
+        return Ok(Some(element));
+    }
     tokio_safe_block_on::tokio_safe_block_forever_on(async move {
-        let maybe_element = call_context
+        call_context
             .host_access
             .workspace()
             .write()
             .await
             .cascade(network)
-            .dht_get(any_dht_hash, get_options)
+            .dht_get_merged(element, crdt_type, get_options)
             .await
-            .map_err(|cascade_error| WasmError::Host(cascade_error.to_string()))?;
-
-        Ok(maybe_element)
+            .map_err(|cascade_error| WasmError::Host(cascade_error.to_string()))
     })
 }
 
+/// Only calls that can read the latest data see entries merged with their
+/// updates. Validation has to get the same element on every agent,
+/// whichever updates have reached them.
+fn merges_updates(host_access: &HostAccess) -> bool {
+    HostFnAccess::from(host_access).read_workspace == Permission::Allow
+}
+
+/// The [CrdtType] declared by the entry def of this header's app entry.
+/// Zome calls read it from the conductor's entry def store. Callbacks can't
+/// reach the store, so they and entry defs missing from it run the
+/// entry defs callback instead.
+fn crdt_type(
+    ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    header: &Header,
+) -> Result<CrdtType, WasmError> {
+    let app_entry_type = match header.entry_type() {
+        Some(EntryType::App(app_entry_type)) => app_entry_type,
+        _ => return Ok(CrdtType::None),
+    };
+    let (zome_name, zome) = match ribosome
+        .dna_def()
+        .zomes
+        .get(app_entry_type.zome_id().index())
+    {
+        Some((zome_name, zome)) => (zome_name.clone(), zome.clone()),
+        None => return Ok(CrdtType::None),
+    };
+    if let HostAccess::ZomeCall(ZomeCallHostAccess {
+        call_zome_handle, ..
+    }) = &call_context.host_access
+    {
+        let key = EntryDefBufferKey::new(zome, app_entry_type.id());
+        if let Some(entry_def) = tokio_safe_block_on::tokio_safe_block_forever_on(
+            call_zome_handle.get_entry_def(&key),
+        ) {
+            return Ok(entry_def.crdt_type);
+        }
+    }
+    match ribosome
+        .run_entry_defs((&call_context.host_access).into(), EntryDefsInvocation)
+        .map_err(|ribosome_error| WasmError::Host(ribosome_error.to_string()))?
+    {
+        EntryDefsResult::Defs(defs) => Ok(defs
+            .get(&zome_name)
+            .and_then(|entry_defs| entry_defs.get(app_entry_type.id()))
+            .map(|entry_def| entry_def.crdt_type)
+            .unwrap_or_default()),
+        EntryDefsResult::Err(zome_name, error) => Err(WasmError::Host(format!(
+            "Couldn't get the entry defs of zome {}: {}",
+            zome_name, error
+        ))),
+    }
+}
+
 // we are relying on the create tests to show the commit/get round trip
 // See commit_entry.rs
+
+#[cfg(test)]
+mod tests {
+    use super::merges_updates;
+    use crate::core::ribosome::HostAccess;
+    use crate::fixt::*;
+    use ::fixt::prelude::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn validation_gets_entries_without_merging_updates() {
+        assert!(merges_updates(&HostAccess::ZomeCall(fixt!(
+            ZomeCallHostAccess
+        ))));
+        assert!(!merges_updates(&HostAccess::Validate(fixt!(
+            ValidateHostAccess
+        ))));
+        assert!(!merges_updates(&HostAccess::ValidateCreateLink(fixt!(
+            ValidateLinkHostAccess
+        ))));
+        assert!(!merges_updates(&HostAccess::ValidationPackage(fixt!(
+            ValidationPackageHostAccess
+        ))));
+    }
+}
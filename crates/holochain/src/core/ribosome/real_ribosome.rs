@@ -4,7 +4,9 @@ use super::guest_callback::migrate_agent::MigrateAgentHostAccess;
 use super::guest_callback::post_commit::PostCommitHostAccess;
 use super::guest_callback::validate::ValidateHostAccess;
 use super::guest_callback::validation_package::ValidationPackageHostAccess;
+use super::host_fn::forbidden_host_fn;
 use super::host_fn::get_agent_activity::get_agent_activity;
+use super::host_fn::host_fn_permission;
 use super::host_fn::HostFnApi;
 use super::HostAccess;
 use super::ZomeCallHostAccess;
@@ -127,7 +129,7 @@ impl RealRibosome {
     }
 
     fn imports(&self, call_context: CallContext) -> ImportObject {
-        let host_fn_access = (&call_context.host_access()).into();
+        let host_fn_access: HostFnAccess = (&call_context.host_access()).into();
 
         // it is important that RealRibosome and ZomeCallInvocation are cheap to clone here
         let self_arc = std::sync::Arc::new((*self).clone());
@@ -167,196 +169,83 @@ impl RealRibosome {
             func!(holochain_wasmer_host::import::__import_data),
         );
 
-        // imported host functions for core
-        ns.insert("__trace", func!(invoke_host_function!(trace)));
-        ns.insert("__hash_entry", func!(invoke_host_function!(hash_entry)));
-        ns.insert("__version", func!(invoke_host_function!(version)));
-        ns.insert("__unreachable", func!(invoke_host_function!(unreachable)));
-
-        if let HostFnAccess {
-            keystore: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert(
-                "__verify_signature",
-                func!(invoke_host_function!(verify_signature)),
-            );
-            ns.insert("__sign", func!(invoke_host_function!(sign)));
-            ns.insert(
-                "__create_x25519_keypair",
-                func!(invoke_host_function!(create_x25519_keypair)),
-            );
-            ns.insert(
-                "__x_salsa20_poly1305_encrypt",
-                func!(invoke_host_function!(x_salsa20_poly1305_encrypt)),
-            );
-            ns.insert(
-                "__x_salsa20_poly1305_decrypt",
-                func!(invoke_host_function!(x_salsa20_poly1305_decrypt)),
-            );
-            ns.insert(
-                "__x_25519_x_salsa20_poly1305_encrypt",
-                func!(invoke_host_function!(x_25519_x_salsa20_poly1305_encrypt)),
-            );
-            ns.insert(
-                "__x_25519_x_salsa20_poly1305_decrypt",
-                func!(invoke_host_function!(x_25519_x_salsa20_poly1305_decrypt)),
-            );
-        } else {
-            ns.insert(
-                "__verify_signature",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert("__sign", func!(invoke_host_function!(unreachable)));
-            ns.insert(
-                "__create_x25519_keypair",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__x_salsa20_poly1305_encrypt",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__x_salsa20_poly1305_decrypt",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__x_25519_x_salsa20_poly1305_encrypt",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__x_25519_x_salsa20_poly1305_decrypt",
-                func!(invoke_host_function!(unreachable)),
-            );
-        }
-
-        if let HostFnAccess {
-            dna_bindings: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__zome_info", func!(invoke_host_function!(zome_info)));
-        } else {
-            ns.insert("__zome_info", func!(invoke_host_function!(unreachable)));
-        }
-
-        if let HostFnAccess {
-            non_determinism: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__random_bytes", func!(invoke_host_function!(random_bytes)));
-            ns.insert("__sys_time", func!(invoke_host_function!(sys_time)));
-        } else {
-            ns.insert("__random_bytes", func!(invoke_host_function!(unreachable)));
-            ns.insert("__sys_time", func!(invoke_host_function!(unreachable)));
-        }
-
-        if let HostFnAccess {
-            agent_info: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__agent_info", func!(invoke_host_function!(agent_info)));
-            ns.insert(
-                "__capability_claims",
-                func!(invoke_host_function!(capability_claims)),
-            );
-            ns.insert(
-                "__capability_grants",
-                func!(invoke_host_function!(capability_grants)),
-            );
-            ns.insert(
-                "__capability_info",
-                func!(invoke_host_function!(capability_info)),
-            );
-        } else {
-            ns.insert("__agent_info", func!(invoke_host_function!(unreachable)));
-            ns.insert(
-                "__capability_claims",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__capability_grants",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__capability_info",
-                func!(invoke_host_function!(unreachable)),
-            );
-        }
-
-        if let HostFnAccess {
-            read_workspace: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__get", func!(invoke_host_function!(get)));
-            ns.insert("__get_details", func!(invoke_host_function!(get_details)));
-            ns.insert("__get_links", func!(invoke_host_function!(get_links)));
-            ns.insert(
-                "__get_link_details",
-                func!(invoke_host_function!(get_link_details)),
-            );
-            ns.insert(
-                "__get_agent_activity",
-                func!(invoke_host_function!(get_agent_activity)),
-            );
-            ns.insert("__query", func!(invoke_host_function!(query)));
-        } else {
-            ns.insert("__get", func!(invoke_host_function!(unreachable)));
-            ns.insert("__get_details", func!(invoke_host_function!(unreachable)));
-            ns.insert("__get_links", func!(invoke_host_function!(unreachable)));
-            ns.insert(
-                "__get_link_details",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__get_agent_activity",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert("__query", func!(invoke_host_function!(unreachable)));
+        // Host functions the call's access doesn't allow are still imported,
+        // so the wasm can be instantiated, but only return an error
+        macro_rules! forbidden_host_function {
+            ( $host_function:ident ) => {{
+                let closure_call_context_arc = std::sync::Arc::clone(&call_context_arc);
+                move |ctx: &mut Ctx, _guest_allocation_ptr: GuestPtr| -> Result<Len, WasmError> {
+                    let result = Err::<(), WasmError>(forbidden_host_fn(
+                        stringify!($host_function),
+                        &closure_call_context_arc.host_access,
+                    ));
+                    $crate::holochain_wasmer_host::import::set_context_data(ctx, result)
+                }
+            }};
         }
-
-        if let HostFnAccess {
-            write_network: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__call_remote", func!(invoke_host_function!(call_remote)));
-            ns.insert(
-                "__remote_signal",
-                func!(invoke_host_function!(remote_signal)),
-            );
-        } else {
-            ns.insert("__call_remote", func!(invoke_host_function!(unreachable)));
-            ns.insert("__remote_signal", func!(invoke_host_function!(unreachable)));
+        macro_rules! insert_host_function {
+            ( $( $host_function:ident ),* $(,)? ) => {
+                $(
+                    let import_name = concat!("__", stringify!($host_function));
+                    match host_fn_permission(stringify!($host_function), &host_fn_access) {
+                        Permission::Allow => ns.insert(
+                            import_name,
+                            func!(invoke_host_function!($host_function)),
+                        ),
+                        Permission::Deny => ns.insert(
+                            import_name,
+                            func!(forbidden_host_function!($host_function)),
+                        ),
+                    };
+                )*
+            };
         }
 
-        if let HostFnAccess {
-            write_workspace: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__call", func!(invoke_host_function!(call)));
-            ns.insert("__create", func!(invoke_host_function!(create)));
-            ns.insert("__emit_signal", func!(invoke_host_function!(emit_signal)));
-            ns.insert("__create_link", func!(invoke_host_function!(create_link)));
-            ns.insert("__delete_link", func!(invoke_host_function!(delete_link)));
-            ns.insert("__update", func!(invoke_host_function!(update)));
-            ns.insert("__delete", func!(invoke_host_function!(delete)));
-            ns.insert("__schedule", func!(invoke_host_function!(schedule)));
-        } else {
-            ns.insert("__call", func!(invoke_host_function!(unreachable)));
-            ns.insert("__create", func!(invoke_host_function!(unreachable)));
-            ns.insert("__emit_signal", func!(invoke_host_function!(unreachable)));
-            ns.insert("__create_link", func!(invoke_host_function!(unreachable)));
-            ns.insert("__delete_link", func!(invoke_host_function!(unreachable)));
-            ns.insert("__update", func!(invoke_host_function!(unreachable)));
-            ns.insert("__delete", func!(invoke_host_function!(unreachable)));
-            ns.insert("__schedule", func!(invoke_host_function!(unreachable)));
-        }
+        // See `host_fn_permission` for the permission each of these needs
+        insert_host_function!(
+            // core
+            trace,
+            hash_entry,
+            version,
+            unreachable,
+            // keystore
+            verify_signature,
+            sign,
+            create_x25519_keypair,
+            x_salsa20_poly1305_encrypt,
+            x_salsa20_poly1305_decrypt,
+            x_25519_x_salsa20_poly1305_encrypt,
+            x_25519_x_salsa20_poly1305_decrypt,
+            // dna bindings
+            zome_info,
+            // non determinism
+            random_bytes,
+            sys_time,
+            // agent info
+            agent_info,
+            capability_claims,
+            capability_grants,
+            capability_info,
+            // read workspace
+            get,
+            get_details,
+            get_links,
+            get_link_details,
+            get_agent_activity,
+            query,
+            // write network
+            call_remote,
+            remote_signal,
+            // write workspace
+            call,
+            create,
+            emit_signal,
+            create_link,
+            delete_link,
+            update,
+            delete,
+            schedule,
+        );
         imports.register("env", ns);
 
         imports
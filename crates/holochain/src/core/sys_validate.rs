@@ -15,6 +15,7 @@ use holochain_p2p::HolochainP2pCell;
 use holochain_state::metadata::ChainItemKey;
 use holochain_state::metadata::MetadataBufT;
 use holochain_types::prelude::*;
+use std::collections::HashMap;
 use std::convert::TryInto;
 
 pub(super) use error::*;
@@ -50,11 +51,43 @@ pub async fn verify_header_signature(
     }
 }
 
+/// The states of author keys that have already been looked up with dpki,
+/// so a key is only looked up once for each time it signed something.
+#[derive(Default)]
+pub struct KeyStateCache(HashMap<(AgentPubKey, Timestamp), Option<KeyState>>);
+
 /// Verify the author key was valid at the time
-/// of signing with dpki
-/// TODO: This is just a stub until we have dpki.
-pub async fn author_key_is_valid(_author: &AgentPubKey) -> SysValidationResult<bool> {
-    Ok(true)
+/// of signing with dpki.
+/// Keys are always valid if the conductor is not using dpki.
+/// If dpki can't be reached the key state is unavailable
+/// and validation can be tried again later.
+pub async fn author_key_is_valid(
+    author: &AgentPubKey,
+    at: Timestamp,
+    conductor_api: &impl CellConductorApiT,
+    key_states: &mut KeyStateCache,
+) -> SysValidationResult<()> {
+    let key = (author.clone(), at);
+    let key_state = match key_states.0.get(&key) {
+        Some(key_state) => *key_state,
+        None => {
+            let key_state = conductor_api
+                .dpki_key_state(author.clone(), at)
+                .await
+                .map_err(|e| {
+                    tracing::warn!(msg = "Failed to look up author key with dpki", ?author, ?e);
+                    ValidationOutcome::KeyStateUnavailable(author.clone())
+                })?;
+            key_states.0.insert(key, key_state);
+            key_state
+        }
+    };
+    match key_state {
+        Some(KeyState::Revoked(revoked_at)) => {
+            Err(ValidationOutcome::RevokedAuthor(author.clone(), revoked_at).into())
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Check that previous header makes sense
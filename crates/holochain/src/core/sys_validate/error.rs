@@ -104,6 +104,8 @@ pub enum ValidationOutcome {
     TagTooLarge(usize, usize),
     #[error("The header {0:?} was expected to be a link add header")]
     NotCreateLink(HeaderHash),
+    #[error("The state of author key {0} could not be looked up with DPKI")]
+    KeyStateUnavailable(AgentPubKey),
     #[error("The header was expected to be a new entry header but was a {0:?}")]
     NotNewEntry(Header),
     #[error("The dependency {0:?} is not held")]
@@ -112,6 +114,8 @@ pub enum ValidationOutcome {
     PrevHeaderError(#[from] PrevHeaderError),
     #[error("StoreEntry should not be gossiped for private entries")]
     PrivateEntry,
+    #[error("The author {0} signed this header after their key was revoked at {1}")]
+    RevokedAuthor(AgentPubKey, Timestamp),
     #[error("Update original EntryType: {0:?} doesn't match new EntryType {1:?}")]
     UpdateTypeMismatch(EntryType, EntryType),
     #[error("Signature {0:?} failed to verify for Header {1:?}")]
@@ -36,6 +36,104 @@ async fn verify_header_signature_test() {
     );
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn author_key_is_valid_test() {
+    let author = fake_agent_pubkey_1();
+    let revoked_at = timestamp::now();
+    let before = Timestamp(revoked_at.0 - 60, revoked_at.1);
+
+    let mut conductor_api = MockCellConductorApi::new();
+    conductor_api
+        .expect_sync_dpki_key_state()
+        .returning(move |_, at| {
+            if at < revoked_at {
+                Ok(Some(KeyState::Valid(at)))
+            } else {
+                Ok(Some(KeyState::Revoked(revoked_at)))
+            }
+        });
+
+    assert_matches!(
+        author_key_is_valid(
+            &author,
+            before,
+            &conductor_api,
+            &mut KeyStateCache::default()
+        )
+        .await,
+        Ok(())
+    );
+    assert_matches!(
+        author_key_is_valid(
+            &author,
+            revoked_at,
+            &conductor_api,
+            &mut KeyStateCache::default()
+        )
+        .await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::RevokedAuthor(_, _)
+        ))
+    );
+
+    // No dpki means every key is valid
+    conductor_api.checkpoint();
+    conductor_api
+        .expect_sync_dpki_key_state()
+        .returning(|_, _| Ok(None));
+    assert_matches!(
+        author_key_is_valid(
+            &author,
+            revoked_at,
+            &conductor_api,
+            &mut KeyStateCache::default()
+        )
+        .await,
+        Ok(())
+    );
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn author_key_states_are_cached_and_failures_can_be_retried() {
+    let author = fake_agent_pubkey_1();
+    let at = timestamp::now();
+
+    let mut conductor_api = MockCellConductorApi::new();
+    conductor_api
+        .expect_sync_dpki_key_state()
+        .times(1)
+        .returning(|_, at| Ok(Some(KeyState::Valid(at))));
+    let mut key_states = KeyStateCache::default();
+    for _ in 0..3 {
+        assert_matches!(
+            author_key_is_valid(&author, at, &conductor_api, &mut key_states).await,
+            Ok(())
+        );
+    }
+
+    // Dpki failing doesn't fail the workflow
+    conductor_api.checkpoint();
+    conductor_api
+        .expect_sync_dpki_key_state()
+        .returning(|_, _| Err(ConductorApiError::DpkiUnavailable));
+    assert_matches!(
+        author_key_is_valid(&author, at, &conductor_api, &mut key_states).await,
+        Ok(())
+    );
+    assert_matches!(
+        author_key_is_valid(
+            &author,
+            at,
+            &conductor_api,
+            &mut KeyStateCache::default()
+        )
+        .await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::KeyStateUnavailable(_)
+        ))
+    );
+}
+
 #[tokio::test(threaded_scheduler)]
 async fn check_previous_header() {
     let mut header = fixt!(CreateLink);
@@ -2,8 +2,17 @@
 use std::convert::TryFrom;
 
 use derivative::Derivative;
+use holo_hash::hash_type::AnyDht;
+use holo_hash::AgentPubKey;
+use holo_hash::AnyDhtHash;
 use holo_hash::DhtOpHash;
+use holochain_lmdb::error::DatabaseResult;
+use holochain_lmdb::prelude::PrefixType;
+use holochain_state::element_buf::ElementBuf;
+use holochain_state::validation_db::ValidationLimboValue;
 use holochain_types::dht_op::DhtOp;
+use holochain_zome_types::timestamp::Timestamp;
+use std::collections::HashMap;
 
 use super::workflow::error::WorkflowResult;
 use super::SourceChainError;
@@ -83,3 +92,97 @@ impl OutcomeOrError<ValidationOutcome, SysValidationError> {
         Err(SourceChainError::InvalidCommit(ValidationOutcome::try_from(self)?.to_string()).into())
     }
 }
+
+/// Split ops into one list per author, keeping the order they were in.
+pub fn ops_by_author<V>(ops: Vec<OrderedOp<V>>) -> Vec<Vec<OrderedOp<V>>> {
+    let mut chains: Vec<Vec<OrderedOp<V>>> = Vec::new();
+    let mut by_author: HashMap<AgentPubKey, usize> = HashMap::new();
+    for op in ops {
+        let author = op.op.header().author().clone();
+        let i = *by_author.entry(author).or_insert_with(|| {
+            chains.push(Vec::new());
+            chains.len() - 1
+        });
+        chains[i].push(op);
+    }
+    chains
+}
+
+/// Should an op in the validation limbo be tried on this run.
+///
+/// Ops that are awaiting dependencies are only tried again once one of the
+/// hashes they are waiting for is held by one of the `stores`
+/// or their backoff has elapsed.
+/// The backoff catches dependencies that can only be found on the network.
+pub fn ready_to_validate(
+    vlv: &ValidationLimboValue,
+    now: Timestamp,
+    stores: &[&dyn HoldsDep],
+) -> DatabaseResult<bool> {
+    if vlv.is_retry_due(now) {
+        return Ok(true);
+    }
+    for dep in vlv.status.awaiting_deps() {
+        for store in stores {
+            if store.holds_dep(dep)? {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// A store that an op's dependencies can turn up in
+pub trait HoldsDep {
+    /// Is the header or entry with this hash in the store
+    fn holds_dep(&self, hash: &AnyDhtHash) -> DatabaseResult<bool>;
+}
+
+impl<P: PrefixType> HoldsDep for ElementBuf<P> {
+    fn holds_dep(&self, hash: &AnyDhtHash) -> DatabaseResult<bool> {
+        match *hash.hash_type() {
+            AnyDht::Entry => self.contains_entry(&hash.clone().into()),
+            AnyDht::Header => self.contains_header(&hash.clone().into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::*;
+    use holochain_types::fixt::*;
+    use holochain_zome_types::header::Header;
+
+    fn op_by(author: &AgentPubKey, seq: u32) -> OrderedOp<u32> {
+        let mut create = fixt!(Create);
+        create.author = author.clone();
+        create.header_seq = seq;
+        let op = DhtOp::RegisterAgentActivity(fixt!(Signature), Header::Create(create));
+        OrderedOp {
+            order: DhtOpOrder::from(&op),
+            hash: fixt!(DhtOpHash),
+            op,
+            value: seq,
+        }
+    }
+
+    #[test]
+    fn ops_by_author_keeps_order() {
+        let alice = fixt!(AgentPubKey);
+        let bob = fixt!(AgentPubKey);
+        let ops = vec![
+            op_by(&alice, 1),
+            op_by(&bob, 1),
+            op_by(&alice, 2),
+            op_by(&bob, 2),
+            op_by(&alice, 3),
+        ];
+        let chains: Vec<Vec<u32>> = ops_by_author(ops)
+            .into_iter()
+            .map(|chain| chain.into_iter().map(|op| op.value).collect())
+            .collect();
+        assert_eq!(chains, vec![vec![1, 2, 3], vec![1, 2]]);
+    }
+}
@@ -26,6 +26,7 @@ pub mod error;
 pub mod app_validation_workflow;
 pub mod call_zome_workflow;
 pub mod genesis_workflow;
+pub mod import_source_chain_workflow;
 pub mod incoming_dht_ops_workflow;
 pub mod initialize_zomes_workflow;
 pub mod integrate_dht_ops_workflow;
@@ -36,6 +37,7 @@ pub mod sys_validation_workflow;
 // TODO: either remove wildcards or add wildcards for all above child modules
 pub use call_zome_workflow::*;
 pub use genesis_workflow::*;
+pub use import_source_chain_workflow::*;
 pub use initialize_zomes_workflow::*;
 
 #[cfg(test)]
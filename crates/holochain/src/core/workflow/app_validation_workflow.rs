@@ -15,6 +15,7 @@ use super::CallZomeWorkspace;
 use super::CallZomeWorkspaceLock;
 use crate::conductor::api::CellConductorApiT;
 use crate::conductor::entry_def_store::get_entry_def;
+use crate::core::metrics::WorkflowRun;
 use crate::core::queue_consumer::OneshotWriter;
 use crate::core::queue_consumer::TriggerSender;
 use crate::core::queue_consumer::WorkComplete;
@@ -26,18 +27,20 @@ use crate::core::ribosome::guest_callback::validate_link::ValidateDeleteLinkInvo
 use crate::core::ribosome::guest_callback::validate_link::ValidateLinkHostAccess;
 use crate::core::ribosome::guest_callback::validate_link::ValidateLinkInvocation;
 use crate::core::ribosome::guest_callback::validate_link::ValidateLinkResult;
-use crate::core::ribosome::guest_callback::validation_package::ValidationPackageHostAccess;
-use crate::core::ribosome::guest_callback::validation_package::ValidationPackageInvocation;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageResult;
 use crate::core::ribosome::real_ribosome::RealRibosome;
 use crate::core::ribosome::Invocation;
 use crate::core::ribosome::RibosomeT;
 use crate::core::ribosome::ZomesToInvoke;
+use crate::core::validation::ops_by_author;
+use crate::core::validation::ready_to_validate;
 use crate::core::validation::DhtOpOrder;
+use crate::core::validation::HoldsDep;
 use crate::core::validation::OrderedOp;
 use error::AppValidationResult;
 pub use error::*;
 use fallible_iterator::FallibleIterator;
+use futures::stream::StreamExt;
 use holo_hash::AgentPubKey;
 use holo_hash::DhtOpHash;
 use holochain_cascade::Cascade;
@@ -69,19 +72,38 @@ mod error;
 mod types;
 pub mod validation_package;
 
-#[instrument(skip(workspace, writer, trigger_integration, conductor_api, network))]
+#[instrument(skip(workspace, writer, trigger_integration, conductor_api, network, run))]
 pub async fn app_validation_workflow(
     mut workspace: AppValidationWorkspace,
     writer: OneshotWriter,
     trigger_integration: &mut TriggerSender,
-    conductor_api: impl CellConductorApiT,
+    conductor_api: impl CellConductorApiT + 'static,
     network: HolochainP2pCell,
+    validation_workers: usize,
+    run: &mut WorkflowRun,
 ) -> WorkflowResult<WorkComplete> {
-    let complete = app_validation_workflow_inner(&mut workspace, conductor_api, &network).await?;
+    let (complete, mut validated) = app_validation_workflow_inner(
+        &mut workspace,
+        conductor_api,
+        &network,
+        validation_workers,
+        run,
+    )
+    .await?;
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
-    // commit the workspace
-    writer.with_writer(|writer| Ok(workspace.flush_to_txn(writer)?))?;
+    // commit the workspace and every worker's results together.
+    // The drained ops are removed from the limbo first
+    // so the workers can put them back.
+    writer
+        .with_writer(|writer| {
+            workspace.flush_to_txn_ref(writer)?;
+            for worker_workspace in validated.iter_mut() {
+                worker_workspace.flush_to_txn_ref(writer)?;
+            }
+            Ok(())
+        })
+        .await?;
 
     // trigger other workflows
     trigger_integration.trigger();
@@ -90,25 +112,39 @@ pub async fn app_validation_workflow(
 }
 async fn app_validation_workflow_inner(
     workspace: &mut AppValidationWorkspace,
-    conductor_api: impl CellConductorApiT,
+    conductor_api: impl CellConductorApiT + 'static,
     network: &HolochainP2pCell,
-) -> WorkflowResult<WorkComplete> {
+    validation_workers: usize,
+    run: &mut WorkflowRun,
+) -> WorkflowResult<(WorkComplete, Vec<AppValidationWorkspace>)> {
     let env = workspace.validation_limbo.env().clone();
 
     // Drain the ops into a sorted binary heap
     let sorted_ops: BinaryHeap<OrderedOp<ValidationLimboValue>> = fresh_reader!(env, |r| {
         let validation_limbo = &mut workspace.validation_limbo;
         let element_pending = &workspace.element_pending;
+        // Stores a dependency turns up in once it is integrated or fetched
+        let dep_stores: [&dyn HoldsDep; 3] = [
+            &workspace.element_vault,
+            &workspace.element_rejected,
+            &workspace.element_cache,
+        ];
+        let now = timestamp::now();
 
         let sorted_ops: Result<BinaryHeap<OrderedOp<ValidationLimboValue>>, WorkflowError> =
             validation_limbo
-                .drain_iter_filter(&r, |(_, vlv)| {
+                .drain_iter_filter(&r, move |(_, vlv)| {
                     match vlv.status {
                         // We only want sys validated or awaiting app dependency ops
-                        ValidationLimboStatus::SysValidated
-                        | ValidationLimboStatus::AwaitingAppDeps(_) => Ok(true),
+                        ValidationLimboStatus::SysValidated => Ok(true),
+                        // that have a reason to be tried again
+                        ValidationLimboStatus::AwaitingAppDeps(_)
+                        | ValidationLimboStatus::AwaitingValidationPackage => {
+                            ready_to_validate(vlv, now, &dep_stores)
+                        }
                         ValidationLimboStatus::Pending
-                        | ValidationLimboStatus::AwaitingSysDeps(_) => Ok(false),
+                        | ValidationLimboStatus::AwaitingSysDeps(_)
+                        | ValidationLimboStatus::AwaitingKeyState => Ok(false),
                     }
                 })?
                 .map_err(WorkflowError::from)
@@ -132,8 +168,37 @@ async fn app_validation_workflow_inner(
         sorted_ops
     })?;
 
-    // Validate all the ops
-    for so in sorted_ops.into_sorted_vec() {
+    // An op can depend on the ops before it in its author's chain
+    // so each author's ops are validated in order by a single worker.
+    // Different authors are validated concurrently, each in a workspace
+    // of their own.
+    run.processed(sorted_ops.len());
+    let mut workers = futures::stream::iter(ops_by_author(sorted_ops.into_sorted_vec()))
+        .map(|ops| {
+            tokio::spawn(validate_chain(
+                ops,
+                env.clone(),
+                conductor_api.clone(),
+                network.clone(),
+            ))
+        })
+        .buffer_unordered(validation_workers.max(1));
+    let mut validated = Vec::new();
+    while let Some(worker_workspace) = workers.next().await {
+        validated.push(worker_workspace??);
+    }
+    Ok((WorkComplete::Complete, validated))
+}
+
+/// Validate one author's ops in order
+async fn validate_chain(
+    ops: Vec<OrderedOp<ValidationLimboValue>>,
+    env: EnvironmentRead,
+    conductor_api: impl CellConductorApiT,
+    network: HolochainP2pCell,
+) -> WorkflowResult<AppValidationWorkspace> {
+    let mut workspace = AppValidationWorkspace::new(env)?;
+    for so in ops {
         let OrderedOp {
             hash,
             op,
@@ -142,13 +207,15 @@ async fn app_validation_workflow_inner(
         } = so;
 
         match &vlv.status {
-            ValidationLimboStatus::AwaitingAppDeps(_) | ValidationLimboStatus::SysValidated => {
+            ValidationLimboStatus::AwaitingAppDeps(_)
+            | ValidationLimboStatus::AwaitingValidationPackage
+            | ValidationLimboStatus::SysValidated => {
                 // Validate this op
                 let outcome = validate_op(
                     op.clone(),
                     vlv.from_agent.clone(),
                     &conductor_api,
-                    workspace,
+                    &mut workspace,
                     &network,
                 )
                 .await
@@ -165,6 +232,10 @@ async fn app_validation_workflow_inner(
                     }
                     Outcome::AwaitingDeps(deps) => {
                         vlv.status = ValidationLimboStatus::AwaitingAppDeps(deps);
+                        workspace.put_val_limbo_or_abandon(hash, vlv, op)?;
+                    }
+                    Outcome::AwaitingValidationPackage => {
+                        vlv.status = ValidationLimboStatus::AwaitingValidationPackage;
                         workspace.put_val_limbo(hash, vlv)?;
                     }
                     Outcome::Rejected(_) => {
@@ -179,7 +250,7 @@ async fn app_validation_workflow_inner(
             _ => unreachable!("Should not contain any other status"),
         }
     }
-    Ok(WorkComplete::Complete)
+    Ok(workspace)
 }
 
 fn to_single_zome(zomes_to_invoke: ZomesToInvoke) -> AppValidationResult<Zome> {
@@ -299,7 +370,9 @@ async fn validate_op(
             )?
         }
     };
-    if let Outcome::AwaitingDeps(_) | Outcome::Rejected(_) = &outcome {
+    if let Outcome::AwaitingDeps(_) | Outcome::AwaitingValidationPackage | Outcome::Rejected(_) =
+        &outcome
+    {
         warn!(
             agent = %which_agent(conductor_api.cell_id().agent_pubkey()),
             msg = "DhtOp has failed app validation",
@@ -529,26 +602,12 @@ async fn get_validation_package(
     match entry_def {
         Some(entry_def) => match workspace {
             Some(workspace) => {
-                get_validation_package_remote(
-                    element,
-                    entry_def,
-                    from_agent,
-                    workspace,
-                    ribosome,
-                    workspace_lock,
-                    network,
-                )
-                .await
+                get_validation_package_remote(element, entry_def, from_agent, workspace, network)
+                    .await
             }
             None => {
-                get_validation_package_local(
-                    element,
-                    entry_def.required_validation_type,
-                    ribosome,
-                    workspace_lock,
-                    network,
-                )
-                .await
+                get_validation_package_local(element, entry_def, ribosome, workspace_lock, network)
+                    .await
             }
         },
         None => {
@@ -560,13 +619,13 @@ async fn get_validation_package(
 
 async fn get_validation_package_local(
     element: &Element,
-    required_validation_type: RequiredValidationType,
+    entry_def: &EntryDef,
     ribosome: &impl RibosomeT,
     workspace_lock: &CallZomeWorkspaceLock,
     network: &HolochainP2pCell,
 ) -> AppValidationOutcome<Option<ValidationPackage>> {
     let header_seq = element.header().header_seq();
-    match required_validation_type {
+    match entry_def.required_validation_type {
         RequiredValidationType::Element => Ok(None),
         RequiredValidationType::SubChain => {
             let app_entry_type = match element.header().entry_type().cloned() {
@@ -596,6 +655,7 @@ async fn get_validation_package_local(
             }
             let result = match get_as_author_custom(
                 element.header_hashed(),
+                entry_def.id.clone(),
                 ribosome,
                 network,
                 workspace_lock.clone(),
@@ -625,8 +685,6 @@ async fn get_validation_package_remote(
     entry_def: &EntryDef,
     from_agent: Option<AgentPubKey>,
     workspace: &mut AppValidationWorkspace,
-    ribosome: &impl RibosomeT,
-    workspace_lock: &CallZomeWorkspaceLock,
     network: &HolochainP2pCell,
 ) -> AppValidationOutcome<Option<ValidationPackage>> {
     match entry_def.required_validation_type {
@@ -707,67 +765,31 @@ async fn get_validation_package_remote(
             }
         }
         RequiredValidationType::Custom => {
-            let validation_package = {
-                let mut cascade = workspace.full_cascade(network.clone());
-                let agent_id = element.header().author().clone();
-                let header_hashed = element.header_hashed();
-                // Call the author
-                let validation_package = cascade
-                    .get_validation_package(agent_id, header_hashed)
-                    .await?;
-
-                // Fallback to gossiper
-                match &validation_package {
-                    Some(_) => validation_package,
-                    None => {
-                        if let Some(from_agent) = from_agent {
-                            cascade
-                                .get_validation_package(from_agent, header_hashed)
-                                .await?
-                        } else {
-                            None
-                        }
-                    }
-                }
-            };
+            let mut cascade = workspace.full_cascade(network.clone());
+            let agent_id = element.header().author().clone();
+            let header_hashed = element.header_hashed();
+            // Call the author
+            if let Some(validation_package) = cascade
+                .get_validation_package(agent_id, header_hashed)
+                .await?
+            {
+                return Ok(Some(validation_package));
+            }
 
-            // Fallback to callback
-            match &validation_package {
-                Some(_) => Ok(validation_package),
-                None => {
-                    let access =
-                        ValidationPackageHostAccess::new(workspace_lock.clone(), network.clone());
-                    let app_entry_type = match element.header().entry_type() {
-                        Some(EntryType::App(a)) => a.clone(),
-                        _ => return Ok(None),
-                    };
-                    let zome = ribosome
-                        .dna_def()
-                        .zomes
-                        .get(app_entry_type.zome_id().index())
-                        .ok_or_else(|| AppValidationError::ZomeId(app_entry_type.zome_id()))?
-                        .clone()
-                        .into();
-                    let invocation = ValidationPackageInvocation::new(zome, app_entry_type);
-                    match ribosome.run_validation_package(access, invocation)? {
-                        ValidationPackageResult::Success(validation_package) => {
-                            Ok(Some(validation_package))
-                        }
-                        ValidationPackageResult::Fail(reason) => {
-                            Outcome::exit_with_rejected(reason)
-                        }
-                        ValidationPackageResult::UnresolvedDependencies(deps) => {
-                            Outcome::exit_with_awaiting(deps)
-                        }
-                        ValidationPackageResult::NotImplemented => {
-                            Outcome::exit_with_rejected(format!(
-                                "Entry definition specifies a custom validation package but the callback isn't defined for {:?}",
-                                element
-                            ))
-                        }
-                    }
+            // Fallback to gossiper
+            if let Some(from_agent) = from_agent {
+                if let Some(validation_package) = cascade
+                    .get_validation_package(from_agent, header_hashed)
+                    .await?
+                {
+                    return Ok(Some(validation_package));
                 }
             }
+
+            // Only the author can build a custom package, so there is no
+            // running the callback here. Try again after a backoff, for as
+            // long as it takes the author or a gossiper to be reachable.
+            Outcome::exit_with_awaiting_package()
         }
     }
 }
@@ -982,6 +1004,29 @@ impl AppValidationWorkspace {
         Ok(())
     }
 
+    /// Put an op that is awaiting dependencies back in the validation limbo
+    /// or abandon it if it has been tried too many times
+    fn put_val_limbo_or_abandon(
+        &mut self,
+        hash: DhtOpHash,
+        vlv: ValidationLimboValue,
+        op: DhtOp,
+    ) -> WorkflowResult<()> {
+        if vlv.should_abandon() {
+            warn!(
+                msg = "Abandoning op that is still missing dependencies",
+                ?vlv
+            );
+            let iv = IntegrationLimboValue {
+                op: vlv.op,
+                validation_status: ValidationStatus::Abandoned,
+            };
+            self.put_int_limbo(hash, iv, op)
+        } else {
+            self.put_val_limbo(hash, vlv)
+        }
+    }
+
     #[tracing::instrument(skip(self, hash))]
     fn put_int_limbo(
         &mut self,
@@ -1047,7 +1092,10 @@ impl Workspace for AppValidationWorkspace {
             .and_then(|o| Arc::try_unwrap(o.into_inner()).ok())
         {
             let mut czws: CallZomeWorkspace = czws.into_inner();
-            czws.flush_to_txn_ref(writer)?;
+            let result = czws.flush_to_txn_ref(writer);
+            // Put it back so the flush can be run again if the map fills up
+            self.call_zome_workspace_lock = Some(CallZomeWorkspaceLock::new(czws));
+            result?;
         }
         Ok(())
     }
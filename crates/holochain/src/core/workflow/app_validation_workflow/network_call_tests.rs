@@ -13,6 +13,7 @@ use holochain_wasm_test_utils::TestWasm;
 use matches::assert_matches;
 
 use crate::conductor::ConductorHandle;
+use crate::core::workflow::incoming_dht_ops_workflow::IncomingDhtOpsWorkspace;
 use crate::test_utils::conductor_setup::CellHostFnCaller;
 use crate::test_utils::conductor_setup::ConductorTestData;
 use crate::test_utils::host_fn_caller::Post;
@@ -569,6 +570,101 @@ async fn get_custom_package_test() {
     conductor_test.shutdown_conductor().await;
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn custom_package_reaches_validate_test() {
+    observability::test_run().ok();
+
+    let zomes = vec![TestWasm::ValidationPackageSuccess];
+    let mut conductor_test = ConductorTestData::two_agents(zomes, true).await;
+    let handle = conductor_test.handle();
+    let alice_call_data = conductor_test.alice_call_data();
+    let bob_call_data = conductor_test.bob_call_data().unwrap();
+    let alice_cell_id = &alice_call_data.cell_id;
+
+    let invocation = new_zome_call(
+        &alice_cell_id,
+        "commit_songs",
+        (),
+        TestWasm::ValidationPackageSuccess,
+    )
+    .unwrap();
+    let result = handle.call_zome(invocation).await.unwrap().unwrap();
+    assert_matches!(result, ZomeCallResponse::Ok(_));
+
+    let invocation = new_zome_call(
+        &alice_cell_id,
+        "commit_artist",
+        (),
+        TestWasm::ValidationPackageSuccess,
+    )
+    .unwrap();
+    let result = handle.call_zome(invocation).await.unwrap().unwrap();
+    let artist_hash: HeaderHash = unwrap_to::unwrap_to!(result => ZomeCallResponse::Ok)
+        .decode()
+        .unwrap();
+
+    // Same ops as `get_custom_package_test`
+    let expected_count = 16 + 30 * 3 + 3;
+    wait_for_integration(
+        &bob_call_data.env,
+        expected_count,
+        NUM_ATTEMPTS,
+        DELAY_PER_ATTEMPT.clone(),
+    )
+    .await;
+
+    // Bob's validate callback only accepts the artist if it
+    // got the package of songs Alice built for it
+    let workspace = IncomingDhtOpsWorkspace::new(bob_call_data.env.clone().into()).unwrap();
+    let artist_ops = fresh_reader_test!(bob_call_data.env, |r| {
+        workspace
+            .integrated_dht_ops
+            .iter(&r)
+            .unwrap()
+            .filter(|(_, i)| Ok(*i.op.header_hash() == artist_hash))
+            .map(|(_, i)| Ok(i.validation_status))
+            .collect::<Vec<_>>()
+            .unwrap()
+    });
+    assert_eq!(artist_ops.len(), 3);
+    for status in artist_ops {
+        assert_eq!(status, ValidationStatus::Valid);
+    }
+
+    // Bob cached the package, so he can hand it out without Alice
+    let artist_header = {
+        let env: EnvironmentRead = bob_call_data.env.clone().into();
+        let element_integrated = ElementBuf::vault(env, false).unwrap();
+        element_integrated
+            .get_header(&artist_hash)
+            .unwrap()
+            .unwrap()
+            .into_header_and_signature()
+            .0
+    };
+    let validation_package = bob_call_data
+        .network
+        .clone()
+        .get_validation_package(
+            bob_call_data.cell_id.agent_pubkey().clone(),
+            artist_hash.clone(),
+        )
+        .await
+        .unwrap()
+        .0
+        .unwrap();
+    assert_eq!(
+        validation_package
+            .0
+            .iter()
+            .filter(|el| el.header().author() == artist_header.author())
+            .count(),
+        30
+    );
+
+    conductor_test.shutdown_conductor().await;
+}
+
 #[tokio::test(threaded_scheduler)]
 async fn get_agent_activity_host_fn_test() {
     observability::test_run().ok();
@@ -15,6 +15,9 @@ pub enum Outcome {
     /// dependency needs is required to validate
     /// and could not be found
     AwaitingDeps(Vec<AnyDhtHash>),
+    /// Stays in limbo because the custom validation package
+    /// could not be fetched from the author or a gossiper
+    AwaitingValidationPackage,
     /// Moves to integration with status rejected
     Rejected(String),
 }
@@ -33,6 +36,10 @@ impl Outcome {
             h.into_iter().map(Into::into).collect(),
         )))
     }
+    /// Exit early while the validation package can't be fetched
+    pub fn exit_with_awaiting_package<T>() -> AppValidationOutcome<T> {
+        Err(OutcomeOrError::Outcome(Outcome::AwaitingValidationPackage))
+    }
     /// Early exits with an accepted outcome
     pub fn accepted<T>() -> AppValidationOutcome<T> {
         Err(OutcomeOrError::Outcome(Outcome::Accepted))
@@ -39,8 +39,11 @@ pub fn get_as_author_full(
     Ok(ValidationPackage::new(elements))
 }
 
+/// Run the zome's `validation_package` callback for the entry type
+/// of this header, as its author
 pub fn get_as_author_custom(
     header_hashed: &HeaderHashed,
+    entry_def_id: EntryDefId,
     ribosome: &impl RibosomeT,
     network: &HolochainP2pCell,
     workspace_lock: CallZomeWorkspaceLock,
@@ -67,7 +70,7 @@ pub fn get_as_author_custom(
         }
     };
 
-    let invocation = ValidationPackageInvocation::new(zome, app_entry_type);
+    let invocation = ValidationPackageInvocation::new(zome, app_entry_type, entry_def_id);
 
     Ok(Some(ribosome.run_validation_package(access, invocation)?))
 }
@@ -1,6 +1,7 @@
 use super::app_validation_workflow;
 use super::error::WorkflowResult;
 use super::sys_validation_workflow::sys_validate_element;
+use super::sys_validation_workflow::SysValidationWorkspace;
 use crate::conductor::api::CellConductorApiT;
 use crate::conductor::interface::SignalBroadcaster;
 use crate::core::queue_consumer::OneshotWriter;
@@ -28,6 +29,9 @@ use holochain_state::workspace::WorkspaceResult;
 use holochain_zome_types::element::Element;
 
 use holochain_types::prelude::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::sync::Arc;
 use tracing::instrument;
 
@@ -77,7 +81,9 @@ pub async fn call_zome_workflow<
     if should_write {
         let mut guard = workspace_lock.write().await;
         let workspace = &mut guard;
-        writer.with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))?;
+        writer
+            .with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))
+            .await?;
     }
 
     trigger_produce_dht_ops.trigger();
@@ -130,35 +136,46 @@ async fn call_zome_workflow_inner<
     .await?;
     tracing::trace!(line = line!());
 
-    let to_app_validate = {
+    // The elements committed by the call, in chain order
+    let new_elements = {
         let mut workspace = workspace_lock.write().await;
-        // Get the new head
-        let chain_head_end_len = workspace.source_chain.len();
-        let new_elements_len = chain_head_end_len - chain_head_start_len;
-
-        // collect all the elements we need to validate in wasm
-        let mut to_app_validate: Vec<Element> = Vec::with_capacity(new_elements_len);
-
-        // Has there been changes?
-        if new_elements_len > 0 {
-            // Loop forwards through all the new elements
-            let mut i = chain_head_start_len;
-            while let Some(element) = workspace.source_chain.get_at_index(i as u32)? {
-                sys_validate_element(&element, &mut workspace, network.clone(), &conductor_api)
-                    .await
-                    // If the was en error exit
-                    // If the validation failed, exit with an InvalidCommit
-                    // If it was ok continue
-                    .or_else(|outcome_or_err| outcome_or_err.invalid_call_zome_commit())?;
-                to_app_validate.push(element);
-                i += 1;
-            }
+        let mut new_elements = Vec::new();
+        let mut i = chain_head_start_len;
+        while let Some(element) = workspace.source_chain.get_at_index(i as u32)? {
+            new_elements.push(element);
+            i += 1;
         }
-        to_app_validate
+        new_elements
     };
 
+    // The links' bases and targets are all gathered before validating,
+    // instead of two lookups per link.
+    let link_entries = {
+        let mut workspace = workspace_lock.write().await;
+        retrieve_link_entries(&new_elements, &mut workspace, network.clone()).await?
+    };
+
+    // The new elements are validated in a single pass, each sys validated
+    // and then app validated. The sys validation workspace is made once,
+    // holding the whole uncommitted chain, instead of once per element.
+    let mut sys_workspace = SysValidationWorkspace::try_from(&*workspace_lock.read().await)?;
     {
-        for chain_element in to_app_validate {
+        for chain_element in new_elements {
+            {
+                let mut workspace = workspace_lock.write().await;
+                sys_validate_element(
+                    &chain_element,
+                    &mut sys_workspace,
+                    &mut workspace,
+                    network.clone(),
+                    &conductor_api,
+                )
+                .await
+                // If the was en error exit
+                // If the validation failed, exit with an InvalidCommit
+                // If it was ok continue
+                .or_else(|outcome_or_err| outcome_or_err.invalid_call_zome_commit())?;
+            }
             let outcome = match chain_element.header() {
                 Header::Dna(_)
                 | Header::AgentValidationPkg(_)
@@ -169,30 +186,9 @@ async fn call_zome_workflow_inner<
                     continue;
                 }
                 Header::CreateLink(link_add) => {
-                    let (base, target) = {
-                        let mut workspace = workspace_lock.write().await;
-                        let mut cascade = workspace.cascade(network.clone());
-                        let base_address = &link_add.base_address;
-                        let base = cascade
-                            .retrieve_entry(base_address.clone(), Default::default())
-                            .await
-                            .map_err(RibosomeError::from)?
-                            .ok_or_else(|| RibosomeError::ElementDeps(base_address.clone().into()))?
-                            .into_content();
-                        let base = Arc::new(base);
-
-                        let target_address = &link_add.target_address;
-                        let target = cascade
-                            .retrieve_entry(target_address.clone(), Default::default())
-                            .await
-                            .map_err(RibosomeError::from)?
-                            .ok_or_else(|| {
-                                RibosomeError::ElementDeps(target_address.clone().into())
-                            })?
-                            .into_content();
-                        let target = Arc::new(target);
-                        (base, target)
-                    };
+                    // Every link's base and target were retrieved above
+                    let base = link_entries[&link_add.base_address].clone();
+                    let target = link_entries[&link_add.target_address].clone();
                     let link_add = Arc::new(link_add.clone());
                     Either::Left(
                         app_validation_workflow::run_create_link_validation_callback(
@@ -236,6 +232,12 @@ async fn call_zome_workflow_inner<
                     app_validation_workflow::Outcome::AwaitingDeps(hashes) => {
                         return Err(SourceChainError::InvalidCommit(format!("{:?}", hashes)).into());
                     }
+                    app_validation_workflow::Outcome::AwaitingValidationPackage => {
+                        return Err(SourceChainError::InvalidCommit(
+                            "Validation package unavailable".into(),
+                        )
+                        .into());
+                    }
                 },
                 Either::Right(outcome) => match outcome {
                     app_validation_workflow::Outcome::Accepted => {}
@@ -252,6 +254,12 @@ async fn call_zome_workflow_inner<
                     app_validation_workflow::Outcome::AwaitingDeps(hashes) => {
                         return Err(SourceChainError::InvalidCommit(format!("{:?}", hashes)).into());
                     }
+                    app_validation_workflow::Outcome::AwaitingValidationPackage => {
+                        return Err(SourceChainError::InvalidCommit(
+                            "Validation package unavailable".into(),
+                        )
+                        .into());
+                    }
                 },
             }
         }
@@ -260,6 +268,52 @@ async fn call_zome_workflow_inner<
     Ok(result)
 }
 
+/// Get the base and target entries of every link created in a zome call.
+/// Entries committed in the same call are taken from the new elements,
+/// and the rest are retrieved from the cascade in a single parallel batch.
+async fn retrieve_link_entries(
+    new_elements: &[Element],
+    workspace: &mut CallZomeWorkspace,
+    network: HolochainP2pCell,
+) -> RibosomeResult<HashMap<EntryHash, Arc<Entry>>> {
+    let mut entries: HashMap<EntryHash, Arc<Entry>> = new_elements
+        .iter()
+        .filter_map(|element| {
+            let entry_hash = element.header().entry_hash()?.clone();
+            let entry = element.entry().as_option()?.clone();
+            Some((entry_hash, Arc::new(entry)))
+        })
+        .collect();
+
+    let to_retrieve: Vec<EntryHash> = new_elements
+        .iter()
+        .filter_map(|element| match element.header() {
+            Header::CreateLink(link_add) => Some(vec![
+                link_add.base_address.clone(),
+                link_add.target_address.clone(),
+            ]),
+            _ => None,
+        })
+        .flatten()
+        .filter(|hash| !entries.contains_key(hash))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    if to_retrieve.is_empty() {
+        return Ok(entries);
+    }
+
+    let retrieved = workspace
+        .cascade(network)
+        .retrieve_entries_parallel(to_retrieve.clone(), Default::default())
+        .await?;
+    for (hash, entry) in to_retrieve.into_iter().zip(retrieved) {
+        let entry = entry.ok_or_else(|| RibosomeError::ElementDeps(hash.clone().into()))?;
+        entries.insert(hash, Arc::new(entry.into_content()));
+    }
+    Ok(entries)
+}
+
 pub struct CallZomeWorkspace {
     pub source_chain: SourceChain,
     pub meta_authored: MetadataBuf<AuthoredPrefix>,
@@ -553,4 +607,100 @@ pub mod tests {
             .unwrap();
         // TODO: Check the workspace has changes
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn link_entries_committed_in_the_call_are_not_retrieved() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+        let new_element = |header: Header, entry: Option<Entry>| {
+            Element::new(
+                SignedHeaderHashed::with_presigned(
+                    HeaderHashed::from_content_sync(header),
+                    fixt!(Signature),
+                ),
+                entry,
+            )
+        };
+
+        let base = fixt!(Entry);
+        let target = fixt!(Entry);
+        let mut base_create = fixt!(Create);
+        base_create.entry_hash = EntryHash::with_data_sync(&base);
+        let mut target_create = fixt!(Create);
+        target_create.entry_hash = EntryHash::with_data_sync(&target);
+        let mut link_add = fixt!(CreateLink);
+        link_add.base_address = base_create.entry_hash.clone();
+        link_add.target_address = target_create.entry_hash.clone();
+        let new_elements = vec![
+            new_element(base_create.into(), Some(base.clone())),
+            new_element(target_create.into(), Some(target.clone())),
+            new_element(link_add.clone().into(), None),
+        ];
+
+        // Nothing is on the network, so this only works
+        // if the entries are taken from the new elements
+        let entries = retrieve_link_entries(&new_elements, &mut workspace, fixt!(HolochainP2pCell))
+            .await
+            .unwrap();
+        assert_eq!(*entries[&link_add.base_address], base);
+        assert_eq!(*entries[&link_add.target_address], target);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn link_entries_from_before_the_call_are_retrieved_in_one_batch() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+        let new_element = |header: Header, entry: Option<Entry>| {
+            Element::new(
+                SignedHeaderHashed::with_presigned(
+                    HeaderHashed::from_content_sync(header),
+                    fixt!(Signature),
+                ),
+                entry,
+            )
+        };
+
+        // The target was committed before the call and is in the cache
+        let target = fixt!(Entry);
+        let mut target_create = fixt!(Create);
+        target_create.entry_hash = EntryHash::with_data_sync(&target);
+        target_create.entry_type = EntryType::App(fixt!(AppEntryType, EntryVisibility::Public));
+        let target_element = new_element(target_create.clone().into(), Some(target.clone()));
+        workspace
+            .element_cache
+            .put(
+                target_element.signed_header().clone(),
+                Some(EntryHashed::from_content_sync(target.clone())),
+            )
+            .unwrap();
+
+        let base = fixt!(Entry);
+        let mut base_create = fixt!(Create);
+        base_create.entry_hash = EntryHash::with_data_sync(&base);
+        let mut link_add = fixt!(CreateLink);
+        link_add.base_address = base_create.entry_hash.clone();
+        link_add.target_address = target_create.entry_hash.clone();
+        let new_elements = vec![
+            new_element(base_create.into(), Some(base.clone())),
+            new_element(link_add.clone().into(), None),
+        ];
+        let entries = retrieve_link_entries(&new_elements, &mut workspace, fixt!(HolochainP2pCell))
+            .await
+            .unwrap();
+        assert_eq!(*entries[&link_add.base_address], base);
+        assert_eq!(*entries[&link_add.target_address], target);
+
+        // An entry held nowhere locally is fetched from the network,
+        // which fails on the stub network
+        let mut missing_link_add = fixt!(CreateLink);
+        missing_link_add.base_address = link_add.base_address.clone();
+        missing_link_add.target_address = EntryHash::with_data_sync(&fixt!(Entry));
+        let new_elements = vec![new_element(missing_link_add.into(), None)];
+        assert_matches!(
+            retrieve_link_entries(&new_elements, &mut workspace, fixt!(HolochainP2pCell)).await,
+            Err(RibosomeError::CascadeError(_))
+        );
+    }
 }
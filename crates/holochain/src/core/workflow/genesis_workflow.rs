@@ -39,7 +39,9 @@ pub async fn genesis_workflow<'env, Api: CellConductorApiT>(
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
     // commit the workspace
-    writer.with_writer(|writer| Ok(workspace.flush_to_txn(writer)?))?;
+    writer
+        .with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))
+        .await?;
 
     Ok(())
 }
@@ -55,14 +57,17 @@ async fn genesis_workflow_inner<Api: CellConductorApiT>(
         membrane_proof,
     } = args;
 
-    // TODO: this is a placeholder for a real DPKI request to show intent
-    if api
-        .dpki_request("is_agent_pubkey_valid".into(), agent_pubkey.to_string())
+    // Make sure DPKI knows about this key and hasn't revoked it
+    // before we start signing with it.
+    api.dpki_register_key(agent_pubkey.clone())
         .await
-        .expect("TODO: actually implement this")
-        == "INVALID"
-    {
-        return Err(WorkflowError::AgentInvalid(agent_pubkey.clone()));
+        .map_err(Box::new)?;
+    let key_state = api
+        .dpki_key_state(agent_pubkey.clone(), timestamp::now())
+        .await
+        .map_err(Box::new)?;
+    if key_state.map_or(false, |state| state.is_revoked()) {
+        return Err(WorkflowError::AgentInvalid(agent_pubkey));
     }
 
     workspace
@@ -133,8 +138,9 @@ pub mod tests {
         {
             let workspace = GenesisWorkspace::new(arc.clone().into()).await?;
             let mut api = MockCellConductorApi::new();
-            api.expect_sync_dpki_request()
-                .returning(|_, _| Ok("mocked dpki request response".to_string()));
+            api.expect_sync_dpki_register_key().returning(|_| Ok(()));
+            api.expect_sync_dpki_key_state()
+                .returning(|_, at| Ok(Some(KeyState::Valid(at))));
             let args = GenesisWorkflowArgs {
                 dna_file: dna.clone(),
                 agent_pubkey: agent_pubkey.clone(),
@@ -165,6 +171,34 @@ pub mod tests {
 
         Ok(())
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn genesis_rejects_revoked_agent_key() -> Result<(), anyhow::Error> {
+        observability::test_run()?;
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let dna = fake_dna_file("a");
+        let agent_pubkey = fake_agent_pubkey_1();
+
+        let workspace = GenesisWorkspace::new(arc.clone().into()).await?;
+        let mut api = MockCellConductorApi::new();
+        api.expect_sync_dpki_register_key().returning(|_| Ok(()));
+        api.expect_sync_dpki_key_state()
+            .returning(|_, at| Ok(Some(KeyState::Revoked(at))));
+        let args = GenesisWorkflowArgs {
+            dna_file: dna,
+            agent_pubkey: agent_pubkey.clone(),
+            membrane_proof: None,
+        };
+        let result = genesis_workflow(workspace, arc.clone().into(), api, args).await;
+        assert_matches!(result, Err(WorkflowError::AgentInvalid(a)) if a == agent_pubkey);
+
+        // Nothing was written to the chain
+        let source_chain = SourceChainBuf::new(arc.clone().into())?;
+        assert!(source_chain.chain_head().is_none());
+
+        Ok(())
+    }
 }
 
 /* TODO: make doc-able
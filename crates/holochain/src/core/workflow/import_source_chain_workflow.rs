@@ -0,0 +1,334 @@
+//! Import Source Chain Workflow: Initialize an empty source chain with the
+//! elements of an exported chain, checking on the way in that they form
+//! a valid chain for this Dna, authored and signed by the agent.
+
+use super::error::WorkflowError;
+use super::error::WorkflowResult;
+use crate::conductor::api::CellConductorApiT;
+use crate::core::queue_consumer::OneshotWriter;
+use crate::core::sys_validate::*;
+use derive_more::Constructor;
+use holochain_lmdb::prelude::*;
+use holochain_state::source_chain::ChainInvalidReason;
+use holochain_state::source_chain::SourceChainBuf;
+use holochain_state::source_chain::SourceChainExport;
+use holochain_state::source_chain::SourceChainExportElement;
+use holochain_state::workspace::Workspace;
+use holochain_state::workspace::WorkspaceResult;
+use holochain_types::prelude::*;
+use tracing::*;
+
+/// The struct which implements the import source chain Workflow
+#[derive(Constructor, Debug)]
+pub struct ImportSourceChainWorkflowArgs {
+    dna_hash: DnaHash,
+    agent_pubkey: AgentPubKey,
+    export: SourceChainExport,
+}
+
+#[instrument(skip(workspace, writer, api, args))]
+/// Write the exported elements to the empty source chain,
+/// failing without writing anything if any of them are invalid
+pub async fn import_source_chain_workflow<Api: CellConductorApiT>(
+    mut workspace: ImportSourceChainWorkspace,
+    writer: OneshotWriter,
+    api: Api,
+    args: ImportSourceChainWorkflowArgs,
+) -> WorkflowResult<()> {
+    import_source_chain_workflow_inner(&mut workspace, args, api).await?;
+
+    // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
+
+    // commit the workspace
+    writer
+        .with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))
+        .await?;
+
+    Ok(())
+}
+
+async fn import_source_chain_workflow_inner<Api: CellConductorApiT>(
+    workspace: &mut ImportSourceChainWorkspace,
+    args: ImportSourceChainWorkflowArgs,
+    api: Api,
+) -> WorkflowResult<()> {
+    let ImportSourceChainWorkflowArgs {
+        dna_hash,
+        agent_pubkey,
+        export,
+    } = args;
+
+    // Same as genesis, make sure DPKI knows about this key
+    // and hasn't revoked it.
+    api.dpki_register_key(agent_pubkey.clone())
+        .await
+        .map_err(Box::new)?;
+    let key_state = api
+        .dpki_key_state(agent_pubkey.clone(), timestamp::now())
+        .await
+        .map_err(Box::new)?;
+    if key_state.map_or(false, |state| state.is_revoked()) {
+        return Err(WorkflowError::AgentInvalid(agent_pubkey));
+    }
+
+    let source_chain = &mut workspace.source_chain;
+    if !source_chain.is_empty() {
+        return Err(SourceChainError::InvalidCommit(
+            "Source chains can only be imported into an empty chain".to_string(),
+        )
+        .into());
+    }
+
+    let mut prev_header: Option<HeaderHashed> = None;
+    let mut key_states = KeyStateCache::default();
+    for SourceChainExportElement { header, entry } in export.elements {
+        let SignedHeader(header, signature) = header;
+        check_imported_header(
+            &header,
+            &signature,
+            &agent_pubkey,
+            &dna_hash,
+            prev_header.as_ref(),
+            &api,
+            &mut key_states,
+        )
+        .await?;
+        let entry = match (header.entry_data(), entry) {
+            (Some((entry_hash, entry_type)), Some(entry)) => {
+                check_entry_type(entry_type, &entry)?;
+                check_entry_hash(entry_hash, &entry).await?;
+                check_entry_size(&entry)?;
+                Some(EntryHashed::from_content_sync(entry))
+            }
+            (None, None) => None,
+            // The authored chain has every entry, even private ones
+            (Some((entry_hash, _)), None) => {
+                return Err(
+                    SourceChainError::InvalidStructure(ChainInvalidReason::MissingData(
+                        entry_hash.clone(),
+                    ))
+                    .into(),
+                )
+            }
+            (None, Some(entry)) => {
+                return Err(SourceChainError::InvalidStructure(
+                    ChainInvalidReason::HeaderAndEntryMismatch(EntryHash::with_data_sync(&entry)),
+                )
+                .into())
+            }
+        };
+        let header = HeaderHashed::from_content_sync(header);
+        source_chain.put_signed(
+            SignedHeaderHashed::with_presigned(header.clone(), signature),
+            entry,
+        )?;
+        prev_header = Some(header);
+    }
+
+    if !source_chain.has_genesis() {
+        return Err(
+            SourceChainError::InvalidStructure(ChainInvalidReason::GenesisDataMissing).into(),
+        );
+    }
+    if source_chain.agent_pubkey()?.as_ref() != Some(&agent_pubkey) {
+        return Err(
+            SourceChainError::InvalidStructure(ChainInvalidReason::MalformedGenesisData).into(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Check the header was signed by the agent and follows on
+/// from the previous header, or is the Dna header if it's the first.
+async fn check_imported_header<Api: CellConductorApiT>(
+    header: &Header,
+    signature: &Signature,
+    agent_pubkey: &AgentPubKey,
+    dna_hash: &DnaHash,
+    prev_header: Option<&HeaderHashed>,
+    api: &Api,
+    key_states: &mut KeyStateCache,
+) -> WorkflowResult<()> {
+    if header.author() != agent_pubkey {
+        return Err(SysValidationError::from(ValidationOutcome::Counterfeit(
+            signature.clone(),
+            header.clone(),
+        ))
+        .into());
+    }
+    if !verify_header_signature(signature, header).await? {
+        return Err(SysValidationError::from(ValidationOutcome::VerifySignature(
+            signature.clone(),
+            header.clone(),
+        ))
+        .into());
+    }
+    author_key_is_valid(header.author(), header.timestamp(), api, key_states).await?;
+    match (header, prev_header) {
+        (Header::Dna(dna), None) if dna.hash == *dna_hash => Ok(()),
+        (_, None) => {
+            Err(SourceChainError::InvalidStructure(ChainInvalidReason::GenesisDataMissing).into())
+        }
+        (_, Some(prev_header)) => {
+            check_prev_header(header)?;
+            if header.prev_header() != Some(prev_header.as_hash()) {
+                return Err(SourceChainError::InvalidPreviousHeader(format!(
+                    "{:?} doesn't follow {}",
+                    header,
+                    prev_header.as_hash()
+                ))
+                .into());
+            }
+            check_prev_seq(header, prev_header.as_content())?;
+            check_prev_timestamp(header, prev_header.as_content())?;
+            Ok(())
+        }
+    }
+}
+
+/// The workspace for importing a source chain
+pub struct ImportSourceChainWorkspace {
+    source_chain: SourceChainBuf,
+}
+
+impl ImportSourceChainWorkspace {
+    /// Constructor
+    pub async fn new(env: EnvironmentRead) -> WorkspaceResult<Self> {
+        Ok(Self {
+            source_chain: SourceChainBuf::new(env)?,
+        })
+    }
+}
+
+impl Workspace for ImportSourceChainWorkspace {
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> WorkspaceResult<()> {
+        self.source_chain.flush_to_txn_ref(writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::conductor::api::MockCellConductorApi;
+    use holochain_lmdb::test_utils::test_cell_env;
+    use holochain_types::test_utils::fake_agent_pubkey_1;
+    use holochain_types::test_utils::fake_dna_file;
+    use matches::assert_matches;
+    use observability;
+
+    fn api() -> MockCellConductorApi {
+        let mut api = MockCellConductorApi::new();
+        api.expect_sync_dpki_register_key().returning(|_| Ok(()));
+        api.expect_sync_dpki_key_state()
+            .returning(|_, at| Ok(Some(KeyState::Valid(at))));
+        api
+    }
+
+    async fn exported_chain(dna_hash: DnaHash, agent_pubkey: AgentPubKey) -> SourceChainExport {
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let mut source_chain = SourceChainBuf::new(arc.clone().into()).unwrap();
+        source_chain
+            .genesis(dna_hash, agent_pubkey, None)
+            .await
+            .unwrap();
+        arc.guard()
+            .with_commit(|writer| source_chain.flush_to_txn(writer))
+            .unwrap();
+        SourceChainBuf::new(arc.clone().into())
+            .unwrap()
+            .export()
+            .unwrap()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn imports_exported_chain() -> Result<(), anyhow::Error> {
+        observability::test_run()?;
+        let dna = fake_dna_file("a");
+        let agent_pubkey = fake_agent_pubkey_1();
+        let export = exported_chain(dna.dna_hash().clone(), agent_pubkey.clone()).await;
+        let bytes = export.to_bytes()?;
+        assert_eq!(SourceChainExport::from_bytes(&bytes)?, export);
+
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let workspace = ImportSourceChainWorkspace::new(arc.clone().into()).await?;
+        let args = ImportSourceChainWorkflowArgs::new(
+            dna.dna_hash().clone(),
+            agent_pubkey.clone(),
+            export.clone(),
+        );
+        import_source_chain_workflow(workspace, arc.clone().into(), api(), args).await?;
+
+        let source_chain = SourceChainBuf::new(arc.clone().into())?;
+        assert_eq!(source_chain.agent_pubkey()?, Some(agent_pubkey));
+        assert_eq!(source_chain.export()?, export);
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rejects_invalid_chains() -> Result<(), anyhow::Error> {
+        observability::test_run()?;
+        let dna = fake_dna_file("a");
+        let agent_pubkey = fake_agent_pubkey_1();
+        let export = exported_chain(dna.dna_hash().clone(), agent_pubkey.clone()).await;
+
+        let import = |dna_hash: DnaHash, export: SourceChainExport| {
+            let agent_pubkey = agent_pubkey.clone();
+            async move {
+                let test_env = test_cell_env();
+                let arc = test_env.env();
+                let workspace = ImportSourceChainWorkspace::new(arc.clone().into())
+                    .await
+                    .unwrap();
+                let args = ImportSourceChainWorkflowArgs::new(dna_hash, agent_pubkey, export);
+                let result =
+                    import_source_chain_workflow(workspace, arc.clone().into(), api(), args).await;
+                // Nothing was written to the chain
+                let source_chain = SourceChainBuf::new(arc.clone().into()).unwrap();
+                assert!(source_chain.chain_head().is_none());
+                result
+            }
+        };
+
+        // The chain of another Dna
+        let result = import(fake_dna_file("b").dna_hash().clone(), export.clone()).await;
+        assert_matches!(
+            result,
+            Err(WorkflowError::SourceChainError(
+                SourceChainError::InvalidStructure(ChainInvalidReason::GenesisDataMissing)
+            ))
+        );
+
+        // A header changed after it was signed
+        let mut tampered = export.clone();
+        if let Header::AgentValidationPkg(avp) = &mut tampered.elements[1].header.0 {
+            avp.membrane_proof = Some(SerializedBytes::from(UnsafeBytes::from(vec![0])));
+        } else {
+            panic!("Expected the agent validation package");
+        }
+        let result = import(dna.dna_hash().clone(), tampered).await;
+        assert_matches!(
+            result,
+            Err(WorkflowError::SysValidationError(
+                SysValidationError::ValidationOutcome(ValidationOutcome::VerifySignature(_, _))
+            ))
+        );
+
+        // Missing an element
+        let mut missing = export;
+        missing.elements.remove(1);
+        let result = import(dna.dna_hash().clone(), missing).await;
+        assert_matches!(
+            result,
+            Err(WorkflowError::SourceChainError(
+                SourceChainError::InvalidPreviousHeader(_)
+            ))
+        );
+
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 //! The workflow and queue consumer for DhtOp integration
 
-use super::incoming_dht_ops_workflow::IncomingDhtOpsWorkspace;
 use super::*;
+use crate::core::metrics::WorkflowRun;
 use crate::core::queue_consumer::OneshotWriter;
 use crate::core::queue_consumer::TriggerSender;
 use crate::core::queue_consumer::WorkComplete;
@@ -17,6 +17,7 @@ use holochain_cascade::Cascade;
 use holochain_cascade::DbPair;
 use holochain_cascade::{error::CascadeError, integrate_single_metadata};
 use holochain_conductor_api::IntegrationStateDump;
+use holochain_conductor_api::StuckOpDump;
 use holochain_lmdb::buffer::BufferedStore;
 use holochain_lmdb::buffer::KvBufFresh;
 use holochain_lmdb::db::INTEGRATED_DHT_OPS;
@@ -30,24 +31,29 @@ use holochain_types::prelude::*;
 use holochain_zome_types::Entry;
 use holochain_zome_types::ValidationStatus;
 
+use produce_dht_ops_workflow::dht_op_light::error::DhtOpConvertError;
 use produce_dht_ops_workflow::dht_op_light::error::DhtOpConvertResult;
 use produce_dht_ops_workflow::dht_op_light::light_to_op;
-use std::collections::BinaryHeap;
+use std::collections::HashSet;
 use std::convert::TryInto;
 use tracing::*;
 
 pub use disintegrate::*;
+use integration_queue::Dependency;
+use integration_queue::IntegrationQueue;
 
 mod disintegrate;
+mod integration_queue;
 
 #[cfg(feature = "test_utils")]
 mod tests;
 
-#[instrument(skip(workspace, writer, trigger_sys))]
+#[instrument(skip(workspace, writer, trigger_sys, run))]
 pub async fn integrate_dht_ops_workflow(
     mut workspace: IntegrateDhtOpsWorkspace,
     writer: OneshotWriter,
     trigger_sys: &mut TriggerSender,
+    run: &mut WorkflowRun,
 ) -> WorkflowResult<WorkComplete> {
     // one of many possible ways to access the env
     let env = workspace.elements.headers().env().clone();
@@ -59,8 +65,8 @@ pub async fn integrate_dht_ops_workflow(
         .drain_iter(&r)?
         .collect())?;
 
-    // Sort the ops
-    let mut sorted_ops = BinaryHeap::new();
+    // Queue the ops so each is tried after the ops it depends on
+    let mut ordered_ops = Vec::with_capacity(ops.len());
     for iv in ops {
         let op = light_to_op(iv.op.clone(), &workspace.element_pending)?;
         let hash = DhtOpHash::with_data_sync(&op);
@@ -71,59 +77,53 @@ pub async fn integrate_dht_ops_workflow(
             op,
             value: iv,
         };
-        sorted_ops.push(v);
+        ordered_ops.push(v);
     }
 
+    let mut queue = IntegrationQueue::new(ordered_ops);
+
     let mut total_integrated: usize = 0;
 
-    // Try to process the queue over and over again, until we either exhaust
-    // the queue, or we can no longer integrate anything in the queue.
-    // We do this because items in the queue may depend on one another but may
-    // be out-of-order wrt. dependencies, so there is a chance that by repeating
-    // integration, we may be able to integrate at least one more item.
-    loop {
-        let mut num_integrated: usize = 0;
-        let mut next_ops = BinaryHeap::new();
-        for so in sorted_ops.into_sorted_vec() {
-            let OrderedOp {
+    // Ops only come out of the queue once the ops in it that they
+    // depend on have been tried, so a single pass integrates every
+    // op that can be.
+    while let Some(so) = queue.next() {
+        let OrderedOp {
+            hash,
+            op,
+            value,
+            order,
+        } = so;
+        // Check validation status and put in correct dbs
+        let outcome = integrate_single_dht_op(value.clone(), op, &mut workspace).await?;
+        match outcome {
+            Outcome::Integrated(integrated) => {
+                queue.integrated(&integrated.op);
+                // TODO We could create a prefix for the integrated ops db
+                // and separate rejected ops from valid ops.
+                // Currently you need to check the IntegratedDhtOpsValue for
+                // the status
+                workspace.integrate(hash, integrated)?;
+                total_integrated += 1;
+            }
+            Outcome::Deferred(op) => queue.deferred(OrderedOp {
                 hash,
+                order,
                 op,
                 value,
-                order,
-            } = so;
-            // Check validation status and put in correct dbs
-            let outcome = integrate_single_dht_op(value.clone(), op, &mut workspace).await?;
-            match outcome {
-                Outcome::Integrated(integrated) => {
-                    // TODO We could create a prefix for the integrated ops db
-                    // and separate rejected ops from valid ops.
-                    // Currently you need to check the IntegratedDhtOpsValue for
-                    // the status
-                    workspace.integrate(hash, integrated)?;
-                    num_integrated += 1;
-                    total_integrated += 1;
-                }
-                Outcome::Deferred(op) => next_ops.push(OrderedOp {
-                    hash,
-                    order,
-                    op,
-                    value,
-                }),
-            }
-        }
-        sorted_ops = next_ops;
-        // Either all ops are integrated or we couldn't integrate any on this pass
-        if sorted_ops.is_empty() || num_integrated == 0 {
-            break;
+            }),
         }
     }
+    let deferred_ops = queue.into_deferred();
+
+    run.processed(total_integrated);
 
-    let result = if sorted_ops.is_empty() {
+    let result = if deferred_ops.is_empty() {
         // There were no ops deferred, meaning we exhausted the queue
         WorkComplete::Complete
     } else {
         // Re-add the remaining ops to the queue, to be picked up next time.
-        for so in sorted_ops {
+        for so in deferred_ops {
             // TODO: it may be desirable to retain the original timestamp
             // when re-adding items to the queue for later processing. This is
             // challenging for now since we don't have access to that original
@@ -136,7 +136,9 @@ pub async fn integrate_dht_ops_workflow(
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
     // commit the workspace
-    writer.with_writer(|writer| Ok(workspace.flush_to_txn(writer)?))?;
+    writer
+        .with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))
+        .await?;
 
     // trigger other workflows
 
@@ -157,34 +159,37 @@ async fn integrate_single_dht_op(
     op: DhtOp,
     workspace: &mut IntegrateDhtOpsWorkspace,
 ) -> WorkflowResult<Outcome> {
-    if op_dependencies_held(&op, workspace).await? {
-        match iv.validation_status {
-            ValidationStatus::Valid => Ok(integrate_data_and_meta(
-                iv,
-                op,
-                &mut workspace.elements,
-                &mut workspace.meta,
-            )?),
-            ValidationStatus::Rejected => {
-                update_activity_status(&op, &mut workspace.meta)?;
-                update_validation_status(&op, &mut workspace.meta)?;
-                Ok(integrate_data(iv, op, &mut workspace.element_rejected)?)
-            }
-            ValidationStatus::Abandoned => {
-                // Throwing away abandoned ops
-                // TODO: keep abandoned ops but remove the entries
-                // and put them in a AbandonedPrefix db
-                let integrated = IntegratedDhtOpsValue {
-                    validation_status: iv.validation_status,
-                    op: iv.op,
-                    when_integrated: timestamp::now(),
-                };
-                Ok(Outcome::Integrated(integrated))
-            }
-        }
-    } else {
+    // Abandoned ops are often abandoned for missing their dependencies,
+    // so they don't wait for them
+    if iv.validation_status != ValidationStatus::Abandoned
+        && !op_dependencies_held(&op, workspace).await?
+    {
         debug!("deferring");
-        Ok(Outcome::Deferred(op))
+        return Ok(Outcome::Deferred(op));
+    }
+    match iv.validation_status {
+        ValidationStatus::Valid => Ok(integrate_data_and_meta(
+            iv,
+            op,
+            &mut workspace.elements,
+            &mut workspace.meta,
+        )?),
+        ValidationStatus::Rejected => {
+            update_activity_status(&op, &mut workspace.meta)?;
+            update_validation_status(&op, &mut workspace.meta)?;
+            Ok(integrate_data(iv, op, &mut workspace.element_rejected)?)
+        }
+        ValidationStatus::Abandoned => {
+            // Throwing away abandoned ops
+            // TODO: keep abandoned ops but remove the entries
+            // and put them in a AbandonedPrefix db
+            let integrated = IntegratedDhtOpsValue {
+                validation_status: iv.validation_status,
+                op: iv.op,
+                when_integrated: timestamp::now(),
+            };
+            Ok(Outcome::Integrated(integrated))
+        }
     }
 }
 
@@ -257,77 +262,15 @@ async fn op_dependencies_held(
     op: &DhtOp,
     workspace: &mut IntegrateDhtOpsWorkspace,
 ) -> CascadeResult<bool> {
-    {
-        match op {
-            DhtOp::StoreElement(_, _, _) | DhtOp::StoreEntry(_, _, _) => {}
-            DhtOp::RegisterAgentActivity(_, header) => {
-                // RegisterAgentActivity is the exception where we need to make
-                // sure that we have integrated the previous RegisterAgentActivity DhtOp
-                // from the same chain.
-                // This is because to say if the chain is valid as a whole we can't
-                // have parts missing.
-                let mut cascade = workspace.cascade();
-                let prev_header_hash = header.prev_header();
-                if let Some(prev_header_hash) = prev_header_hash.cloned() {
-                    match cascade
-                        .retrieve_header(prev_header_hash, Default::default())
-                        .await?
-                    {
-                        Some(prev_header) => {
-                            let op_hash = DhtOpHash::with_data_sync(
-                                &UniqueForm::RegisterAgentActivity(prev_header.header()),
-                            );
-                            if workspace.integration_limbo.contains(&op_hash)? {
-                                return Ok(true);
-                            }
-                        }
-                        None => return Ok(false),
-                    }
-                }
-            }
-            DhtOp::RegisterUpdatedContent(_, entry_update, _)
-            | DhtOp::RegisterUpdatedElement(_, entry_update, _) => {
-                // Check if we have the header with entry that we are updating
-                // or defer the op.
-                if !header_with_entry_is_stored(
-                    &entry_update.original_header_address,
-                    workspace.cascade(),
-                )
-                .await?
-                {
-                    return Ok(false);
-                }
-            }
-            DhtOp::RegisterDeletedBy(_, element_delete)
-            | DhtOp::RegisterDeletedEntryHeader(_, element_delete) => {
-                // Check if we have the header with the entry that we are removing
-                // or defer the op.
-                if !header_with_entry_is_stored(
-                    &element_delete.deletes_address,
-                    workspace.cascade(),
-                )
-                .await?
-                {
-                    return Ok(false);
-                }
-            }
-            DhtOp::RegisterAddLink(_, create_link) => {
-                // Check whether we have the base address.
-                // If not then this should put the op back on the queue.
-                if !entry_is_stored(&create_link.base_address, workspace.cascade()).await? {
-                    return Ok(false);
-                }
-            }
-            DhtOp::RegisterRemoveLink(_, delete_link) => {
-                // Check whether we have the link add address.
-                // If not then this should put the op back on the queue.
-                if !header_is_stored(&delete_link.link_add_address, workspace.cascade()).await? {
-                    return Ok(false);
-                }
-            }
+    match Dependency::of_op(op) {
+        None => Ok(true),
+        Some(Dependency::Header(hash)) => header_is_stored(&hash, workspace.cascade()).await,
+        // Check if we have the header with entry that we are updating
+        // or removing or defer the op.
+        Some(Dependency::HeaderWithEntry(hash)) => {
+            header_with_entry_is_stored(&hash, workspace.cascade()).await
         }
-
-        Ok(true)
+        Some(Dependency::Entry(hash)) => entry_is_stored(&hash, workspace.cascade()).await,
     }
 }
 
@@ -577,18 +520,79 @@ impl IntegrateDhtOpsWorkspace {
     }
 }
 
-pub fn dump_state(env: EnvironmentRead) -> WorkspaceResult<IntegrationStateDump> {
-    let workspace = IncomingDhtOpsWorkspace::new(env.clone())?;
+pub async fn dump_state(env: EnvironmentRead) -> WorkflowResult<IntegrationStateDump> {
+    let mut workspace = IntegrateDhtOpsWorkspace::new(env.clone())?;
     let (validation_limbo, integration_limbo, integrated) = fresh_reader!(env, |r| {
         let v = workspace.validation_limbo.iter(&r)?.count()?;
         let il = workspace.integration_limbo.iter(&r)?.count()?;
         let i = workspace.integrated_dht_ops.iter(&r)?.count()?;
         DatabaseResult::Ok((v, il, i))
     })?;
+    let stuck_ops = stuck_ops(env, &mut workspace).await?;
 
     Ok(IntegrationStateDump {
         validation_limbo,
         integration_limbo,
         integrated,
+        stuck_ops,
     })
 }
+
+/// Find the ops in the integration limbo that are not currently integrable.
+/// They may still be integrated once what they await arrives.
+///
+/// An op is stuck when the data it depends on isn't held and no op
+/// in either limbo provides it, or when its own data is missing.
+/// Whether the data is held is checked the same way integration does.
+async fn stuck_ops(
+    env: EnvironmentRead,
+    workspace: &mut IntegrateDhtOpsWorkspace,
+) -> WorkflowResult<Vec<StuckOpDump>> {
+    let (integration_ops, validation_ops) = fresh_reader!(env, |r| {
+        let integration_ops: Vec<(DhtOpHash, DhtOpLight)> = workspace
+            .integration_limbo
+            .iter(&r)?
+            .map(|(k, v)| Ok((DhtOpHash::from_raw_39_panicky(k.to_vec()), v.op)))
+            .collect()?;
+        let validation_ops: Vec<DhtOpLight> = workspace
+            .validation_limbo
+            .iter(&r)?
+            .map(|(_, v)| Ok(v.op))
+            .collect()?;
+        DatabaseResult::Ok((integration_ops, validation_ops))
+    })?;
+
+    let provided: HashSet<Dependency> = integration_ops
+        .iter()
+        .map(|(_, op)| op)
+        .chain(validation_ops.iter())
+        .flat_map(Dependency::provided_by)
+        .collect();
+
+    let mut stuck_ops = Vec::new();
+    for (op_hash, light) in integration_ops {
+        let op_type = light.to_string();
+        let op = match light_to_op(light, &workspace.element_pending) {
+            Ok(op) => op,
+            Err(DhtOpConvertError::MissingData(awaiting)) => {
+                stuck_ops.push(StuckOpDump {
+                    op_hash,
+                    op_type,
+                    awaiting,
+                });
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(dependency) = Dependency::of_op(&op) {
+            if !provided.contains(&dependency) && !op_dependencies_held(&op, workspace).await? {
+                stuck_ops.push(StuckOpDump {
+                    op_hash,
+                    op_type,
+                    awaiting: dependency.hash(),
+                });
+            }
+        }
+    }
+    Ok(stuck_ops)
+}
@@ -0,0 +1,278 @@
+//! Orders the ops in the integration limbo so that every op is tried
+//! after the ops it depends on.
+
+use crate::core::validation::OrderedOp;
+use holo_hash::AnyDhtHash;
+use holo_hash::EntryHash;
+use holo_hash::HeaderHash;
+use holochain_state::dht_op_integration::IntegrationLimboValue;
+use holochain_types::dht_op::DhtOp;
+use holochain_types::dht_op::DhtOpLight;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+
+/// An op from the integration limbo
+pub type QueuedOp = OrderedOp<IntegrationLimboValue>;
+
+/// Data that must be held before an op can be integrated
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Dependency {
+    /// The header, which every op about it stores
+    Header(HeaderHash),
+    /// The header stored with its entry
+    HeaderWithEntry(HeaderHash),
+    /// The entry
+    Entry(EntryHash),
+}
+
+impl Dependency {
+    /// The data this op needs integrated before it can be, if any
+    pub fn of_op(op: &DhtOp) -> Option<Self> {
+        match op {
+            DhtOp::StoreElement(_, _, _) | DhtOp::StoreEntry(_, _, _) => None,
+            // RegisterAgentActivity needs the previous header from the
+            // same chain, because to say if the chain is valid as a whole
+            // we can't have parts missing.
+            DhtOp::RegisterAgentActivity(_, header) => {
+                header.prev_header().cloned().map(Self::Header)
+            }
+            DhtOp::RegisterUpdatedContent(_, entry_update, _)
+            | DhtOp::RegisterUpdatedElement(_, entry_update, _) => Some(Self::HeaderWithEntry(
+                entry_update.original_header_address.clone(),
+            )),
+            DhtOp::RegisterDeletedBy(_, element_delete)
+            | DhtOp::RegisterDeletedEntryHeader(_, element_delete) => Some(Self::HeaderWithEntry(
+                element_delete.deletes_address.clone(),
+            )),
+            DhtOp::RegisterAddLink(_, create_link) => {
+                Some(Self::Entry(create_link.base_address.clone()))
+            }
+            DhtOp::RegisterRemoveLink(_, delete_link) => {
+                Some(Self::Header(delete_link.link_add_address.clone()))
+            }
+        }
+    }
+
+    /// The data integrating this op makes available to other ops
+    pub fn provided_by(op: &DhtOpLight) -> Vec<Self> {
+        let header = Self::Header(op.header_hash().clone());
+        match op {
+            DhtOpLight::StoreElement(header_hash, Some(entry_hash), _)
+            | DhtOpLight::StoreEntry(header_hash, entry_hash, _) => vec![
+                header,
+                Self::HeaderWithEntry(header_hash.clone()),
+                Self::Entry(entry_hash.clone()),
+            ],
+            _ => vec![header],
+        }
+    }
+
+    /// The hash of the header or entry depended on
+    pub fn hash(&self) -> AnyDhtHash {
+        match self {
+            Self::Header(hash) | Self::HeaderWithEntry(hash) => hash.clone().into(),
+            Self::Entry(hash) => hash.clone().into(),
+        }
+    }
+}
+
+/// A queue of the ops in the integration limbo in topological order.
+///
+/// Ops come out in [`DhtOpOrder`](crate::core::validation::DhtOpOrder),
+/// except that an op whose [`Dependency`] is provided by other ops in the
+/// queue waits until one of those ops is integrated, or all of them have
+/// been tried. So a `RegisterAddLink` comes out after the `StoreEntry`
+/// for its base and one pass over the queue integrates everything it can.
+pub struct IntegrationQueue {
+    /// Ops that aren't waiting on any other op in the queue
+    ready: BinaryHeap<Reverse<QueuedOp>>,
+    /// Ops waiting on a dependency that ops in the queue provide
+    waiting: HashMap<Dependency, Vec<QueuedOp>>,
+    /// How many ops not yet tried provide each dependency
+    providers: HashMap<Dependency, usize>,
+    /// Ops that couldn't be integrated
+    deferred: Vec<QueuedOp>,
+}
+
+impl IntegrationQueue {
+    /// Create the queue from the ops in the integration limbo
+    pub fn new<I: IntoIterator<Item = QueuedOp>>(ops: I) -> Self {
+        let ops: Vec<_> = ops.into_iter().collect();
+        let mut providers = HashMap::new();
+        for op in &ops {
+            for dep in Dependency::provided_by(&op.value.op) {
+                *providers.entry(dep).or_insert(0) += 1;
+            }
+        }
+        let mut ready = BinaryHeap::new();
+        let mut waiting: HashMap<_, Vec<_>> = HashMap::new();
+        for op in ops {
+            match Dependency::of_op(&op.op).filter(|dep| providers.contains_key(dep)) {
+                Some(dep) => waiting.entry(dep).or_default().push(op),
+                None => ready.push(Reverse(op)),
+            }
+        }
+        Self {
+            ready,
+            waiting,
+            providers,
+            deferred: Vec::new(),
+        }
+    }
+
+    /// The next op to try to integrate
+    pub fn next(&mut self) -> Option<QueuedOp> {
+        self.ready.pop().map(|Reverse(op)| op)
+    }
+
+    /// The op was integrated, so the ops waiting on it can be tried
+    pub fn integrated(&mut self, op: &DhtOpLight) {
+        self.tried(op, true);
+    }
+
+    /// The op couldn't be integrated on this pass
+    pub fn deferred(&mut self, op: QueuedOp) {
+        self.tried(&op.value.op, false);
+        self.deferred.push(op);
+    }
+
+    /// The ops that couldn't be integrated
+    pub fn into_deferred(self) -> Vec<QueuedOp> {
+        let Self {
+            ready,
+            waiting,
+            mut deferred,
+            ..
+        } = self;
+        deferred.extend(ready.into_iter().map(|Reverse(op)| op));
+        // Only ops in a dependency cycle are still waiting,
+        // which valid hashes can't form
+        deferred.extend(waiting.into_iter().flat_map(|(_, ops)| ops));
+        deferred
+    }
+
+    fn tried(&mut self, op: &DhtOpLight, integrated: bool) {
+        for dep in Dependency::provided_by(op) {
+            let remaining = self.providers.get_mut(&dep).map(|count| {
+                *count = count.saturating_sub(1);
+                *count
+            });
+            // Once no other op can provide the dependency the ops waiting
+            // on it are still tried, because it might already be held.
+            if integrated || remaining == Some(0) {
+                if let Some(ops) = self.waiting.remove(&dep) {
+                    self.ready.extend(ops.into_iter().map(Reverse));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::validation::DhtOpOrder;
+    use ::fixt::prelude::*;
+    use holo_hash::DhtOpHash;
+    use holochain_types::fixt::*;
+    use holochain_types::prelude::*;
+
+    fn queued(op: DhtOp, light: DhtOpLight) -> QueuedOp {
+        OrderedOp {
+            order: DhtOpOrder::from(&op),
+            hash: DhtOpHash::with_data_sync(&op),
+            op,
+            value: IntegrationLimboValue {
+                validation_status: ValidationStatus::Valid,
+                op: light,
+            },
+        }
+    }
+
+    /// A `RegisterAddLink` on a base whose `StoreEntry` is in the queue
+    fn link_and_base() -> (QueuedOp, QueuedOp) {
+        let entry = fixt!(Entry);
+        let mut create = fixt!(Create);
+        create.entry_hash = EntryHash::with_data_sync(&entry);
+        let mut create_link = fixt!(CreateLink);
+        create_link.base_address = create.entry_hash.clone();
+
+        let store_entry = DhtOp::StoreEntry(
+            fixt!(Signature),
+            NewEntryHeader::Create(create.clone()),
+            Box::new(entry),
+        );
+        let header_hash = HeaderHash::with_data_sync(&Header::Create(create.clone()));
+        let store_entry_light = DhtOpLight::StoreEntry(
+            header_hash,
+            create.entry_hash.clone(),
+            create.entry_hash.clone().into(),
+        );
+        let register_add_link = DhtOp::RegisterAddLink(fixt!(Signature), create_link.clone());
+        let link_light = DhtOpLight::RegisterAddLink(
+            HeaderHash::with_data_sync(&Header::CreateLink(create_link.clone())),
+            create_link.base_address.clone().into(),
+        );
+        (
+            queued(register_add_link, link_light),
+            queued(store_entry, store_entry_light),
+        )
+    }
+
+    #[test]
+    fn ops_come_after_their_dependencies() {
+        let (link, base) = link_and_base();
+        let mut queue = IntegrationQueue::new(vec![link.clone(), base.clone()]);
+
+        let first = queue.next().unwrap();
+        assert_eq!(first.hash, base.hash);
+        // The link waits until its base is tried
+        assert!(queue.next().is_none());
+
+        queue.integrated(&first.value.op);
+        assert_eq!(queue.next().unwrap().hash, link.hash);
+        assert!(queue.next().is_none());
+        assert!(queue.into_deferred().is_empty());
+    }
+
+    #[test]
+    fn ops_are_tried_once_their_dependency_is_deferred() {
+        let (link, base) = link_and_base();
+        let mut queue = IntegrationQueue::new(vec![link.clone(), base.clone()]);
+
+        let first = queue.next().unwrap();
+        queue.deferred(first);
+        let second = queue.next().unwrap();
+        assert_eq!(second.hash, link.hash);
+        queue.deferred(second);
+
+        let deferred: Vec<_> = queue
+            .into_deferred()
+            .into_iter()
+            .map(|op| op.hash)
+            .collect();
+        assert_eq!(deferred, vec![base.hash, link.hash]);
+    }
+
+    #[test]
+    fn ops_without_dependencies_in_the_queue_are_ready() {
+        let (link, _) = link_and_base();
+        let mut queue = IntegrationQueue::new(vec![link.clone()]);
+        assert_eq!(queue.next().unwrap().hash, link.hash);
+    }
+
+    #[test]
+    fn store_entry_provides_its_header_and_entry() {
+        let (_, base) = link_and_base();
+        let provided = Dependency::provided_by(&base.value.op);
+        let entry_hash = match &base.value.op {
+            DhtOpLight::StoreEntry(_, entry_hash, _) => entry_hash.clone(),
+            _ => unreachable!(),
+        };
+        assert!(provided.contains(&Dependency::Entry(entry_hash)));
+        assert!(provided.contains(&Dependency::HeaderWithEntry(
+            base.value.op.header_hash().clone()
+        )));
+    }
+}
@@ -530,9 +530,14 @@ impl Db {
 async fn call_workflow<'env>(env: EnvironmentWrite) {
     let workspace = IntegrateDhtOpsWorkspace::new(env.clone().into()).unwrap();
     let (mut qt, _rx) = TriggerSender::new();
-    integrate_dht_ops_workflow(workspace, env.clone().into(), &mut qt)
-        .await
-        .unwrap();
+    integrate_dht_ops_workflow(
+        workspace,
+        env.clone().into(),
+        &mut qt,
+        &mut WorkflowRun::start(),
+    )
+    .await
+    .unwrap();
 }
 
 // Need to clear the data from the previous test
@@ -835,13 +840,164 @@ async fn test_ops_state() {
     }
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn test_ops_integrate_with_their_dependencies_in_one_pass() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let a = TestData::new().await;
+
+    // The activity of a header needs the previous header on the chain,
+    // which is stored by an op that DhtOpOrder puts after it.
+    let store_element = DhtOp::StoreElement(
+        a.signature.clone(),
+        a.original_header.clone().into(),
+        Some(Box::new(a.original_entry.clone())),
+    );
+    let mut next_header = a.link_add.clone();
+    next_header.prev_header = a.original_header_hash.clone();
+    let next_header = Header::CreateLink(next_header);
+    let activity = DhtOp::RegisterAgentActivity(a.signature.clone(), next_header.clone());
+    assert!(DhtOpOrder::from(&activity) < DhtOpOrder::from(&store_element));
+
+    let pre_state = vec![
+        Db::IntQueue(activity.clone()),
+        Db::IntQueue(store_element.clone()),
+    ];
+    let pre_state = add_op_to_judged(pre_state, &activity);
+    let pre_state = add_op_to_judged(pre_state, &store_element);
+    Db::set(pre_state, env.clone()).await;
+    call_workflow(env.clone()).await;
+
+    let expect = vec![
+        Db::Integrated(store_element),
+        Db::Integrated(activity),
+        Db::MetaActivity(next_header),
+        Db::IntQueueEmpty,
+    ];
+    Db::check(
+        expect,
+        env.clone(),
+        here!("activity and previous header").to_string(),
+    )
+    .await;
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_abandoned_ops_integrate_without_their_dependencies() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let a = TestData::new().await;
+
+    // The link's base isn't held and no op provides it
+    let add_link = DhtOp::RegisterAddLink(a.signature.clone(), a.link_add.clone());
+    let op_hash = DhtOpHashed::from_content_sync(add_link.clone()).into_hash();
+    let mut workspace = IntegrateDhtOpsWorkspace::new(env.clone().into()).unwrap();
+    workspace
+        .integration_limbo
+        .put(
+            op_hash.clone(),
+            IntegrationLimboValue {
+                validation_status: ValidationStatus::Abandoned,
+                op: add_link.to_light(),
+            },
+        )
+        .unwrap();
+    env.guard()
+        .with_commit::<WorkspaceError, _, _>(|writer| {
+            workspace.integration_limbo.flush_to_txn_ref(writer)?;
+            Ok(())
+        })
+        .unwrap();
+    call_workflow(env.clone()).await;
+
+    let workspace = IntegrateDhtOpsWorkspace::new(env.clone().into()).unwrap();
+    let integrated = workspace
+        .integrated_dht_ops
+        .get(&op_hash)
+        .unwrap()
+        .expect("The abandoned op is integrated");
+    assert_eq!(integrated.validation_status, ValidationStatus::Abandoned);
+    Db::check(
+        vec![Db::IntQueueEmpty],
+        env.clone(),
+        here!("abandoned link").to_string(),
+    )
+    .await;
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_dump_state_reports_stuck_ops() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let a = TestData::new().await;
+
+    // The link's base isn't held and no op provides it
+    let add_link = DhtOp::RegisterAddLink(a.signature.clone(), a.link_add.clone());
+    let pre_state = add_op_to_judged(vec![Db::IntQueue(add_link.clone())], &add_link);
+    Db::set(pre_state, env.clone()).await;
+
+    let dump = dump_state(env.clone().into()).await.unwrap();
+    assert_eq!(dump.integration_limbo, 1);
+    assert_eq!(dump.stuck_ops.len(), 1);
+    let stuck = &dump.stuck_ops[0];
+    assert_eq!(
+        stuck.op_hash,
+        DhtOpHashed::from_content_sync(add_link).into_hash()
+    );
+    assert_eq!(stuck.op_type, "RegisterAddLink");
+    assert_eq!(
+        stuck.awaiting,
+        AnyDhtHash::from(a.link_add.base_address.clone())
+    );
+
+    // Once the base is on its way through the validation limbo the link isn't stuck
+    let store_entry = DhtOp::StoreEntry(
+        a.signature.clone(),
+        a.original_header.clone(),
+        Box::new(a.original_entry.clone()),
+    );
+    let mut workspace = IntegrateDhtOpsWorkspace::new(env.clone().into()).unwrap();
+    workspace
+        .validation_limbo
+        .put(
+            DhtOpHashed::from_content_sync(store_entry.clone()).into_hash(),
+            ValidationLimboValue {
+                status: ValidationLimboStatus::Pending,
+                op: store_entry.to_light(),
+                basis: store_entry.dht_basis(),
+                time_added: timestamp::now(),
+                last_try: None,
+                num_tries: 0,
+                from_agent: None,
+            },
+        )
+        .unwrap();
+    env.guard()
+        .with_commit::<WorkspaceError, _, _>(|writer| {
+            workspace.validation_limbo.flush_to_txn_ref(writer)?;
+            Ok(())
+        })
+        .unwrap();
+    let dump = dump_state(env.clone().into()).await.unwrap();
+    assert_eq!(dump.validation_limbo, 1);
+    assert!(dump.stuck_ops.is_empty());
+}
+
 /// Call the produce dht ops workflow
 async fn produce_dht_ops<'env>(env: EnvironmentWrite) {
     let (mut qt, _rx) = TriggerSender::new();
     let workspace = ProduceDhtOpsWorkspace::new(env.clone().into()).unwrap();
-    produce_dht_ops_workflow(workspace, env.clone().into(), &mut qt)
-        .await
-        .unwrap();
+    produce_dht_ops_workflow(
+        workspace,
+        env.clone().into(),
+        &mut qt,
+        &mut WorkflowRun::start(),
+    )
+    .await
+    .unwrap();
 }
 
 /// Run genesis on the source chain
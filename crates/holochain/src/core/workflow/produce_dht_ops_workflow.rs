@@ -1,4 +1,5 @@
 use super::error::WorkflowResult;
+use crate::core::metrics::WorkflowRun;
 use crate::core::queue_consumer::OneshotWriter;
 use crate::core::queue_consumer::TriggerSender;
 use crate::core::queue_consumer::WorkComplete;
@@ -14,18 +15,21 @@ use tracing::*;
 
 pub mod dht_op_light;
 
-#[instrument(skip(workspace, writer, trigger_publish))]
+#[instrument(skip(workspace, writer, trigger_publish, run))]
 pub async fn produce_dht_ops_workflow(
     mut workspace: ProduceDhtOpsWorkspace,
     writer: OneshotWriter,
     trigger_publish: &mut TriggerSender,
+    run: &mut WorkflowRun,
 ) -> WorkflowResult<WorkComplete> {
-    let complete = produce_dht_ops_workflow_inner(&mut workspace).await?;
+    let complete = produce_dht_ops_workflow_inner(&mut workspace, run).await?;
 
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
     // commit the workspace
-    writer.with_writer(|writer| Ok(workspace.flush_to_txn(writer)?))?;
+    writer
+        .with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))
+        .await?;
 
     // trigger other workflows
     trigger_publish.trigger();
@@ -35,11 +39,13 @@ pub async fn produce_dht_ops_workflow(
 
 async fn produce_dht_ops_workflow_inner(
     workspace: &mut ProduceDhtOpsWorkspace,
+    run: &mut WorkflowRun,
 ) -> WorkflowResult<WorkComplete> {
     debug!("Starting dht op workflow");
     let all_ops = workspace.source_chain.get_incomplete_dht_ops().await?;
 
     for (index, ops) in all_ops {
+        run.processed(ops.len());
         for op in ops {
             let (op, hash) = DhtOpHashed::from_content_sync(op).into_inner();
             debug!(?hash, ?op);
@@ -207,9 +213,10 @@ mod tests {
         // Run the workflow and commit it
         {
             let mut workspace = ProduceDhtOpsWorkspace::new(env.clone().into()).unwrap();
-            let complete = produce_dht_ops_workflow_inner(&mut workspace)
-                .await
-                .unwrap();
+            let complete =
+                produce_dht_ops_workflow_inner(&mut workspace, &mut WorkflowRun::start())
+                    .await
+                    .unwrap();
             assert_matches!(complete, WorkComplete::Complete);
             env_ref
                 .with_commit(|writer| workspace.flush_to_txn(writer))
@@ -254,9 +261,10 @@ mod tests {
         // because no new ops should hav been added
         {
             let mut workspace = ProduceDhtOpsWorkspace::new(env.clone().into()).unwrap();
-            let complete = produce_dht_ops_workflow_inner(&mut workspace)
-                .await
-                .unwrap();
+            let complete =
+                produce_dht_ops_workflow_inner(&mut workspace, &mut WorkflowRun::start())
+                    .await
+                    .unwrap();
             assert_matches!(complete, WorkComplete::Complete);
             env_ref
                 .with_commit(|writer| workspace.flush_to_txn(writer))
@@ -13,6 +13,9 @@
 use super::error::WorkflowResult;
 use super::produce_dht_ops_workflow::dht_op_light::error::DhtOpConvertError;
 use super::produce_dht_ops_workflow::dht_op_light::light_to_op;
+use crate::conductor::api::CellConductorApiT;
+use crate::conductor::entry_def_store::get_entry_def_from_ids;
+use crate::core::metrics::WorkflowRun;
 use crate::core::queue_consumer::OneshotWriter;
 use crate::core::queue_consumer::WorkComplete;
 use fallible_iterator::FallibleIterator;
@@ -31,17 +34,22 @@ use std::collections::HashMap;
 use std::time;
 use tracing::*;
 
-/// Default redundancy factor for validation receipts
-// TODO: Pull this from the wasm entry def and only use this if it's missing
+/// Default redundancy factor for validation receipts.
+/// Used for ops which aren't about an app entry, or whose [EntryDef]
+/// can't be found. These ops are published with the network's default fan-out.
+/// Otherwise the entry def's `required_validations` is used.
 // TODO: Put a default in the DnaBundle
-// TODO: build zome_types/entry_def map to get the (AppEntryType map to entry def)
-pub const DEFAULT_RECEIPT_BUNDLE_SIZE: u32 = 5;
+pub const DEFAULT_RECEIPT_BUNDLE_SIZE: u8 = 5;
 
 /// Don't publish a DhtOp more than once during this interval.
 /// This allows us to trigger the publish workflow as often as we like, without
 /// flooding the network with spurious publishes.
 pub const MIN_PUBLISH_INTERVAL: time::Duration = time::Duration::from_secs(5);
 
+/// Ops to publish, by basis, along with how many agents to publish them to.
+/// `None` leaves the number of agents to the network.
+pub type OpsToPublish = HashMap<AnyDhtHash, (Option<u8>, Vec<(DhtOpHash, DhtOp)>)>;
+
 /// Database buffers required for publishing [DhtOp]s
 pub struct PublishDhtOpsWorkspace {
     /// Database of authored DhtOps, with data about prior publishing
@@ -50,30 +58,39 @@ pub struct PublishDhtOpsWorkspace {
     elements: ElementBuf<AuthoredPrefix>,
 }
 
-#[instrument(skip(workspace, writer, network))]
+#[instrument(skip(workspace, writer, network, conductor_api, run))]
 pub async fn publish_dht_ops_workflow(
     mut workspace: PublishDhtOpsWorkspace,
     writer: OneshotWriter,
     network: &mut HolochainP2pCell,
+    conductor_api: &impl CellConductorApiT,
+    run: &mut WorkflowRun,
 ) -> WorkflowResult<WorkComplete> {
-    let to_publish = publish_dht_ops_workflow_inner(&mut workspace).await?;
+    let to_publish = publish_dht_ops_workflow_inner(&mut workspace, conductor_api).await?;
 
     // Commit to the network
-    for (basis, ops) in to_publish {
-        network.publish(true, basis, ops, None).await?;
+    for (basis, (remote_agent_count, ops)) in to_publish {
+        run.processed(ops.len());
+        network
+            .publish(true, basis, ops, remote_agent_count, None)
+            .await?;
     }
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
     // commit the workspace
-    writer.with_writer(|writer| Ok(workspace.flush_to_txn(writer)?))?;
+    writer
+        .with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))
+        .await?;
 
     Ok(WorkComplete::Complete)
 }
 
-/// Read the authored for ops with receipt count < R
+/// Read the authored for ops with receipt count < R,
+/// where R is the `required_validations` of the op's entry type
 pub async fn publish_dht_ops_workflow_inner(
     workspace: &mut PublishDhtOpsWorkspace,
-) -> WorkflowResult<HashMap<AnyDhtHash, Vec<(DhtOpHash, DhtOp)>>> {
+    conductor_api: &impl CellConductorApiT,
+) -> WorkflowResult<OpsToPublish> {
     // TODO: PERF: We need to check all ops every time this runs
     // instead we could have a queue of ops where count < R and a kv for count > R.
     // Then if the count for an ops reduces below R move it to the queue.
@@ -88,24 +105,14 @@ pub async fn publish_dht_ops_workflow_inner(
     let values = fresh_reader!(env, |r| workspace
         .authored()
         .iter(&r)?
-        .filter_map(|(k, mut r)| {
-            Ok(if r.receipt_count < DEFAULT_RECEIPT_BUNDLE_SIZE {
-                let needs_publish = r
-                    .last_publish_time
-                    .and_then(|last| now.checked_difference_signed(&last))
-                    .map(|duration| duration > interval)
-                    .unwrap_or(true);
-                if needs_publish {
-                    r.last_publish_time = Some(now);
-                    // HACK: Incrementing the receipt count to prevent publishing
-                    // forever although without receipts this could lead to data loss
-                    // and relies on gossip for data integrity.
-                    // This should be removed when receipts are implemented.
-                    r.receipt_count += 1;
-                    Some((DhtOpHash::from_raw_39_panicky(k.to_vec()), r))
-                } else {
-                    None
-                }
+        .filter_map(|(k, r)| {
+            let needs_publish = r
+                .last_publish_time
+                .and_then(|last| now.checked_difference_signed(&last))
+                .map(|duration| duration > interval)
+                .unwrap_or(true);
+            Ok(if needs_publish {
+                Some((DhtOpHash::from_raw_39_panicky(k.to_vec()), r))
             } else {
                 None
             })
@@ -114,9 +121,24 @@ pub async fn publish_dht_ops_workflow_inner(
 
     // Ops to publish by basis
     let mut to_publish = HashMap::new();
+    let mut redundancy = RequiredValidationsCache::default();
+
+    for (op_hash, mut value) in values {
+        let required_validations = redundancy
+            .get(&value.op, workspace.elements(), conductor_api)
+            .await?;
+        let required_receipts = required_validations.unwrap_or(DEFAULT_RECEIPT_BUNDLE_SIZE);
+        if value.receipt_count >= u32::from(required_receipts) {
+            continue;
+        }
 
-    for (op_hash, value) in values {
-        // Insert updated values into database for items about to be published
+        // Update the values for items about to be published
+        value.last_publish_time = Some(now);
+        // HACK: Incrementing the receipt count to prevent publishing
+        // forever although without receipts this could lead to data loss
+        // and relies on gossip for data integrity.
+        // This should be removed when receipts are implemented.
+        value.receipt_count += 1;
         let op = value.op.clone();
         workspace.authored().put(op_hash.clone(), value)?;
 
@@ -126,16 +148,66 @@ pub async fn publish_dht_ops_workflow_inner(
             r => r?,
         };
         // For every op publish a request
-        // Collect and sort ops by basis
-        to_publish
+        // Collect and sort ops by basis, publishing to as many agents
+        // as the most demanding op at that basis requires
+        let (remote_agent_count, ops) = to_publish
             .entry(op.dht_basis())
-            .or_insert_with(Vec::new)
-            .push((op_hash, op));
+            .or_insert_with(|| (None, Vec::new()));
+        *remote_agent_count = std::cmp::max(*remote_agent_count, required_validations);
+        ops.push((op_hash, op));
     }
 
     Ok(to_publish)
 }
 
+/// Looks up how many validation receipts an op should collect
+/// from the [EntryDef] of the app entry its header is for,
+/// or `None` if there is no such entry def.
+/// The Dna and entry defs are only fetched once per workflow run.
+#[derive(Default)]
+struct RequiredValidationsCache {
+    dna_file: Option<DnaFile>,
+    by_entry_type: HashMap<AppEntryType, Option<u8>>,
+}
+
+impl RequiredValidationsCache {
+    async fn get(
+        &mut self,
+        op: &DhtOpLight,
+        elements: &ElementBuf<AuthoredPrefix>,
+        conductor_api: &impl CellConductorApiT,
+    ) -> WorkflowResult<Option<u8>> {
+        let app_entry_type = match elements
+            .get_header(op.header_hash())?
+            .and_then(|shh| shh.header().entry_type().cloned())
+        {
+            Some(EntryType::App(aet)) => aet,
+            _ => return Ok(None),
+        };
+        if let Some(required_validations) = self.by_entry_type.get(&app_entry_type) {
+            return Ok(*required_validations);
+        }
+
+        if self.dna_file.is_none() {
+            self.dna_file = Some(conductor_api.get_this_dna().await.map_err(Box::new)?);
+        }
+        let dna_file = self.dna_file.as_ref().expect("Dna was just fetched");
+        let required_validations = get_entry_def_from_ids(
+            app_entry_type.zome_id(),
+            app_entry_type.id(),
+            dna_file.dna(),
+            conductor_api,
+        )
+        .await?
+        // Every op is published to at least one agent
+        .map(|entry_def| u8::from(entry_def.required_validations).max(1));
+
+        self.by_entry_type
+            .insert(app_entry_type, required_validations);
+        Ok(required_validations)
+    }
+}
+
 impl Workspace for PublishDhtOpsWorkspace {
     fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> WorkspaceResult<()> {
         self.authored_dht_ops.flush_to_txn_ref(writer)?;
@@ -167,6 +239,7 @@ impl PublishDhtOpsWorkspace {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::conductor::api::MockCellConductorApi;
     use crate::core::queue_consumer::TriggerSender;
     use crate::core::workflow::fake_genesis;
     use crate::core::workflow::produce_dht_ops_workflow::produce_dht_ops_workflow;
@@ -180,9 +253,11 @@ mod tests {
     use futures::future::FutureExt;
     use holochain_p2p::actor::HolochainP2pSender;
     use holochain_p2p::HolochainP2pRef;
+    use holochain_wasm_test_utils::TestWasm;
     use matches::assert_matches;
     use observability;
     use std::collections::HashMap;
+    use std::convert::TryFrom;
     use std::convert::TryInto;
     use std::sync::atomic::AtomicU32;
     use std::sync::atomic::Ordering;
@@ -315,12 +390,50 @@ mod tests {
         (test_network, cell_network, recv_task, rx_complete)
     }
 
+    /// A conductor api for a Dna whose entry defs all require
+    /// this many validation receipts
+    async fn test_conductor_api(required_validations: u8) -> MockCellConductorApi {
+        let dna_file = DnaFile::new(
+            DnaDef {
+                name: "publish_dht_ops_test".to_string(),
+                uuid: "2b3b2a2e-4a1c-4a52-b1a5-0f6e1e0d9c47".to_string(),
+                properties: SerializedBytes::try_from(()).unwrap(),
+                zomes: vec![TestWasm::EntryDefs.into()].into(),
+            },
+            vec![TestWasm::EntryDefs.into()],
+        )
+        .await
+        .unwrap();
+        let mut entry_def = fixt!(EntryDef);
+        entry_def.required_validations = required_validations.into();
+
+        let mut conductor_api = MockCellConductorApi::new();
+        conductor_api.expect_sync_get_this_dna().returning({
+            let dna_file = dna_file.clone();
+            move || Ok(dna_file.clone())
+        });
+        conductor_api
+            .expect_sync_get_dna()
+            .return_const(Some(dna_file));
+        conductor_api
+            .expect_sync_get_entry_def()
+            .return_const(Some(entry_def));
+        conductor_api
+    }
+
     /// Call the workflow
     async fn call_workflow(env: EnvironmentWrite, mut cell_network: HolochainP2pCell) {
         let workspace = PublishDhtOpsWorkspace::new(env.clone().into()).unwrap();
-        publish_dht_ops_workflow(workspace, env.clone().into(), &mut cell_network)
-            .await
-            .unwrap();
+        let conductor_api = test_conductor_api(DEFAULT_RECEIPT_BUNDLE_SIZE).await;
+        publish_dht_ops_workflow(
+            workspace,
+            env.clone().into(),
+            &mut cell_network,
+            &conductor_api,
+            &mut WorkflowRun::start(),
+        )
+        .await
+        .unwrap();
     }
 
     /// There is a test that shows that network messages would be sent to all agents via broadcast.
@@ -408,7 +521,7 @@ mod tests {
                     .iter(&reader)
                     .unwrap()
                     .map(|(k, mut v)| {
-                        v.receipt_count = DEFAULT_RECEIPT_BUNDLE_SIZE;
+                        v.receipt_count = DEFAULT_RECEIPT_BUNDLE_SIZE.into();
                         Ok((DhtOpHash::from_raw_39_panicky(k.to_vec()), v))
                     })
                     .collect::<Vec<_>>()
@@ -443,6 +556,77 @@ mod tests {
         });
     }
 
+    /// Ops about an app entry collect as many receipts as its entry def requires,
+    /// and are published to that many agents
+    #[tokio::test(threaded_scheduler)]
+    async fn test_required_validations_from_entry_def() {
+        observability::test_run().ok();
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let env_ref = env.guard();
+
+        // Author an op for an app entry which already has two receipts
+        let mut create = fixt!(Create);
+        create.entry_type = EntryType::App(AppEntryType::new(
+            0.into(),
+            0.into(),
+            EntryVisibility::Public,
+        ));
+        let header_hash = HeaderHashed::from_content_sync(create.clone().into());
+        let op_light = DhtOpLight::StoreElement(
+            header_hash.as_hash().clone(),
+            Some(create.entry_hash.clone()),
+            header_hash.as_hash().clone().into(),
+        );
+        {
+            let mut workspace = PublishDhtOpsWorkspace::new(env.clone().into()).unwrap();
+            let mut authored_value = AuthoredDhtOpsValue::from_light(op_light.clone());
+            authored_value.receipt_count = 2;
+            workspace
+                .authored_dht_ops
+                .put(fixt!(DhtOpHash), authored_value)
+                .unwrap();
+            let signed_header = SignedHeaderHashed::with_presigned(header_hash, fixt!(Signature));
+            workspace.elements.put(signed_header, None).unwrap();
+            env_ref
+                .with_commit::<DatabaseError, _, _>(|writer| {
+                    workspace.authored_dht_ops.flush_to_txn(writer)?;
+                    workspace.elements.flush_to_txn(writer)?;
+                    Ok(())
+                })
+                .unwrap();
+        }
+        let mut workspace = PublishDhtOpsWorkspace::new(env.clone().into()).unwrap();
+
+        // Two receipts are enough when the entry def requires two
+        let to_publish =
+            publish_dht_ops_workflow_inner(&mut workspace, &test_conductor_api(2).await)
+                .await
+                .unwrap();
+        assert!(to_publish.is_empty());
+
+        // but not when it requires seven
+        let to_publish =
+            publish_dht_ops_workflow_inner(&mut workspace, &test_conductor_api(7).await)
+                .await
+                .unwrap();
+        assert_eq!(to_publish.len(), 1);
+        let (remote_agent_count, ops) = to_publish.values().next().unwrap();
+        assert_eq!(*remote_agent_count, Some(7));
+        assert_eq!(ops.len(), 1);
+
+        // An entry def requiring no receipts still has its ops published to one agent
+        let required_validations = RequiredValidationsCache::default()
+            .get(
+                &op_light,
+                workspace.elements(),
+                &test_conductor_api(0).await,
+            )
+            .await
+            .unwrap();
+        assert_eq!(required_validations, Some(1));
+    }
+
     /// There is a test to shows that DHTOps that were produced on private entries are not published.
     /// Some do get published
     /// Current private constraints:
@@ -499,9 +683,14 @@ mod tests {
                 {
                     let workspace = ProduceDhtOpsWorkspace::new(env.clone().into()).unwrap();
                     let (mut qt, _rx) = TriggerSender::new();
-                    let complete = produce_dht_ops_workflow(workspace, env.clone().into(), &mut qt)
-                        .await
-                        .unwrap();
+                    let complete = produce_dht_ops_workflow(
+                        workspace,
+                        env.clone().into(),
+                        &mut qt,
+                        &mut WorkflowRun::start(),
+                    )
+                    .await
+                    .unwrap();
                     assert_matches!(complete, WorkComplete::Complete);
                 }
                 {
@@ -638,9 +827,14 @@ mod tests {
                 {
                     let workspace = ProduceDhtOpsWorkspace::new(env.clone().into()).unwrap();
                     let (mut qt, _rx) = TriggerSender::new();
-                    let complete = produce_dht_ops_workflow(workspace, env.clone().into(), &mut qt)
-                        .await
-                        .unwrap();
+                    let complete = produce_dht_ops_workflow(
+                        workspace,
+                        env.clone().into(),
+                        &mut qt,
+                        &mut WorkflowRun::start(),
+                    )
+                    .await
+                    .unwrap();
                     assert_matches!(complete, WorkComplete::Complete);
                 }
 
@@ -3,6 +3,7 @@
 
 use super::*;
 use crate::conductor::api::CellConductorApiT;
+use crate::core::metrics::WorkflowRun;
 use crate::core::queue_consumer::OneshotWriter;
 use crate::core::queue_consumer::TriggerSender;
 use crate::core::queue_consumer::WorkComplete;
@@ -11,6 +12,7 @@ use crate::core::validation::*;
 use error::WorkflowError;
 use error::WorkflowResult;
 use fallible_iterator::FallibleIterator;
+use futures::stream::StreamExt;
 use holo_hash::DhtOpHash;
 use holochain_cascade::Cascade;
 use holochain_cascade::DbPair;
@@ -49,7 +51,8 @@ mod tests;
     trigger_app_validation,
     sys_validation_trigger,
     network,
-    conductor_api
+    conductor_api,
+    run
 ))]
 pub async fn sys_validation_workflow(
     mut workspace: SysValidationWorkspace,
@@ -57,20 +60,45 @@ pub async fn sys_validation_workflow(
     trigger_app_validation: &mut TriggerSender,
     sys_validation_trigger: TriggerSender,
     network: HolochainP2pCell,
-    conductor_api: impl CellConductorApiT,
+    conductor_api: impl CellConductorApiT + 'static,
+    validation_workers: usize,
+    run: &mut WorkflowRun,
 ) -> WorkflowResult<WorkComplete> {
-    let complete = sys_validation_workflow_inner(
+    let (complete, mut validated) = sys_validation_workflow_inner(
         &mut workspace,
         network,
         conductor_api,
-        sys_validation_trigger,
+        sys_validation_trigger.clone(),
+        validation_workers,
+        run,
     )
     .await?;
 
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
-    // commit the workspace
-    writer.with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))?;
+    // commit the workspace and every worker's results together.
+    // The drained ops are removed from the limbo first
+    // so the workers can put them back.
+    writer
+        .with_writer(|writer| {
+            workspace.flush_to_txn_ref(writer)?;
+            for worker_workspace in validated.iter_mut() {
+                worker_workspace.flush_to_txn_ref(writer)?;
+            }
+            Ok(())
+        })
+        .await?;
+
+    // Ops awaiting dependencies are skipped until their backoff
+    // has elapsed so make sure we run again by then.
+    // App validation is triggered from here so this covers its ops too.
+    if let Some(next_retry) = next_retry(&workspace.env)? {
+        let delay = next_retry
+            .checked_difference_signed(&timestamp::now())
+            .and_then(|d| d.to_std().ok())
+            .unwrap_or_default();
+        sys_validation_trigger.trigger_after(delay);
+    }
 
     // trigger other workflows
     trigger_app_validation.trigger();
@@ -78,27 +106,51 @@ pub async fn sys_validation_workflow(
     Ok(complete)
 }
 
+/// The earliest time an op in the validation limbo is due to be retried
+fn next_retry(env: &EnvironmentRead) -> WorkflowResult<Option<Timestamp>> {
+    let validation_limbo = ValidationLimboStore::new(env.clone())?;
+    let next_retry = fresh_reader!(env, |r| validation_limbo
+        .iter(&r)?
+        .filter_map(|(_, vlv)| Ok(vlv.next_retry()))
+        .min())?;
+    Ok(next_retry)
+}
+
 async fn sys_validation_workflow_inner(
     workspace: &mut SysValidationWorkspace,
     network: HolochainP2pCell,
-    conductor_api: impl CellConductorApiT,
+    conductor_api: impl CellConductorApiT + 'static,
     sys_validation_trigger: TriggerSender,
-) -> WorkflowResult<WorkComplete> {
+    validation_workers: usize,
+    run: &mut WorkflowRun,
+) -> WorkflowResult<(WorkComplete, Vec<SysValidationWorkspace>)> {
     let env = workspace.validation_limbo.env().clone();
     // Drain all the ops
     let sorted_ops: BinaryHeap<OrderedOp<ValidationLimboValue>> = fresh_reader!(env, |r| {
         let validation_limbo = &mut workspace.validation_limbo;
         let element_pending = &workspace.element_pending;
+        // Stores a dependency turns up in once it is integrated or fetched
+        let dep_stores: [&dyn HoldsDep; 3] = [
+            &workspace.element_vault,
+            &workspace.element_rejected,
+            &workspace.element_cache,
+        ];
+        let now = timestamp::now();
 
         let sorted_ops: Result<BinaryHeap<OrderedOp<ValidationLimboValue>>, WorkflowError> =
             validation_limbo
-                .drain_iter_filter(&r, |(_, vlv)| {
+                .drain_iter_filter(&r, move |(_, vlv)| {
                     match vlv.status {
                         // We only want pending or awaiting sys dependency ops
-                        ValidationLimboStatus::Pending
-                        | ValidationLimboStatus::AwaitingSysDeps(_) => Ok(true),
+                        ValidationLimboStatus::Pending => Ok(true),
+                        // that have a reason to be tried again
+                        ValidationLimboStatus::AwaitingSysDeps(_)
+                        | ValidationLimboStatus::AwaitingKeyState => {
+                            ready_to_validate(vlv, now, &dep_stores)
+                        }
                         ValidationLimboStatus::SysValidated
-                        | ValidationLimboStatus::AwaitingAppDeps(_) => Ok(false),
+                        | ValidationLimboStatus::AwaitingAppDeps(_)
+                        | ValidationLimboStatus::AwaitingValidationPackage => Ok(false),
                     }
                 })?
                 .map_err(WorkflowError::from)
@@ -121,12 +173,43 @@ async fn sys_validation_workflow_inner(
         sorted_ops
     })?;
 
-    // Process each op
-    for so in sorted_ops.into_sorted_vec() {
+    // An op can depend on the ops before it in its author's chain
+    // so each author's ops are validated in order by a single worker.
+    // Different authors are validated concurrently, each in a workspace
+    // of their own.
+    run.processed(sorted_ops.len());
+    let mut workers = futures::stream::iter(ops_by_author(sorted_ops.into_sorted_vec()))
+        .map(|ops| {
+            tokio::spawn(validate_chain(
+                ops,
+                env.clone(),
+                network.clone(),
+                conductor_api.clone(),
+                sys_validation_trigger.clone(),
+            ))
+        })
+        .buffer_unordered(validation_workers.max(1));
+    let mut validated = Vec::new();
+    while let Some(worker_workspace) = workers.next().await {
+        validated.push(worker_workspace??);
+    }
+    Ok((WorkComplete::Complete, validated))
+}
+
+/// Validate one author's ops in order
+async fn validate_chain(
+    ops: Vec<OrderedOp<ValidationLimboValue>>,
+    env: EnvironmentRead,
+    network: HolochainP2pCell,
+    conductor_api: impl CellConductorApiT,
+    sys_validation_trigger: TriggerSender,
+) -> WorkflowResult<SysValidationWorkspace> {
+    let mut workspace = SysValidationWorkspace::new(env)?;
+    for so in ops {
         let OrderedOp {
             hash: op_hash,
             op,
-            value: mut vlv,
+            value: vlv,
             ..
         } = so;
 
@@ -138,52 +221,15 @@ async fn sys_validation_workflow_inner(
 
         let outcome = validate_op(
             &op,
-            workspace,
+            &mut workspace,
             network.clone(),
             &conductor_api,
             Some(incoming_dht_ops_sender),
         )
         .await?;
-
-        match outcome {
-            Outcome::Accepted => {
-                vlv.status = ValidationLimboStatus::SysValidated;
-                workspace.put_val_limbo(op_hash, vlv)?;
-            }
-            Outcome::SkipAppValidation => {
-                let iv = IntegrationLimboValue {
-                    op: vlv.op,
-                    validation_status: ValidationStatus::Valid,
-                };
-                workspace.put_int_limbo(op_hash, iv)?;
-            }
-            Outcome::AwaitingOpDep(missing_dep) => {
-                // TODO: Try and get this dependency to add to limbo
-                //
-                // I actually can't see how we can do this because there's no
-                // way to get an DhtOpHash without either having the op or the full
-                // header. We have neither that's why where here.
-                //
-                // We need to be holding the dependency because
-                // we were meant to get a StoreElement or StoreEntry or
-                // RegisterAgentActivity or RegisterAddLink.
-                vlv.status = ValidationLimboStatus::AwaitingSysDeps(missing_dep);
-                workspace.put_val_limbo(op_hash, vlv)?;
-            }
-            Outcome::MissingDhtDep => {
-                vlv.status = ValidationLimboStatus::Pending;
-                workspace.put_val_limbo(op_hash, vlv)?;
-            }
-            Outcome::Rejected => {
-                let iv = IntegrationLimboValue {
-                    op: vlv.op,
-                    validation_status: ValidationStatus::Rejected,
-                };
-                workspace.put_int_limbo(op_hash, iv)?;
-            }
-        }
+        workspace.put_outcome(op_hash, vlv, outcome)?;
     }
-    Ok(WorkComplete::Complete)
+    Ok(workspace)
 }
 
 async fn validate_op(
@@ -233,12 +279,13 @@ fn handle_failed(error: ValidationOutcome) -> Outcome {
         ValidationOutcome::Counterfeit(_, _) => {
             unreachable!("Counterfeit ops are dropped before sys validation")
         }
-        ValidationOutcome::DepMissingFromDht(_) => MissingDhtDep,
+        ValidationOutcome::DepMissingFromDht(dep) => MissingDhtDep(dep),
         ValidationOutcome::EntryDefId(_) => Rejected,
         ValidationOutcome::EntryHash => Rejected,
         ValidationOutcome::EntryTooLarge(_, _) => Rejected,
         ValidationOutcome::EntryType => Rejected,
         ValidationOutcome::EntryVisibility(_) => Rejected,
+        ValidationOutcome::KeyStateUnavailable(_) => AwaitingKeyState,
         ValidationOutcome::TagTooLarge(_, _) => Rejected,
         ValidationOutcome::NotCreateLink(_) => Rejected,
         ValidationOutcome::NotNewEntry(_) => Rejected,
@@ -248,6 +295,7 @@ fn handle_failed(error: ValidationOutcome) -> Outcome {
         }
         ValidationOutcome::PrevHeaderError(_) => Rejected,
         ValidationOutcome::PrivateEntry => Rejected,
+        ValidationOutcome::RevokedAuthor(_, _) => Rejected,
         ValidationOutcome::UpdateTypeMismatch(_, _) => Rejected,
         ValidationOutcome::VerifySignature(_, _) => Rejected,
         ValidationOutcome::ZomeId(_) => Rejected,
@@ -261,6 +309,14 @@ async fn validate_op_inner(
     conductor_api: &impl CellConductorApiT,
     incoming_dht_ops_sender: Option<IncomingDhtOpSender>,
 ) -> SysValidationResult<()> {
+    let op_header = op.header();
+    author_key_is_valid(
+        op_header.author(),
+        op_header.timestamp(),
+        conductor_api,
+        &mut workspace.key_states,
+    )
+    .await?;
     match op {
         DhtOp::StoreElement(_, header, entry) => {
             store_element(header, workspace, network.clone()).await?;
@@ -350,42 +406,56 @@ async fn validate_op_inner(
     }
 }
 
-#[instrument(skip(element, call_zome_workspace, network, conductor_api))]
+#[instrument(skip(element, workspace, call_zome_workspace, network, conductor_api))]
 /// Direct system validation call that takes
 /// an Element instead of an op.
 /// Does not require holding dependencies.
 /// Will not await dependencies and instead returns
 /// that outcome immediately.
+/// The `workspace` is made once per zome call from the call zome workspace,
+/// so it holds the whole uncommitted chain.
 pub async fn sys_validate_element(
     element: &Element,
+    workspace: &mut SysValidationWorkspace,
     call_zome_workspace: &mut CallZomeWorkspace,
     network: HolochainP2pCell,
     conductor_api: &impl CellConductorApiT,
 ) -> SysValidationOutcome<()> {
     trace!(?element);
-    // Create a SysValidationWorkspace with the scratches from the CallZomeWorkspace
-    let mut workspace = SysValidationWorkspace::try_from(&*call_zome_workspace)?;
-    let result =
-        match sys_validate_element_inner(element, &mut workspace, network, conductor_api).await {
-            // Validation succeeded
-            Ok(_) => Ok(()),
-            // Validation failed so exit with that outcome
-            Err(SysValidationError::ValidationOutcome(validation_outcome)) => {
-                error!(msg = "Direct validation failed", ?element);
-                validation_outcome.into_outcome()
-            }
-            // An error occurred so return it
-            Err(e) => Err(OutcomeOrError::Err(e)),
-        };
-
-    // Set the call zome workspace to the updated
-    // cache from the sys validation workspace
-    call_zome_workspace.meta_cache = workspace.meta_cache;
-    call_zome_workspace.element_cache = workspace.element_cache;
+    // Validate with the call zome workspace's caches, so each sees
+    // what the other has fetched
+    swap_caches(workspace, call_zome_workspace);
+    let result = match sys_validate_element_inner(element, workspace, network, conductor_api).await
+    {
+        // Validation succeeded
+        Ok(_) => Ok(()),
+        // Validation failed so exit with that outcome
+        Err(SysValidationError::ValidationOutcome(validation_outcome)) => {
+            error!(msg = "Direct validation failed", ?element);
+            validation_outcome.into_outcome()
+        }
+        // An error occurred so return it
+        Err(e) => Err(OutcomeOrError::Err(e)),
+    };
+    swap_caches(workspace, call_zome_workspace);
 
     result
 }
 
+fn swap_caches(
+    workspace: &mut SysValidationWorkspace,
+    call_zome_workspace: &mut CallZomeWorkspace,
+) {
+    std::mem::swap(
+        &mut workspace.element_cache,
+        &mut call_zome_workspace.element_cache,
+    );
+    std::mem::swap(
+        &mut workspace.meta_cache,
+        &mut call_zome_workspace.meta_cache,
+    );
+}
+
 async fn sys_validate_element_inner(
     element: &Element,
     workspace: &mut SysValidationWorkspace,
@@ -399,6 +469,13 @@ async fn sys_validate_element_inner(
     if !counterfeit_check(signature, header).await? {
         return Err(ValidationOutcome::Counterfeit(signature.clone(), header.clone()).into());
     }
+    author_key_is_valid(
+        header.author(),
+        header.timestamp(),
+        conductor_api,
+        &mut workspace.key_states,
+    )
+    .await?;
     store_element(header, workspace, network.clone()).await?;
     if let Some((entry, EntryVisibility::Public)) =
         &entry.and_then(|e| header.entry_type().map(|et| (e, et.visibility())))
@@ -433,14 +510,15 @@ async fn sys_validate_element_inner(
     Ok(())
 }
 
-/// Check if the op has valid signature and author.
+/// Check if the op has a valid signature.
 /// Ops that fail this check should be dropped.
+/// Whether the author's key was revoked is checked
+/// during validation as it needs the conductor.
 pub async fn counterfeit_check(
     signature: &Signature,
     header: &Header,
 ) -> SysValidationResult<bool> {
-    Ok(verify_header_signature(&signature, &header).await?
-        && author_key_is_valid(header.author()).await?)
+    verify_header_signature(&signature, &header).await
 }
 
 async fn register_agent_activity(
@@ -698,6 +776,8 @@ pub struct SysValidationWorkspace {
     /// Cached data
     pub element_cache: ElementBuf,
     pub meta_cache: MetadataBuf,
+    /// Author key states looked up with dpki on this run
+    pub key_states: KeyStateCache,
     pub env: EnvironmentRead,
 }
 
@@ -755,10 +835,62 @@ impl SysValidationWorkspace {
             meta_authored,
             element_cache,
             meta_cache,
+            key_states: KeyStateCache::default(),
             env,
         })
     }
 
+    /// Move an op to wherever the outcome of validating it sends it
+    fn put_outcome(
+        &mut self,
+        op_hash: DhtOpHash,
+        mut vlv: ValidationLimboValue,
+        outcome: Outcome,
+    ) -> WorkflowResult<()> {
+        match outcome {
+            Outcome::Accepted => {
+                vlv.status = ValidationLimboStatus::SysValidated;
+                self.put_val_limbo(op_hash, vlv)?;
+            }
+            Outcome::SkipAppValidation => {
+                let iv = IntegrationLimboValue {
+                    op: vlv.op,
+                    validation_status: ValidationStatus::Valid,
+                };
+                self.put_int_limbo(op_hash, iv)?;
+            }
+            Outcome::AwaitingOpDep(missing_dep) => {
+                // TODO: Try and get this dependency to add to limbo
+                //
+                // I actually can't see how we can do this because there's no
+                // way to get an DhtOpHash without either having the op or the full
+                // header. We have neither that's why where here.
+                //
+                // We need to be holding the dependency because
+                // we were meant to get a StoreElement or StoreEntry or
+                // RegisterAgentActivity or RegisterAddLink.
+                vlv.status = ValidationLimboStatus::AwaitingSysDeps(missing_dep);
+                self.put_val_limbo_or_abandon(op_hash, vlv)?;
+            }
+            Outcome::MissingDhtDep(missing_dep) => {
+                vlv.status = ValidationLimboStatus::AwaitingSysDeps(missing_dep);
+                self.put_val_limbo_or_abandon(op_hash, vlv)?;
+            }
+            Outcome::AwaitingKeyState => {
+                vlv.status = ValidationLimboStatus::AwaitingKeyState;
+                self.put_val_limbo(op_hash, vlv)?;
+            }
+            Outcome::Rejected => {
+                let iv = IntegrationLimboValue {
+                    op: vlv.op,
+                    validation_status: ValidationStatus::Rejected,
+                };
+                self.put_int_limbo(op_hash, iv)?;
+            }
+        }
+        Ok(())
+    }
+
     fn put_val_limbo(
         &mut self,
         hash: DhtOpHash,
@@ -770,6 +902,28 @@ impl SysValidationWorkspace {
         Ok(())
     }
 
+    /// Put an op that is awaiting dependencies back in the validation limbo
+    /// or abandon it if it has been tried too many times
+    fn put_val_limbo_or_abandon(
+        &mut self,
+        hash: DhtOpHash,
+        vlv: ValidationLimboValue,
+    ) -> WorkflowResult<()> {
+        if vlv.should_abandon() {
+            warn!(
+                msg = "Abandoning op that is still missing dependencies",
+                ?vlv
+            );
+            let iv = IntegrationLimboValue {
+                op: vlv.op,
+                validation_status: ValidationStatus::Abandoned,
+            };
+            self.put_int_limbo(hash, iv)
+        } else {
+            self.put_val_limbo(hash, vlv)
+        }
+    }
+
     #[tracing::instrument(skip(self, hash))]
     fn put_int_limbo(&mut self, hash: DhtOpHash, iv: IntegrationLimboValue) -> WorkflowResult<()> {
         self.integration_limbo.put(hash, iv)?;
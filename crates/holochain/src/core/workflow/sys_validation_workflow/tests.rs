@@ -1,4 +1,10 @@
+use crate::conductor::api::error::ConductorApiError;
+use crate::conductor::api::MockCellConductorApi;
 use crate::conductor::ConductorHandle;
+use crate::core::metrics::WorkflowRun;
+use crate::core::queue_consumer::TriggerSender;
+use crate::core::sys_validate::ValidationOutcome;
+use crate::core::validation::ready_to_validate;
 use crate::core::workflow::incoming_dht_ops_workflow::IncomingDhtOpsWorkspace;
 use crate::test_utils::host_fn_caller::*;
 use crate::test_utils::setup_app;
@@ -10,19 +16,29 @@ use holo_hash::AnyDhtHash;
 use holo_hash::DhtOpHash;
 use holo_hash::EntryHash;
 use holo_hash::HeaderHash;
+use holochain_lmdb::env::EnvironmentWrite;
 use holochain_lmdb::fresh_reader_test;
 use holochain_lmdb::prelude::ReadManager;
+use holochain_lmdb::prelude::WriteManager;
+use holochain_lmdb::test_utils::test_cell_env;
+use holochain_p2p::HolochainP2pCellFixturator;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_state::element_buf::ElementBuf;
 use holochain_state::validation_db::ValidationLimboStatus;
+use holochain_state::validation_db::ValidationLimboValue;
+use holochain_state::validation_db::VALIDATION_MAX_TRIES;
+use holochain_state::workspace::Workspace;
+use holochain_state::workspace::WorkspaceError;
 use holochain_types::prelude::*;
 use holochain_wasm_test_utils::TestWasm;
 use holochain_zome_types::cell::CellId;
 use holochain_zome_types::Entry;
 use holochain_zome_types::ValidationStatus;
 use matches::assert_matches;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::convert::TryInto;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::*;
 
@@ -66,6 +82,245 @@ async fn sys_validation_workflow_test() {
     shutdown.await.unwrap();
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn ops_are_not_abandoned_while_dpki_is_unavailable() {
+    let test_env = test_cell_env();
+    let mut workspace = super::SysValidationWorkspace::new(test_env.env().into()).unwrap();
+    let op_hash = fixt!(DhtOpHash);
+    let mut vlv = ValidationLimboValue {
+        status: ValidationLimboStatus::Pending,
+        op: DhtOpLight::RegisterAgentActivity(fixt!(HeaderHash), fixt!(AnyDhtHash)),
+        basis: fixt!(AnyDhtHash),
+        time_added: timestamp::now(),
+        last_try: None,
+        num_tries: 0,
+        from_agent: None,
+    };
+
+    // Every run fails to reach dpki
+    for _ in 0..VALIDATION_MAX_TRIES * 2 {
+        let outcome =
+            super::handle_failed(ValidationOutcome::KeyStateUnavailable(fake_agent_pubkey_1()));
+        workspace
+            .put_outcome(op_hash.clone(), vlv, outcome)
+            .unwrap();
+        vlv = workspace
+            .validation_limbo
+            .get(&op_hash)
+            .unwrap()
+            .expect("The op was abandoned");
+        assert_eq!(vlv.status, ValidationLimboStatus::AwaitingKeyState);
+        // It waits for the backoff before it is tried again
+        assert!(!ready_to_validate(&vlv, timestamp::now(), &[]).unwrap());
+    }
+    assert_eq!(vlv.num_tries, VALIDATION_MAX_TRIES * 2);
+    assert!(workspace.integration_limbo.get(&op_hash).unwrap().is_none());
+}
+
+/// Put a pending `RegisterAgentActivity` op in the validation limbo
+/// for a Dna header by each author at each of these times
+fn put_pending_activity(
+    env: &EnvironmentWrite,
+    authors: &[AgentPubKey],
+    times: &[i64],
+) -> Vec<DhtOpHash> {
+    let mut workspace = super::SysValidationWorkspace::new(env.clone().into()).unwrap();
+    let mut op_hashes = Vec::new();
+    for author in authors {
+        for secs in times {
+            let header = Header::Dna(Dna {
+                author: author.clone(),
+                timestamp: Timestamp(*secs, 0),
+                hash: fixt!(DnaHash),
+            });
+            let signature = fixt!(Signature);
+            workspace
+                .element_pending
+                .put(
+                    SignedHeaderHashed::with_presigned(
+                        HeaderHashed::from_content_sync(header.clone()),
+                        signature.clone(),
+                    ),
+                    None,
+                )
+                .unwrap();
+            let op = DhtOp::RegisterAgentActivity(signature, header);
+            let op_hash = DhtOpHash::with_data_sync(&op);
+            workspace
+                .validation_limbo
+                .put(
+                    op_hash.clone(),
+                    ValidationLimboValue {
+                        status: ValidationLimboStatus::Pending,
+                        op: op.to_light(),
+                        basis: op.dht_basis(),
+                        time_added: timestamp::now(),
+                        last_try: None,
+                        num_tries: 0,
+                        from_agent: None,
+                    },
+                )
+                .unwrap();
+            op_hashes.push(op_hash);
+        }
+    }
+    env.guard()
+        .with_commit::<WorkspaceError, _, _>(|writer| workspace.flush_to_txn_ref(writer))
+        .unwrap();
+    op_hashes
+}
+
+/// Dpki lookups made by the validation workers
+#[derive(Clone, Default)]
+struct DpkiLookups {
+    /// Every lookup in the order they were made
+    made: Arc<parking_lot::Mutex<Vec<(AgentPubKey, Timestamp)>>>,
+    /// The authors being looked up so far
+    authors: Arc<(std::sync::Mutex<HashSet<AgentPubKey>>, std::sync::Condvar)>,
+}
+
+/// A conductor api which only answers dpki lookups once
+/// `authors` different authors have been looked up,
+/// so they only succeed if those authors are validated at the same time.
+fn concurrent_dpki_api(lookups: DpkiLookups, authors: usize) -> MockCellConductorApi {
+    let mut conductor_api = MockCellConductorApi::new();
+    let l = lookups.clone();
+    conductor_api
+        .expect_sync_dpki_key_state()
+        .returning(move |author, at| {
+            l.made.lock().push((author.clone(), at));
+            // Let the runtime carry on with the other workers while this one waits
+            tokio::task::block_in_place(|| {
+                let (looked_up, all_here) = &*l.authors;
+                let mut looked_up = looked_up.lock().unwrap();
+                looked_up.insert(author);
+                all_here.notify_all();
+                let (_, waited) = all_here
+                    .wait_timeout_while(looked_up, Duration::from_secs(5), |looked_up| {
+                        looked_up.len() < authors
+                    })
+                    .unwrap();
+                if waited.timed_out() {
+                    Err(ConductorApiError::DpkiUnavailable)
+                } else {
+                    Ok(None)
+                }
+            })
+        });
+    conductor_api.expect_cell_id().return_const(fake_cell_id(1));
+    conductor_api
+        .expect_clone()
+        .returning(move || concurrent_dpki_api(lookups.clone(), authors));
+    conductor_api
+}
+
+async fn call_sys_validation_workflow(
+    env: &EnvironmentWrite,
+    conductor_api: MockCellConductorApi,
+    validation_workers: usize,
+) -> WorkflowResult<WorkComplete> {
+    let workspace = super::SysValidationWorkspace::new(env.clone().into()).unwrap();
+    let (mut trigger_app_validation, _rx) = TriggerSender::new();
+    let (sys_validation_trigger, _rx) = TriggerSender::new();
+    super::sys_validation_workflow(
+        workspace,
+        env.clone().into(),
+        &mut trigger_app_validation,
+        sys_validation_trigger,
+        fixt!(HolochainP2pCell),
+        conductor_api,
+        validation_workers,
+        &mut WorkflowRun::start(),
+    )
+    .await
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn authors_are_validated_concurrently_and_their_ops_in_order() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let authors = vec![fake_agent_pubkey_1(), fake_agent_pubkey_2()];
+    let times = [3, 1, 2];
+    let op_hashes = put_pending_activity(&env, &authors, &times);
+
+    let lookups = DpkiLookups::default();
+    call_sys_validation_workflow(&env, concurrent_dpki_api(lookups.clone(), authors.len()), 2)
+        .await
+        .unwrap();
+
+    // Each author's ops were validated in chain order
+    for author in &authors {
+        let times: Vec<_> = lookups
+            .made
+            .lock()
+            .iter()
+            .filter(|(a, _)| a == author)
+            .map(|(_, at)| at.0)
+            .collect();
+        assert_eq!(times, vec![1, 2, 3]);
+    }
+    // No lookup timed out waiting for the other author,
+    // so every op went through to integration
+    let workspace = super::SysValidationWorkspace::new(env.clone().into()).unwrap();
+    for op_hash in &op_hashes {
+        assert!(workspace.validation_limbo.get(op_hash).unwrap().is_none());
+        assert_eq!(
+            workspace
+                .integration_limbo
+                .get(op_hash)
+                .unwrap()
+                .map(|iv| iv.validation_status),
+            Some(ValidationStatus::Valid)
+        );
+    }
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn no_results_are_committed_if_a_worker_fails() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let failing_author = fake_agent_pubkey_2();
+    let op_hashes = put_pending_activity(
+        &env,
+        &[fake_agent_pubkey_1(), failing_author.clone()],
+        &[1, 2],
+    );
+
+    let mut conductor_api = MockCellConductorApi::new();
+    conductor_api.expect_clone().returning(move || {
+        let failing_author = failing_author.clone();
+        let mut conductor_api = MockCellConductorApi::new();
+        conductor_api
+            .expect_sync_dpki_key_state()
+            .returning(move |author, _| {
+                if author == failing_author {
+                    panic!("The worker for this author fails");
+                }
+                Ok(None)
+            });
+        conductor_api
+    });
+    assert!(call_sys_validation_workflow(&env, conductor_api, 2)
+        .await
+        .is_err());
+
+    // The ops the other worker validated are still pending
+    let workspace = super::SysValidationWorkspace::new(env.clone().into()).unwrap();
+    for op_hash in &op_hashes {
+        assert_eq!(
+            workspace
+                .validation_limbo
+                .get(op_hash)
+                .unwrap()
+                .map(|vlv| vlv.status),
+            Some(ValidationLimboStatus::Pending)
+        );
+        assert!(workspace.integration_limbo.get(op_hash).unwrap().is_none());
+    }
+}
+
 async fn run_test(
     alice_cell_id: CellId,
     bob_cell_id: CellId,
@@ -279,6 +534,7 @@ async fn run_test(
                         assert_matches!(
                             i.status,
                             ValidationLimboStatus::Pending
+                                | ValidationLimboStatus::AwaitingSysDeps(_)
                                 | ValidationLimboStatus::AwaitingAppDeps(_)
                         );
                         Ok(())
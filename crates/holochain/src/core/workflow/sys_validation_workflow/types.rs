@@ -13,7 +13,10 @@ pub(super) enum Outcome {
     /// Stays in limbo because a dependency could not
     /// be found currently on the DHT.
     /// Note this is not proof it doesn't exist.
-    MissingDhtDep,
+    MissingDhtDep(AnyDhtHash),
+    /// Stays in limbo because dpki could not be reached
+    /// to check the author's key state
+    AwaitingKeyState,
     /// Moves to integration with status rejected
     Rejected,
 }
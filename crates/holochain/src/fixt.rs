@@ -290,8 +290,10 @@ fixturator!(
 );
 
 fn make_call_zome_handle(cell_id: CellId) -> CellConductorReadHandle {
-    let handle = Arc::new(MockConductorHandleT::new());
-    let cell_conductor_api = CellConductorApi::new(handle, cell_id);
+    let mut handle = MockConductorHandleT::new();
+    // Host fns fall back to the entry defs callback
+    handle.expect_get_entry_def().return_const(None);
+    let cell_conductor_api = CellConductorApi::new(Arc::new(handle), cell_id);
     Arc::new(cell_conductor_api)
 }
 
@@ -412,7 +414,7 @@ fixturator!(
 
 fixturator!(
     ValidationPackageInvocation;
-    constructor fn new(Zome, AppEntryType);
+    constructor fn new(Zome, AppEntryType, EntryDefId);
 );
 
 fixturator!(
@@ -4,7 +4,6 @@ use crate::conductor::api::RealAppInterfaceApi;
 use crate::conductor::api::ZomeCall;
 use crate::conductor::config::AdminInterfaceConfig;
 use crate::conductor::config::ConductorConfig;
-use crate::conductor::config::InterfaceDriver;
 use crate::conductor::p2p_store;
 use crate::conductor::ConductorBuilder;
 use crate::conductor::ConductorHandle;
@@ -288,9 +287,7 @@ pub async fn setup_app_inner(
 ) -> (Arc<TempDir>, RealAppInterfaceApi, ConductorHandle) {
     let conductor_handle = ConductorBuilder::new()
         .config(ConductorConfig {
-            admin_interfaces: Some(vec![AdminInterfaceConfig {
-                driver: InterfaceDriver::Websocket { port: 0 },
-            }]),
+            admin_interfaces: Some(vec![AdminInterfaceConfig::websocket(0)]),
             network,
             ..Default::default()
         })
@@ -498,7 +495,9 @@ async fn get_counts(envs: &[&EnvironmentWrite]) -> IntegrationStateDumps {
 }
 
 async fn count_integration(env: &EnvironmentWrite) -> IntegrationStateDump {
-    integrate_dht_ops_workflow::dump_state(env.clone().into()).unwrap()
+    integrate_dht_ops_workflow::dump_state(env.clone().into())
+        .await
+        .unwrap()
 }
 
 async fn display_integration(env: &EnvironmentWrite) -> usize {
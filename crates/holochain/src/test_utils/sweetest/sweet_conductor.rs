@@ -110,7 +110,7 @@ impl SweetConductorBatch {
     /// Let each conductor know about each others' agents so they can do networking
     pub async fn exchange_peer_info(&self) {
         let envs = self.0.iter().map(|c| c.envs().p2p()).collect();
-        crate::conductor::p2p_store::exchange_peer_info(envs);
+        crate::conductor::p2p_store::exchange_peer_info(envs).await;
     }
 }
 
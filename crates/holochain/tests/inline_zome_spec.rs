@@ -377,3 +377,78 @@ async fn simple_validation() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Zome whose validation calls the host function named by the entry
+fn non_deterministic_validation_zome() -> InlineZome {
+    let entry_def = EntryDef::default_with_id("string");
+
+    InlineZome::new_unique(vec![entry_def.clone()])
+        .callback("create", move |api, s: AppString| {
+            let entry_def_id: EntryDefId = entry_def.id.clone();
+            let entry = Entry::app(s.try_into().unwrap()).unwrap();
+            let hash = api.create(EntryWithDefId::new(entry_def_id, entry))?;
+            Ok(hash)
+        })
+        .callback("validate_create_entry", |api, data: ValidateData| {
+            let s: AppString = data.element.entry().to_app_option().unwrap().unwrap();
+            let hash = data.element.header_address().clone().into();
+            let result = match s.0.as_str() {
+                "sys_time" => api.sys_time(()).map(|_| ()),
+                "random_bytes" => api.random_bytes(1).map(|_| ()),
+                "get_latest" => api
+                    .get(GetInput::new(hash, GetOptions::latest()))
+                    .map(|_| ()),
+                "get_content" => api
+                    .get(GetInput::new(hash, GetOptions::content()))
+                    .map(|_| ()),
+                _ => Ok(()),
+            };
+            match result {
+                Ok(()) => Ok(ValidateResult::Valid),
+                Err(e) => Ok(ValidateResult::Invalid(e.to_string())),
+            }
+        })
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn validation_cannot_call_non_deterministic_host_fns() -> anyhow::Result<()> {
+    let (dna_file, _) =
+        SweetDnaFile::unique_from_inline_zome("zome", non_deterministic_validation_zome()).await?;
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let app = conductor.setup_app("app", &[dna_file]).await;
+    let alice = app.into_cells()[0].zome("zome");
+
+    // Gets for content by hash are the same for every validator
+    let _: HeaderHash = conductor
+        .call(&alice, "create", AppString::new("get_content"))
+        .await;
+
+    for (host_fn, expected) in &[
+        (
+            "sys_time",
+            "Host function `sys_time` is not allowed in a validate callback",
+        ),
+        (
+            "random_bytes",
+            "Host function `random_bytes` is not allowed in a validate callback",
+        ),
+        (
+            "get_latest",
+            "Host function `get` with `GetStrategy::Latest` is not allowed in a validate callback",
+        ),
+    ] {
+        let err: ConductorApiResult<HeaderHash> = conductor
+            .call_fallible(&alice, "create", AppString::new(*host_fn))
+            .await;
+        let reason = match err {
+            Err(ConductorApiError::CellError(CellError::WorkflowError(e))) => match *e {
+                WorkflowError::SourceChainError(SourceChainError::InvalidCommit(reason)) => reason,
+                e => panic!("{}: unexpected error {:?}", host_fn, e),
+            },
+            r => panic!("{}: unexpected result {:?}", host_fn, r),
+        };
+        assert!(reason.contains(expected), "{}: {}", host_fn, reason);
+    }
+
+    Ok(())
+}
@@ -27,7 +27,6 @@ use holochain::conductor::api::RealAppInterfaceApi;
 use holochain::conductor::api::ZomeCall;
 use holochain::conductor::config::AdminInterfaceConfig;
 use holochain::conductor::config::ConductorConfig;
-use holochain::conductor::config::InterfaceDriver;
 use holochain::conductor::ConductorBuilder;
 use holochain::conductor::ConductorHandle;
 
@@ -193,7 +192,12 @@ async fn speed_test(n: Option<usize>) -> TestEnvironments {
 
     // Setup websocket handle and app interface
     let (mut client, _) = websocket_client(&handle).await.unwrap();
-    let request = AdminRequest::AttachAppInterface { port: None };
+    let request = AdminRequest::AttachAppInterface {
+        port: None,
+        bind_address: None,
+        allowed_origins: None,
+        installed_app_id: None,
+    };
     let response = client.request(request);
     let response = response.await.unwrap();
     let app_port = match response {
@@ -319,9 +323,7 @@ pub async fn setup_app(
 
     let conductor_handle = ConductorBuilder::with_mock_dna_store(dna_store)
         .config(ConductorConfig {
-            admin_interfaces: Some(vec![AdminInterfaceConfig {
-                driver: InterfaceDriver::Websocket { port: 0 },
-            }]),
+            admin_interfaces: Some(vec![AdminInterfaceConfig::websocket(0)]),
             ..Default::default()
         })
         .test(&envs)
@@ -72,9 +72,7 @@ pub async fn check_started(holochain: &mut Child) {
 
 fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
     ConductorConfig {
-        admin_interfaces: Some(vec![AdminInterfaceConfig {
-            driver: InterfaceDriver::Websocket { port },
-        }]),
+        admin_interfaces: Some(vec![AdminInterfaceConfig::websocket(port)]),
         environment_path: environment_path.into(),
         network: None,
         signing_service_uri: None,
@@ -86,6 +84,9 @@ fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
         }),
         keystore_path: None,
         use_dangerous_test_keystore: true,
+        lmdb_map_sizes: None,
+        validation_workers: None,
+        metrics_endpoint: None,
     }
 }
 
@@ -265,7 +266,12 @@ pub async fn attach_app_interface(
     holochain: &mut Child,
     port: Option<u16>,
 ) -> u16 {
-    let request = AdminRequest::AttachAppInterface { port };
+    let request = AdminRequest::AttachAppInterface {
+        port,
+        bind_address: None,
+        allowed_origins: None,
+        installed_app_id: None,
+    };
     let response = client.request(request);
     let response = check_timeout(holochain, response, 1000).await;
     match response {
@@ -442,7 +448,7 @@ async fn remote_signals() -> anyhow::Result<()> {
     for mut rx in rxs {
         let r = rx.try_recv();
         // Each handle should recv a signal
-        assert_matches!(r, Ok(Signal::App(_, a)) if a == signal);
+        assert_matches!(r, Ok(Signal::App(_, _, a)) if a == signal);
     }
 
     Ok(())
@@ -534,7 +540,11 @@ async fn emit_signals() {
     let sig2: SerializedBytes = unwrap_to::unwrap_to!(msg2 => WebsocketMessage::Signal).clone();
 
     assert_eq!(
-        Signal::App(cell_id, AppSignal::new(ExternIO::encode(()).unwrap())),
+        Signal::App(
+            cell_id,
+            TestWasm::EmitSignal.into(),
+            AppSignal::new(ExternIO::encode(()).unwrap())
+        ),
         Signal::try_from(sig1.clone()).unwrap(),
     );
     assert_eq!(sig1, sig2);
@@ -47,6 +47,9 @@ pub enum CascadeError {
 
     #[error("Cell is an authority for is missing or incorrect: {0}")]
     AuthorityDataError(#[from] AuthorityDataError),
+
+    #[error(transparent)]
+    CrdtError(#[from] CrdtError),
 }
 
 pub type CascadeResult<T> = Result<T, CascadeError>;
@@ -26,6 +26,7 @@ use holochain_state::prelude::*;
 use holochain_types::prelude::*;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::collections::HashSet;
 use tracing::*;
 use tracing_futures::Instrument;
 
@@ -1509,6 +1510,89 @@ where
         }
     }
 
+    #[instrument(skip(self, element, options))]
+    /// Merges the entry of this [Element] with the updates made to it,
+    /// the updates made to those and so on, following the [CrdtType]
+    /// declared by its entry def.
+    /// Deleted or invalid versions, and the updates made to them, are left out.
+    ///
+    /// The update tree is walked one depth at a time. The versions at a depth
+    /// are fetched from their authorities at once, and the updates made to them
+    /// are then read from the metadata.
+    ///
+    /// The merged entry is returned in the element of the latest version,
+    /// so for the set and counter types the header's entry hash isn't the
+    /// hash of the returned entry.
+    pub async fn dht_get_merged(
+        &mut self,
+        element: Element,
+        crdt_type: CrdtType,
+        options: GetOptions,
+    ) -> CascadeResult<Option<Element>> {
+        if let CrdtType::None = crdt_type {
+            return Ok(Some(element));
+        }
+        let get_call = options.strategy;
+        let mut options: NetworkGetOptions = options.into();
+        options.all_live_headers_with_metadata = true;
+
+        let mut versions: Vec<CrdtVersion> = Vec::new();
+        let mut seen = HashSet::new();
+        // The versions at the current depth, with the index of the version they update
+        let mut depth = vec![(element.header_address().clone(), None)];
+
+        while !depth.is_empty() {
+            depth.retain(|(header_hash, _)| seen.insert(header_hash.clone()));
+            let mut to_fetch = Vec::new();
+            for (header_hash, _) in &depth {
+                let hash: AnyDhtHash = header_hash.clone().into();
+                if self.am_i_authoring(&hash).await? {
+                    // Authored versions are already local
+                } else if self.am_i_an_authority(hash.clone()).await? {
+                    self.update_cache_from_integrated(hash, options.clone())?;
+                } else if get_call == GetStrategy::Content
+                    && self.create_element_details(header_hash.clone())?.is_some()
+                {
+                    // The content is local and the caller doesn't need the latest metadata
+                } else {
+                    to_fetch.push(header_hash.clone());
+                }
+            }
+            self.fetch_elements_via_header_parallel(to_fetch, options.clone())
+                .await?;
+
+            let mut next_depth = Vec::new();
+            for (header_hash, parent) in depth {
+                let details = match self.create_element_details(header_hash)? {
+                    Some(details) => details,
+                    None => continue,
+                };
+                if !details.deletes.is_empty()
+                    || details.validation_status != ValidationStatus::Valid
+                {
+                    continue;
+                }
+                if details.element.entry().as_option().is_none() {
+                    continue;
+                }
+                let index = versions.len();
+                next_depth.extend(
+                    details
+                        .updates
+                        .into_iter()
+                        .map(|update| (update.header_address().clone(), Some(index))),
+                );
+                versions.push(CrdtVersion {
+                    element: details.element,
+                    parent,
+                });
+            }
+            depth = next_depth;
+        }
+
+        Ok(merge_crdt_versions(crdt_type, &versions)?)
+    }
+
     #[instrument(skip(self))]
     pub async fn get_details(
         &mut self,
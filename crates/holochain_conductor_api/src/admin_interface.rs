@@ -1,7 +1,12 @@
+use crate::config::AdminPermission;
+use crate::config::InterfaceDriver;
+use crate::signal_subscription::SignalSubscription;
+use crate::InstalledAppInfo;
 use holo_hash::*;
 use holochain_types::prelude::*;
 use holochain_zome_types::cell::CellId;
 use kitsune_p2p::agent_store::AgentInfoSigned;
+use std::collections::HashMap;
 
 /// Represents the available conductor functions to call over an Admin interface
 /// and will result in a corresponding [`AdminResponse`] message being sent back over the
@@ -156,6 +161,35 @@ pub enum AdminRequest {
         /// The InstalledAppId to deactivate
         installed_app_id: InstalledAppId,
     },
+    /// Removes the `App` specified by argument `installed_app_id` from the conductor,
+    /// whether it is active or inactive.
+    /// Cells which no other `App` uses are removed too, and if `delete_data` is set
+    /// their LMDB environments are deleted, which can't be undone.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppUninstalled`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::AppUninstalled`]: enum.AdminResponse.html#variant.AppUninstalled
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    UninstallApp {
+        /// The InstalledAppId to uninstall
+        installed_app_id: InstalledAppId,
+        /// Delete the data of the removed Cells
+        #[serde(default)]
+        delete_data: bool,
+    },
+    /// List info about the installed Apps in the conductor,
+    /// optionally only those with the given status.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppsListed`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::AppsListed`]: enum.AdminResponse.html#variant.AppsListed
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    ListApps {
+        /// Only list apps with this status, or every app if `None`
+        status_filter: Option<AppStatusFilter>,
+    },
     /// Open up a new websocket interface at the networking port
     /// (optionally) specified by argument `port` (or using any free port if argument `port` is `None`)
     /// over which you can then use the [`AppRequest`] API.
@@ -172,6 +206,63 @@ pub enum AdminRequest {
         /// Optional port, use None to let the
         /// OS choose a free port
         port: Option<u16>,
+        /// Optional address to bind to, use None to
+        /// only bind the loopback address `127.0.0.1`
+        #[serde(default)]
+        bind_address: Option<std::net::IpAddr>,
+        /// Optional origins browsers may connect from,
+        /// use None to allow any origin
+        #[serde(default)]
+        allowed_origins: Option<Vec<String>>,
+        /// Optional app to attach the interface to,
+        /// which is the app [`AppRequest::Crypto`] requests act for.
+        /// Crypto requests are refused if this is None.
+        ///
+        /// [`AppRequest::Crypto`]: ../app_interface/enum.AppRequest.html#variant.Crypto
+        #[serde(default)]
+        installed_app_id: Option<InstalledAppId>,
+    },
+    /// Open up a new app interface on a Unix domain socket at `path`,
+    /// carrying the same messages as a websocket app interface.
+    /// Only the user running the conductor can connect to the socket.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppUnixSocketAttached`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::AppUnixSocketAttached`]: enum.AdminResponse.html#variant.AppUnixSocketAttached
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    AttachAppUnixSocket {
+        /// Where to create the socket
+        path: std::path::PathBuf,
+        /// Optional app to attach the interface to,
+        /// as for [`AdminRequest::AttachAppInterface`]
+        ///
+        /// [`AdminRequest::AttachAppInterface`]: enum.AdminRequest.html#variant.AttachAppInterface
+        #[serde(default)]
+        installed_app_id: Option<InstalledAppId>,
+    },
+    /// List the app interfaces attached to the conductor,
+    /// with their drivers and signal subscriptions.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppInterfacesListed`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::AppInterfacesListed`]: enum.AdminResponse.html#variant.AppInterfacesListed
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    ListAppInterfaces,
+    /// Stop the app interface with the id `interface_id`, closing its
+    /// connections, and remove it so it isn't started again on restart.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppInterfaceDetached`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::AppInterfaceDetached`]: enum.AdminResponse.html#variant.AppInterfaceDetached
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    DetachAppInterface {
+        /// The id of the interface, as listed by [`AdminRequest::ListAppInterfaces`]
+        ///
+        /// [`AdminRequest::ListAppInterfaces`]: enum.AdminRequest.html#variant.ListAppInterfaces
+        interface_id: String,
     },
     /// Dump the full state of the `Cell` specified by argument `cell_id`,
     /// including its chain, as a string containing JSON.
@@ -185,6 +276,33 @@ pub enum AdminRequest {
         /// The `CellId` for which to dump state
         cell_id: Box<CellId>,
     },
+    /// Export the authored source chain of the `Cell` specified by argument
+    /// `cell_id`, including private entries like cap grants, in a versioned
+    /// binary format which can be restored with [`AdminRequest::ImportSourceChain`].
+    ///
+    /// Will be responded to with an [`AdminResponse::SourceChainExported`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminRequest::ImportSourceChain`]: enum.AdminRequest.html#variant.ImportSourceChain
+    /// [`AdminResponse::SourceChainExported`]: enum.AdminResponse.html#variant.SourceChainExported
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    ExportSourceChain {
+        /// The `CellId` whose source chain to export
+        cell_id: Box<CellId>,
+    },
+    /// Install a new inactive `App` with a single `Cell`, whose source chain
+    /// is re-created from a chain exported by [`AdminRequest::ExportSourceChain`].
+    /// The chain is checked to be a valid chain for its Dna, authored by its agent.
+    /// The Dna must already be registered, and the agent key must be in this
+    /// conductor's keystore for the `Cell` to author new elements.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppInstalled`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminRequest::ExportSourceChain`]: enum.AdminRequest.html#variant.ExportSourceChain
+    /// [`AdminResponse::AppInstalled`]: enum.AdminResponse.html#variant.AppInstalled
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    ImportSourceChain(Box<ImportSourceChainPayload>),
     /// Add a list [AgentInfoSigned] to this conductor's peer store.
     /// This is another way of finding peers on a dht.
     ///
@@ -210,6 +328,150 @@ pub enum AdminRequest {
         /// Optionally choose a specific agent info
         cell_id: Option<CellId>,
     },
+    /// Start sending [`ConductorEvent`]s to this connection as signals,
+    /// like task failures, App status changes, Cell genesis results
+    /// and changes in the number of peers of each Dna.
+    /// Events which happen before subscribing are not sent.
+    ///
+    /// Will be responded to with an [`AdminResponse::ConductorEventsSubscribed`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`ConductorEvent`]: conductor_event/enum.ConductorEvent.html
+    /// [`AdminResponse::ConductorEventsSubscribed`]: enum.AdminResponse.html#variant.ConductorEventsSubscribed
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    SubscribeConductorEvents,
+    /// List the long running tasks the conductor looks after,
+    /// like interfaces and each Cell's queue consumers, with their status.
+    ///
+    /// Will be responded to with an [`AdminResponse::ManagedTasksListed`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::ManagedTasksListed`]: enum.AdminResponse.html#variant.ManagedTasksListed
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    ListManagedTasks,
+    /// Render the workflow and limbo metrics of every running `Cell`
+    /// in the Prometheus text format.
+    /// They can also be scraped over HTTP by setting `metrics_endpoint`
+    /// in the conductor config.
+    ///
+    /// Will be responded to with an [`AdminResponse::MetricsDumped`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::MetricsDumped`]: enum.AdminResponse.html#variant.MetricsDumped
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    DumpMetrics,
+    /// Revoke an agent key with the DPKI app configured for this conductor.
+    /// Headers signed by the key after it was revoked will fail validation.
+    ///
+    /// Will be responded to with an [`AdminResponse::AgentKeyRevoked`]
+    /// or an [`AdminResponse::Error`] if DPKI is not configured.
+    ///
+    /// [`AdminResponse::AgentKeyRevoked`]: enum.AdminResponse.html#variant.AgentKeyRevoked
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    RevokeAgentKey {
+        /// The key to revoke
+        agent_key: AgentPubKey,
+    },
+    /// Ask for a challenge to sign with [`AdminRequest::Authenticate`],
+    /// on an interface which authenticates with signed challenges.
+    /// Each new challenge replaces the last one given to the connection.
+    ///
+    /// Will be responded to with an [`AdminResponse::AuthChallengeIssued`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminRequest::Authenticate`]: enum.AdminRequest.html#variant.Authenticate
+    /// [`AdminResponse::AuthChallengeIssued`]: enum.AdminResponse.html#variant.AuthChallengeIssued
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    RequestAuthChallenge,
+    /// Authenticate this connection with an interface which requires it.
+    /// Until it has, every other request besides [`AdminRequest::RequestAuthChallenge`]
+    /// is responded to with an [`ExternalApiWireError::AdminUnauthorized`].
+    ///
+    /// Will be responded to with an [`AdminResponse::Authenticated`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminRequest::RequestAuthChallenge`]: enum.AdminRequest.html#variant.RequestAuthChallenge
+    /// [`ExternalApiWireError::AdminUnauthorized`]: enum.ExternalApiWireError.html#variant.AdminUnauthorized
+    /// [`AdminResponse::Authenticated`]: enum.AdminResponse.html#variant.Authenticated
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    Authenticate(AdminCredential),
+}
+
+impl AdminRequest {
+    /// The permission an interface needs to be allowed to make this request
+    pub fn required_permission(&self) -> AdminPermission {
+        use AdminRequest::*;
+        match self {
+            ListDnas
+            | ListCellIds
+            | ListActiveApps
+            | ListApps { .. }
+            | ListAppInterfaces
+            | RequestAgentInfo { .. }
+            | SubscribeConductorEvents
+            | ListManagedTasks
+            | DumpMetrics
+            | RequestAuthChallenge
+            | Authenticate(_) => AdminPermission::ReadOnly,
+            RegisterDna(_)
+            | CreateCloneCell(_)
+            | InstallApp(_)
+            | ImportSourceChain(_)
+            | InstallAppBundle(_)
+            | GenerateAgentPubKey
+            | ActivateApp { .. }
+            | DeactivateApp { .. }
+            | UninstallApp { .. }
+            | AttachAppInterface { .. }
+            | AttachAppUnixSocket { .. }
+            | DetachAppInterface { .. }
+            | AddAgentInfo { .. } => AdminPermission::ManageApps,
+            // Dumps and exports include private entries and cap grant secrets
+            AddAdminInterfaces(_)
+            | RevokeAgentKey { .. }
+            | DumpState { .. }
+            | ExportSourceChain { .. } => AdminPermission::Full,
+        }
+    }
+}
+
+/// Prefixed to a challenge from [`AdminRequest::RequestAuthChallenge`]
+/// to make the message signed for [`AdminCredential::SignedChallenge`],
+/// so the signature can't be passed off as the key signing anything else.
+///
+/// [`AdminRequest::RequestAuthChallenge`]: enum.AdminRequest.html#variant.RequestAuthChallenge
+/// [`AdminCredential::SignedChallenge`]: enum.AdminCredential.html#variant.SignedChallenge
+pub const ADMIN_AUTH_CONTEXT: &[u8] = b"holochain-admin-auth:";
+
+/// Proof that a connection may use an Admin interface,
+/// matching the interface's [`AdminAuthConfig`].
+///
+/// [`AdminAuthConfig`]: ../config/enum.AdminAuthConfig.html
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "data")]
+pub enum AdminCredential {
+    /// The interface's shared token
+    Token(String),
+    /// The last challenge issued to this connection, signed by an authorized key
+    SignedChallenge {
+        /// The key which signed the challenge
+        key: AgentPubKey,
+        /// The signature of the message from [`AdminCredential::challenge_message`]
+        ///
+        /// [`AdminCredential::challenge_message`]: enum.AdminCredential.html#method.challenge_message
+        signature: Signature,
+    },
+}
+
+impl AdminCredential {
+    /// The message to sign for a [`AdminCredential::SignedChallenge`]:
+    /// [`ADMIN_AUTH_CONTEXT`] followed by the challenge bytes
+    ///
+    /// [`AdminCredential::SignedChallenge`]: enum.AdminCredential.html#variant.SignedChallenge
+    /// [`ADMIN_AUTH_CONTEXT`]: constant.ADMIN_AUTH_CONTEXT.html
+    pub fn challenge_message(challenge: &[u8]) -> Vec<u8> {
+        [ADMIN_AUTH_CONTEXT, challenge].concat()
+    }
 }
 
 /// Represents the possible responses to an [`AdminRequest`]
@@ -318,6 +580,30 @@ pub enum AdminResponse {
         port: u16,
     },
 
+    /// The succesful response to an [`AdminRequest::AttachAppUnixSocket`].
+    ///
+    /// `AppInterfaceApi` successfully attached at the socket `path`.
+    ///
+    /// [`AdminRequest::AttachAppUnixSocket`]: enum.AdminRequest.html#variant.AttachAppUnixSocket
+    AppUnixSocketAttached {
+        /// Path of the new `AppInterfaceApi` socket
+        path: std::path::PathBuf,
+    },
+
+    /// The succesful response to an [`AdminRequest::ListAppInterfaces`].
+    ///
+    /// Contains info about every attached app interface
+    ///
+    /// [`AdminRequest::ListAppInterfaces`]: enum.AdminRequest.html#variant.ListAppInterfaces
+    AppInterfacesListed(Vec<AppInterfaceInfo>),
+
+    /// The succesful response to an [`AdminRequest::DetachAppInterface`].
+    ///
+    /// The interface was stopped and removed
+    ///
+    /// [`AdminRequest::DetachAppInterface`]: enum.AdminRequest.html#variant.DetachAppInterface
+    AppInterfaceDetached,
+
     /// The succesful response to an [`AdminRequest::ActivateApp`].
     ///
     /// It means the `App` was activated successfully
@@ -332,6 +618,20 @@ pub enum AdminResponse {
     /// [`AdminRequest::DeactivateApp`]: enum.AdminRequest.html#variant.DeactivateApp
     AppDeactivated,
 
+    /// The succesful response to an [`AdminRequest::UninstallApp`].
+    ///
+    /// It means the `App` was uninstalled successfully.
+    ///
+    /// [`AdminRequest::UninstallApp`]: enum.AdminRequest.html#variant.UninstallApp
+    AppUninstalled,
+
+    /// The succesful response to an [`AdminRequest::ListApps`].
+    ///
+    /// Contains info about the installed `App`s which matched the filter.
+    ///
+    /// [`AdminRequest::ListApps`]: enum.AdminRequest.html#variant.ListApps
+    AppsListed(Vec<InstalledAppInfo>),
+
     /// The succesful response to an [`AdminRequest::DumpState`].
     ///
     /// The result contains a string of serialized JSON data which can be deserialized to access the
@@ -340,6 +640,13 @@ pub enum AdminResponse {
     /// [`AdminRequest::DumpState`]: enum.AdminRequest.html#variant.DumpState
     StateDumped(String),
 
+    /// The succesful response to an [`AdminRequest::ExportSourceChain`].
+    ///
+    /// Contains the exported source chain
+    ///
+    /// [`AdminRequest::ExportSourceChain`]: enum.AdminRequest.html#variant.ExportSourceChain
+    SourceChainExported(#[serde(with = "serde_bytes")] Vec<u8>),
+
     /// The succesful response to an [`AdminRequest::AddAgentInfo`].
     ///
     /// This means the agent info was successfully added to the peer store.
@@ -353,6 +660,107 @@ pub enum AdminResponse {
     ///
     /// [`AdminRequest::RequestAgentInfo`]: enum.AdminRequest.html#variant.RequestAgentInfo
     AgentInfoRequested(Vec<AgentInfoSigned>),
+
+    /// The succesful response to an [`AdminRequest::SubscribeConductorEvents`].
+    ///
+    /// [`ConductorEvent`]s will now be sent to this connection as signals.
+    ///
+    /// [`AdminRequest::SubscribeConductorEvents`]: enum.AdminRequest.html#variant.SubscribeConductorEvents
+    /// [`ConductorEvent`]: conductor_event/enum.ConductorEvent.html
+    ConductorEventsSubscribed,
+
+    /// The succesful response to an [`AdminRequest::ListManagedTasks`].
+    ///
+    /// Contains every task the conductor is looking after
+    /// and the tasks which failed and weren't restarted.
+    ///
+    /// [`AdminRequest::ListManagedTasks`]: enum.AdminRequest.html#variant.ListManagedTasks
+    ManagedTasksListed(Vec<ManagedTaskInfo>),
+
+    /// The succesful response to an [`AdminRequest::DumpMetrics`].
+    ///
+    /// Contains the metrics in the Prometheus text format.
+    ///
+    /// [`AdminRequest::DumpMetrics`]: enum.AdminRequest.html#variant.DumpMetrics
+    MetricsDumped(String),
+
+    /// The succesful response to an [`AdminRequest::RevokeAgentKey`].
+    ///
+    /// This means the DPKI app has recorded the key as revoked.
+    ///
+    /// [`AdminRequest::RevokeAgentKey`]: enum.AdminRequest.html#variant.RevokeAgentKey
+    AgentKeyRevoked,
+
+    /// The successful response to an [`AdminRequest::RequestAuthChallenge`].
+    ///
+    /// Contains the bytes to sign and send back in an [`AdminRequest::Authenticate`].
+    ///
+    /// [`AdminRequest::RequestAuthChallenge`]: enum.AdminRequest.html#variant.RequestAuthChallenge
+    /// [`AdminRequest::Authenticate`]: enum.AdminRequest.html#variant.Authenticate
+    AuthChallengeIssued(#[serde(with = "serde_bytes")] Vec<u8>),
+
+    /// The successful response to an [`AdminRequest::Authenticate`].
+    ///
+    /// The connection can now make any request the interface allows.
+    ///
+    /// [`AdminRequest::Authenticate`]: enum.AdminRequest.html#variant.Authenticate
+    Authenticated,
+}
+
+/// The status an app must have to be listed by [`AdminRequest::ListApps`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppStatusFilter {
+    /// Only list active apps
+    Active,
+    /// Only list inactive apps
+    Inactive,
+}
+
+/// Info about an attached app interface,
+/// as listed by [`AdminRequest::ListAppInterfaces`]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AppInterfaceInfo {
+    /// The id used to detach the interface
+    pub interface_id: String,
+    /// The driver of the interface, including the port it is bound to
+    pub driver: InterfaceDriver,
+    /// The latest signal subscription made over the interface for each App.
+    /// Each connection only gets the signals of its own subscriptions.
+    pub signal_subscriptions: HashMap<InstalledAppId, SignalSubscription>,
+    /// The app the interface is attached to, if any
+    #[serde(default)]
+    pub installed_app_id: Option<InstalledAppId>,
+}
+
+/// A long running task the conductor looks after,
+/// as listed by [`AdminRequest::ListManagedTasks`]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ManagedTaskInfo {
+    /// What the task does, e.g. `sys_validation_consumer` and the Cell it is for
+    pub name: String,
+    /// What the task is doing now
+    pub status: ManagedTaskStatus,
+    /// How many times the task has been restarted
+    pub restarts: u32,
+}
+
+/// The status of a task the conductor looks after
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "data")]
+pub enum ManagedTaskStatus {
+    /// The task is running
+    Running,
+    /// The task failed and will be started again after a delay
+    Restarting {
+        /// The error the task failed with
+        error: String,
+    },
+    /// The task failed and won't be started again
+    Failed {
+        /// The error the task failed with
+        error: String,
+    },
 }
 
 /// Error type that goes over the websocket wire.
@@ -376,6 +784,11 @@ pub enum ExternalApiWireError {
     ActivateApp(String),
     /// The zome call is unauthorized
     ZomeCallUnauthorized(String),
+    /// The crypto request used a key the app is not allowed to use
+    CryptoUnauthorized(String),
+    /// The admin connection is not authenticated or
+    /// the interface doesn't allow the request
+    AdminUnauthorized(String),
 }
 
 impl ExternalApiWireError {
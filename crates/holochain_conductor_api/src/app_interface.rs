@@ -20,10 +20,15 @@ pub enum AppRequest {
         /// The InstalledAppId for which to get information
         installed_app_id: InstalledAppId,
     },
-    /// Asks the conductor to do some crypto.
+    /// Asks the conductor to do some crypto with the keys of the app
+    /// this interface is attached to.
+    /// See [`CryptoRequest`] for the available operations.
     ///
-    /// Is currently unimplemented and will return
-    /// an [`AppResponse::Unimplemented`](enum.AppResponse.html#variant.Unimplemented)
+    /// Will be responded to with an [`AppResponse::Crypto`]
+    /// or an [`AppResponse::Error`]
+    ///
+    /// [`AppResponse::Crypto`]: enum.AppResponse.html#variant.Crypto
+    /// [`AppResponse::Error`]: enum.AppResponse.html#variant.Error
     Crypto(Box<CryptoRequest>),
     /// Call a zome function. See the inner [`ZomeCall`]
     /// struct to understand the data that must be provided.
@@ -39,10 +44,15 @@ pub enum AppRequest {
     /// DEPRECATED. Use `ZomeCall`.
     ZomeCallInvocation(Box<ZomeCall>),
 
-    /// Update signal subscriptions.
+    /// Update which signals from an app's cells are sent over this connection.
+    /// Replaces any previous subscription for the same app.
+    /// Signals from cells of apps without a subscription are all sent.
+    ///
+    /// Will be responded to with an [`AppResponse::SignalSubscriptionUpdated`]
+    /// or an [`AppResponse::Error`]
     ///
-    /// Is currently unimplemented and will return
-    /// an [`AppResponse::Unimplemented`](enum.AppResponse.html#variant.Unimplemented)
+    /// [`AppResponse::SignalSubscriptionUpdated`]: enum.AppResponse.html#variant.SignalSubscriptionUpdated
+    /// [`AppResponse::Error`]: enum.AppResponse.html#variant.Error
     SignalSubscription(SignalSubscription),
 }
 
@@ -83,6 +93,18 @@ pub enum AppResponse {
 
     /// DEPRECATED. See `ZomeCall`.
     ZomeCallInvocation(Box<ExternIO>),
+
+    /// The successful response to an [`AppRequest::Crypto`].
+    ///
+    /// The variant of [`CryptoResponse`] matches the variant of the [`CryptoRequest`].
+    ///
+    /// [`AppRequest::Crypto`]: enum.AppRequest.html#variant.Crypto
+    Crypto(Box<CryptoResponse>),
+
+    /// The successful response to an [`AppRequest::SignalSubscription`].
+    ///
+    /// [`AppRequest::SignalSubscription`]: enum.AppRequest.html#variant.SignalSubscription
+    SignalSubscriptionUpdated,
 }
 
 /// The data provided across an App interface in order to make a zome call
@@ -109,13 +131,53 @@ pub struct ZomeCall {
     pub provenance: AgentPubKey,
 }
 
-#[allow(missing_docs)]
+/// Crypto operations an app's client can ask the conductor to do,
+/// without going through a zome call.
+///
+/// The requests act for the app the app interface is attached to,
+/// and fail on an interface that isn't attached to an app.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
 pub enum CryptoRequest {
-    Sign(String),
-    Decrypt(String),
-    Encrypt(String),
+    /// Sign data with one of the app's agent keys.
+    Sign {
+        /// The agent key to sign with.
+        /// Must be the agent of one of the app's cells.
+        key: AgentPubKey,
+        /// The data to sign
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+    },
+    /// Create a new x25519 keypair for the app,
+    /// which can then be used to box and open messages.
+    CreateX25519Keypair,
+    /// Box data from one x25519 key to another.
+    /// The sender must be a key created for the app.
+    Encrypt {
+        /// The sender and recipient keys and the data to box
+        input: X25519XSalsa20Poly1305Encrypt,
+    },
+    /// Open a box sent from one x25519 key to another.
+    /// The recipient must be a key created for the app.
+    Decrypt {
+        /// The sender and recipient keys and the box to open
+        input: X25519XSalsa20Poly1305Decrypt,
+    },
+}
+
+/// The results of a [`CryptoRequest`]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "data")]
+pub enum CryptoResponse {
+    /// The signature for [`CryptoRequest::Sign`]
+    Signature(Signature),
+    /// The new public key for [`CryptoRequest::CreateX25519Keypair`]
+    X25519PubKey(X25519PubKey),
+    /// The box for [`CryptoRequest::Encrypt`]
+    Encrypted(XSalsa20Poly1305EncryptedData),
+    /// The opened box for [`CryptoRequest::Decrypt`],
+    /// or `None` if it could not be opened with these keys.
+    Decrypted(Option<XSalsa20Poly1305Data>),
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
@@ -0,0 +1,64 @@
+//! Events the conductor sends to Admin connections which have subscribed
+//! with [`AdminRequest::SubscribeConductorEvents`].
+//!
+//! [`AdminRequest::SubscribeConductorEvents`]: ../enum.AdminRequest.html#variant.SubscribeConductorEvents
+
+use holo_hash::DnaHash;
+use holochain_serialized_bytes::prelude::*;
+use holochain_types::app::InstalledAppId;
+use holochain_zome_types::cell::CellId;
+
+/// Something which happened in the conductor that an admin may want to know about.
+/// These are sent as signals over the Admin interface.
+///
+/// Serialized with the enum variant on a key `type` and its contents on a key `data`,
+/// e.g. `{ type: 'app_status_changed', data: { installed_app_id: 'test_app', active: false, reason: null } }`
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes)]
+#[serde(rename_all = "snake_case", tag = "type", content = "data")]
+pub enum ConductorEvent {
+    /// A task managed by the conductor, like an interface or a Cell's
+    /// queue consumer, stopped with an error.
+    TaskFailed {
+        /// What the task does, as listed by [`AdminRequest::ListManagedTasks`]
+        ///
+        /// [`AdminRequest::ListManagedTasks`]: ../enum.AdminRequest.html#variant.ListManagedTasks
+        name: String,
+        /// The error the task failed with
+        error: String,
+    },
+    /// A managed task was started again after it stopped,
+    /// following its restart policy.
+    TaskRestarted {
+        /// What the task does, as listed by [`AdminRequest::ListManagedTasks`]
+        ///
+        /// [`AdminRequest::ListManagedTasks`]: ../enum.AdminRequest.html#variant.ListManagedTasks
+        name: String,
+        /// How many times the task has been restarted
+        restarts: u32,
+    },
+    /// An App was activated or deactivated, or its Cells failed to start
+    AppStatusChanged {
+        /// The App whose status changed
+        installed_app_id: InstalledAppId,
+        /// Whether the App's Cells are now running
+        active: bool,
+        /// Why the status changed, if it wasn't requested by an admin
+        reason: Option<String>,
+    },
+    /// Genesis was run on the source chain of a new Cell,
+    /// while installing its App.
+    CellGenesis {
+        /// The Cell whose source chain it was
+        cell_id: CellId,
+        /// The error genesis failed with, if it did
+        error: Option<String>,
+    },
+    /// The number of peers in this conductor's peer store
+    /// for a Dna's network changed.
+    PeerCountChanged {
+        /// The Dna whose network this is
+        dna_hash: DnaHash,
+        /// The number of unexpired agent infos for the network
+        peer_count: usize,
+    },
+}
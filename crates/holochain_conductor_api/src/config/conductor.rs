@@ -9,6 +9,7 @@ mod admin_interface_config;
 mod dpki_config;
 #[allow(missing_docs)]
 mod error;
+mod lmdb_config;
 mod passphrase_service_config;
 pub mod paths;
 //mod logger_config;
@@ -19,6 +20,7 @@ pub use super::*;
 pub use dpki_config::DpkiConfig;
 //pub use logger_config::LoggerConfig;
 pub use error::*;
+pub use lmdb_config::*;
 pub use passphrase_service_config::PassphraseServiceConfig;
 //pub use signal_config::SignalConfig;
 use std::path::Path;
@@ -38,22 +40,25 @@ pub struct ConductorConfig {
     #[serde(default)]
     pub use_dangerous_test_keystore: bool,
 
-    /// Optional URI for a websocket connection to an outsourced signing service.
+    /// Optional URI of an outsourced signing service.
     /// Bootstrapping step for Holo closed-alpha.
-    /// If set, all agents with holo_remote_key = true will be emulated by asking for signatures
-    /// over this websocket.
+    /// Either an `http://` / `https://` url or a `unix://<path>` socket.
+    /// If set, signatures are requested from this service, and lair signs
+    /// for any agent the service doesn't hold the key for.
     pub signing_service_uri: Option<String>,
 
-    /// Optional URI for a websocket connection to an outsourced encryption service.
+    /// Optional URI of an outsourced encryption service.
     /// Bootstrapping step for Holo closed-alpha.
-    /// If set, all agents with holo_remote_key = true will be emulated by asking for signatures
-    /// over this websocket.
+    /// Either an `http://` / `https://` url or a `unix://<path>` socket.
+    /// If set, x25519 boxes are requested from this service, and lair boxes
+    /// for any key the service doesn't hold.
     pub encryption_service_uri: Option<String>,
 
-    /// Optional URI for a websocket connection to an outsourced decryption service.
+    /// Optional URI of an outsourced decryption service.
     /// Bootstrapping step for Holo closed-alpha.
-    /// If set, all agents with holo_remote_key = true will be emulated by asking for signatures
-    /// over this websocket.
+    /// Either an `http://` / `https://` url or a `unix://<path>` socket.
+    /// If set, x25519 boxes are opened by this service, and lair opens
+    /// boxes for any key the service doesn't hold.
     pub decryption_service_uri: Option<String>,
 
     /// Optional DPKI configuration if conductor is using a DPKI app to initalize and manage
@@ -68,6 +73,7 @@ pub struct ConductorConfig {
     /// The conductor is independent of the specialized implementation of the trait
     /// PassphraseService. It just needs something to provide a passphrase when needed.
     /// This config setting selects one of the available services (i.e. CLI prompt, IPC, FromConfig)
+    /// If not set, the keystore is unlocked with a blank passphrase.
     pub passphrase_service: Option<PassphraseServiceConfig>,
 
     /// Setup admin interfaces to control this conductor through a websocket connection
@@ -75,6 +81,24 @@ pub struct ConductorConfig {
 
     /// Config options for the network module. Optional.
     pub network: Option<holochain_p2p::kitsune_p2p::KitsuneP2pConfig>,
+
+    /// Initial and maximum LMDB map sizes for each kind of environment.
+    /// The maps grow automatically between the two. Optional.
+    #[serde(default)]
+    pub lmdb_map_sizes: Option<LmdbMapSizesConfig>,
+
+    /// The number of ops each cell validates at the same time. Ops by the
+    /// same author are still validated one after the other, in chain order.
+    /// Defaults to the number of CPUs.
+    #[serde(default)]
+    pub validation_workers: Option<usize>,
+
+    /// The address to serve metrics about each cell's workflows from,
+    /// in the Prometheus text format, e.g. `127.0.0.1:9100`.
+    /// It must be a loopback address.
+    /// Any path is answered with the metrics. Optional.
+    #[serde(default)]
+    pub metrics_endpoint: Option<std::net::SocketAddr>,
     //
     //
     // /// Which signals to emit
@@ -150,6 +174,9 @@ pub mod tests {
                 keystore_path: None,
                 admin_interfaces: None,
                 use_dangerous_test_keystore: false,
+                lmdb_map_sizes: None,
+                validation_workers: None,
+                metrics_endpoint: None,
             }
         );
     }
@@ -176,6 +203,9 @@ pub mod tests {
       - driver:
           type: websocket
           port: 1234
+          bind_address: 0.0.0.0
+          allowed_origins:
+            - http://localhost:8888
 
     network:
       bootstrap_service: https://bootstrap-staging.holo.host
@@ -196,6 +226,18 @@ pub mod tests {
         default_rpc_multi_timeout_ms: 42
         agent_info_expires_after_ms: 42
 
+    lmdb_map_sizes:
+      cell:
+        initial_mb: 10
+        max_mb: 1000
+      p2p:
+        initial_mb: 100
+        max_mb: 100
+
+    validation_workers: 4
+
+    metrics_endpoint: "127.0.0.1:9100"
+
     "#;
         let result: ConductorConfigResult<ConductorConfig> = config_from_yaml(yaml);
         use holochain_p2p::kitsune_p2p::*;
@@ -236,9 +278,28 @@ pub mod tests {
                 passphrase_service: Some(PassphraseServiceConfig::Cmd),
                 keystore_path: None,
                 admin_interfaces: Some(vec![AdminInterfaceConfig {
-                    driver: InterfaceDriver::Websocket { port: 1234 }
+                    driver: InterfaceDriver::Websocket {
+                        port: 1234,
+                        bind_address: Some([0, 0, 0, 0].into()),
+                        allowed_origins: Some(vec!["http://localhost:8888".to_string()]),
+                    },
+                    auth: None,
+                    permission: AdminPermission::Full,
                 }]),
                 network: Some(network_config),
+                lmdb_map_sizes: Some(LmdbMapSizesConfig {
+                    cell: Some(LmdbMapSizeConfig {
+                        initial_mb: 10,
+                        max_mb: 1000,
+                    }),
+                    p2p: Some(LmdbMapSizeConfig {
+                        initial_mb: 100,
+                        max_mb: 100,
+                    }),
+                    ..Default::default()
+                }),
+                validation_workers: Some(4),
+                metrics_endpoint: Some(([127, 0, 0, 1], 9100).into()),
             }
         );
     }
@@ -270,6 +331,9 @@ pub mod tests {
                 keystore_path: Some(PathBuf::from("/path/to/keystore").into()),
                 admin_interfaces: None,
                 use_dangerous_test_keystore: true,
+                lmdb_map_sizes: None,
+                validation_workers: None,
+                metrics_endpoint: None,
             }
         );
     }
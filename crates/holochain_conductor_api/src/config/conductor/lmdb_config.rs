@@ -0,0 +1,30 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Initial and maximum LMDB map sizes for each kind of environment.
+/// Any kind which is not specified uses the defaults of 100MB initially,
+/// growing as needed up to 64GB.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq)]
+pub struct LmdbMapSizesConfig {
+    /// Map sizes for each cell's environment
+    #[serde(default)]
+    pub cell: Option<LmdbMapSizeConfig>,
+    /// Map size for the conductor's environment
+    #[serde(default)]
+    pub conductor: Option<LmdbMapSizeConfig>,
+    /// Map size for the wasm environment
+    #[serde(default)]
+    pub wasm: Option<LmdbMapSizeConfig>,
+    /// Map size for the p2p environment
+    #[serde(default)]
+    pub p2p: Option<LmdbMapSizeConfig>,
+}
+
+/// The size an LMDB memory map starts at, and the size it may grow to.
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq)]
+pub struct LmdbMapSizeConfig {
+    /// The size of the map when the environment is first opened, in megabytes
+    pub initial_mb: usize,
+    /// The size the map will never be grown beyond, in megabytes
+    pub max_mb: usize,
+}
@@ -7,8 +7,11 @@ use std::path::PathBuf;
 /// is not feasible.
 /// Setting the type to "unixsocket" and providing a path to a file socket enables
 /// arbitrary UIs to connect to the conductor and prompt the user for a passphrase.
-/// The according `PassphraseServiceUnixSocket` will send a request message over the socket
+/// The conductor listens on the socket and, each time the keystore needs unlocking,
+/// sends `request_passphrase\n` to the connected client,
 /// then receives bytes as passphrase until a newline is sent.
+/// If the keystore rejects the passphrase the request is sent again,
+/// up to three times before the conductor gives up.
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum PassphraseServiceConfig {
@@ -1,5 +1,8 @@
+use holo_hash::AgentPubKey;
 use serde::Deserialize;
 use serde::Serialize;
+use std::net::IpAddr;
+use std::path::PathBuf;
 
 /// Information neeeded to spawn an Admin interface
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
@@ -7,11 +10,67 @@ pub struct AdminInterfaceConfig {
     /// By what means will the interface be exposed?
     /// Current only option is a local websocket running on a configurable port.
     pub driver: InterfaceDriver,
+    /// How must a connection authenticate before making requests?
+    /// If `None`, anything that can reach the interface can use it.
+    #[serde(default)]
+    pub auth: Option<AdminAuthConfig>,
+    /// Which requests can be made over this interface?
+    #[serde(default)]
+    pub permission: AdminPermission,
     // /// How long will this interface be accessible between authentications?
     // /// TODO: implement once we have authentication
     // _session_duration_seconds: Option<u32>,
 }
 
+impl AdminInterfaceConfig {
+    /// An unauthenticated websocket interface with full permission
+    pub fn websocket(port: u16) -> Self {
+        Self {
+            driver: InterfaceDriver::websocket(port),
+            auth: None,
+            permission: AdminPermission::default(),
+        }
+    }
+}
+
+/// The ways a connection can authenticate with an Admin interface.
+/// Until it has, every request other than authenticating is refused.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminAuthConfig {
+    /// The connection must present this token
+    Token {
+        /// The shared secret
+        token: String,
+    },
+    /// The connection must sign a challenge from the conductor,
+    /// prefixed with `ADMIN_AUTH_CONTEXT`, with one of these keys
+    SignedChallenge {
+        /// The keys allowed to authenticate
+        authorized_keys: Vec<AgentPubKey>,
+    },
+}
+
+/// The scope of requests allowed over an Admin interface.
+/// Each level allows everything the levels before it do.
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminPermission {
+    /// Only requests which list state or report metrics
+    ReadOnly,
+    /// Also register DNAs and install, activate, deactivate and uninstall apps
+    ManageApps,
+    /// Every request, including adding interfaces, revoking keys
+    /// and dumping or exporting a Cell's private data
+    Full,
+}
+
+impl Default for AdminPermission {
+    fn default() -> Self {
+        AdminPermission::Full
+    }
+}
+
 /// Configuration for interfaces, specifying the means by which an interface
 /// should be opened.
 ///
@@ -24,5 +83,30 @@ pub enum InterfaceDriver {
     Websocket {
         /// The port on which to establish the WebsocketListener
         port: u16,
+        /// The address to bind to. If `None`, only the loopback
+        /// address `127.0.0.1` is bound.
+        #[serde(default)]
+        bind_address: Option<IpAddr>,
+        /// The origins browsers may connect from. If `None`, any origin is allowed.
+        /// Clients which don't send an `Origin` header are always allowed.
+        #[serde(default)]
+        allowed_origins: Option<Vec<String>>,
     },
+    /// An interface carrying the same framing as the websocket driver,
+    /// over a Unix domain socket which only its owner can access
+    UnixSocket {
+        /// Where to create the socket
+        path: PathBuf,
+    },
+}
+
+impl InterfaceDriver {
+    /// A websocket on the loopback address, allowing any origin
+    pub fn websocket(port: u16) -> Self {
+        InterfaceDriver::Websocket {
+            port,
+            bind_address: None,
+            allowed_origins: None,
+        }
+    }
 }
@@ -2,11 +2,13 @@
 
 mod admin_interface;
 mod app_interface;
+pub mod conductor_event;
 pub mod config;
 pub mod signal_subscription;
 pub mod state_dump;
 
 pub use admin_interface::*;
 pub use app_interface::*;
+pub use conductor_event::ConductorEvent;
 pub use config::*;
 pub use state_dump::*;
@@ -1,16 +1,19 @@
 use holochain_serialized_bytes::prelude::*;
 use holochain_types::app::InstalledAppId;
+use holochain_types::signal::Signal;
 use holochain_zome_types::cell::CellId;
+use holochain_zome_types::zome::ZomeName;
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 /// Declares updated Signal subscription settings for an App.
 /// This message is part of the AppInterfaceApi
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes)]
 pub struct SignalSubscription {
     /// The app for which to manage subscription
-    installed_app_id: InstalledAppId,
+    pub installed_app_id: InstalledAppId,
     /// Fine-grained per-cell filters
-    filters: SignalFilterSet,
+    pub filters: SignalFilterSet,
 }
 
 /// Associate a SignalFilter with each Cell in an App.
@@ -19,6 +22,9 @@ pub struct SignalSubscription {
 ///
 /// An empty Exclude filter means "allow all signals" (subscribe to all).
 /// An empty Include filter means "block all signals" (unsubscribe from all).
+///
+/// System signals don't come from a Cell, so they are checked against
+/// the filters of every Cell in the set.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes)]
 pub enum SignalFilterSet {
     /// Only allow signals from the specified Cells with the specified filters,
@@ -46,22 +52,184 @@ impl SignalFilterSet {
     pub fn block_all() -> Self {
         SignalFilterSet::Include(HashMap::new())
     }
+
+    /// Should this signal be let through?
+    pub fn allows(&self, signal: &Signal) -> bool {
+        match self {
+            SignalFilterSet::Include(filters) => Self::any_match(filters, signal),
+            SignalFilterSet::Exclude(filters) => !Self::any_match(filters, signal),
+        }
+    }
+
+    fn any_match(filters: &HashMap<CellId, SignalFilter>, signal: &Signal) -> bool {
+        match signal {
+            Signal::App(cell_id, _, _) => filters
+                .get(cell_id)
+                .map(|filter| filter.matches(signal))
+                .unwrap_or(false),
+            Signal::System(_) => filters.values().any(|filter| filter.matches(signal)),
+        }
+    }
 }
 
-/// Specifies fine-grained filter controls for the signals
-#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes)]
-pub struct SignalFilter;
+/// The kinds of [`Signal`] that can be filtered on
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, SerializedBytes,
+)]
+pub enum SignalKind {
+    /// [`Signal::App`]
+    App,
+    /// [`Signal::System`]
+    System,
+}
 
-impl Default for SignalFilter {
-    fn default() -> Self {
-        // The default is no filter
-        Self::empty()
+impl From<&Signal> for SignalKind {
+    fn from(signal: &Signal) -> Self {
+        match signal {
+            Signal::App(_, _, _) => SignalKind::App,
+            Signal::System(_) => SignalKind::System,
+        }
     }
 }
 
+/// Specifies fine-grained filter controls for the signals
+///
+/// A signal matches if it matches every non-empty field.
+/// An empty field matches everything.
+#[derive(
+    Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes,
+)]
+pub struct SignalFilter {
+    /// Only match signals of these kinds
+    #[serde(default)]
+    pub kinds: HashSet<SignalKind>,
+    /// Only match app signals emitted by these zomes
+    #[serde(default)]
+    pub zomes: HashSet<ZomeName>,
+    /// Only match app signals emitted with one of these topics
+    #[serde(default)]
+    pub topics: HashSet<String>,
+}
+
 impl SignalFilter {
     /// A passthrough filter which filters nothing
     pub fn empty() -> Self {
-        SignalFilter
+        Self::default()
+    }
+
+    /// Match only these kinds of signal
+    pub fn kinds(kinds: impl IntoIterator<Item = SignalKind>) -> Self {
+        Self {
+            kinds: kinds.into_iter().collect(),
+            ..Self::empty()
+        }
+    }
+
+    /// Match only app signals from these zomes
+    pub fn zomes(zomes: impl IntoIterator<Item = ZomeName>) -> Self {
+        Self {
+            zomes: zomes.into_iter().collect(),
+            ..Self::empty()
+        }
+    }
+
+    /// Match only app signals with these topics
+    pub fn topics(topics: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            topics: topics.into_iter().collect(),
+            ..Self::empty()
+        }
+    }
+
+    /// Does this signal match the filter?
+    pub fn matches(&self, signal: &Signal) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&SignalKind::from(signal)) {
+            return false;
+        }
+        match signal {
+            Signal::App(_, zome_name, app_signal) => {
+                (self.zomes.is_empty() || self.zomes.contains(zome_name))
+                    && (self.topics.is_empty()
+                        || app_signal
+                            .topic()
+                            .map(|topic| self.topics.contains(topic))
+                            .unwrap_or(false))
+            }
+            // System signals have no zome or topic
+            Signal::System(_) => self.zomes.is_empty() && self.topics.is_empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_types::signal::test_signal;
+    use holochain_zome_types::signal::AppSignal;
+    use holochain_zome_types::test_utils::fake_cell_id;
+    use holochain_zome_types::ExternIO;
+
+    fn app_signal(cell_id: &CellId, zome: &str, topic: Option<&str>) -> Signal {
+        let payload = ExternIO::encode(()).unwrap();
+        let app_signal = match topic {
+            Some(topic) => AppSignal::with_topic(topic.to_string(), payload),
+            None => AppSignal::new(payload),
+        };
+        Signal::App(cell_id.clone(), zome.into(), app_signal)
+    }
+
+    #[test]
+    fn filter_matches() {
+        let cell_id = fake_cell_id(1);
+        let posts = app_signal(&cell_id, "posts", Some("new_post"));
+        let comments = app_signal(&cell_id, "comments", None);
+        let system = test_signal("test");
+
+        let filter = SignalFilter::empty();
+        assert!(filter.matches(&posts));
+        assert!(filter.matches(&comments));
+        assert!(filter.matches(&system));
+
+        let filter = SignalFilter::kinds(vec![SignalKind::System]);
+        assert!(!filter.matches(&posts));
+        assert!(filter.matches(&system));
+
+        let filter = SignalFilter::zomes(vec!["posts".into()]);
+        assert!(filter.matches(&posts));
+        assert!(!filter.matches(&comments));
+        assert!(!filter.matches(&system));
+
+        let filter = SignalFilter::topics(vec!["new_post".to_string()]);
+        assert!(filter.matches(&posts));
+        assert!(!filter.matches(&comments));
+    }
+
+    #[test]
+    fn filter_set_allows() {
+        let cell_1 = fake_cell_id(1);
+        let cell_2 = fake_cell_id(2);
+        let posts_1 = app_signal(&cell_1, "posts", None);
+        let comments_1 = app_signal(&cell_1, "comments", None);
+        let posts_2 = app_signal(&cell_2, "posts", None);
+        let system = test_signal("test");
+
+        assert!(SignalFilterSet::allow_all().allows(&posts_1));
+        assert!(SignalFilterSet::allow_all().allows(&system));
+        assert!(!SignalFilterSet::block_all().allows(&posts_1));
+        assert!(!SignalFilterSet::block_all().allows(&system));
+
+        let mut filters = HashMap::new();
+        filters.insert(cell_1.clone(), SignalFilter::zomes(vec!["posts".into()]));
+        let include = SignalFilterSet::Include(filters.clone());
+        assert!(include.allows(&posts_1));
+        assert!(!include.allows(&comments_1));
+        assert!(!include.allows(&posts_2));
+        assert!(!include.allows(&system));
+
+        let exclude = SignalFilterSet::Exclude(filters);
+        assert!(!exclude.allows(&posts_1));
+        assert!(exclude.allows(&comments_1));
+        assert!(exclude.allows(&posts_2));
+        assert!(exclude.allows(&system));
     }
 }
@@ -1,4 +1,6 @@
 use holo_hash::AgentPubKey;
+use holo_hash::AnyDhtHash;
+use holo_hash::DhtOpHash;
 use holo_hash::DnaHash;
 use holochain_state::source_chain::SourceChainJsonDump;
 use serde::Deserialize;
@@ -30,6 +32,22 @@ pub struct IntegrationStateDump {
     /// Ops that are integrated.
     /// This includes rejected.
     pub integrated: usize,
+    /// Ops in the integration limbo that are not currently integrable,
+    /// because what they depend on isn't held and no op in
+    /// either limbo provides it yet.
+    #[serde(default)]
+    pub stuck_ops: Vec<StuckOpDump>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+/// An op stuck in the integration limbo
+pub struct StuckOpDump {
+    /// The op's hash.
+    pub op_hash: DhtOpHash,
+    /// The kind of op, e.g. `RegisterAddLink`.
+    pub op_type: String,
+    /// The header or entry the op is waiting for.
+    pub awaiting: AnyDhtHash,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -71,6 +89,13 @@ impl std::fmt::Display for JsonDump {
             "Ops: Limbo (validation: {} integration: {}) Integrated: {}",
             int.validation_limbo, int.integration_limbo, int.integrated
         )?;
+        for stuck in &int.stuck_ops {
+            writeln!(
+                f,
+                "Stuck op: {} {} awaiting {}",
+                stuck.op_type, stuck.op_hash, stuck.awaiting
+            )?;
+        }
         writeln!(
             f,
             "Elements authored: {}, Ops published: {}",
@@ -24,6 +24,22 @@ pub enum KeystoreError {
     #[error("Secure primitive error: {0}")]
     SecurePrimitiveError(#[from] holochain_zome_types::SecurePrimitiveError),
 
+    /// Io error while getting a passphrase.
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
+    /// The configured passphrase service could not provide a passphrase.
+    #[error("Could not get a passphrase to unlock the keystore: {0}")]
+    PassphraseUnavailable(String),
+
+    /// Lair did not accept the passphrase it was given.
+    #[error("The keystore rejected the passphrase {0} time(s) in a row, is it correct?")]
+    PassphraseRejected(usize),
+
+    /// A remote keystore service failed or sent something unexpected.
+    #[error("Remote keystore service error: {0}")]
+    RemoteService(String),
+
     /// Unexpected Internal Error.
     #[error("Other: {0}")]
     Other(String),
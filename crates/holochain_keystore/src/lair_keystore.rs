@@ -1,14 +1,45 @@
 //! Keystore backed by lair_keystore_client.
 
 use crate::*;
+use ghost_actor::dependencies::futures::future::Fuse;
 use ghost_actor::dependencies::futures::future::FutureExt;
 use ghost_actor::dependencies::futures::stream::StreamExt;
 use lair_keystore_api::actor::*;
 use lair_keystore_api::*;
+use std::path::PathBuf;
+
+/// The message sent over a [`PassphraseService::UnixSocket`] connection
+/// to ask the other side for a passphrase.
+pub const PASSPHRASE_REQUEST: &[u8] = b"request_passphrase\n";
+
+/// How many times in a row lair may reject a passphrase before we give up.
+pub const MAX_PASSPHRASE_ATTEMPTS: usize = 3;
+
+/// Used when no passphrase service is configured.
+const BLANK_PASSPHRASE: &str = "[blank-passphrase]";
+
+/// Where to get the passphrase for unlocking lair from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PassphraseService {
+    /// Prompt on the terminal and read a line from stdin.
+    Cmd,
+    /// Listen on a Unix domain socket at this path.
+    /// Once a client connects, each time a passphrase is needed
+    /// [`PASSPHRASE_REQUEST`] is written to the client and
+    /// everything it sends up to a newline is used as the passphrase.
+    /// The socket is only accessible by the user running the conductor.
+    UnixSocket(PathBuf),
+    /// Always use this passphrase.
+    Fixed(String),
+}
 
 /// Spawn a new keystore backed by lair_keystore_client.
+///
+/// Lair's requests to unlock are answered from the `passphrase_service`.
+/// If none is given a blank passphrase is used.
 pub async fn spawn_lair_keystore(
     lair_dir: Option<&std::path::Path>,
+    passphrase_service: Option<PassphraseService>,
 ) -> KeystoreApiResult<KeystoreSender> {
     let mut config = Config::builder();
     if let Some(lair_dir) = lair_dir {
@@ -17,18 +48,337 @@ pub async fn spawn_lair_keystore(
     let config = config.build();
     let (api, mut evt) = lair_keystore_client::assert_running_lair_and_connect(config).await?;
 
-    // TODO - actual passphrase handling
+    let unlock_api = api.clone();
     tokio::task::spawn(async move {
-        while let Some(r) = evt.next().await {
-            match r {
-                LairClientEvent::RequestUnlockPassphrase { respond, .. } => {
-                    respond.respond(Ok(async move { Ok("[blank-passphrase]".to_string()) }
-                        .boxed()
-                        .into()));
+        let mut prompt = passphrase_service.map(PassphrasePrompt::new);
+        let mut attempts = 0;
+        // Resolves once lair answers a request after we gave it a passphrase,
+        // which it can only do once unlocked.
+        let mut unlocked = Fuse::terminated();
+        loop {
+            tokio::select! {
+                r = evt.next() => match r {
+                    Some(LairClientEvent::RequestUnlockPassphrase { respond, .. }) => {
+                        // Lair only asks again on the same connection
+                        // if the passphrase we gave it didn't unlock it.
+                        let retry = attempts > 0;
+                        attempts += 1;
+
+                        let result = if attempts > MAX_PASSPHRASE_ATTEMPTS {
+                            Err(KeystoreError::PassphraseRejected(MAX_PASSPHRASE_ATTEMPTS))
+                        } else {
+                            if retry {
+                                tracing::error!(
+                                    "Lair rejected the passphrase, attempt {} of {}",
+                                    attempts,
+                                    MAX_PASSPHRASE_ATTEMPTS
+                                );
+                            }
+                            match &mut prompt {
+                                Some(prompt) => prompt.request_passphrase(retry).await,
+                                None => Ok(BLANK_PASSPHRASE.to_string()),
+                            }
+                        };
+                        match &result {
+                            Ok(_) => {
+                                let api = unlock_api.clone();
+                                unlocked = async move {
+                                    api.lair_get_last_entry_index().await.is_ok()
+                                }
+                                .boxed()
+                                .fuse();
+                            }
+                            Err(e) => tracing::error!(msg = "Failed to unlock lair", ?e),
+                        }
+
+                        let result = result.map_err(LairError::from);
+                        respond.respond(Ok(async move { result }.boxed().into()));
+                    }
+                    None => break,
+                },
+                is_unlocked = &mut unlocked => {
+                    if is_unlocked {
+                        // Any later request is for a new unlock, not a retry.
+                        attempts = 0;
+                    }
                 }
             }
         }
+        if attempts > MAX_PASSPHRASE_ATTEMPTS {
+            tracing::error!(
+                "Lair closed the connection after rejecting the passphrase {} times",
+                MAX_PASSPHRASE_ATTEMPTS
+            );
+        } else {
+            tracing::warn!("Lair closed the connection");
+        }
     });
 
     Ok(api)
 }
+
+/// A [`PassphraseService`] along with any connection it has open.
+struct PassphrasePrompt {
+    service: PassphraseService,
+    #[cfg(unix)]
+    socket: Option<unix_socket::PassphraseSocket>,
+}
+
+impl PassphrasePrompt {
+    fn new(service: PassphraseService) -> Self {
+        Self {
+            service,
+            #[cfg(unix)]
+            socket: None,
+        }
+    }
+
+    async fn request_passphrase(&mut self, retry: bool) -> KeystoreApiResult<String> {
+        match &self.service {
+            PassphraseService::Cmd => read_stdin_passphrase(retry).await,
+            PassphraseService::Fixed(passphrase) => {
+                if retry {
+                    // Asking again won't change the answer.
+                    Err(KeystoreError::PassphraseRejected(1))
+                } else {
+                    Ok(passphrase.clone())
+                }
+            }
+            #[cfg(unix)]
+            PassphraseService::UnixSocket(path) => {
+                if self.socket.is_none() {
+                    self.socket = Some(unix_socket::PassphraseSocket::bind(path)?);
+                }
+                let socket = self.socket.as_mut().expect("socket was just bound");
+                let result = socket.request_passphrase().await;
+                if result.is_err() {
+                    // Wait for a fresh connection next time.
+                    socket.disconnect();
+                }
+                result
+            }
+            #[cfg(not(unix))]
+            PassphraseService::UnixSocket(_) => Err(KeystoreError::PassphraseUnavailable(
+                "Unix sockets are not supported on this platform".to_string(),
+            )),
+        }
+    }
+}
+
+async fn read_stdin_passphrase(retry: bool) -> KeystoreApiResult<String> {
+    tokio::task::spawn_blocking(move || {
+        use std::io::BufRead;
+        use std::io::Write;
+        if retry {
+            eprintln!("Incorrect passphrase, please try again.");
+        }
+        eprint!("Passphrase to unlock the keystore: ");
+        std::io::stderr().flush().ok();
+        let mut line = Vec::new();
+        let read = std::io::stdin().lock().read_until(b'\n', &mut line)?;
+        passphrase_from_line(read, line)
+    })
+    .await
+    .map_err(|e| KeystoreError::PassphraseUnavailable(e.to_string()))?
+}
+
+/// Turn a line read from a passphrase source into the passphrase.
+fn passphrase_from_line(read: usize, mut line: Vec<u8>) -> KeystoreApiResult<String> {
+    if read == 0 || line.last() != Some(&b'\n') {
+        return Err(KeystoreError::PassphraseUnavailable(
+            "The passphrase source closed before sending a newline".to_string(),
+        ));
+    }
+    line.pop();
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    String::from_utf8(line).map_err(|_| {
+        KeystoreError::PassphraseUnavailable("The passphrase was not valid utf8".to_string())
+    })
+}
+
+#[cfg(unix)]
+mod unix_socket {
+    use super::*;
+    use std::os::unix::fs::DirBuilderExt;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::io::BufReader;
+    use tokio::net::UnixListener;
+    use tokio::net::UnixStream;
+
+    /// The listening end of a [`PassphraseService::UnixSocket`].
+    pub(super) struct PassphraseSocket {
+        path: PathBuf,
+        listener: UnixListener,
+        stream: Option<BufReader<UnixStream>>,
+    }
+
+    impl PassphraseSocket {
+        /// The socket file is only accessible by its owner.
+        /// A socket left behind at `path` by a previous run is replaced,
+        /// but any other kind of file there is an error.
+        pub(super) fn bind(path: &std::path::Path) -> KeystoreApiResult<Self> {
+            if let Ok(metadata) = std::fs::symlink_metadata(path) {
+                if !metadata.file_type().is_socket() {
+                    return Err(KeystoreError::PassphraseUnavailable(format!(
+                        "{} exists and is not a socket",
+                        path.display()
+                    )));
+                }
+            }
+            let listener = bind_owner_only(path)?;
+            Ok(Self {
+                path: path.to_path_buf(),
+                listener,
+                stream: None,
+            })
+        }
+
+        pub(super) async fn request_passphrase(&mut self) -> KeystoreApiResult<String> {
+            if self.stream.is_none() {
+                tracing::info!(
+                    "Waiting for a passphrase client to connect to {}",
+                    self.path.display()
+                );
+                let (stream, _) = self.listener.accept().await?;
+                self.stream = Some(BufReader::new(stream));
+            }
+            let stream = self.stream.as_mut().expect("stream was just accepted");
+            stream.get_mut().write_all(PASSPHRASE_REQUEST).await?;
+            let mut line = Vec::new();
+            let read = stream.read_until(b'\n', &mut line).await?;
+            passphrase_from_line(read, line)
+        }
+
+        pub(super) fn disconnect(&mut self) {
+            self.stream = None;
+        }
+    }
+
+    /// Bind the socket and set its permissions inside a new directory
+    /// only the owner can enter, then move it to `path`,
+    /// so nobody else can connect to it in between.
+    fn bind_owner_only(path: &std::path::Path) -> KeystoreApiResult<UnixListener> {
+        let file_name = path.file_name().ok_or_else(|| {
+            KeystoreError::PassphraseUnavailable(format!("{} is not a socket path", path.display()))
+        })?;
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        let private_dir = path
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new(""))
+            .join(format!(
+                ".{}-{}-{}",
+                file_name.to_string_lossy(),
+                std::process::id(),
+                nanos
+            ));
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&private_dir)?;
+        let private_path = private_dir.join("socket");
+        let result = (|| -> KeystoreApiResult<UnixListener> {
+            let listener = UnixListener::bind(&private_path)?;
+            std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(0o600))?;
+            // Replaces a socket left at `path` in one step.
+            std::fs::rename(&private_path, path)?;
+            Ok(listener)
+        })();
+        if result.is_err() {
+            std::fs::remove_file(&private_path).ok();
+        }
+        std::fs::remove_dir(&private_dir).ok();
+        result
+    }
+
+    impl Drop for PassphraseSocket {
+        fn drop(&mut self) {
+            std::fs::remove_file(&self.path).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passphrase_lines() {
+        assert_eq!(
+            passphrase_from_line(8, b"hunter2\n".to_vec()).unwrap(),
+            "hunter2"
+        );
+        assert_eq!(
+            passphrase_from_line(9, b"hunter2\r\n".to_vec()).unwrap(),
+            "hunter2"
+        );
+        assert!(passphrase_from_line(0, Vec::new()).is_err());
+        assert!(passphrase_from_line(7, b"hunter2".to_vec()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(threaded_scheduler)]
+    async fn unix_socket_passphrase() {
+        use std::time::Duration;
+        use tokio::io::AsyncBufReadExt;
+        use tokio::io::AsyncWriteExt;
+
+        let path = std::env::temp_dir().join(format!(
+            "holochain-passphrase-test-{}.sock",
+            std::process::id()
+        ));
+        let mut prompt = PassphrasePrompt::new(PassphraseService::UnixSocket(path.clone()));
+        let server = tokio::task::spawn(async move {
+            let first = prompt.request_passphrase(false).await;
+            let second = prompt.request_passphrase(true).await;
+            (first, second)
+        });
+
+        // Wait for the socket to be bound.
+        let stream = loop {
+            match tokio::net::UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::delay_for(Duration::from_millis(10)).await,
+            }
+        };
+        let mut stream = tokio::io::BufReader::new(stream);
+        for passphrase in &[&b"wrong\n"[..], &b"hunter2\n"[..]] {
+            let mut request = Vec::new();
+            stream.read_until(b'\n', &mut request).await.unwrap();
+            assert_eq!(request, PASSPHRASE_REQUEST);
+            stream.get_mut().write_all(passphrase).await.unwrap();
+        }
+
+        let (first, second) = server.await.unwrap();
+        assert_eq!(first.unwrap(), "wrong");
+        assert_eq!(second.unwrap(), "hunter2");
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(threaded_scheduler)]
+    async fn unix_socket_is_private_and_never_replaces_other_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!(
+            "holochain-passphrase-perms-test-{}.sock",
+            std::process::id()
+        ));
+        std::fs::write(&path, b"not a socket").unwrap();
+        assert!(unix_socket::PassphraseSocket::bind(&path).is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"not a socket");
+        std::fs::remove_file(&path).unwrap();
+
+        let socket = unix_socket::PassphraseSocket::bind(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        drop(socket);
+        assert!(!path.exists());
+    }
+}
@@ -45,4 +45,5 @@ mod agent_pubkey_ext;
 pub use agent_pubkey_ext::*;
 
 pub mod lair_keystore;
+pub mod remote_keystore;
 pub mod test_keystore;
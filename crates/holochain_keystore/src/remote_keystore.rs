@@ -0,0 +1,772 @@
+//! Keystore that sends signing and x25519 operations to remote services.
+//!
+//! Each operation can be given its own service. Operations without a service,
+//! everything other than signing and x25519 boxes, and keys the service
+//! reports it doesn't hold are all handled by a fallback keystore, usually lair.
+//!
+//! # Protocol
+//!
+//! A request is a msgpack encoded [`RemoteKeystoreRequest`] and the service
+//! answers with a msgpack encoded [`RemoteKeystoreResponse`].
+//!
+//! - `http://` and `https://` services receive the request as the body of a
+//!   `POST` to the uri and reply with the response as the body.
+//! - `unix://<path>` services receive a new connection per request. Both the
+//!   request and the response are framed as a big endian `u32` length
+//!   followed by that many bytes.
+
+use crate::*;
+use ghost_actor::dependencies::futures::future::FutureExt;
+use holo_hash::AgentPubKey;
+use holochain_zome_types::signature::Sign;
+use holochain_zome_types::signature::Signature;
+use holochain_zome_types::x_salsa20_poly1305::data::XSalsa20Poly1305Data;
+use holochain_zome_types::x_salsa20_poly1305::encrypted_data::XSalsa20Poly1305EncryptedData;
+use holochain_zome_types::x_salsa20_poly1305::X25519XSalsa20Poly1305Decrypt;
+use holochain_zome_types::x_salsa20_poly1305::X25519XSalsa20Poly1305Encrypt;
+use lair_keystore_api::actor::*;
+use lair_keystore_api::internal::crypto_box::CryptoBoxData;
+use lair_keystore_api::internal::crypto_box::CryptoBoxEncryptedData;
+use lair_keystore_api::internal::sign_ed25519::SignEd25519PubKey;
+use lair_keystore_api::internal::sign_ed25519::SignEd25519Signature;
+use lair_keystore_api::internal::x25519::X25519PubKey;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod test_service;
+
+/// The largest message accepted over a unix socket service.
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// How long to wait to connect to a service.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for a service to answer a request, including connecting.
+/// Signing blocks committing to the source chain, so a stuck service
+/// must not hold it up forever.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where a remote keystore service is listening.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteServiceUri {
+    /// An `http://` or `https://` url.
+    Http(String),
+    /// The path of a Unix domain socket, given as `unix://<path>`.
+    UnixSocket(PathBuf),
+}
+
+impl std::str::FromStr for RemoteServiceUri {
+    type Err = KeystoreError;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        if uri.starts_with("http://") || uri.starts_with("https://") {
+            Ok(RemoteServiceUri::Http(uri.to_string()))
+        } else if let Some(path) = uri.strip_prefix("unix://") {
+            Ok(RemoteServiceUri::UnixSocket(PathBuf::from(path)))
+        } else {
+            Err(KeystoreError::RemoteService(format!(
+                "Unsupported remote keystore service uri {}, expected http://, https:// or unix://",
+                uri
+            )))
+        }
+    }
+}
+
+/// Which remote service handles each operation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemoteKeystoreConfig {
+    /// Service for ed25519 signatures.
+    pub signing: Option<RemoteServiceUri>,
+    /// Service for x25519 boxes.
+    pub encryption: Option<RemoteServiceUri>,
+    /// Service for opening x25519 boxes.
+    pub decryption: Option<RemoteServiceUri>,
+}
+
+impl RemoteKeystoreConfig {
+    /// Is any operation sent to a remote service?
+    pub fn is_empty(&self) -> bool {
+        self.signing.is_none() && self.encryption.is_none() && self.decryption.is_none()
+    }
+}
+
+/// A request sent to a remote keystore service.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "data")]
+pub enum RemoteKeystoreRequest {
+    /// Sign the data with the key.
+    Sign(Sign),
+    /// Box the data from the sender to the recipient.
+    Encrypt(X25519XSalsa20Poly1305Encrypt),
+    /// Open a box from the sender to the recipient.
+    Decrypt(X25519XSalsa20Poly1305Decrypt),
+}
+
+/// A remote keystore service's answer to a [`RemoteKeystoreRequest`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "data")]
+pub enum RemoteKeystoreResponse {
+    /// Answer to [`RemoteKeystoreRequest::Sign`].
+    Signature(Signature),
+    /// Answer to [`RemoteKeystoreRequest::Encrypt`].
+    Encrypted(XSalsa20Poly1305EncryptedData),
+    /// Answer to [`RemoteKeystoreRequest::Decrypt`].
+    /// `None` if the box could not be opened.
+    Decrypted(Option<XSalsa20Poly1305Data>),
+    /// The service doesn't hold the key for this request,
+    /// so the fallback keystore should handle it.
+    UnknownKey,
+    /// The service failed to handle the request.
+    Error(String),
+}
+
+/// Spawn a keystore which sends operations to the services in `config`
+/// and everything else to `fallback`.
+pub async fn spawn_remote_keystore(
+    config: RemoteKeystoreConfig,
+    fallback: KeystoreSender,
+) -> KeystoreApiResult<KeystoreSender> {
+    let builder = ghost_actor::actor_builder::GhostActorBuilder::new();
+    let sender = builder
+        .channel_factory()
+        .create_channel::<LairClientApi>()
+        .await?;
+    let client = RemoteClient::new(REQUEST_TIMEOUT)?;
+    tokio::task::spawn(builder.spawn(RemoteKeystore {
+        config: Arc::new(config),
+        client,
+        fallback,
+    }));
+    Ok(sender)
+}
+
+struct RemoteKeystore {
+    config: Arc<RemoteKeystoreConfig>,
+    client: RemoteClient,
+    fallback: KeystoreSender,
+}
+
+/// Sends requests to services, giving up on any that take too long.
+#[derive(Clone)]
+struct RemoteClient {
+    http: reqwest::Client,
+    timeout: Duration,
+}
+
+impl RemoteClient {
+    fn new(timeout: Duration) -> KeystoreApiResult<Self> {
+        let http = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(|e| KeystoreError::RemoteService(e.to_string()))?;
+        Ok(Self { http, timeout })
+    }
+}
+
+impl ghost_actor::GhostControlHandler for RemoteKeystore {}
+
+impl ghost_actor::GhostHandler<LairClientApi> for RemoteKeystore {}
+
+impl LairClientApiHandler for RemoteKeystore {
+    fn handle_lair_get_server_info(&mut self) -> LairClientApiHandlerResult<LairServerInfo> {
+        Ok(self.fallback.lair_get_server_info())
+    }
+
+    fn handle_lair_get_last_entry_index(&mut self) -> LairClientApiHandlerResult<KeystoreIndex> {
+        Ok(self.fallback.lair_get_last_entry_index())
+    }
+
+    fn handle_lair_get_entry_type(
+        &mut self,
+        keystore_index: KeystoreIndex,
+    ) -> LairClientApiHandlerResult<LairEntryType> {
+        Ok(self.fallback.lair_get_entry_type(keystore_index))
+    }
+
+    fn handle_tls_cert_new_self_signed_from_entropy(
+        &mut self,
+        options: TlsCertOptions,
+    ) -> LairClientApiHandlerResult<(KeystoreIndex, CertSni, CertDigest)> {
+        Ok(self.fallback.tls_cert_new_self_signed_from_entropy(options))
+    }
+
+    fn handle_tls_cert_get(
+        &mut self,
+        keystore_index: KeystoreIndex,
+    ) -> LairClientApiHandlerResult<(CertSni, CertDigest)> {
+        Ok(self.fallback.tls_cert_get(keystore_index))
+    }
+
+    fn handle_tls_cert_get_cert_by_index(
+        &mut self,
+        keystore_index: KeystoreIndex,
+    ) -> LairClientApiHandlerResult<Cert> {
+        Ok(self.fallback.tls_cert_get_cert_by_index(keystore_index))
+    }
+
+    fn handle_tls_cert_get_cert_by_digest(
+        &mut self,
+        cert_digest: CertDigest,
+    ) -> LairClientApiHandlerResult<Cert> {
+        Ok(self.fallback.tls_cert_get_cert_by_digest(cert_digest))
+    }
+
+    fn handle_tls_cert_get_cert_by_sni(
+        &mut self,
+        cert_sni: CertSni,
+    ) -> LairClientApiHandlerResult<Cert> {
+        Ok(self.fallback.tls_cert_get_cert_by_sni(cert_sni))
+    }
+
+    fn handle_tls_cert_get_priv_key_by_index(
+        &mut self,
+        keystore_index: KeystoreIndex,
+    ) -> LairClientApiHandlerResult<CertPrivKey> {
+        Ok(self.fallback.tls_cert_get_priv_key_by_index(keystore_index))
+    }
+
+    fn handle_tls_cert_get_priv_key_by_digest(
+        &mut self,
+        cert_digest: CertDigest,
+    ) -> LairClientApiHandlerResult<CertPrivKey> {
+        Ok(self.fallback.tls_cert_get_priv_key_by_digest(cert_digest))
+    }
+
+    fn handle_tls_cert_get_priv_key_by_sni(
+        &mut self,
+        cert_sni: CertSni,
+    ) -> LairClientApiHandlerResult<CertPrivKey> {
+        Ok(self.fallback.tls_cert_get_priv_key_by_sni(cert_sni))
+    }
+
+    fn handle_sign_ed25519_new_from_entropy(
+        &mut self,
+    ) -> LairClientApiHandlerResult<(KeystoreIndex, SignEd25519PubKey)> {
+        Ok(self.fallback.sign_ed25519_new_from_entropy())
+    }
+
+    fn handle_sign_ed25519_get(
+        &mut self,
+        keystore_index: KeystoreIndex,
+    ) -> LairClientApiHandlerResult<SignEd25519PubKey> {
+        Ok(self.fallback.sign_ed25519_get(keystore_index))
+    }
+
+    fn handle_sign_ed25519_sign_by_index(
+        &mut self,
+        keystore_index: KeystoreIndex,
+        message: Arc<Vec<u8>>,
+    ) -> LairClientApiHandlerResult<SignEd25519Signature> {
+        Ok(self
+            .fallback
+            .sign_ed25519_sign_by_index(keystore_index, message))
+    }
+
+    fn handle_sign_ed25519_sign_by_pub_key(
+        &mut self,
+        pub_key: SignEd25519PubKey,
+        message: Arc<Vec<u8>>,
+    ) -> LairClientApiHandlerResult<SignEd25519Signature> {
+        let uri = match &self.config.signing {
+            Some(uri) => uri.clone(),
+            None => return Ok(self.fallback.sign_ed25519_sign_by_pub_key(pub_key, message)),
+        };
+        let client = self.client.clone();
+        let fallback = self.fallback.clone();
+        Ok(async move {
+            match remote_sign(&client, &uri, &pub_key, &message).await? {
+                Some(signature) => Ok(signature),
+                None => {
+                    fallback
+                        .sign_ed25519_sign_by_pub_key(pub_key, message)
+                        .await
+                }
+            }
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_x25519_new_from_entropy(
+        &mut self,
+    ) -> LairClientApiHandlerResult<(KeystoreIndex, X25519PubKey)> {
+        Ok(self.fallback.x25519_new_from_entropy())
+    }
+
+    fn handle_x25519_get(
+        &mut self,
+        keystore_index: KeystoreIndex,
+    ) -> LairClientApiHandlerResult<X25519PubKey> {
+        Ok(self.fallback.x25519_get(keystore_index))
+    }
+
+    fn handle_crypto_box_by_index(
+        &mut self,
+        keystore_index: KeystoreIndex,
+        recipient: X25519PubKey,
+        data: Arc<CryptoBoxData>,
+    ) -> LairClientApiHandlerResult<CryptoBoxEncryptedData> {
+        Ok(self
+            .fallback
+            .crypto_box_by_index(keystore_index, recipient, data))
+    }
+
+    fn handle_crypto_box_by_pub_key(
+        &mut self,
+        pub_key: X25519PubKey,
+        recipient: X25519PubKey,
+        data: Arc<CryptoBoxData>,
+    ) -> LairClientApiHandlerResult<CryptoBoxEncryptedData> {
+        let uri = match &self.config.encryption {
+            Some(uri) => uri.clone(),
+            None => {
+                return Ok(self
+                    .fallback
+                    .crypto_box_by_pub_key(pub_key, recipient, data))
+            }
+        };
+        let client = self.client.clone();
+        let fallback = self.fallback.clone();
+        Ok(async move {
+            match remote_encrypt(&client, &uri, &pub_key, &recipient, &data).await? {
+                Some(encrypted) => Ok(encrypted),
+                None => {
+                    fallback
+                        .crypto_box_by_pub_key(pub_key, recipient, data)
+                        .await
+                }
+            }
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_crypto_box_open_by_index(
+        &mut self,
+        keystore_index: KeystoreIndex,
+        sender: X25519PubKey,
+        encrypted_data: Arc<CryptoBoxEncryptedData>,
+    ) -> LairClientApiHandlerResult<Option<CryptoBoxData>> {
+        Ok(self
+            .fallback
+            .crypto_box_open_by_index(keystore_index, sender, encrypted_data))
+    }
+
+    fn handle_crypto_box_open_by_pub_key(
+        &mut self,
+        pub_key: X25519PubKey,
+        sender: X25519PubKey,
+        encrypted_data: Arc<CryptoBoxEncryptedData>,
+    ) -> LairClientApiHandlerResult<Option<CryptoBoxData>> {
+        let uri = match &self.config.decryption {
+            Some(uri) => uri.clone(),
+            None => {
+                return Ok(self.fallback.crypto_box_open_by_pub_key(
+                    pub_key,
+                    sender,
+                    encrypted_data,
+                ))
+            }
+        };
+        let client = self.client.clone();
+        let fallback = self.fallback.clone();
+        Ok(async move {
+            match remote_decrypt(&client, &uri, &pub_key, &sender, &encrypted_data).await? {
+                Some(data) => Ok(data),
+                None => {
+                    fallback
+                        .crypto_box_open_by_pub_key(pub_key, sender, encrypted_data)
+                        .await
+                }
+            }
+        }
+        .boxed()
+        .into())
+    }
+}
+
+/// Sign remotely, or `None` if the service doesn't hold the key.
+async fn remote_sign(
+    client: &RemoteClient,
+    uri: &RemoteServiceUri,
+    pub_key: &SignEd25519PubKey,
+    message: &[u8],
+) -> KeystoreApiResult<Option<SignEd25519Signature>> {
+    let request = RemoteKeystoreRequest::Sign(Sign::new_raw(
+        AgentPubKey::from_raw_32(pub_key.to_vec()),
+        message.to_vec(),
+    ));
+    match call_remote(client, uri, &request).await? {
+        RemoteKeystoreResponse::Signature(signature) => Ok(Some(signature.0.into())),
+        RemoteKeystoreResponse::UnknownKey => Ok(None),
+        response => Err(unexpected_response(response)),
+    }
+}
+
+/// Box remotely, or `None` if the service doesn't hold the sender's key.
+async fn remote_encrypt(
+    client: &RemoteClient,
+    uri: &RemoteServiceUri,
+    sender: &X25519PubKey,
+    recipient: &X25519PubKey,
+    data: &CryptoBoxData,
+) -> KeystoreApiResult<Option<CryptoBoxEncryptedData>> {
+    let request = RemoteKeystoreRequest::Encrypt(X25519XSalsa20Poly1305Encrypt::new(
+        AsRef::<[u8]>::as_ref(sender).try_into()?,
+        AsRef::<[u8]>::as_ref(recipient).try_into()?,
+        data.data.to_vec().into(),
+    ));
+    match call_remote(client, uri, &request).await? {
+        RemoteKeystoreResponse::Encrypted(encrypted) => Ok(Some(CryptoBoxEncryptedData {
+            nonce: AsRef::<[u8]>::as_ref(encrypted.as_nonce_ref()).try_into()?,
+            encrypted_data: Arc::new(encrypted.as_encrypted_data_ref().to_vec()),
+        })),
+        RemoteKeystoreResponse::UnknownKey => Ok(None),
+        response => Err(unexpected_response(response)),
+    }
+}
+
+/// Open a box remotely, or `None` if the service doesn't hold the recipient's key.
+async fn remote_decrypt(
+    client: &RemoteClient,
+    uri: &RemoteServiceUri,
+    recipient: &X25519PubKey,
+    sender: &X25519PubKey,
+    encrypted_data: &CryptoBoxEncryptedData,
+) -> KeystoreApiResult<Option<Option<CryptoBoxData>>> {
+    let request = RemoteKeystoreRequest::Decrypt(X25519XSalsa20Poly1305Decrypt::new(
+        AsRef::<[u8]>::as_ref(sender).try_into()?,
+        AsRef::<[u8]>::as_ref(recipient).try_into()?,
+        XSalsa20Poly1305EncryptedData::new(
+            AsRef::<[u8]>::as_ref(&encrypted_data.nonce).try_into()?,
+            encrypted_data.encrypted_data.to_vec(),
+        ),
+    ));
+    match call_remote(client, uri, &request).await? {
+        RemoteKeystoreResponse::Decrypted(data) => Ok(Some(data.map(|data| CryptoBoxData {
+            data: Arc::new(data.as_ref().to_vec()),
+        }))),
+        RemoteKeystoreResponse::UnknownKey => Ok(None),
+        response => Err(unexpected_response(response)),
+    }
+}
+
+fn unexpected_response(response: RemoteKeystoreResponse) -> KeystoreError {
+    match response {
+        RemoteKeystoreResponse::Error(e) => KeystoreError::RemoteService(e),
+        response => KeystoreError::RemoteService(format!("Unexpected response {:?}", response)),
+    }
+}
+
+async fn call_remote(
+    client: &RemoteClient,
+    uri: &RemoteServiceUri,
+    request: &RemoteKeystoreRequest,
+) -> KeystoreApiResult<RemoteKeystoreResponse> {
+    let request = holochain_serialized_bytes::encode(request)?;
+    let call = async {
+        let response: KeystoreApiResult<Vec<u8>> = match uri {
+            RemoteServiceUri::Http(url) => {
+                let response = client
+                    .http
+                    .post(url)
+                    .body(request)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| KeystoreError::RemoteService(e.to_string()))?;
+                Ok(response
+                    .bytes()
+                    .await
+                    .map_err(|e| KeystoreError::RemoteService(e.to_string()))?
+                    .to_vec())
+            }
+            #[cfg(unix)]
+            RemoteServiceUri::UnixSocket(path) => {
+                let mut stream = tokio::net::UnixStream::connect(path).await?;
+                write_frame(&mut stream, &request).await?;
+                read_frame(&mut stream).await
+            }
+            #[cfg(not(unix))]
+            RemoteServiceUri::UnixSocket(_) => Err(KeystoreError::RemoteService(
+                "Unix sockets are not supported on this platform".to_string(),
+            )),
+        };
+        response
+    };
+    let response = tokio::time::timeout(client.timeout, call)
+        .await
+        .map_err(|_| {
+            KeystoreError::RemoteService(format!(
+                "The service at {:?} didn't answer within {:?}",
+                uri, client.timeout
+            ))
+        })??;
+    Ok(holochain_serialized_bytes::decode(&response)?)
+}
+
+/// Write a length prefixed frame to a unix socket service.
+pub(crate) async fn write_frame<W>(writer: &mut W, data: &[u8]) -> KeystoreApiResult<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::AsyncWriteExt;
+    writer.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    writer.write_all(data).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read a length prefixed frame from a unix socket service.
+pub(crate) async fn read_frame<R>(reader: &mut R) -> KeystoreApiResult<Vec<u8>>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+    let mut len = [0; 4];
+    reader.read_exact(&mut len).await?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(KeystoreError::RemoteService(format!(
+            "Frame of {} bytes is larger than the limit of {}",
+            len, MAX_FRAME_SIZE
+        )));
+    }
+    let mut data = vec![0; len];
+    reader.read_exact(&mut data).await?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_service::*;
+    use super::*;
+    use crate::test_keystore::spawn_test_keystore;
+    use holochain_zome_types::x_salsa20_poly1305::x25519::X25519PubKey as ZomeX25519PubKey;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
+    struct MyData(Vec<u8>);
+
+    /// The remote service holds `remote_agent`,
+    /// the fallback holds every other key.
+    async fn check_remote_keystore(
+        uri: RemoteServiceUri,
+        service: &TestRemoteKeystoreService,
+        service_keystore: &KeystoreSender,
+        remote_agent: AgentPubKey,
+    ) {
+        let data = MyData(b"remote keystore test data".to_vec());
+        let fallback = spawn_test_keystore().await.unwrap();
+        // Find a key that only the fallback holds.
+        let local_agent = loop {
+            let agent = fallback
+                .generate_sign_keypair_from_pure_entropy()
+                .await
+                .unwrap();
+            if agent.sign(service_keystore, &data).await.is_err() {
+                break agent;
+            }
+        };
+        let keystore = spawn_remote_keystore(
+            RemoteKeystoreConfig {
+                signing: Some(uri.clone()),
+                encryption: Some(uri.clone()),
+                decryption: Some(uri),
+            },
+            fallback,
+        )
+        .await
+        .unwrap();
+
+        let signature = remote_agent.sign(&keystore, &data).await.unwrap();
+        assert!(remote_agent
+            .verify_signature(&signature, &data)
+            .await
+            .unwrap());
+        assert_eq!(service.requests(), 1);
+
+        // The service doesn't know this agent so lair signs instead.
+        let signature = local_agent.sign(&keystore, &data).await.unwrap();
+        assert!(local_agent
+            .verify_signature(&signature, &data)
+            .await
+            .unwrap());
+        assert_eq!(service.requests(), 2);
+
+        let alice: ZomeX25519PubKey = keystore.create_x25519_keypair().await.unwrap();
+        let bob: ZomeX25519PubKey = keystore.create_x25519_keypair().await.unwrap();
+        let data = XSalsa20Poly1305Data::from(b"remote box".to_vec());
+        let encrypted = keystore
+            .x_25519_x_salsa20_poly1305_encrypt(X25519XSalsa20Poly1305Encrypt::new(
+                alice,
+                bob,
+                data.clone(),
+            ))
+            .await
+            .unwrap();
+        let decrypted = keystore
+            .x_25519_x_salsa20_poly1305_decrypt(X25519XSalsa20Poly1305Decrypt::new(
+                alice, bob, encrypted,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(decrypted, Some(data));
+        assert_eq!(service.requests(), 4);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn remote_keystore_over_http() {
+        let service_keystore = spawn_test_keystore().await.unwrap();
+        let remote_agent = service_keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let service = TestRemoteKeystoreService::new(service_keystore.clone());
+        let addr = service.serve_http(([127, 0, 0, 1], 0)).await.unwrap();
+        let uri = format!("http://{}", addr).parse().unwrap();
+        check_remote_keystore(uri, &service, &service_keystore, remote_agent).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(threaded_scheduler)]
+    async fn remote_keystore_over_unix_socket() {
+        let service_keystore = spawn_test_keystore().await.unwrap();
+        let remote_agent = service_keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let service = TestRemoteKeystoreService::new(service_keystore.clone());
+        let path = std::env::temp_dir().join(format!(
+            "holochain-remote-keystore-test-{}.sock",
+            std::process::id()
+        ));
+        service.serve_unix_socket(&path).await.unwrap();
+        let uri = format!("unix://{}", path.display()).parse().unwrap();
+        check_remote_keystore(uri, &service, &service_keystore, remote_agent).await;
+        std::fs::remove_file(path).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(threaded_scheduler)]
+    async fn unix_socket_service_that_never_answers_times_out() {
+        let path = std::env::temp_dir().join(format!(
+            "holochain-remote-keystore-stuck-test-{}.sock",
+            std::process::id()
+        ));
+        std::fs::remove_file(&path).ok();
+        // Connections are queued but never accepted or answered
+        let _listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let client = RemoteClient::new(Duration::from_millis(100)).unwrap();
+        let request = RemoteKeystoreRequest::Sign(Sign::new_raw(
+            AgentPubKey::from_raw_32(vec![0; 32]),
+            vec![],
+        ));
+        let result = call_remote(
+            &client,
+            &RemoteServiceUri::UnixSocket(path.clone()),
+            &request,
+        )
+        .await;
+        assert!(matches!(result, Err(KeystoreError::RemoteService(_))));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(threaded_scheduler)]
+    async fn x25519_boxes_are_made_and_opened_by_the_remote_service() {
+        let path = std::env::temp_dir().join(format!(
+            "holochain-remote-keystore-stub-test-{}.sock",
+            std::process::id()
+        ));
+        std::fs::remove_file(&path).ok();
+        // A stub service with canned answers that the fallback could never give
+        let encrypted = XSalsa20Poly1305EncryptedData::new([7; 24].into(), vec![1, 2, 3]);
+        let opened = XSalsa20Poly1305Data::from(b"opened by the stub".to_vec());
+        let mut listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let (requests_tx, mut requests_rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::task::spawn({
+            let encrypted = encrypted.clone();
+            let opened = opened.clone();
+            async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let request: RemoteKeystoreRequest =
+                        holochain_serialized_bytes::decode(&read_frame(&mut stream).await.unwrap())
+                            .unwrap();
+                    let response = match &request {
+                        RemoteKeystoreRequest::Encrypt(_) => {
+                            RemoteKeystoreResponse::Encrypted(encrypted.clone())
+                        }
+                        RemoteKeystoreRequest::Decrypt(_) => {
+                            RemoteKeystoreResponse::Decrypted(Some(opened.clone()))
+                        }
+                        RemoteKeystoreRequest::Sign(_) => RemoteKeystoreResponse::UnknownKey,
+                    };
+                    let response = holochain_serialized_bytes::encode(&response).unwrap();
+                    write_frame(&mut stream, &response).await.unwrap();
+                    requests_tx.send(request).ok();
+                }
+            }
+        });
+        let uri = RemoteServiceUri::UnixSocket(path.clone());
+        let keystore = spawn_remote_keystore(
+            RemoteKeystoreConfig {
+                signing: None,
+                encryption: Some(uri.clone()),
+                decryption: Some(uri),
+            },
+            spawn_test_keystore().await.unwrap(),
+        )
+        .await
+        .unwrap();
+
+        // The fallback holds both keys, so it could box and open these itself.
+        let alice: ZomeX25519PubKey = keystore.create_x25519_keypair().await.unwrap();
+        let bob: ZomeX25519PubKey = keystore.create_x25519_keypair().await.unwrap();
+        let encrypt = X25519XSalsa20Poly1305Encrypt::new(
+            alice,
+            bob,
+            XSalsa20Poly1305Data::from(b"remote box".to_vec()),
+        );
+        assert_eq!(
+            keystore
+                .x_25519_x_salsa20_poly1305_encrypt(encrypt.clone())
+                .await
+                .unwrap(),
+            encrypted
+        );
+        assert_eq!(
+            requests_rx.recv().await,
+            Some(RemoteKeystoreRequest::Encrypt(encrypt))
+        );
+
+        let decrypt = X25519XSalsa20Poly1305Decrypt::new(alice, bob, encrypted);
+        assert_eq!(
+            keystore
+                .x_25519_x_salsa20_poly1305_decrypt(decrypt.clone())
+                .await
+                .unwrap(),
+            Some(opened)
+        );
+        assert_eq!(
+            requests_rx.recv().await,
+            Some(RemoteKeystoreRequest::Decrypt(decrypt))
+        );
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn parse_uris() {
+        assert_eq!(
+            "http://localhost:9001".parse::<RemoteServiceUri>().unwrap(),
+            RemoteServiceUri::Http("http://localhost:9001".to_string())
+        );
+        assert_eq!(
+            "unix:///tmp/signer.sock"
+                .parse::<RemoteServiceUri>()
+                .unwrap(),
+            RemoteServiceUri::UnixSocket("/tmp/signer.sock".into())
+        );
+        assert!("ws://localhost:9001".parse::<RemoteServiceUri>().is_err());
+    }
+}
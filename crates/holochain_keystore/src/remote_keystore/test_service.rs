@@ -0,0 +1,137 @@
+//! DANGER! This is a stand-in remote keystore service for testing,
+//! DO NOT USE THIS IN PRODUCTION!
+//!
+//! Answers [`RemoteKeystoreRequest`]s from a local keystore, over http or a
+//! unix socket, so the remote keystore can be tested without a real service.
+
+use super::*;
+use hyper::service::make_service_fn;
+use hyper::service::service_fn;
+use hyper::Body;
+use hyper::Response;
+use hyper::StatusCode;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+/// A remote keystore service backed by a local keystore.
+#[derive(Clone)]
+pub struct TestRemoteKeystoreService {
+    keystore: KeystoreSender,
+    requests: Arc<AtomicUsize>,
+}
+
+impl TestRemoteKeystoreService {
+    /// Answer requests with the keys held by this keystore.
+    /// Requests for any other key are answered with
+    /// [`RemoteKeystoreResponse::UnknownKey`].
+    pub fn new(keystore: KeystoreSender) -> Self {
+        Self {
+            keystore,
+            requests: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// How many requests this service has answered.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// Serve over http on `addr` and return the bound address.
+    /// Bind to port `0` to let the os pick a free port.
+    pub async fn serve_http(&self, addr: impl Into<SocketAddr>) -> KeystoreApiResult<SocketAddr> {
+        let this = self.clone();
+        let make_svc = make_service_fn(move |_conn| {
+            let this = this.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: hyper::Request<Body>| {
+                    let this = this.clone();
+                    async move {
+                        let response = match hyper::body::to_bytes(req.into_body()).await {
+                            Ok(body) => match this.respond(&body).await {
+                                Ok(body) => Response::new(Body::from(body)),
+                                Err(e) => error_response(e.to_string()),
+                            },
+                            Err(e) => error_response(e.to_string()),
+                        };
+                        Ok::<_, Infallible>(response)
+                    }
+                }))
+            }
+        });
+        let server = hyper::Server::try_bind(&addr.into())
+            .map_err(|e| KeystoreError::RemoteService(e.to_string()))?
+            .serve(make_svc);
+        let addr = server.local_addr();
+        tokio::task::spawn(async move {
+            if let Err(e) = server.await {
+                tracing::error!(msg = "test remote keystore service stopped", ?e);
+            }
+        });
+        Ok(addr)
+    }
+
+    /// Serve over a unix socket bound at `path`.
+    #[cfg(unix)]
+    pub async fn serve_unix_socket(&self, path: &std::path::Path) -> KeystoreApiResult<()> {
+        let mut listener = tokio::net::UnixListener::bind(path)?;
+        let this = self.clone();
+        tokio::task::spawn(async move {
+            loop {
+                let mut stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::error!(msg = "test remote keystore service stopped", ?e);
+                        break;
+                    }
+                };
+                let this = this.clone();
+                tokio::task::spawn(async move {
+                    let result = async {
+                        let request = read_frame(&mut stream).await?;
+                        let response = this.respond(&request).await?;
+                        write_frame(&mut stream, &response).await
+                    }
+                    .await;
+                    if let Err(e) = result {
+                        tracing::error!(msg = "test remote keystore service request failed", ?e);
+                    }
+                });
+            }
+        });
+        Ok(())
+    }
+
+    /// Decode a request, answer it and encode the response.
+    async fn respond(&self, request: &[u8]) -> KeystoreApiResult<Vec<u8>> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        let request: RemoteKeystoreRequest = holochain_serialized_bytes::decode(request)?;
+        // The keystore errors if it doesn't hold the key.
+        let response = match request {
+            RemoteKeystoreRequest::Sign(input) => self
+                .keystore
+                .sign(input)
+                .await
+                .map(RemoteKeystoreResponse::Signature),
+            RemoteKeystoreRequest::Encrypt(input) => self
+                .keystore
+                .x_25519_x_salsa20_poly1305_encrypt(input)
+                .await
+                .map(RemoteKeystoreResponse::Encrypted),
+            RemoteKeystoreRequest::Decrypt(input) => self
+                .keystore
+                .x_25519_x_salsa20_poly1305_decrypt(input)
+                .await
+                .map(RemoteKeystoreResponse::Decrypted),
+        }
+        .unwrap_or(RemoteKeystoreResponse::UnknownKey);
+        Ok(holochain_serialized_bytes::encode(&response)?)
+    }
+}
+
+fn error_response(msg: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(msg));
+    *response.status_mut() = StatusCode::BAD_REQUEST;
+    response
+}
@@ -17,15 +17,52 @@ use parking_lot::RwLockReadGuard;
 use rkv::EnvironmentFlags;
 use rkv::Rkv;
 use shrinkwraprs::Shrinkwrap;
+use std::cell::Cell;
 use std::collections::hash_map;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 
 const DEFAULT_INITIAL_MAP_SIZE: usize = 100 * 1024 * 1024; // 100MB
+#[cfg(target_pointer_width = "64")]
+const DEFAULT_MAX_MAP_SIZE: usize = 64 * 1024 * 1024 * 1024; // 64GB
+#[cfg(not(target_pointer_width = "64"))]
+const DEFAULT_MAX_MAP_SIZE: usize = 1024 * 1024 * 1024; // 1GB
 const MAX_DBS: u32 = 32;
 
+/// Grow the map when less than 1 / MIN_FREE_DIVISOR of it is free.
+const MIN_FREE_DIVISOR: usize = 4;
+
+/// How many times [EnvironmentWrite::with_commit_retry] will grow the map
+/// and re-run a transaction before giving up.
+const MAX_MAP_FULL_RETRIES: usize = 3;
+
+/// How long to wait for open transactions to finish before giving up on a
+/// resize. If the current task holds a transaction itself the resize can
+/// never happen, so this must not block forever.
+const RESIZE_LOCK_TIMEOUT: Duration = Duration::from_secs(1);
+
+thread_local! {
+    /// Set when a write on this thread fails because the map is full.
+    /// A write transaction never leaves the thread it was opened on, so this
+    /// catches a full map inside a transaction's closure too, where the
+    /// error may have been converted into some other error type.
+    static MAP_FULL_ON_THIS_THREAD: Cell<bool> = Cell::new(false);
+}
+
+/// Record that a write on this thread failed because the map is full
+pub(crate) fn note_map_full() {
+    MAP_FULL_ON_THIS_THREAD.with(|map_full| map_full.set(true));
+}
+
+fn take_map_full_on_this_thread() -> bool {
+    MAP_FULL_ON_THIS_THREAD.with(|map_full| map_full.replace(false))
+}
+
 lazy_static! {
     static ref ENVIRONMENTS: RwLock<HashMap<PathBuf, EnvironmentWrite>> = {
         // This is just a convenient place that we know gets initialized
@@ -71,6 +108,46 @@ fn required_flags() -> EnvironmentFlags {
     EnvironmentFlags::default()
 }
 
+/// The initial and maximum size of an environment's memory map, in bytes.
+///
+/// The map starts at `initial` and is grown as the environment fills up,
+/// but never beyond `max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapSize {
+    /// Size of the map when the environment is first opened
+    pub initial: usize,
+    /// Size the map will never be grown beyond
+    pub max: usize,
+}
+
+impl Default for MapSize {
+    fn default() -> Self {
+        Self {
+            initial: DEFAULT_INITIAL_MAP_SIZE,
+            max: DEFAULT_MAX_MAP_SIZE,
+        }
+    }
+}
+
+impl MapSize {
+    /// Construct from sizes in megabytes
+    pub fn from_mb(initial_mb: usize, max_mb: usize) -> Self {
+        const MB: usize = 1024 * 1024;
+        let initial = initial_mb.saturating_mul(MB);
+        Self {
+            initial,
+            max: max_mb.saturating_mul(MB).max(initial),
+        }
+    }
+}
+
+/// Returns (bytes used, map size) for an environment.
+fn map_usage(rkv: &Rkv) -> DatabaseResult<(usize, usize)> {
+    let info = rkv.info()?;
+    let page_size = rkv.stat()?.page_size() as usize;
+    Ok(((info.last_pgno() + 1) * page_size, info.map_size()))
+}
+
 fn rkv_builder(
     initial_map_size: Option<usize>,
     flags: Option<EnvironmentFlags>,
@@ -95,6 +172,10 @@ pub struct EnvironmentRead {
     kind: EnvironmentKind,
     path: PathBuf,
     keystore: KeystoreSender,
+    max_map_size: usize,
+    /// Set when a write fails because the map is full so the next
+    /// transaction grows the map first.
+    map_full: Arc<AtomicBool>,
 }
 
 impl EnvironmentRead {
@@ -105,6 +186,7 @@ impl EnvironmentRead {
     pub fn guard(&self) -> EnvironmentReadRef<'_> {
         EnvironmentReadRef {
             rkv: self.arc.read(),
+            map_full: &self.map_full,
         }
     }
 
@@ -122,6 +204,16 @@ impl EnvironmentRead {
     pub fn path(&self) -> &PathBuf {
         &self.path
     }
+
+    /// The current size of the memory map in bytes
+    pub fn map_size(&self) -> DatabaseResult<usize> {
+        Ok(self.arc.read().info()?.map_size())
+    }
+
+    /// The size the memory map will never be grown beyond
+    pub fn max_map_size(&self) -> usize {
+        self.max_map_size
+    }
 }
 
 impl GetDb for EnvironmentWrite {
@@ -156,6 +248,17 @@ impl EnvironmentWrite {
         path_prefix: &Path,
         kind: EnvironmentKind,
         keystore: KeystoreSender,
+    ) -> DatabaseResult<EnvironmentWrite> {
+        Self::new_with_map_size(path_prefix, kind, keystore, MapSize::default())
+    }
+
+    /// Create an environment with a specific initial and maximum map size.
+    /// If the environment is already open the map size is ignored.
+    pub fn new_with_map_size(
+        path_prefix: &Path,
+        kind: EnvironmentKind,
+        keystore: KeystoreSender,
+        map_size: MapSize,
     ) -> DatabaseResult<EnvironmentWrite> {
         let mut map = ENVIRONMENTS.write();
         let path = path_prefix.join(kind.path());
@@ -167,7 +270,7 @@ impl EnvironmentWrite {
             hash_map::Entry::Occupied(e) => e.get().clone(),
             hash_map::Entry::Vacant(e) => e
                 .insert({
-                    let rkv = rkv_builder(None, None)(&path)?;
+                    let rkv = rkv_builder(Some(map_size.initial), None)(&path)?;
                     tracing::debug!("Initializing databases for path {:?}", path);
                     initialize_databases(&rkv, &kind)?;
                     EnvironmentWrite(EnvironmentRead {
@@ -175,6 +278,8 @@ impl EnvironmentWrite {
                         kind,
                         keystore,
                         path,
+                        max_map_size: map_size.max,
+                        map_full: Arc::new(AtomicBool::new(false)),
                     })
                 })
                 .clone(),
@@ -197,6 +302,107 @@ impl EnvironmentWrite {
         EnvironmentWriteRef(self.0.guard())
     }
 
+    /// Like [WriteManager::with_commit], but if the map fills up, either
+    /// while the closure writes or when the transaction is committed, the map
+    /// is grown and the closure is re-run in a fresh transaction, up to a few
+    /// times. The closure must be safe to run again.
+    ///
+    /// Growing the map waits for open transactions to finish, so it is done
+    /// on the blocking thread pool rather than on the async executor.
+    pub async fn with_commit_retry<E, R, F: Send>(&self, mut f: F) -> Result<R, E>
+    where
+        E: From<DatabaseError>,
+        F: FnMut(&mut Writer) -> Result<R, E>,
+    {
+        let mut retries = 0;
+        loop {
+            if self.needs_to_grow()? {
+                let env = self.clone();
+                tokio::task::spawn_blocking(move || {
+                    env.grow_map_if_needed(Some(RESIZE_LOCK_TIMEOUT))
+                })
+                .await
+                .map_err(|e| DatabaseError::ResizeFailed(e.to_string()))??;
+            }
+            match self.guard().commit(&mut f) {
+                (Err(_), true) if retries < MAX_MAP_FULL_RETRIES => {
+                    retries += 1;
+                    tracing::info!(
+                        "Retrying transaction on {:?} after the map filled up",
+                        self.path
+                    );
+                }
+                (result, _) => return result,
+            }
+        }
+    }
+
+    /// Is a write known to have failed because the map was full,
+    /// or is the map running low on free space
+    fn needs_to_grow(&self) -> DatabaseResult<bool> {
+        if self.map_full.load(Ordering::Acquire) {
+            return Ok(true);
+        }
+        // Recursive so we don't queue behind a pending resize while the
+        // caller may be holding a transaction.
+        let (used, size) = map_usage(&self.arc.read_recursive())?;
+        Ok(size - used.min(size) < size / MIN_FREE_DIVISOR)
+    }
+
+    /// Grow the memory map if a write previously failed because it was full,
+    /// or if it is running low on free space.
+    ///
+    /// Resizing requires that no transactions are open on this environment.
+    /// Every transaction holds a read lock on the [Rkv] so taking the write
+    /// lock pauses new transactions and waits up to `wait` for the open ones
+    /// to finish. Without a `wait` the resize is only done if no transactions
+    /// are open, so this never blocks.
+    fn grow_map_if_needed(&self, wait: Option<Duration>) -> DatabaseResult<()> {
+        if !self.needs_to_grow()? {
+            return Ok(());
+        }
+        let rkv = match wait {
+            Some(wait) => self.arc.try_write_for(wait),
+            None => self.arc.try_write(),
+        };
+        let rkv = match rkv {
+            Some(rkv) => rkv,
+            None => {
+                tracing::warn!(
+                    "Could not pause transactions on {:?} to grow the map, will try again later",
+                    self.path
+                );
+                return Ok(());
+            }
+        };
+        // Someone else may have grown the map while we waited for the lock.
+        let (used, size) = map_usage(&rkv)?;
+        if !self.map_full.load(Ordering::Acquire)
+            && size - used.min(size) >= size / MIN_FREE_DIVISOR
+        {
+            return Ok(());
+        }
+        let new_size = size.saturating_mul(2).min(self.max_map_size);
+        if new_size <= size {
+            tracing::error!(
+                "Environment {:?} is using {} of its maximum map size of {} bytes and cannot grow",
+                self.path,
+                used,
+                self.max_map_size
+            );
+            return Ok(());
+        }
+        rkv.set_map_size(new_size)?;
+        self.map_full.store(false, Ordering::Release);
+        tracing::info!(
+            "Grew map for {:?} from {} to {} bytes",
+            self.path,
+            size,
+            new_size
+        );
+        Ok(())
+    }
+
     /// Remove the db and directory
     pub async fn remove(self) -> DatabaseResult<()> {
         let mut map = ENVIRONMENTS.write();
@@ -237,6 +443,7 @@ impl EnvironmentKind {
 /// because unlike [EnvironmentWriteRef], this does not implement WriteManager
 pub struct EnvironmentReadRef<'e> {
     rkv: RwLockReadGuard<'e, Rkv>,
+    map_full: &'e AtomicBool,
 }
 
 impl<'e> EnvironmentReadRef<'e> {
@@ -262,8 +469,12 @@ pub trait ReadManager<'e> {
 pub trait WriteManager<'e> {
     /// Run a closure, passing in a mutable reference to a read-write
     /// transaction, and commit the transaction after the closure has run.
-    /// If there is a LMDB error, recover from it and re-run the closure.
-    // FIXME: B-01566: implement write failure detection
+    ///
+    /// Writing through [EnvironmentWrite] grows the memory map first if it is
+    /// running low on space, or if a write failed with [DatabaseError::MapFull],
+    /// as long as no other transactions are open. See
+    /// [EnvironmentWrite::with_commit_retry] to wait for them and re-run the
+    /// closure.
     fn with_commit<E, R, F: Send>(&self, f: F) -> Result<R, E>
     where
         E: From<DatabaseError>,
@@ -291,10 +502,7 @@ impl<'e> WriteManager<'e> for EnvironmentWriteRef<'e> {
         E: From<DatabaseError>,
         F: FnOnce(&mut Writer) -> Result<R, E>,
     {
-        let mut writer = Writer::from(self.rkv.write().map_err(Into::into)?);
-        let result = f(&mut writer)?;
-        writer.commit().map_err(Into::into)?;
-        Ok(result)
+        self.commit(f).0
     }
 }
 
@@ -304,11 +512,35 @@ impl<'e> WriteManager<'e> for EnvironmentWrite {
         E: From<DatabaseError>,
         F: FnOnce(&mut Writer) -> Result<R, E>,
     {
+        self.grow_map_if_needed(None)?;
         EnvironmentWriteRef::with_commit(&self.guard(), f)
     }
 }
 
 impl<'e> EnvironmentWriteRef<'e> {
+    /// Run the closure in a transaction and commit it. Also returns whether
+    /// the map filled up, either while the closure wrote or on commit, in which
+    /// case the map is grown before the next write.
+    fn commit<E, R, F>(&self, f: F) -> (Result<R, E>, bool)
+    where
+        E: From<DatabaseError>,
+        F: FnOnce(&mut Writer) -> Result<R, E>,
+    {
+        take_map_full_on_this_thread();
+        let result: Result<R, E> = (|| {
+            let mut writer = Writer::from(self.rkv.write().map_err(DatabaseError::from)?);
+            let result = f(&mut writer)?;
+            writer.commit()?;
+            Ok(result)
+        })();
+        let map_full = take_map_full_on_this_thread();
+        if map_full {
+            tracing::warn!("LMDB map is full, it will be grown before the next write");
+            self.map_full.store(true, Ordering::Release);
+        }
+        (result, map_full)
+    }
+
     /// Access the underlying Rkv lock guard
     #[cfg(test)]
     pub(crate) fn inner(&'e self) -> &Rkv {
@@ -341,3 +573,114 @@ impl<'e> ReadManager<'e> for EnvironmentWriteRef<'e> {
         self.0.with_reader(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_keystore;
+    use rkv::StoreOptions;
+    use rkv::Value;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn map_grows_instead_of_filling_up() -> DatabaseResult<()> {
+        let tmpdir = tempdir::TempDir::new("holochain-test-map-size").unwrap();
+        let env = EnvironmentWrite::new_with_map_size(
+            tmpdir.path(),
+            EnvironmentKind::Wasm,
+            test_keystore(),
+            MapSize::from_mb(1, 64),
+        )?;
+        let db = env
+            .guard()
+            .inner()
+            .open_single("map_growth", StoreOptions::create())?;
+        let value = vec![0u8; 256 * 1024];
+
+        // Writes well over the initial 1MB map
+        for i in 0u32..32 {
+            env.with_commit_retry(|writer| {
+                db.put(writer, i.to_be_bytes(), &Value::Blob(&value))?;
+                DatabaseResult::Ok(())
+            })
+            .await?;
+        }
+
+        assert!(env.map_size()? > 1024 * 1024);
+        assert!(env.map_size()? <= env.max_map_size());
+        env.guard().with_reader::<DatabaseError, _, _>(|reader| {
+            assert!(db.get(&reader, 31u32.to_be_bytes())?.is_some());
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn full_map_is_reported_once_it_cannot_grow() -> DatabaseResult<()> {
+        let tmpdir = tempdir::TempDir::new("holochain-test-map-full").unwrap();
+        let env = EnvironmentWrite::new_with_map_size(
+            tmpdir.path(),
+            EnvironmentKind::Wasm,
+            test_keystore(),
+            MapSize::from_mb(1, 1),
+        )?;
+        let db = env
+            .guard()
+            .inner()
+            .open_single("map_full", StoreOptions::create())?;
+        let value = vec![0u8; 256 * 1024];
+
+        let result = env
+            .with_commit_retry(|writer| {
+                for i in 0u32..8 {
+                    db.put(writer, i.to_be_bytes(), &Value::Blob(&value))?;
+                }
+                DatabaseResult::Ok(())
+            })
+            .await;
+        assert_eq!(result, Err(DatabaseError::MapFull));
+        Ok(())
+    }
+
+    #[derive(Debug)]
+    struct OtherError;
+
+    impl From<DatabaseError> for OtherError {
+        fn from(_: DatabaseError) -> Self {
+            Self
+        }
+    }
+
+    #[tokio::test]
+    async fn map_full_in_the_closure_is_retried_whatever_the_error_type() -> DatabaseResult<()> {
+        let tmpdir = tempdir::TempDir::new("holochain-test-map-full-closure").unwrap();
+        let env = EnvironmentWrite::new_with_map_size(
+            tmpdir.path(),
+            EnvironmentKind::Wasm,
+            test_keystore(),
+            MapSize::from_mb(1, 64),
+        )?;
+        let db = env
+            .guard()
+            .inner()
+            .open_single("map_full_closure", StoreOptions::create())?;
+        let value = vec![0u8; 256 * 1024];
+
+        // A single transaction bigger than the initial map fills it up
+        // on a put, before the transaction is committed
+        let mut tries = 0;
+        env.with_commit_retry(|writer| {
+            tries += 1;
+            for i in 0u32..8 {
+                db.put(writer, i.to_be_bytes(), &Value::Blob(&value))
+                    .map_err(DatabaseError::from)?;
+            }
+            Result::<_, OtherError>::Ok(())
+        })
+        .await
+        .unwrap();
+
+        assert!(tries > 1);
+        assert!(env.map_size()? > 1024 * 1024);
+        Ok(())
+    }
+}
@@ -81,6 +81,12 @@ pub enum DatabaseError {
 
     #[error("Unable to construct a value key")]
     KeyConstruction,
+
+    #[error("The LMDB memory map is full")]
+    MapFull,
+
+    #[error("Growing the LMDB memory map failed: {0}")]
+    ResizeFailed(String),
 }
 
 impl PartialEq for DatabaseError {
@@ -107,7 +113,13 @@ pub type DatabaseResult<T> = Result<T, DatabaseError>;
 // }
 impl From<rkv::StoreError> for DatabaseError {
     fn from(e: rkv::StoreError) -> DatabaseError {
-        DatabaseError::LmdbStoreError(e.compat())
+        match e {
+            rkv::StoreError::LmdbError(rkv::LmdbError::MapFull) => {
+                crate::env::note_map_full();
+                DatabaseError::MapFull
+            }
+            e => DatabaseError::LmdbStoreError(e.compat()),
+        }
     }
 }
 
@@ -48,12 +48,14 @@ pub trait HolochainP2pCellT {
     ) -> actor::HolochainP2pResult<SerializedBytes>;
 
     /// Publish data to the correct neighborhood.
+    /// Set `remote_agent_count` to `None` for a default "best-effort" fan-out.
     #[allow(clippy::ptr_arg)]
     async fn publish(
         &mut self,
         request_validation_receipt: bool,
         dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        remote_agent_count: Option<u8>,
         timeout_ms: Option<u64>,
     ) -> actor::HolochainP2pResult<()>;
 
@@ -164,6 +166,7 @@ impl HolochainP2pCellT for HolochainP2pCell {
         request_validation_receipt: bool,
         dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        remote_agent_count: Option<u8>,
         timeout_ms: Option<u64>,
     ) -> actor::HolochainP2pResult<()> {
         self.sender
@@ -173,6 +176,7 @@ impl HolochainP2pCellT for HolochainP2pCell {
                 request_validation_receipt,
                 dht_hash,
                 ops,
+                remote_agent_count,
                 timeout_ms,
             )
             .await
@@ -581,6 +581,7 @@ impl HolochainP2pHandler for HolochainP2pActor {
         request_validation_receipt: bool,
         dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        remote_agent_count: Option<u8>,
         timeout_ms: Option<u64>,
     ) -> HolochainP2pHandlerResult<()> {
         let space = dna_hash.into_kitsune();
@@ -597,7 +598,7 @@ impl HolochainP2pHandler for HolochainP2pActor {
                     space,
                     from_agent,
                     basis,
-                    remote_agent_count: None, // default best-effort
+                    remote_agent_count,
                     timeout_ms,
                     payload,
                 })
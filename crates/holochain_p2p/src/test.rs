@@ -327,7 +327,7 @@ mod tests {
             holo_hash::hash_type::AnyDht::Header,
         );
 
-        p2p.publish(dna, a1, true, header_hash, vec![], Some(200))
+        p2p.publish(dna, a1, true, header_hash, vec![], None, Some(200))
             .await
             .unwrap();
 
@@ -209,12 +209,14 @@ ghost_actor::ghost_chan! {
         ) -> SerializedBytes;
 
         /// Publish data to the correct neighborhood.
+        /// Set `remote_agent_count` to `None` for a default "best-effort" fan-out.
         fn publish(
             dna_hash: DnaHash,
             from_agent: AgentPubKey,
             request_validation_receipt: bool,
             dht_hash: holo_hash::AnyDhtHash,
             ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+            remote_agent_count: Option<u8>,
             timeout_ms: Option<u64>,
         ) -> ();
 
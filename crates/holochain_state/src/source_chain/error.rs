@@ -68,6 +68,9 @@ pub enum SourceChainError {
 
     #[error(transparent)]
     ElementGroupError(#[from] ElementGroupError),
+
+    #[error("Source chain export version {0} is not supported, expected version {1}")]
+    UnsupportedExportVersion(u32, u32),
 }
 
 // serde_json::Error does not implement PartialEq - why is that a requirement??
@@ -36,6 +36,50 @@ pub struct SourceChainJsonElement {
     pub entry: Option<Entry>,
 }
 
+/// The version of [SourceChainExport] written by this conductor.
+/// Bump it when the format changes.
+pub const SOURCE_CHAIN_EXPORT_VERSION: u32 = 1;
+
+/// A cell's authored source chain from genesis to head, including private
+/// entries like cap grants, so it can be backed up and imported elsewhere.
+/// Hashes are left out because they are recomputed on import.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct SourceChainExport {
+    pub version: u32,
+    pub elements: Vec<SourceChainExportElement>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SourceChainExportElement {
+    pub header: SignedHeader,
+    pub entry: Option<Entry>,
+}
+
+/// Only the version, so it can be checked before decoding the rest
+#[derive(Deserialize)]
+struct SourceChainExportVersion {
+    version: u32,
+}
+
+impl SourceChainExport {
+    /// Encode this export in its binary format
+    pub fn to_bytes(&self) -> SourceChainResult<Vec<u8>> {
+        Ok(holochain_serialized_bytes::encode(self)?)
+    }
+
+    /// Decode an export, failing if it was written in another version
+    pub fn from_bytes(bytes: &[u8]) -> SourceChainResult<Self> {
+        let SourceChainExportVersion { version } = holochain_serialized_bytes::decode(bytes)?;
+        if version != SOURCE_CHAIN_EXPORT_VERSION {
+            return Err(SourceChainError::UnsupportedExportVersion(
+                version,
+                SOURCE_CHAIN_EXPORT_VERSION,
+            ));
+        }
+        Ok(holochain_serialized_bytes::decode(bytes)?)
+    }
+}
+
 impl SourceChainBuf {
     pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
         Ok(Self {
@@ -138,12 +182,22 @@ impl SourceChainBuf {
         maybe_entry: Option<Entry>,
     ) -> SourceChainResult<HeaderHash> {
         let header = HeaderHashed::from_content_sync(header);
-        let header_address = header.as_hash().to_owned();
         let signed_header = SignedHeaderHashed::new(&self.keystore, header).await?;
         let maybe_entry = match maybe_entry {
             None => None,
             Some(entry) => Some(EntryHashed::from_content_sync(entry)),
         };
+        self.put_signed(signed_header, maybe_entry)
+    }
+
+    /// Add a Element that was already signed to the source chain.
+    /// Nothing is checked, so the element must come from a valid chain.
+    pub fn put_signed(
+        &mut self,
+        signed_header: SignedHeaderHashed,
+        maybe_entry: Option<EntryHashed>,
+    ) -> SourceChainResult<HeaderHash> {
+        let header_address = signed_header.header_address().to_owned();
 
         /*
         FIXME: this needs to happen here.
@@ -234,6 +288,27 @@ impl SourceChainBuf {
         })
     }
 
+    /// Export every element from genesis to the chain head
+    pub fn export(&self) -> SourceChainResult<SourceChainExport> {
+        let elements = (0..self.len() as u32)
+            .map(|i| {
+                let element = self
+                    .get_at_index(i)?
+                    .ok_or(SourceChainError::ElementMissing(format!("at index {}", i)))?;
+                let (signed_header, entry) = element.into_inner();
+                let (header, _) = signed_header.into_inner();
+                Ok(SourceChainExportElement {
+                    header,
+                    entry: entry.into_option(),
+                })
+            })
+            .collect::<SourceChainResult<_>>()?;
+        Ok(SourceChainExport {
+            version: SOURCE_CHAIN_EXPORT_VERSION,
+            elements,
+        })
+    }
+
     /// Commit the genesis entries to this source chain, making the chain ready
     /// to use as a `SourceChain`
     pub async fn genesis(
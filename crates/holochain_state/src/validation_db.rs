@@ -11,6 +11,18 @@ use holochain_lmdb::prelude::GetDb;
 use holochain_serialized_bytes::prelude::*;
 use holochain_types::prelude::*;
 use shrinkwraprs::Shrinkwrap;
+use std::time::Duration;
+
+/// How long to wait before retrying an op that is awaiting dependencies
+/// for the first time. The wait doubles with every try after that.
+pub const VALIDATION_RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// The longest we will wait between retries of an op that is awaiting dependencies
+pub const VALIDATION_RETRY_MAX_DELAY: Duration = Duration::from_secs(60 * 10);
+
+/// The number of tries after which an op that is still awaiting
+/// dependencies is abandoned
+pub const VALIDATION_MAX_TRIES: u32 = 20;
 
 #[derive(Shrinkwrap)]
 #[shrinkwrap(mutable)]
@@ -44,12 +56,74 @@ pub struct ValidationLimboValue {
 pub enum ValidationLimboStatus {
     /// Is awaiting to be system validated
     Pending,
-    /// Is waiting for dependencies so the op can proceed to system validation
+    /// Is waiting for a dependency so the op can proceed to system validation
     AwaitingSysDeps(AnyDhtHash),
     /// Is awaiting to be app validated
     SysValidated,
     /// Is waiting for dependencies so the op can proceed to app validation
     AwaitingAppDeps(Vec<AnyDhtHash>),
+    /// Is waiting for dpki to be reachable so the author's key state
+    /// can be checked in system validation.
+    /// It is tried again after a backoff and never abandoned.
+    AwaitingKeyState,
+    /// Is waiting for the author, or an agent that has cached it, to be
+    /// reachable so the op's custom validation package can be fetched
+    /// for app validation.
+    /// It is tried again after a backoff and never abandoned.
+    AwaitingValidationPackage,
+}
+
+impl ValidationLimboStatus {
+    /// The hashes the op is waiting for, if any
+    pub fn awaiting_deps(&self) -> &[AnyDhtHash] {
+        match self {
+            ValidationLimboStatus::AwaitingSysDeps(dep) => std::slice::from_ref(dep),
+            ValidationLimboStatus::AwaitingAppDeps(deps) => &deps[..],
+            ValidationLimboStatus::Pending
+            | ValidationLimboStatus::SysValidated
+            | ValidationLimboStatus::AwaitingKeyState
+            | ValidationLimboStatus::AwaitingValidationPackage => &[],
+        }
+    }
+
+    /// Is the op waiting on something, so it should back off between tries
+    pub fn backs_off(&self) -> bool {
+        match self {
+            ValidationLimboStatus::AwaitingKeyState
+            | ValidationLimboStatus::AwaitingValidationPackage => true,
+            _ => !self.awaiting_deps().is_empty(),
+        }
+    }
+}
+
+impl ValidationLimboValue {
+    /// When an op that is awaiting dependencies should be retried
+    /// even if none of them have turned up.
+    /// Ops that aren't awaiting anything are always ready.
+    pub fn next_retry(&self) -> Option<Timestamp> {
+        if !self.status.backs_off() {
+            return None;
+        }
+        let last_try = self.last_try?;
+        let delay = VALIDATION_RETRY_BASE_DELAY
+            .checked_mul(2u32.saturating_pow(self.num_tries.saturating_sub(1)))
+            .unwrap_or(VALIDATION_RETRY_MAX_DELAY)
+            .min(VALIDATION_RETRY_MAX_DELAY);
+        last_try.checked_add(&delay)
+    }
+
+    /// Has the backoff for this op elapsed
+    pub fn is_retry_due(&self, now: Timestamp) -> bool {
+        self.next_retry().map(|t| now >= t).unwrap_or(true)
+    }
+
+    /// Should we give up on ever finding this op's dependencies.
+    /// Ops awaiting the author's key state or a validation package are
+    /// never abandoned because they only depend on dpki or the author
+    /// being reachable again.
+    pub fn should_abandon(&self) -> bool {
+        !self.status.awaiting_deps().is_empty() && self.num_tries >= VALIDATION_MAX_TRIES
+    }
 }
 
 impl ValidationLimboStore {
@@ -59,3 +133,68 @@ impl ValidationLimboStore {
         Ok(Self(KvBufFresh::new(env, db)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::*;
+
+    fn awaiting(num_tries: u32, last_try: Timestamp) -> ValidationLimboValue {
+        ValidationLimboValue {
+            status: ValidationLimboStatus::AwaitingAppDeps(vec![fixt!(AnyDhtHash)]),
+            op: DhtOpLight::RegisterAgentActivity(fixt!(HeaderHash), fixt!(AnyDhtHash)),
+            basis: fixt!(AnyDhtHash),
+            time_added: last_try,
+            last_try: Some(last_try),
+            num_tries,
+            from_agent: None,
+        }
+    }
+
+    #[test]
+    fn retry_backs_off_exponentially() {
+        let last_try = Timestamp(1000, 0);
+        let delay = |num_tries| {
+            (awaiting(num_tries, last_try).next_retry().unwrap() - last_try)
+                .unwrap()
+                .num_seconds()
+        };
+        assert_eq!(delay(1), 1);
+        assert_eq!(delay(2), 2);
+        assert_eq!(delay(5), 16);
+        assert_eq!(delay(40), VALIDATION_RETRY_MAX_DELAY.as_secs() as i64);
+
+        let vlv = awaiting(3, last_try);
+        assert!(!vlv.is_retry_due(Timestamp(1003, 0)));
+        assert!(vlv.is_retry_due(Timestamp(1004, 0)));
+    }
+
+    #[test]
+    fn only_ops_awaiting_deps_back_off() {
+        let mut vlv = awaiting(VALIDATION_MAX_TRIES, Timestamp(1000, 0));
+        assert!(vlv.should_abandon());
+
+        vlv.status = ValidationLimboStatus::SysValidated;
+        assert_eq!(vlv.next_retry(), None);
+        assert!(vlv.is_retry_due(Timestamp(1000, 0)));
+        assert!(!vlv.should_abandon());
+    }
+
+    #[test]
+    fn ops_awaiting_reachability_back_off_but_are_never_abandoned() {
+        for status in vec![
+            ValidationLimboStatus::AwaitingKeyState,
+            ValidationLimboStatus::AwaitingValidationPackage,
+        ] {
+            let mut vlv = awaiting(VALIDATION_MAX_TRIES * 2, Timestamp(1000, 0));
+            vlv.status = status;
+            assert!(!vlv.should_abandon());
+            assert_eq!(
+                vlv.next_retry(),
+                Timestamp(1000, 0).checked_add(&VALIDATION_RETRY_MAX_DELAY)
+            );
+            assert!(!vlv.is_retry_due(Timestamp(1001, 0)));
+        }
+    }
+}
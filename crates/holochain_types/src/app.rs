@@ -100,6 +100,21 @@ pub struct InstallAppPayload {
     pub dnas: Vec<InstallAppDnaPayload>,
 }
 
+/// An exported source chain and the app id and [CellNick] to install
+/// its Cell under. The Cell's Dna and agent are those of the exported chain.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ImportSourceChainPayload {
+    /// The unique identifier for the new app in this conductor
+    pub installed_app_id: InstalledAppId,
+
+    /// The CellNick to give the imported Cell
+    pub nick: CellNick,
+
+    /// The source chain, as exported by another conductor
+    #[serde(with = "serde_bytes")]
+    pub source_chain: Vec<u8>,
+}
+
 /// An [AppBundle] along with an [AgentPubKey] and optional [InstalledAppId]
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct InstallAppBundlePayload {
@@ -0,0 +1,465 @@
+//! Merging the versions of an entry whose entry def declares a [CrdtType].
+//! The versions are the original entry and the tree of updates made to it,
+//! which the cascade collects from the DHT.
+
+use crate::prelude::*;
+use serde::de::Deserializer;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::ser::SerializeMap;
+use serde::ser::SerializeSeq;
+use serde::ser::Serializer;
+use std::collections::HashSet;
+use std::fmt;
+use thiserror::Error;
+
+/// Errors merging the versions of a CRDT entry
+#[derive(Error, Debug)]
+pub enum CrdtError {
+    /// A version's entry is missing or isn't an app entry
+    #[error("The CRDT version created by header {0} doesn't have an app entry")]
+    NotAppEntry(HeaderHash),
+
+    /// A version's entry isn't the shape its [CrdtType] needs
+    #[error(transparent)]
+    SerializedBytesError(#[from] SerializedBytesError),
+
+    /// The merged entry is too large
+    #[error(transparent)]
+    EntryError(#[from] EntryError),
+}
+
+/// Result of merging CRDT versions
+pub type CrdtResult<T> = Result<T, CrdtError>;
+
+/// One version of a CRDT entry: the original or one of the updates.
+#[derive(Clone, Debug)]
+pub struct CrdtVersion {
+    /// The element of the create or update header, with its entry
+    pub element: Element,
+    /// The index of the version this one updates, or `None` for the original
+    pub parent: Option<usize>,
+}
+
+impl CrdtVersion {
+    /// Versions are ordered by header timestamp, then by header hash
+    fn order(&self) -> (Timestamp, &HeaderHash) {
+        (
+            self.element.header().timestamp(),
+            self.element.header_address(),
+        )
+    }
+}
+
+/// Merge the versions of an entry, following its [CrdtType].
+///
+/// The first version must be the original, and every version's parent must come
+/// before it. The merged entry is returned in the element of the latest version,
+/// so for the set and counter types its entry hash won't match the entry.
+pub fn merge_crdt_versions(
+    crdt_type: CrdtType,
+    versions: &[CrdtVersion],
+) -> CrdtResult<Option<Element>> {
+    let latest = match versions.iter().max_by(|a, b| a.order().cmp(&b.order())) {
+        Some(latest) => latest,
+        None => return Ok(None),
+    };
+    let merged = match crdt_type {
+        CrdtType::None => return Ok(versions.first().map(|v| v.element.clone())),
+        CrdtType::LastWriterWins => return Ok(Some(latest.element.clone())),
+        CrdtType::GrowOnlySet => encode_entry(&merge_grow_only_set(versions)?)?,
+        CrdtType::ObservedRemoveSet => encode_entry(&merge_observed_remove_set(versions)?)?,
+        CrdtType::Counter => encode_entry(&merge_counter(versions)?)?,
+    };
+    Ok(Some(Element::new(
+        latest.element.signed_header().clone(),
+        Some(merged),
+    )))
+}
+
+fn merge_grow_only_set(versions: &[CrdtVersion]) -> CrdtResult<Vec<CrdtValue>> {
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    for i in in_order(versions) {
+        for (key, item) in set_items(&versions[i])? {
+            if seen.insert(key) {
+                merged.push(item);
+            }
+        }
+    }
+    Ok(merged)
+}
+
+fn merge_observed_remove_set(versions: &[CrdtVersion]) -> CrdtResult<Vec<CrdtValue>> {
+    let items = versions
+        .iter()
+        .map(set_items)
+        .collect::<CrdtResult<Vec<_>>>()?;
+    let keys: Vec<HashSet<&[u8]>> = items
+        .iter()
+        .map(|items| items.iter().map(|(key, _)| key.as_slice()).collect())
+        .collect();
+    // An item is added by a version which has it when the version it updates doesn't,
+    // and removed by a version which doesn't have it when the version it updates does.
+    let is_removed_in = |i: usize, key: &[u8]| match versions[i].parent {
+        Some(parent) => keys[parent].contains(key) && !keys[i].contains(key),
+        None => false,
+    };
+
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    for added_in in in_order(versions) {
+        for (key, item) in &items[added_in] {
+            let is_added = match versions[added_in].parent {
+                Some(parent) => !keys[parent].contains(key.as_slice()),
+                None => true,
+            };
+            if !is_added || seen.contains(key) {
+                continue;
+            }
+            // Only removals which have seen this add cancel it
+            let removed = (0..versions.len()).any(|removed_in| {
+                is_removed_in(removed_in, key.as_slice())
+                    && descends_from(versions, removed_in, added_in)
+            });
+            if !removed {
+                seen.insert(key.clone());
+                merged.push(item.clone());
+            }
+        }
+    }
+    Ok(merged)
+}
+
+fn merge_counter(versions: &[CrdtVersion]) -> CrdtResult<i64> {
+    let values = versions
+        .iter()
+        .map(|version| {
+            Ok(holochain_serialized_bytes::decode(
+                app_entry_bytes(version)?.bytes(),
+            )?)
+        })
+        .collect::<CrdtResult<Vec<i64>>>()?;
+    Ok(versions
+        .iter()
+        .zip(values.iter())
+        .fold(0i64, |total, (version, value)| match version.parent {
+            Some(parent) => total.saturating_add(value.saturating_sub(values[parent])),
+            None => total.saturating_add(*value),
+        }))
+}
+
+/// Indices of the versions, oldest first
+fn in_order(versions: &[CrdtVersion]) -> Vec<usize> {
+    let mut order = (0..versions.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| versions[*a].order().cmp(&versions[*b].order()));
+    order
+}
+
+/// Is the version at index `i` an update, or an update of an update and so on,
+/// of the version at index `ancestor`?
+fn descends_from(versions: &[CrdtVersion], i: usize, ancestor: usize) -> bool {
+    let mut current = versions[i].parent;
+    while let Some(parent) = current {
+        if parent == ancestor {
+            return true;
+        }
+        current = versions[parent].parent;
+    }
+    false
+}
+
+fn app_entry_bytes(version: &CrdtVersion) -> CrdtResult<&SerializedBytes> {
+    match version.element.entry().as_option() {
+        Some(Entry::App(bytes)) => Ok(bytes.as_ref()),
+        _ => Err(CrdtError::NotAppEntry(
+            version.element.header_address().clone(),
+        )),
+    }
+}
+
+/// The items of a set entry, with their serialized bytes to compare them by
+fn set_items(version: &CrdtVersion) -> CrdtResult<Vec<(Vec<u8>, CrdtValue)>> {
+    let items: Vec<CrdtValue> =
+        holochain_serialized_bytes::decode(app_entry_bytes(version)?.bytes())?;
+    items
+        .into_iter()
+        .map(|item| Ok((holochain_serialized_bytes::encode(&item)?, item)))
+        .collect()
+}
+
+fn encode_entry<T: serde::Serialize>(value: &T) -> CrdtResult<Entry> {
+    let bytes = holochain_serialized_bytes::encode(value)?;
+    Ok(Entry::app(UnsafeBytes::from(bytes).into())?)
+}
+
+/// Any value in an entry, so the host can take apart and put back together
+/// the items of set entries without knowing their type.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub enum CrdtValue {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    F32(f32),
+    F64(f64),
+    Str(String),
+    Bin(Vec<u8>),
+    Array(Vec<CrdtValue>),
+    Map(Vec<(CrdtValue, CrdtValue)>),
+}
+
+impl serde::Serialize for CrdtValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            CrdtValue::Nil => serializer.serialize_unit(),
+            CrdtValue::Bool(b) => serializer.serialize_bool(*b),
+            CrdtValue::Int(i) => serializer.serialize_i64(*i),
+            CrdtValue::UInt(u) => serializer.serialize_u64(*u),
+            CrdtValue::F32(f) => serializer.serialize_f32(*f),
+            CrdtValue::F64(f) => serializer.serialize_f64(*f),
+            CrdtValue::Str(s) => serializer.serialize_str(s),
+            CrdtValue::Bin(b) => serializer.serialize_bytes(b),
+            CrdtValue::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            CrdtValue::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (k, v) in entries {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for CrdtValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(CrdtValueVisitor)
+    }
+}
+
+struct CrdtValueVisitor;
+
+impl<'de> Visitor<'de> for CrdtValueVisitor {
+    type Value = CrdtValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_unit<E>(self) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::Nil)
+    }
+
+    fn visit_none<E>(self) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::Nil)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<CrdtValue, D::Error> {
+        serde::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::Int(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::UInt(v))
+    }
+
+    fn visit_f32<E>(self, v: f32) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::F32(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::F64(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::Str(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::Str(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::Bin(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<CrdtValue, E> {
+        Ok(CrdtValue::Bin(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<CrdtValue, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(CrdtValue::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<CrdtValue, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(CrdtValue::Map(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+
+    /// A version with an entry holding this value, authored at this second
+    fn version<T: serde::Serialize>(value: T, secs: i64, parent: Option<usize>) -> CrdtVersion {
+        let entry = encode_entry(&value).unwrap();
+        let mut create = fixt!(Create);
+        create.timestamp = Timestamp(secs, 0);
+        create.entry_hash = EntryHash::with_data_sync(&entry);
+        let header = HeaderHashed::from_content_sync(Header::Create(create));
+        let signed_header = SignedHeaderHashed::with_presigned(header, fixt!(Signature));
+        CrdtVersion {
+            element: Element::new(signed_header, Some(entry)),
+            parent,
+        }
+    }
+
+    fn merged<T: serde::de::DeserializeOwned>(crdt_type: CrdtType, versions: &[CrdtVersion]) -> T {
+        let element = merge_crdt_versions(crdt_type, versions)
+            .unwrap()
+            .expect("There are versions to merge");
+        match element.entry().as_option() {
+            Some(Entry::App(bytes)) => holochain_serialized_bytes::decode(bytes.bytes()).unwrap(),
+            _ => panic!("Merged element has no app entry"),
+        }
+    }
+
+    #[test]
+    fn last_writer_wins() {
+        let versions = vec![
+            version("original", 1, None),
+            version("latest", 3, Some(0)),
+            version("concurrent", 2, Some(0)),
+        ];
+        assert_eq!(
+            merged::<String>(CrdtType::LastWriterWins, &versions),
+            "latest"
+        );
+        assert_eq!(merged::<String>(CrdtType::None, &versions), "original");
+    }
+
+    #[test]
+    fn grow_only_set_is_a_union() {
+        let versions = vec![
+            version(vec![1, 2], 1, None),
+            version(vec![2, 3], 3, Some(0)),
+            version(vec![4], 2, Some(0)),
+        ];
+        assert_eq!(
+            merged::<Vec<u32>>(CrdtType::GrowOnlySet, &versions),
+            vec![1, 2, 4, 3]
+        );
+    }
+
+    #[test]
+    fn observed_remove_set_add_wins() {
+        let mut versions = vec![
+            version(Vec::<String>::new(), 1, None),
+            version(vec!["x"], 2, Some(0)),
+            // Removes the x it has seen
+            version(Vec::<String>::new(), 3, Some(1)),
+        ];
+        assert!(merged::<Vec<String>>(CrdtType::ObservedRemoveSet, &versions).is_empty());
+
+        // A concurrent add of x which the removal hasn't seen
+        versions.push(version(vec!["x", "y"], 4, Some(0)));
+        assert_eq!(
+            merged::<Vec<String>>(CrdtType::ObservedRemoveSet, &versions),
+            vec!["x", "y"]
+        );
+    }
+
+    #[test]
+    fn observed_remove_set_keeps_removals_of_items_not_re_added() {
+        let versions = vec![
+            version(vec!["a", "b"], 1, None),
+            version(vec!["b"], 2, Some(0)),
+            // Still has a, but only because it didn't change it
+            version(vec!["a", "b", "c"], 3, Some(0)),
+        ];
+        assert_eq!(
+            merged::<Vec<String>>(CrdtType::ObservedRemoveSet, &versions),
+            vec!["b", "c"]
+        );
+    }
+
+    #[test]
+    fn counter_sums_concurrent_changes() {
+        let versions = vec![
+            version(10, 1, None),
+            version(15, 2, Some(0)),
+            version(7, 3, Some(0)),
+            version(16, 4, Some(1)),
+        ];
+        assert_eq!(merged::<i64>(CrdtType::Counter, &versions), 13);
+    }
+
+    #[test]
+    fn merged_entries_come_in_the_latest_element() {
+        let versions = vec![
+            version(vec![1], 1, None),
+            version(vec![2], 3, Some(0)),
+            version(vec![3], 2, Some(0)),
+        ];
+        let latest = &versions[1].element;
+        let entry_hash = |element: &Element| {
+            EntryHash::with_data_sync(element.entry().as_option().expect("Versions have entries"))
+        };
+
+        // The last writer's element is returned as it is
+        let element = merge_crdt_versions(CrdtType::LastWriterWins, &versions)
+            .unwrap()
+            .unwrap();
+        assert_eq!(element.header_address(), latest.header_address());
+        assert_eq!(Some(&entry_hash(&element)), element.header().entry_hash());
+
+        // A merged set is a new entry in the latest header's element,
+        // so the header's entry hash is for that version's own entry
+        let element = merge_crdt_versions(CrdtType::GrowOnlySet, &versions)
+            .unwrap()
+            .unwrap();
+        assert_eq!(element.header_address(), latest.header_address());
+        assert_ne!(Some(&entry_hash(&element)), element.header().entry_hash());
+        assert_eq!(element.header().entry_hash(), latest.header().entry_hash());
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let value = CrdtValue::Map(vec![
+            (
+                CrdtValue::Str("a".into()),
+                CrdtValue::Array(vec![CrdtValue::Int(-1)]),
+            ),
+            (CrdtValue::Str("b".into()), CrdtValue::Bin(vec![1, 2, 3])),
+            (CrdtValue::Str("c".into()), CrdtValue::Nil),
+        ]);
+        let bytes = holochain_serialized_bytes::encode(&value).unwrap();
+        let round: CrdtValue = holochain_serialized_bytes::decode(&bytes).unwrap();
+        assert_eq!(value, round);
+    }
+}
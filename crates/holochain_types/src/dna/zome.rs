@@ -168,6 +168,9 @@ pub struct HostFnAccess {
     pub agent_info: Permission,
     /// Can access the workspace
     pub read_workspace: Permission,
+    /// Can read data that is the same for every agent,
+    /// i.e. gets for content by hash
+    pub read_workspace_deterministic: Permission,
     /// Can write and workspace
     pub write_workspace: Permission,
     /// Can write to the network
@@ -178,6 +181,9 @@ pub struct HostFnAccess {
     pub non_determinism: Permission,
     /// Access to functions that use the keystore in the conductor
     pub keystore: Permission,
+    /// Access to keystore functions that don't depend on this agent's keys,
+    /// i.e. verifying signatures
+    pub keystore_deterministic: Permission,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -185,7 +191,7 @@ pub struct HostFnAccess {
 pub enum Permission {
     /// Host functions with this access will be included
     Allow,
-    /// Host functions with this access will be unreachable
+    /// Host functions with this access return an error
     Deny,
 }
 
@@ -201,11 +207,13 @@ impl HostFnAccess {
     pub fn all() -> Self {
         HostFnAccess {
             read_workspace: Permission::Allow,
+            read_workspace_deterministic: Permission::Allow,
             write_workspace: Permission::Allow,
             agent_info: Permission::Allow,
             non_determinism: Permission::Allow,
             write_network: Permission::Allow,
             keystore: Permission::Allow,
+            keystore_deterministic: Permission::Allow,
             dna_bindings: Permission::Allow,
         }
     }
@@ -214,11 +222,13 @@ impl HostFnAccess {
     pub fn none() -> Self {
         HostFnAccess {
             read_workspace: Permission::Deny,
+            read_workspace_deterministic: Permission::Deny,
             write_workspace: Permission::Deny,
             agent_info: Permission::Deny,
             non_determinism: Permission::Deny,
             write_network: Permission::Deny,
             keystore: Permission::Deny,
+            keystore_deterministic: Permission::Deny,
             dna_bindings: Permission::Deny,
         }
     }
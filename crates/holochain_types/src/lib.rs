@@ -16,6 +16,7 @@ pub mod activity;
 pub mod app;
 pub mod autonomic;
 pub mod chain;
+pub mod crdt;
 pub mod db;
 pub mod dht_op;
 pub mod dna;
@@ -13,6 +13,7 @@ pub use crate::app::error::*;
 pub use crate::app::*;
 pub use crate::autonomic::*;
 pub use crate::chain::*;
+pub use crate::crdt::*;
 pub use crate::db::*;
 pub use crate::dht_op::error::*;
 pub use crate::dht_op::*;
@@ -126,6 +126,12 @@ pub use websocket_receiver::*;
 mod websocket_listener;
 pub use websocket_listener::*;
 
+// binding and connecting over unix domain sockets
+#[cfg(unix)]
+mod websocket_unix;
+#[cfg(unix)]
+pub use websocket_unix::*;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,4 +178,101 @@ mod tests {
 
         assert_eq!("echo: test", &rsp.0,);
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket_test() {
+        observability::test_run().ok();
+        let path = std::env::temp_dir().join(format!(
+            "holochain-websocket-test-{}.sock",
+            nanoid::nanoid!()
+        ));
+        let mut server = websocket_bind_unix(&path, Arc::new(WebsocketConfig::default()))
+            .await
+            .unwrap();
+
+        tokio::task::spawn(async move {
+            while let Some(maybe_con) = server.next().await {
+                let (_send, mut recv) = maybe_con.unwrap();
+
+                tokio::task::spawn(async move {
+                    if let Some(WebsocketMessage::Request(data, respond)) = recv.next().await {
+                        let msg: TestMessage = data.try_into().unwrap();
+                        let msg = TestMessage(format!("echo: {}", msg.0));
+                        respond(msg.try_into().unwrap()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        let (mut send, _recv) = websocket_connect_unix(&path, Arc::new(WebsocketConfig::default()))
+            .await
+            .unwrap();
+
+        let msg = TestMessage("test".to_string());
+        let rsp: TestMessage = send.request(msg).await.unwrap();
+
+        assert_eq!("echo: test", &rsp.0,);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket_is_private_and_not_replaced_while_listening() {
+        use std::os::unix::fs::PermissionsExt;
+        observability::test_run().ok();
+        let path = std::env::temp_dir().join(format!(
+            "holochain-websocket-test-{}.sock",
+            nanoid::nanoid!()
+        ));
+        let server = websocket_bind_unix(&path, Arc::new(WebsocketConfig::default()))
+            .await
+            .unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let err = websocket_bind_unix(&path, Arc::new(WebsocketConfig::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AddrInUse);
+        assert!(std::os::unix::net::UnixStream::connect(&path).is_ok());
+
+        drop(server);
+        assert!(!path.exists());
+
+        // a socket nobody listens on any more is replaced
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+        let _server = websocket_bind_unix(&path, Arc::new(WebsocketConfig::default()))
+            .await
+            .unwrap();
+        assert!(std::os::unix::net::UnixStream::connect(&path).is_ok());
+    }
+
+    async fn connects_with_origin(url: &Url2, origin: Option<&str>) -> bool {
+        let mut request = tungstenite::http::Request::builder().uri(url.as_str());
+        if let Some(origin) = origin {
+            request = request.header("Origin", origin);
+        }
+        tokio_tungstenite::connect_async(request.body(()).unwrap())
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn refuses_disallowed_origins() {
+        observability::test_run().ok();
+        let config =
+            WebsocketConfig::default().allowed_origins(vec!["http://localhost:8888".to_string()]);
+        let mut server = websocket_bind(url2!("ws://127.0.0.1:0"), Arc::new(config))
+            .await
+            .unwrap();
+
+        let binding = server.local_addr().clone();
+
+        tokio::task::spawn(async move { while server.next().await.is_some() {} });
+
+        assert!(connects_with_origin(&binding, Some("http://localhost:8888")).await);
+        assert!(connects_with_origin(&binding, None).await);
+        assert!(!connects_with_origin(&binding, Some("http://evil.example")).await);
+    }
 }
@@ -61,7 +61,7 @@ pub(crate) fn init_tracing() {
 }
 
 /// internal socket type
-pub(crate) type RawSocket = tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>;
+pub(crate) type RawSocket<S> = tokio_tungstenite::WebSocketStream<S>;
 
 /// internal helper to convert addrs to urls
 pub(crate) fn addr_to_url(a: SocketAddr, scheme: &str) -> Url2 {
@@ -26,6 +26,12 @@ pub struct WebsocketConfig {
 
     /// Maximum number of pending new incoming connections. [default = 255]
     pub max_pending_connections: usize,
+
+    /// If set, listeners refuse handshakes with an `Origin` header
+    /// that isn't in this list. Clients which don't send an `Origin`
+    /// header, which browsers always do, are not affected.
+    /// [default = None]
+    pub allowed_origins: Option<Vec<String>>,
 }
 
 impl Default for WebsocketConfig {
@@ -38,6 +44,7 @@ impl Default for WebsocketConfig {
             max_message_size: 64 << 20,
             max_frame_size: 16 << 20,
             max_pending_connections: 255,
+            allowed_origins: None,
         }
     }
 }
@@ -78,6 +85,12 @@ impl WebsocketConfig {
         self.max_frame_size = max;
         self
     }
+
+    /// Builder-style setter.
+    pub fn allowed_origins(mut self, origins: Vec<String>) -> Self {
+        self.allowed_origins = Some(origins);
+        self
+    }
 }
 
 /// internal helper to convert our configs into tungstenite configs
@@ -3,6 +3,11 @@
 use crate::*;
 use futures::stream::BoxStream;
 use futures::stream::StreamExt;
+use tungstenite::handshake::server::ErrorResponse;
+use tungstenite::handshake::server::Request;
+use tungstenite::handshake::server::Response;
+use tungstenite::http::header::ORIGIN;
+use tungstenite::http::StatusCode;
 
 /// Websocket listening / server socket. This struct is an async Stream -
 /// calling `.next().await` will give you a Future that will in turn resolve
@@ -14,9 +19,29 @@ pub struct WebsocketListener {
     config: Arc<WebsocketConfig>,
     local_addr: Url2,
     socket: BoxStream<'static, Result<(WebsocketSender, WebsocketReceiver)>>,
+    /// The socket file to clean up if listening on a unix socket
+    unix_path: Option<std::path::PathBuf>,
 }
 
 impl WebsocketListener {
+    pub(crate) fn priv_new(
+        config: Arc<WebsocketConfig>,
+        local_addr: Url2,
+        socket: BoxStream<'static, Result<(WebsocketSender, WebsocketReceiver)>>,
+        unix_path: Option<std::path::PathBuf>,
+    ) -> Self {
+        tracing::info!(
+            message = "bind",
+            local_addr = %local_addr,
+        );
+        Self {
+            config,
+            local_addr,
+            socket,
+            unix_path,
+        }
+    }
+
     /// Get the url of the bound local listening socket.
     pub fn local_addr(&self) -> &Url2 {
         &self.local_addr
@@ -28,6 +53,14 @@ impl WebsocketListener {
     }
 }
 
+impl Drop for WebsocketListener {
+    fn drop(&mut self) {
+        if let Some(path) = &self.unix_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
 impl tokio::stream::Stream for WebsocketListener {
     type Item = Result<(WebsocketSender, WebsocketReceiver)>;
 
@@ -64,15 +97,9 @@ pub async fn websocket_bind(addr: Url2, config: Arc<WebsocketConfig>) -> Result<
         .buffer_unordered(config.max_pending_connections)
         .boxed();
 
-    tracing::info!(
-        message = "bind",
-        local_addr = %local_addr,
-    );
-    Ok(WebsocketListener {
-        config,
-        local_addr,
-        socket,
-    })
+    Ok(WebsocketListener::priv_new(
+        config, local_addr, socket, None,
+    ))
 }
 
 /// Connects the new listener
@@ -85,22 +112,69 @@ async fn connect(
             socket.set_keepalive(Some(std::time::Duration::from_secs(
                 config.tcp_keepalive_s as u64,
             )))?;
-            tracing::debug!(
-                message = "accepted incoming raw socket",
-                remote_addr = %socket.peer_addr()?,
-            );
-            let socket = tokio_tungstenite::accept_async_with_config(
-                socket,
-                Some(tungstenite::protocol::WebSocketConfig {
-                    max_send_queue: Some(config.max_send_queue),
-                    max_message_size: Some(config.max_message_size),
-                    max_frame_size: Some(config.max_frame_size),
-                }),
-            )
-            .await
-            .map_err(|e| Error::new(ErrorKind::Other, e))?;
-            build_websocket_pair(config, socket)
+            let remote_addr = addr_to_url(socket.peer_addr()?, config.scheme);
+            accept(config, remote_addr, socket).await
         }
         Err(e) => Err(Error::new(ErrorKind::Other, e)),
     }
 }
+
+/// Run the server side of the websocket handshake on an accepted stream
+pub(crate) async fn accept<S>(
+    config: Arc<WebsocketConfig>,
+    remote_addr: Url2,
+    socket: S,
+) -> Result<(WebsocketSender, WebsocketReceiver)>
+where
+    S: 'static + tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send,
+{
+    tracing::debug!(
+        message = "accepted incoming raw socket",
+        %remote_addr,
+    );
+    let check = {
+        let config = config.clone();
+        move |request: &Request, response: Response| {
+            check_origin(config.allowed_origins.as_deref(), request, response)
+        }
+    };
+    let socket = tokio_tungstenite::accept_hdr_async_with_config(
+        socket,
+        check,
+        Some(tungstenite::protocol::WebSocketConfig {
+            max_send_queue: Some(config.max_send_queue),
+            max_message_size: Some(config.max_message_size),
+            max_frame_size: Some(config.max_frame_size),
+        }),
+    )
+    .await
+    .map_err(|e| Error::new(ErrorKind::Other, e))?;
+    build_websocket_pair(config, remote_addr, socket)
+}
+
+/// Refuse the handshake if the request comes from an origin that isn't allowed.
+fn check_origin(
+    allowed_origins: Option<&[String]>,
+    request: &Request,
+    response: Response,
+) -> std::result::Result<Response, ErrorResponse> {
+    let (allowed_origins, origin) = match (allowed_origins, request.headers().get(ORIGIN)) {
+        (Some(allowed_origins), Some(origin)) => (allowed_origins, origin),
+        _ => return Ok(response),
+    };
+    let allowed = origin
+        .to_str()
+        .map(|origin| allowed_origins.iter().any(|allowed| allowed == origin))
+        .unwrap_or(false);
+    if allowed {
+        Ok(response)
+    } else {
+        tracing::warn!(
+            message = "refused websocket handshake from origin",
+            origin = ?origin,
+        );
+        let mut response = ErrorResponse::new(Some("Origin not allowed".to_string()));
+        *response.status_mut() = StatusCode::FORBIDDEN;
+        Err(response)
+    }
+}
@@ -110,6 +110,7 @@ pub async fn websocket_connect(
     socket.set_keepalive(Some(std::time::Duration::from_secs(
         config.tcp_keepalive_s as u64,
     )))?;
+    let remote_addr = addr_to_url(socket.peer_addr()?, config.scheme);
     let (socket, _) = tokio_tungstenite::client_async_with_config(
         url.as_str(),
         socket,
@@ -117,20 +118,20 @@ pub async fn websocket_connect(
     )
     .await
     .map_err(|e| Error::new(ErrorKind::Other, e))?;
-    build_websocket_pair(config, socket)
+    build_websocket_pair(config, remote_addr, socket)
 }
 
 /// internal set up the tokio tasks that keep a websocket running
 /// and produce the public (WebsocketSender, WebsocketReceiver) pair.
-pub(crate) fn build_websocket_pair(
+pub(crate) fn build_websocket_pair<S>(
     config: Arc<WebsocketConfig>,
-    socket: RawSocket,
-) -> Result<(WebsocketSender, WebsocketReceiver)> {
-    let remote_addr = url2!(
-        "{}#{}",
-        addr_to_url(socket.get_ref().peer_addr()?, config.scheme),
-        nanoid::nanoid!(),
-    );
+    remote_addr: Url2,
+    socket: RawSocket<S>,
+) -> Result<(WebsocketSender, WebsocketReceiver)>
+where
+    S: 'static + tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send,
+{
+    let remote_addr = url2!("{}#{}", remote_addr, nanoid::nanoid!());
 
     // split the sink and stream so we can handle them simultaneously
     use futures::stream::StreamExt;
@@ -0,0 +1,133 @@
+//! defines binding and connecting websockets over unix domain sockets
+
+use crate::*;
+use futures::stream::StreamExt;
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// Bind a new websocket listening socket on a unix domain socket at `path`,
+/// and begin awaiting incoming connections.
+/// The socket file is only accessible by its owner,
+/// and is removed when the listener is dropped.
+/// A stale socket left behind at `path` by a previous listener is replaced,
+/// but a socket still accepting connections, or any other kind of file there,
+/// is an error.
+/// Returns a [WebsocketListener](struct.WebsocketListener.html) instance.
+pub async fn websocket_bind_unix(
+    path: &Path,
+    config: Arc<WebsocketConfig>,
+) -> Result<WebsocketListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ));
+        }
+        match std::os::unix::net::UnixStream::connect(path) {
+            // nothing is listening, so it's safe to replace
+            Err(e) if e.kind() == ErrorKind::ConnectionRefused => (),
+            Ok(_) => {
+                return Err(Error::new(
+                    ErrorKind::AddrInUse,
+                    format!("{} is already accepting connections", path.display()),
+                ))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    let socket = bind_owner_only(path)?;
+
+    let local_addr = unix_url(path);
+    let socket = socket
+        .map({
+            let config = config.clone();
+            let local_addr = local_addr.clone();
+            move |socket_result| {
+                let config = config.clone();
+                let local_addr = local_addr.clone();
+                async move {
+                    match socket_result {
+                        // unix socket clients are usually unnamed,
+                        // so connections are identified by the listener's path
+                        Ok(socket) => {
+                            crate::websocket_listener::accept(config, local_addr, socket).await
+                        }
+                        Err(e) => Err(Error::new(ErrorKind::Other, e)),
+                    }
+                }
+            }
+        })
+        .buffer_unordered(config.max_pending_connections)
+        .boxed();
+
+    Ok(WebsocketListener::priv_new(
+        config,
+        local_addr,
+        socket,
+        Some(path.to_path_buf()),
+    ))
+}
+
+/// Establish a new outgoing websocket connection over the unix domain
+/// socket at `path`. Returns a split websocket connection pair: (
+/// [WebsocketSender](struct.WebsocketSender.html),
+/// [WebsocketReceiver](struct.WebsocketReceiver.html)
+/// ).
+pub async fn websocket_connect_unix(
+    path: &Path,
+    config: Arc<WebsocketConfig>,
+) -> Result<(WebsocketSender, WebsocketReceiver)> {
+    let socket = tokio::net::UnixStream::connect(path).await?;
+    // the handshake needs a url, but the host is never resolved
+    let url = url2!("{}://localhost", config.scheme);
+    let (socket, _) = tokio_tungstenite::client_async_with_config(
+        url.as_str(),
+        socket,
+        Some(config.to_tungstenite()),
+    )
+    .await
+    .map_err(|e| Error::new(ErrorKind::Other, e))?;
+    build_websocket_pair(config, unix_url(path), socket)
+}
+
+/// internal helper to bind a socket at `path` which only its owner can use.
+/// The socket is bound and given its permissions inside a new directory
+/// only the owner can enter, then moved to `path`,
+/// so nobody else can connect to it in between.
+fn bind_owner_only(path: &Path) -> Result<tokio::net::UnixListener> {
+    let file_name = path.file_name().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is not a socket path", path.display()),
+        )
+    })?;
+    let private_dir = path.parent().unwrap_or_else(|| Path::new("")).join(format!(
+        ".{}-{}",
+        file_name.to_string_lossy(),
+        nanoid::nanoid!(8)
+    ));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)?;
+    let private_path = private_dir.join("socket");
+    let result = (|| -> Result<tokio::net::UnixListener> {
+        let socket = tokio::net::UnixListener::bind(&private_path)?;
+        std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(0o600))?;
+        // replaces a stale socket at `path` in one step
+        std::fs::rename(&private_path, path)?;
+        Ok(socket)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&private_path);
+    }
+    let _ = std::fs::remove_dir(&private_dir);
+    result
+}
+
+/// internal helper to convert socket paths to urls
+fn unix_url(path: &Path) -> Url2 {
+    url2!("unix://{}", path.display())
+}
@@ -1,2 +1,98 @@
-#[derive(Default, Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-pub struct CrdtType;
+//! How concurrent [`Update`]s to an entry are merged.
+//!
+//! An entry def declares a [`CrdtType`] and `get` on the hash of an entry of
+//! that type returns the value merged from the entry and every update descended
+//! from it, instead of the entry alone.
+//!
+//! [`Update`]: crate::header::Update
+
+/// The strategy the host uses to merge an entry with the updates made to it.
+///
+/// Updates form a tree: each [`Update`] names the header it updates,
+/// which is the original create or another update.
+/// Versions which were deleted, or whose entries can't be found,
+/// are left out along with the updates made to them.
+///
+/// Entry defs serialized before [`CrdtType`] had variants hold nil,
+/// which deserializes as [`CrdtType::None`].
+///
+/// [`Update`]: crate::header::Update
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrdtType {
+    /// Updates are not merged. `get` returns the oldest live entry.
+    None,
+    /// The version with the latest header timestamp wins.
+    /// Ties are broken by the larger header hash.
+    LastWriterWins,
+    /// The entry serializes to a sequence and each version adds items to it.
+    /// The merged value is the union of every version's items.
+    GrowOnlySet,
+    /// The entry serializes to a sequence. An update adds the items that
+    /// weren't in the version it updates, and removes the items that were
+    /// and aren't anymore. A removal only cancels the adds it has seen,
+    /// so when an add and a remove of an item are concurrent the add wins.
+    ObservedRemoveSet,
+    /// The entry serializes to an integer. Each update adds the difference
+    /// between its value and the value of the version it updates,
+    /// so concurrent increments and decrements all count.
+    Counter,
+}
+
+impl Default for CrdtType {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// The serialized form of a [`CrdtType`]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CrdtTypeRepr {
+    None,
+    LastWriterWins,
+    GrowOnlySet,
+    ObservedRemoveSet,
+    Counter,
+}
+
+impl<'de> serde::Deserialize<'de> for CrdtType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr: Option<CrdtTypeRepr> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(match repr {
+            None | Some(CrdtTypeRepr::None) => Self::None,
+            Some(CrdtTypeRepr::LastWriterWins) => Self::LastWriterWins,
+            Some(CrdtTypeRepr::GrowOnlySet) => Self::GrowOnlySet,
+            Some(CrdtTypeRepr::ObservedRemoveSet) => Self::ObservedRemoveSet,
+            Some(CrdtTypeRepr::Counter) => Self::Counter,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crdt_types_round_trip() {
+        for crdt_type in vec![
+            CrdtType::None,
+            CrdtType::LastWriterWins,
+            CrdtType::GrowOnlySet,
+            CrdtType::ObservedRemoveSet,
+            CrdtType::Counter,
+        ] {
+            let bytes = holochain_serialized_bytes::encode(&crdt_type).unwrap();
+            let round: CrdtType = holochain_serialized_bytes::decode(&bytes).unwrap();
+            assert_eq!(crdt_type, round);
+        }
+    }
+
+    #[test]
+    fn nil_is_none() {
+        // What the unit struct CrdtType used to serialize to
+        let bytes = holochain_serialized_bytes::encode(&()).unwrap();
+        let crdt_type: CrdtType = holochain_serialized_bytes::decode(&bytes).unwrap();
+        assert_eq!(crdt_type, CrdtType::None);
+    }
+}
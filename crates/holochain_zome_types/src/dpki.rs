@@ -0,0 +1,65 @@
+//! Types for the conductor to talk to a DPKI hApp.
+//!
+//! A conductor configured with a `DpkiConfig` calls these functions on the
+//! first cell of the configured DPKI app. Any hApp exposing these functions
+//! in a zome called [`DPKI_ZOME_NAME`] can be used as the DPKI service.
+
+use crate::timestamp::Timestamp;
+use holo_hash::AgentPubKey;
+use holochain_serialized_bytes::prelude::*;
+
+/// The zome the conductor expects the DPKI functions to be in.
+pub const DPKI_ZOME_NAME: &str = "dpki";
+/// Function taking a [`RegisterKey`] and returning `()`.
+pub const DPKI_REGISTER_KEY_FN: &str = "register_key";
+/// Function taking a [`RevokeKey`] and returning `()`.
+pub const DPKI_REVOKE_KEY_FN: &str = "revoke_key";
+/// Function taking a [`KeyStateQuery`] and returning a [`KeyState`].
+pub const DPKI_KEY_STATE_FN: &str = "key_state";
+
+/// Register a newly generated agent key with DPKI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct RegisterKey {
+    /// The key to register.
+    pub key: AgentPubKey,
+    /// When the key was registered.
+    pub registered_at: Timestamp,
+}
+
+/// Revoke an agent key so that nothing signed by it after `revoked_at` is valid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct RevokeKey {
+    /// The key to revoke.
+    pub key: AgentPubKey,
+    /// When the key stops being valid.
+    pub revoked_at: Timestamp,
+}
+
+/// Ask DPKI for the state of a key at a point in time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct KeyStateQuery {
+    /// The key to look up.
+    pub key: AgentPubKey,
+    /// The time the key was used, e.g. the timestamp of a header it signed.
+    pub at: Timestamp,
+}
+
+/// The state of an agent key at the time asked for in a [`KeyStateQuery`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub enum KeyState {
+    /// DPKI has no record of this key.
+    /// This can happen before the registration has reached this agent.
+    NotFound,
+    /// The key was registered and not revoked at the time.
+    Valid(Timestamp),
+    /// The key was revoked at or before the time.
+    Revoked(Timestamp),
+}
+
+impl KeyState {
+    /// Is this key revoked?
+    /// Keys that DPKI has not seen are not treated as revoked.
+    pub fn is_revoked(&self) -> bool {
+        matches!(self, KeyState::Revoked(_))
+    }
+}
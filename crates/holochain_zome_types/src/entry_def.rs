@@ -119,6 +119,10 @@ impl EntryDefs {
             .position(|entry_def| entry_def.id == entry_def_id)
             .map(|u_size| EntryDefIndex(u_size as u8))
     }
+
+    pub fn get(&self, index: EntryDefIndex) -> Option<&EntryDef> {
+        self.0.get(index.index())
+    }
 }
 
 impl std::ops::Index<usize> for EntryDefs {
@@ -187,7 +191,7 @@ mod tests {
             vec![EntryDef {
                 id: "bar".into(),
                 visibility: EntryVisibility::Public,
-                crdt_type: CrdtType,
+                crdt_type: CrdtType::None,
                 required_validations: 5.into(),
                 required_validation_type: RequiredValidationType::default(),
             }]
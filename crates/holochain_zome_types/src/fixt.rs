@@ -397,9 +397,7 @@ fixturator!(
 
 fixturator!(
     CrdtType;
-    curve Empty CrdtType;
-    curve Unpredictable CrdtType;
-    curve Predictable CrdtType;
+    unit variants [ None LastWriterWins GrowOnlySet ObservedRemoveSet Counter ] empty None;
 );
 
 fixturator!(
@@ -9,6 +9,7 @@ pub use crate::capability::*;
 pub use crate::cell::*;
 pub use crate::cell::*;
 pub use crate::crdt::*;
+pub use crate::dpki::*;
 pub use crate::element::*;
 pub use crate::entry::*;
 pub use crate::entry::*;
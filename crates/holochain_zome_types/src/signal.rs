@@ -5,14 +5,38 @@ use holochain_serialized_bytes::prelude::*;
 
 /// A signal emitted by an app via `emit_signal`
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
-#[repr(transparent)]
-#[serde(transparent)]
-pub struct AppSignal(crate::ExternIO);
+pub struct AppSignal {
+    /// An app defined topic that clients can filter signals on.
+    topic: Option<String>,
+    /// The signal data.
+    payload: crate::ExternIO,
+}
 
 impl AppSignal {
     /// Constructor
     pub fn new(extern_io: crate::ExternIO) -> Self {
-        Self(extern_io)
+        Self {
+            topic: None,
+            payload: extern_io,
+        }
+    }
+
+    /// Constructor for a signal with a topic
+    pub fn with_topic(topic: String, extern_io: crate::ExternIO) -> Self {
+        Self {
+            topic: Some(topic),
+            payload: extern_io,
+        }
+    }
+
+    /// The topic of this signal, if it has one
+    pub fn topic(&self) -> Option<&str> {
+        self.topic.as_deref()
+    }
+
+    /// The signal data
+    pub fn payload(&self) -> &crate::ExternIO {
+        &self.payload
     }
 }
 
@@ -0,0 +1,49 @@
+use kitsune_p2p_bootstrap::*;
+use kitsune_p2p_types::dependencies::ghost_actor::dependencies::tracing;
+use structopt::StructOpt;
+
+/// Option Parsing
+#[derive(structopt::StructOpt, Debug)]
+#[structopt(name = "kitsune-p2p-bootstrap")]
+struct Opt {
+    /// To which network interface / port should we bind?
+    #[structopt(short = "b", long, default_value = "127.0.0.1:0")]
+    bind_to: std::net::SocketAddr,
+
+    /// How often to drop expired agent infos from memory, in seconds.
+    #[structopt(long, default_value = "60")]
+    prune_interval_s: u64,
+}
+
+#[tokio::main]
+async fn main() {
+    let _ = tracing::subscriber::set_global_default(
+        tracing_subscriber::FmtSubscriber::builder()
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .finish(),
+    );
+
+    let opt = Opt::from_args();
+    let store = BootstrapStore::default();
+
+    let (driver, addr) = match run_with_store(opt.bind_to, store.clone()).await {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("{:?}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let prune_interval = std::time::Duration::from_secs(opt.prune_interval_s);
+    tokio::task::spawn(async move {
+        loop {
+            tokio::time::delay_for(prune_interval).await;
+            if let Err(e) = store.prune() {
+                tracing::error!(?e);
+            }
+        }
+    });
+
+    println!("http://{}", addr);
+    driver.await;
+}
@@ -0,0 +1,68 @@
+//! Errors for the bootstrap server.
+
+/// Error type for the bootstrap server.
+#[derive(Debug, thiserror::Error)]
+pub enum BootstrapError {
+    /// std::io::Error, including msgpack encoding errors.
+    #[error(transparent)]
+    StdIoError(#[from] std::io::Error),
+
+    /// Hyper http server error.
+    #[error(transparent)]
+    Hyper(#[from] hyper::Error),
+
+    /// Lair error while verifying a signature.
+    #[error(transparent)]
+    LairError(#[from] lair_keystore_api::LairError),
+
+    /// Kitsune error while decoding an agent info.
+    #[error(transparent)]
+    KitsuneP2pError(#[from] kitsune_p2p::KitsuneP2pError),
+
+    /// SystemTime call failed.
+    #[error(transparent)]
+    SystemTime(#[from] std::time::SystemTimeError),
+
+    /// Integer casting failed.
+    #[error(transparent)]
+    TryFromInt(#[from] std::num::TryFromIntError),
+
+    /// The signature does not match the signed agent info.
+    #[error("Invalid signature for agent info")]
+    InvalidSignature,
+
+    /// The outer signing agent is not the agent in the signed agent info.
+    #[error("Signing agent does not match the agent in the agent info")]
+    AgentMismatch,
+
+    /// The agent info has already expired.
+    #[error("Agent info is expired")]
+    Expired,
+
+    /// The agent info was signed too far in the future of the server's clock.
+    #[error("Agent info signed at {0}ms is in the future")]
+    SignedInFuture(u64),
+
+    /// The agent info asks to be held for longer than the server allows.
+    #[error("Agent info expires after {0}ms which is longer than the maximum of {1}ms")]
+    ExpiresTooLate(u64, u64),
+
+    /// The request had no `X-Op` header.
+    #[error("Missing op header")]
+    MissingOp,
+
+    /// The `X-Op` header is not an op this server understands.
+    #[error("Unknown op: {0}")]
+    UnknownOp(String),
+
+    /// The request body is larger than the server reads.
+    #[error("Request body is larger than the maximum of {0} bytes")]
+    BodyTooLarge(usize),
+
+    /// The space already holds as many agent infos as the server allows.
+    #[error("Space is full with {0} agents")]
+    SpaceFull(usize),
+}
+
+/// Bootstrap server result type.
+pub type BootstrapResult<T> = Result<T, BootstrapError>;
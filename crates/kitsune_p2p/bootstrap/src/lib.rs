@@ -0,0 +1,188 @@
+#![deny(missing_docs)]
+//! Bootstrap server for kitsune nodes to find each other.
+//!
+//! Implements the same msgpack `put` / `now` / `random` api as the remote
+//! bootstrap service so that private networks and integration tests can run
+//! without depending on the public service.
+//!
+//! All requests are `POST`s to the root path with the op selected by the
+//! `X-Op` header and the msgpack encoded input as the body.
+
+use futures::future::BoxFuture;
+use futures::future::FutureExt;
+use hyper::service::make_service_fn;
+use hyper::service::service_fn;
+use hyper::Body;
+use hyper::Method;
+use hyper::Request;
+use hyper::Response;
+use hyper::StatusCode;
+use kitsune_p2p_types::dependencies::ghost_actor::dependencies::tracing;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+mod error;
+pub use error::*;
+
+mod store;
+pub use store::*;
+
+/// The HTTP header name for setting the op on POST requests.
+pub const OP_HEADER: &str = "X-Op";
+/// The header op to put a signed agent info.
+pub const OP_PUT: &str = "put";
+/// The header op to return the server's opinion of 'now' in milliseconds.
+pub const OP_NOW: &str = "now";
+/// The header op to return a random set of agents in a specific space.
+pub const OP_RANDOM: &str = "random";
+
+/// The largest request body the server reads.
+/// Signed agent infos and random queries are far smaller than this.
+pub const MAX_BODY_SIZE: usize = 1024 * 64;
+
+/// A future that drives the bootstrap server until it is dropped.
+pub type BootstrapDriver = BoxFuture<'static, ()>;
+
+/// Bind a bootstrap server to `addr` with a fresh in-memory store.
+///
+/// Returns the driver future, which must be spawned or awaited for the server
+/// to accept requests, along with the actual bound address.
+/// Bind to port `0` to let the os pick a free port.
+pub async fn run(addr: impl Into<SocketAddr>) -> BootstrapResult<(BootstrapDriver, SocketAddr)> {
+    run_with_store(addr, BootstrapStore::default()).await
+}
+
+/// Same as [`run`] but serving from an existing store.
+pub async fn run_with_store(
+    addr: impl Into<SocketAddr>,
+    store: BootstrapStore,
+) -> BootstrapResult<(BootstrapDriver, SocketAddr)> {
+    let make_svc = make_service_fn(move |_conn| {
+        let store = store.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(store.clone(), req))) }
+    });
+    let server = hyper::Server::try_bind(&addr.into())?.serve(make_svc);
+    let addr = server.local_addr();
+    let driver = async move {
+        if let Err(e) = server.await {
+            tracing::error!(msg = "bootstrap server stopped", ?e);
+        }
+    }
+    .boxed();
+    Ok((driver, addr))
+}
+
+async fn handle(store: BootstrapStore, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if req.method() != Method::POST {
+        return Ok(error_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "Only POST is supported".to_string(),
+        ));
+    }
+    let op = req
+        .headers()
+        .get(OP_HEADER)
+        .and_then(|op| op.to_str().ok())
+        .map(str::to_string);
+    let result = match read_body(req.into_body()).await {
+        Ok(body) => dispatch(&store, op, &body).await,
+        Err(e) => Err(e),
+    };
+    Ok(match result {
+        Ok(body) => Response::new(Body::from(body)),
+        Err(e) => {
+            tracing::debug!(msg = "rejected bootstrap request", ?e);
+            let status = match e {
+                BootstrapError::BodyTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+                BootstrapError::SpaceFull(_) => StatusCode::SERVICE_UNAVAILABLE,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            error_response(status, e.to_string())
+        }
+    })
+}
+
+/// Read the whole body, giving up as soon as it is larger than [`MAX_BODY_SIZE`].
+async fn read_body(mut body: Body) -> BootstrapResult<Vec<u8>> {
+    use hyper::body::HttpBody;
+    let mut out = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if out.len() + chunk.len() > MAX_BODY_SIZE {
+            return Err(BootstrapError::BodyTooLarge(MAX_BODY_SIZE));
+        }
+        out.extend_from_slice(&chunk);
+    }
+    Ok(out)
+}
+
+async fn dispatch(
+    store: &BootstrapStore,
+    op: Option<String>,
+    mut body: &[u8],
+) -> BootstrapResult<Vec<u8>> {
+    let mut out = Vec::new();
+    match op.as_deref() {
+        Some(OP_PUT) => {
+            let agent_info_signed = kitsune_p2p_types::codec::rmp_decode(&mut body)?;
+            store.put(agent_info_signed).await?;
+            kitsune_p2p_types::codec::rmp_encode(&mut out, ())?;
+        }
+        Some(OP_NOW) => {
+            kitsune_p2p_types::codec::rmp_encode(&mut out, now_millis()?)?;
+        }
+        Some(OP_RANDOM) => {
+            let query: RandomQuery = kitsune_p2p_types::codec::rmp_decode(&mut body)?;
+            let random = store
+                .random(&query.space, query.limit as usize)?
+                .into_iter()
+                .map(serde_bytes::ByteBuf::from)
+                .collect::<Vec<_>>();
+            kitsune_p2p_types::codec::rmp_encode(&mut out, random)?;
+        }
+        Some(op) => return Err(BootstrapError::UnknownOp(op.to_string())),
+        None => return Err(BootstrapError::MissingOp),
+    }
+    Ok(out)
+}
+
+fn error_response(status: StatusCode, msg: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(msg));
+    *response.status_mut() = status;
+    response
+}
+
+/// The server's opinion of 'now' as unix milliseconds.
+pub fn now_millis() -> BootstrapResult<u64> {
+    use std::convert::TryInto;
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis()
+        .try_into()?)
+}
+
+/// Query for the `random` op.
+///
+/// Must match the encoding of the query sent by the kitsune bootstrap client.
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct RandomQuery {
+    /// The space to get random agents from.
+    pub space: std::sync::Arc<kitsune_p2p::KitsuneSpace>,
+    /// The maximum number of random agents to return.
+    pub limit: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rejects_large_bodies() {
+        let req = Request::post("/")
+            .header(OP_HEADER, OP_PUT)
+            .body(Body::from(vec![0; MAX_BODY_SIZE + 1]))
+            .unwrap();
+        let response = handle(BootstrapStore::default(), req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
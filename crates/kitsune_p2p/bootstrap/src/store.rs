@@ -0,0 +1,262 @@
+//! In-memory store of signed agent infos, keyed by space and agent.
+
+use crate::now_millis;
+use crate::BootstrapError;
+use crate::BootstrapResult;
+use kitsune_p2p::agent_store::AgentInfo;
+use kitsune_p2p::agent_store::AgentInfoSigned;
+use kitsune_p2p::KitsuneAgent;
+use kitsune_p2p::KitsuneBinType;
+use kitsune_p2p::KitsuneSpace;
+use lair_keystore_api::internal::sign_ed25519::SignEd25519PubKey;
+use lair_keystore_api::internal::sign_ed25519::SignEd25519Signature;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+/// How far ahead of the server's clock an agent info may be signed.
+/// Agents are expected to correct their clocks against the `now` op so this
+/// only needs to cover latency and drift.
+pub const SIGNED_AT_TOLERANCE_MS: u64 = 1000 * 60 * 5;
+
+/// The longest an agent info may ask to be held for.
+pub const MAX_EXPIRES_AFTER_MS: u64 = 1000 * 60 * 60;
+
+/// The default number of unexpired agent infos held for a single space.
+pub const MAX_AGENTS_PER_SPACE: usize = 1000;
+
+/// An agent info as it was `put`, along with when the server can drop it.
+#[derive(Debug, Clone)]
+struct StoredAgentInfo {
+    /// The msgpack encoded [`AgentInfoSigned`] exactly as it will be returned.
+    encoded: Vec<u8>,
+    /// Unix ms after which this agent info is no longer returned.
+    expires_at_ms: u64,
+}
+
+/// Shared store of agent infos for a bootstrap server.
+#[derive(Debug, Clone)]
+pub struct BootstrapStore {
+    spaces: Arc<Mutex<HashMap<KitsuneSpace, HashMap<KitsuneAgent, StoredAgentInfo>>>>,
+    max_agents_per_space: usize,
+}
+
+impl Default for BootstrapStore {
+    fn default() -> Self {
+        Self::new(MAX_AGENTS_PER_SPACE)
+    }
+}
+
+impl BootstrapStore {
+    /// An empty store holding at most `max_agents_per_space` agent infos
+    /// for any one space.
+    pub fn new(max_agents_per_space: usize) -> Self {
+        Self {
+            spaces: Default::default(),
+            max_agents_per_space,
+        }
+    }
+
+    /// Validate and store a signed agent info.
+    ///
+    /// The signature must verify against the agent, the signing agent must be
+    /// the agent in the info and the info must not be expired.
+    /// A newer agent info for the same agent replaces the older one.
+    /// A new agent is only accepted while its space holds fewer than the
+    /// maximum number of unexpired agent infos.
+    pub async fn put(&self, agent_info_signed: AgentInfoSigned) -> BootstrapResult<()> {
+        let agent_info = AgentInfo::try_from(&agent_info_signed)?;
+        if agent_info.as_agent_ref() != agent_info_signed.as_agent_ref() {
+            return Err(BootstrapError::AgentMismatch);
+        }
+        verify(&agent_info_signed).await?;
+
+        let now = now_millis()?;
+        let signed_at_ms = agent_info.signed_at_ms();
+        let expires_after_ms = agent_info.expires_after_ms();
+        if signed_at_ms > now + SIGNED_AT_TOLERANCE_MS {
+            return Err(BootstrapError::SignedInFuture(signed_at_ms));
+        }
+        if expires_after_ms > MAX_EXPIRES_AFTER_MS {
+            return Err(BootstrapError::ExpiresTooLate(
+                expires_after_ms,
+                MAX_EXPIRES_AFTER_MS,
+            ));
+        }
+        let expires_at_ms = signed_at_ms.saturating_add(expires_after_ms);
+        if expires_at_ms <= now {
+            return Err(BootstrapError::Expired);
+        }
+
+        let mut encoded = Vec::new();
+        kitsune_p2p_types::codec::rmp_encode(&mut encoded, &agent_info_signed)?;
+
+        let mut spaces = self.spaces.lock();
+        let agents = spaces.entry(agent_info.as_space_ref().clone()).or_default();
+        let held_expires_at_ms = agents
+            .get(agent_info.as_agent_ref())
+            .map(|existing| existing.expires_at_ms);
+        match held_expires_at_ms {
+            // Never replace a fresher agent info with a stale one.
+            Some(held_expires_at_ms) if held_expires_at_ms > expires_at_ms => (),
+            held_expires_at_ms => {
+                if held_expires_at_ms.is_none() && agents.len() >= self.max_agents_per_space {
+                    agents.retain(|_, info| info.expires_at_ms > now);
+                    if agents.len() >= self.max_agents_per_space {
+                        return Err(BootstrapError::SpaceFull(self.max_agents_per_space));
+                    }
+                }
+                agents.insert(
+                    agent_info.as_agent_ref().clone(),
+                    StoredAgentInfo {
+                        encoded,
+                        expires_at_ms,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Up to `limit` unexpired encoded agent infos from `space` in random order.
+    pub fn random(&self, space: &KitsuneSpace, limit: usize) -> BootstrapResult<Vec<Vec<u8>>> {
+        use rand::seq::IteratorRandom;
+        use rand::seq::SliceRandom;
+        let now = now_millis()?;
+        let mut spaces = self.spaces.lock();
+        Ok(match spaces.get_mut(space) {
+            Some(agents) => {
+                agents.retain(|_, info| info.expires_at_ms > now);
+                let mut rng = rand::thread_rng();
+                let mut out: Vec<Vec<u8>> = agents
+                    .values()
+                    .map(|info| info.encoded.clone())
+                    .choose_multiple(&mut rng, limit);
+                // `choose_multiple` does not randomize the order of the output.
+                out.shuffle(&mut rng);
+                out
+            }
+            None => Vec::new(),
+        })
+    }
+
+    /// Drop every expired agent info from the store.
+    pub fn prune(&self) -> BootstrapResult<()> {
+        let now = now_millis()?;
+        let mut spaces = self.spaces.lock();
+        for agents in spaces.values_mut() {
+            agents.retain(|_, info| info.expires_at_ms > now);
+        }
+        spaces.retain(|_, agents| !agents.is_empty());
+        Ok(())
+    }
+}
+
+async fn verify(agent_info_signed: &AgentInfoSigned) -> BootstrapResult<()> {
+    let pub_key: SignEd25519PubKey = agent_info_signed.as_agent_ref().get_bytes().to_vec().into();
+    let signature: SignEd25519Signature = agent_info_signed.as_signature_ref().0.clone().into();
+    let data = Arc::new(agent_info_signed.as_agent_info_ref().to_vec());
+    if pub_key.verify(data, signature).await? {
+        Ok(())
+    } else {
+        Err(BootstrapError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kitsune_p2p::KitsuneSignature;
+    use lair_keystore_api::internal::sign_ed25519::sign_ed25519_keypair_new_from_entropy;
+
+    async fn signed(space: KitsuneSpace, signed_at_ms: u64) -> AgentInfoSigned {
+        let keypair = sign_ed25519_keypair_new_from_entropy().await.unwrap();
+        let agent = KitsuneAgent::new((*keypair.pub_key.0).clone());
+        let agent_info = AgentInfo::new(space, agent.clone(), Vec::new(), signed_at_ms, 1000 * 60);
+        let mut data = Vec::new();
+        kitsune_p2p_types::codec::rmp_encode(&mut data, &agent_info).unwrap();
+        let signature = keypair.sign(Arc::new(data.clone())).await.unwrap();
+        AgentInfoSigned::try_new(agent, KitsuneSignature((*signature.0).clone()), data).unwrap()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn put_and_random() {
+        let store = BootstrapStore::default();
+        let space = KitsuneSpace::new(vec![1; 36]);
+        let now = now_millis().unwrap();
+
+        let alice = signed(space.clone(), now).await;
+        let bob = signed(space.clone(), now).await;
+        store.put(alice).await.unwrap();
+        store.put(bob).await.unwrap();
+
+        assert_eq!(store.random(&space, 10).unwrap().len(), 2);
+        assert_eq!(store.random(&space, 1).unwrap().len(), 1);
+        assert!(store
+            .random(&KitsuneSpace::new(vec![2; 36]), 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rejects_bad_agent_infos() {
+        let store = BootstrapStore::default();
+        let space = KitsuneSpace::new(vec![1; 36]);
+        let now = now_millis().unwrap();
+
+        // Expired.
+        let expired = signed(space.clone(), now - 1000 * 60 * 2).await;
+        assert!(matches!(
+            store.put(expired).await,
+            Err(BootstrapError::Expired)
+        ));
+
+        // Signed in the future.
+        let future = signed(space.clone(), now + SIGNED_AT_TOLERANCE_MS * 2).await;
+        assert!(matches!(
+            store.put(future).await,
+            Err(BootstrapError::SignedInFuture(_))
+        ));
+
+        // Signature from a different key.
+        let good = signed(space.clone(), now).await;
+        let other = signed(space.clone(), now).await;
+        let forged = AgentInfoSigned::try_new(
+            good.as_agent_ref().clone(),
+            other.as_signature_ref().clone(),
+            good.as_agent_info_ref().to_vec(),
+        )
+        .unwrap();
+        assert!(matches!(
+            store.put(forged).await,
+            Err(BootstrapError::InvalidSignature)
+        ));
+
+        assert!(store.random(&space, 10).unwrap().is_empty());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn caps_agents_per_space() {
+        let store = BootstrapStore::new(2);
+        let space = KitsuneSpace::new(vec![1; 36]);
+        let now = now_millis().unwrap();
+
+        let alice = signed(space.clone(), now).await;
+        store.put(alice.clone()).await.unwrap();
+        store.put(signed(space.clone(), now).await).await.unwrap();
+        assert!(matches!(
+            store.put(signed(space.clone(), now).await).await,
+            Err(BootstrapError::SpaceFull(2))
+        ));
+
+        // Agents already held can still refresh their info,
+        // and other spaces are unaffected.
+        store.put(alice).await.unwrap();
+        store
+            .put(signed(KitsuneSpace::new(vec![2; 36]), now).await)
+            .await
+            .unwrap();
+        assert_eq!(store.random(&space, 10).unwrap().len(), 2);
+    }
+}
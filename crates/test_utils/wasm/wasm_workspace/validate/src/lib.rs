@@ -19,7 +19,7 @@ impl From<&ThisWasmEntry> for EntryDefId {
 
 impl From<&ThisWasmEntry> for CrdtType {
     fn from(_: &ThisWasmEntry) -> Self {
-        Self
+        Self::None
     }
 }
 
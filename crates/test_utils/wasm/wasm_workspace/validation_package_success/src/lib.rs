@@ -43,6 +43,38 @@ fn validation_package(
     }
 }
 
+/// An artist is only valid with a package of
+/// at least 30 songs by the same author
+#[hdk_extern]
+fn validate_create_entry_artist(data: ValidateData) -> ExternResult<ValidateCallbackResult> {
+    let author = data.element.header().author().clone();
+    let songs = match data.validation_package {
+        Some(ValidationPackage(elements)) => elements
+            .into_iter()
+            .filter(|el| {
+                el.header().author() == &author
+                    && matches!(
+                        el.header().entry_type(),
+                        Some(EntryType::App(aet)) if u8::from(aet.id()) == 0
+                    )
+            })
+            .count(),
+        None => {
+            return Ok(ValidateCallbackResult::Invalid(
+                "Artist is missing its validation package".to_string(),
+            ))
+        }
+    };
+    if songs >= NUM_SONGS {
+        Ok(ValidateCallbackResult::Valid)
+    } else {
+        Ok(ValidateCallbackResult::Invalid(format!(
+            "Artist only has {} songs in its validation package",
+            songs
+        )))
+    }
+}
+
 #[hdk_extern]
 fn commit_artist(_: ()) -> ExternResult<HeaderHash> {
     create_entry(&Artist)